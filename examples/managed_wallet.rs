@@ -3,36 +3,18 @@ use dotenv::dotenv;
 use env_logger::Env;
 use futures::future::join_all;
 use log::{error, info};
-use once_cell::sync::Lazy;
 
-use circle_api::api::CircleClient;
+use circle_api::config::CircleConfig;
 use circle_api::models::blockchain::Blockchain;
 use circle_api::models::wallet_balance::WalletBalanceQueryParams;
 use circle_api::models::wallet_list::WalletListQueryParams;
 use circle_api::models::wallet_nfts::WalletNftsQueryParams;
 use circle_api::models::wallet_set::WalletSetsQueryParams;
 
-pub fn get_env(env: &'static str) -> String {
-    std::env::var(env).unwrap_or_else(|_| panic!("Cannot get the {} env variable", env))
-}
-
-pub struct Config {
-    pub circle_api_key: String,
-    pub circle_entity_secret: String,
-}
-
-pub static CONFIG: Lazy<Config> = Lazy::new(|| {
-    dotenv().expect("Failed to read .env file");
-
-    Config {
-        circle_api_key: get_env("CIRCLE_API_KEY"),
-        circle_entity_secret: get_env("CIRCLE_ENTITY_SECRET"),
-    }
-});
-
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::init_from_env(Env::default().default_filter_or("info"));
+    dotenv().expect("Failed to read .env file");
 
     match run().await {
         Ok(_) => {}
@@ -46,11 +28,7 @@ async fn main() -> Result<()> {
 
 async fn run() -> Result<(), anyhow::Error> {
     info!("Starting payments-service");
-    let circle_client = CircleClient::new(
-        CONFIG.circle_api_key.clone(),
-        CONFIG.circle_entity_secret.clone(),
-    )
-    .await?;
+    let circle_client = CircleConfig::from_env()?.build_client().await?;
 
     let wallet_set_name = "test_wallet_set";
     let idempotency_key = uuid::Uuid::new_v4();
@@ -76,11 +54,11 @@ async fn run() -> Result<(), anyhow::Error> {
     let wallet_sets_response = circle_client
         .list_wallet_sets(WalletSetsQueryParams::default())
         .await?;
-    for wallet_set in &wallet_sets_response.wallet_sets {
+    for wallet_set in &wallet_sets_response.items {
         info!("Wallet set: {:?}", wallet_set);
     }
 
-    let wallet_set = &wallet_sets_response.wallet_sets[0];
+    let wallet_set = &wallet_sets_response.items[0];
 
     let idempotency_key = uuid::Uuid::new_v4();
     let create_wallet_response = circle_client
@@ -98,30 +76,28 @@ async fn run() -> Result<(), anyhow::Error> {
     let list_wallet_response = circle_client
         .list_wallets(WalletListQueryParams::default())
         .await?;
-    for (i, wallet) in list_wallet_response.wallets.iter().enumerate() {
+    for (i, wallet) in list_wallet_response.items.iter().enumerate() {
         info!("Wallet #{}: {:?}", i, wallet);
     }
 
-    let wallet = &list_wallet_response.wallets[0];
+    let wallet = &list_wallet_response.items[0];
     let get_wallet_response = circle_client.get_wallet(wallet.id).await?;
     info!("Get wallet response: {:?}", get_wallet_response);
 
     // get non-existent wallet
-    let get_wallet_response = circle_client.get_wallet(uuid::Uuid::new_v4()).await;
+    let get_wallet_response = circle_client.get_wallet(uuid::Uuid::new_v4().into()).await;
     info!(
         "Get non-existent wallet response: {:?}",
         get_wallet_response
     );
 
     // Update wallet
-    let wallet = &list_wallet_response.wallets[0];
+    let wallet = &list_wallet_response.items[0];
     let update_wallet_response = circle_client
         .update_wallet(
             wallet.id,
-            circle_api::models::wallet_update::WalletUpdateRequest {
-                name: "test_wallet".to_string(),
-                ref_id: "test_ref_id".to_string(),
-            },
+            circle_api::models::wallet_update::WalletUpdateRequest::new("test_wallet")
+                .ref_id("test_ref_id"),
         )
         .await?;
     info!("Update wallet response: {:?}", update_wallet_response);
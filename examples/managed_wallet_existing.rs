@@ -5,41 +5,22 @@ use dotenv::dotenv;
 use env_logger::Env;
 use futures::future::join_all;
 use log::{error, info};
-use once_cell::sync::Lazy;
 use tokio::time::sleep;
 use uuid::Uuid;
 
 use circle_api::api::CircleClient;
+use circle_api::config::CircleConfig;
 use circle_api::models::transaction_state::TransactionState;
-use circle_api::models::transaction_transfer_create::{
-    FeeLevel, TransactionTransferCreateRequestBuilder,
-};
+use circle_api::models::transaction_transfer_create::{FeeLevel, TransferRequestBuilder};
 use circle_api::models::wallet_balance::{WalletBalanceQueryParams, WalletBalanceResponse};
 use circle_api::models::wallet_detail::WalletDetail;
 use circle_api::models::wallet_list::WalletListQueryParams;
 use circle_api::models::wallet_set::WalletSetsQueryParams;
 
-pub fn get_env(env: &'static str) -> String {
-    std::env::var(env).unwrap_or_else(|_| panic!("Cannot get the {} env variable", env))
-}
-
-pub struct Config {
-    pub circle_api_key: String,
-    pub circle_entity_secret: String,
-}
-
-pub static CONFIG: Lazy<Config> = Lazy::new(|| {
-    dotenv().expect("Failed to read .env file");
-
-    Config {
-        circle_api_key: get_env("CIRCLE_API_KEY"),
-        circle_entity_secret: get_env("CIRCLE_ENTITY_SECRET"),
-    }
-});
-
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::init_from_env(Env::default().default_filter_or("info"));
+    dotenv().expect("Failed to read .env file");
 
     match run().await {
         Ok(_) => {}
@@ -88,11 +69,7 @@ fn print_balances(balances: &[WalletBalanceResponse]) {
 
 async fn run() -> Result<(), anyhow::Error> {
     info!("Starting payments-service");
-    let circle_client = CircleClient::new(
-        CONFIG.circle_api_key.clone(),
-        CONFIG.circle_entity_secret.clone(),
-    )
-    .await?;
+    let circle_client = CircleConfig::from_env()?.build_client().await?;
 
     let wallet_set_name = "test_wallet_set";
 
@@ -100,7 +77,7 @@ async fn run() -> Result<(), anyhow::Error> {
         .list_wallet_sets(WalletSetsQueryParams::new())
         .await?;
     let wallet_set = list_wallet_set_response
-        .wallet_sets
+        .items
         .iter()
         .filter(|x| x.name.is_some())
         .find(|x| x.name.as_ref().unwrap() == wallet_set_name)
@@ -110,7 +87,7 @@ async fn run() -> Result<(), anyhow::Error> {
         .list_wallets(WalletListQueryParams::default().wallet_set_id(wallet_set.id))
         .await?;
 
-    let wallets = &list_wallet_response.wallets[0..2];
+    let wallets = &list_wallet_response.items[0..2];
     for (i, wallet) in wallets.iter().enumerate() {
         info!("Wallet #{}: {:?}", i, wallet);
     }
@@ -130,15 +107,15 @@ async fn run() -> Result<(), anyhow::Error> {
     info!("Sending a bit of eth from wallet 0 to wallet 1...");
 
     let idempotency_key = Uuid::new_v4();
-    let transaction_request_builder = TransactionTransferCreateRequestBuilder::new(
-        wallets[1].address.clone(),
-        native_token.id,
-        wallets[0].id,
-        0.0001,
-    )
-    .fee_level(FeeLevel::Low);
+    let transfer = TransferRequestBuilder::new()
+        .from_wallet(wallets[0].id)
+        .to_address(wallets[1].address.clone())
+        .token(native_token.id)
+        .amount("0.0001")
+        .fee_level(FeeLevel::Low)
+        .build()?;
     let tx_request = circle_client
-        .create_transfer_transaction(idempotency_key, transaction_request_builder)
+        .create_transfer_transaction(idempotency_key, transfer)
         .await?;
 
     info!("Transaction created: {:?}", tx_request);
@@ -148,7 +125,7 @@ async fn run() -> Result<(), anyhow::Error> {
         info!("Transaction: {:?}", tx.transaction.state);
 
         // if matches!(tx.transaction.state, TransactionState::Queued) {
-        //     let tx_cancel = circle_client.cancel_transaction(tx_request.id).await?;
+        //     let tx_cancel = circle_client.cancel_transaction(tx_request.id, None).await?;
         //     info!("Transaction cancelled: {:?}", tx_cancel);
         // }
 
@@ -166,7 +143,7 @@ async fn run() -> Result<(), anyhow::Error> {
     print_balances(&balances);
 
     let tx_list = circle_client.list_transactions(Default::default()).await?;
-    for tx in tx_list.transactions {
+    for tx in tx_list {
         info!("Transaction: {:?}", tx);
     }
 
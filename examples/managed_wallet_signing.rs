@@ -2,35 +2,17 @@ use anyhow::Result;
 use dotenv::dotenv;
 use env_logger::Env;
 use log::{error, info};
-use once_cell::sync::Lazy;
 use web3::signing::keccak256;
 
-use circle_api::api::CircleClient;
+use circle_api::config::CircleConfig;
 
 use circle_api::models::wallet_list::WalletListQueryParams;
 use circle_api::models::wallet_set::WalletSetsQueryParams;
 
-pub fn get_env(env: &'static str) -> String {
-    std::env::var(env).unwrap_or_else(|_| panic!("Cannot get the {} env variable", env))
-}
-
-pub struct Config {
-    pub circle_api_key: String,
-    pub circle_entity_secret: String,
-}
-
-pub static CONFIG: Lazy<Config> = Lazy::new(|| {
-    dotenv().expect("Failed to read .env file");
-
-    Config {
-        circle_api_key: get_env("CIRCLE_API_KEY"),
-        circle_entity_secret: get_env("CIRCLE_ENTITY_SECRET"),
-    }
-});
-
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::init_from_env(Env::default().default_filter_or("info"));
+    dotenv().expect("Failed to read .env file");
 
     match run().await {
         Ok(_) => {}
@@ -43,18 +25,14 @@ async fn main() -> Result<()> {
 }
 
 async fn run() -> Result<(), anyhow::Error> {
-    let circle_client = CircleClient::new(
-        CONFIG.circle_api_key.clone(),
-        CONFIG.circle_entity_secret.clone(),
-    )
-    .await?;
+    let circle_client = CircleConfig::from_env()?.build_client().await?;
     let wallet_set_name = "test_wallet_set";
 
     let list_wallet_set_response = circle_client
         .list_wallet_sets(WalletSetsQueryParams::new())
         .await?;
     let wallet_set = list_wallet_set_response
-        .wallet_sets
+        .items
         .iter()
         .filter(|x| x.name.is_some())
         .find(|x| x.name.as_ref().unwrap() == wallet_set_name)
@@ -64,7 +42,7 @@ async fn run() -> Result<(), anyhow::Error> {
         .list_wallets(WalletListQueryParams::default().wallet_set_id(wallet_set.id))
         .await?;
 
-    let wallet = &list_wallet_response.wallets[0];
+    let wallet = &list_wallet_response.items[0];
     info!("Wallet: {:?}", wallet);
 
     let message = "Dummy message for the world!";
@@ -0,0 +1,24 @@
+-- Tables behind circle_api::sql::SqlStore. Plain TEXT and BIGINT columns, so the same
+-- migration runs on Postgres and SQLite.
+
+CREATE TABLE IF NOT EXISTS circle_deposit_addresses (
+    user_id TEXT NOT NULL,
+    blockchain TEXT NOT NULL,
+    wallet_id TEXT NOT NULL UNIQUE,
+    address TEXT NOT NULL,
+    PRIMARY KEY (user_id, blockchain)
+);
+
+CREATE TABLE IF NOT EXISTS circle_outbox (
+    id TEXT PRIMARY KEY,
+    enqueued_at BIGINT NOT NULL,
+    finished BOOLEAN NOT NULL,
+    entry TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS circle_outbox_unfinished ON circle_outbox (finished, enqueued_at);
+
+CREATE TABLE IF NOT EXISTS circle_schedules (
+    id TEXT PRIMARY KEY,
+    schedule TEXT NOT NULL
+);
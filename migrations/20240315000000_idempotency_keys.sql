@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS circle_idempotency_keys (
+    operation TEXT PRIMARY KEY,
+    idempotency_key TEXT NOT NULL
+);
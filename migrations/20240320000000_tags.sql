@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS circle_tags (
+    kind TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    tag_key TEXT NOT NULL,
+    tag_value TEXT NOT NULL,
+    PRIMARY KEY (kind, target_id, tag_key)
+);
+
+CREATE INDEX IF NOT EXISTS circle_tags_by_value ON circle_tags (tag_key, tag_value);
@@ -0,0 +1,16 @@
+-- Spends counted by SqlStore's SpendStore. Every try_record first bumps the single lock row,
+-- which serializes reservations on Postgres (row lock) and SQLite (write lock) alike.
+CREATE TABLE IF NOT EXISTS circle_spends (
+    id TEXT PRIMARY KEY,
+    spent_at BIGINT NOT NULL,
+    spend TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS circle_spends_by_time ON circle_spends (spent_at);
+
+CREATE TABLE IF NOT EXISTS circle_spend_lock (
+    id BIGINT PRIMARY KEY,
+    version BIGINT NOT NULL
+);
+
+INSERT INTO circle_spend_lock (id, version) VALUES (1, 0) ON CONFLICT DO NOTHING;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS circle_approvals (
+    id TEXT PRIMARY KEY,
+    requested_at BIGINT NOT NULL,
+    finished BOOLEAN NOT NULL,
+    transfer TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS circle_approvals_pending ON circle_approvals (finished, requested_at);
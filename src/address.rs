@@ -0,0 +1,186 @@
+//! Making sense of addresses people paste, before they're validated with Circle and long before
+//! a transfer goes to the wrong chain.
+
+use crate::models::blockchain::Blockchain;
+use crate::networks;
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// A chain a pasted address could be on, with the address as it should be sent there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressCandidate {
+    pub blockchain: Blockchain,
+    pub address: String,
+}
+
+/// Every chain `input` could be an address on, mainnets or testnets only. Whitespace and
+/// `ethereum:` (EIP-681), `solana:` and `near:` URIs are stripped first; an EIP-681 chain id
+/// narrows the candidates to that chain. Empty if `input` isn't an address of any chain.
+///
+/// EVM addresses are the same on every EVM chain, so they come back once per chain; ask the
+/// user which one they mean rather than guessing.
+pub fn detect_address(input: &str, testnet: bool) -> Vec<AddressCandidate> {
+    let input: String = input.chars().filter(|x| !x.is_whitespace()).collect();
+    let (scheme, rest) = match input.split_once(':') {
+        Some((scheme, rest)) => (Some(scheme.to_ascii_lowercase()), rest),
+        None => (None, input.as_str()),
+    };
+    // The address ends where a URI's chain id, function or parameters start.
+    let end = rest.find(['@', '/', '?']).unwrap_or(rest.len());
+    let address = match scheme.as_deref() {
+        Some("ethereum") => rest[..end].strip_prefix("pay-").unwrap_or(&rest[..end]),
+        _ => &rest[..end],
+    };
+    let candidates = |chains: Vec<Blockchain>, address: String| {
+        chains
+            .into_iter()
+            .filter(|x| x.is_testnet() == testnet)
+            .map(|blockchain| AddressCandidate {
+                blockchain,
+                address: address.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+    match scheme.as_deref() {
+        None | Some("ethereum") if is_evm(address) => {
+            let address = address.to_ascii_lowercase();
+            let chain_id = match rest[end..].strip_prefix('@') {
+                Some(chain_id) => {
+                    let end = chain_id.find(['/', '?']).unwrap_or(chain_id.len());
+                    match chain_id[..end].parse::<u64>() {
+                        Ok(chain_id) => Some(chain_id),
+                        Err(_) => return Vec::new(),
+                    }
+                }
+                None => None,
+            };
+            let chains = networks::all()
+                .iter()
+                .filter(|x| x.chain_id().is_some())
+                .filter(|x| chain_id.map_or(true, |id| x.chain_id() == Some(id)))
+                .map(|x| x.blockchain.clone())
+                .collect();
+            candidates(chains, address)
+        }
+        None | Some("solana") if is_solana(address) => candidates(
+            vec![Blockchain::Sol, Blockchain::SolDevnet],
+            address.to_string(),
+        ),
+        None | Some("near") if is_near(address) => {
+            let address = address.to_ascii_lowercase();
+            let chains = match address.rsplit_once('.').map(|x| x.1) {
+                Some("near") => vec![Blockchain::Near],
+                Some("testnet") => vec![Blockchain::NearTestnet],
+                Some(_) => Vec::new(),
+                None => vec![Blockchain::Near, Blockchain::NearTestnet],
+            };
+            candidates(chains, address)
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn is_evm(address: &str) -> bool {
+    match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => hex.len() == 40 && hex.bytes().all(|x| x.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
+/// A base58 public key, which decodes to 32 bytes.
+fn is_solana(address: &str) -> bool {
+    if !(32..=44).contains(&address.len()) {
+        return false;
+    }
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in address.bytes() {
+        let Some(mut carry) = BASE58_ALPHABET.iter().position(|x| *x == c) else {
+            return false;
+        };
+        for byte in bytes.iter_mut() {
+            carry += *byte as usize * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let leading_zeros = address.bytes().take_while(|x| *x == b'1').count();
+    bytes.len() + leading_zeros == 32
+}
+
+/// A 64-character implicit account, or a named account such as `alice.near`.
+fn is_near(address: &str) -> bool {
+    let address = address.to_ascii_lowercase();
+    if address.len() == 64 && address.bytes().all(|x| x.is_ascii_hexdigit()) {
+        return true;
+    }
+    (2..=64).contains(&address.len())
+        && address.contains('.')
+        && address.split('.').all(|part| {
+            !part.is_empty()
+                && part
+                    .bytes()
+                    .all(|x| x.is_ascii_lowercase() || x.is_ascii_digit() || x == b'-' || x == b'_')
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chains(input: &str, testnet: bool) -> Vec<Blockchain> {
+        detect_address(input, testnet)
+            .into_iter()
+            .map(|x| x.blockchain)
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_address() {
+        let pasted = " 0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3\n";
+        let candidates = detect_address(pasted, false);
+        assert_eq!(candidates.len(), 5);
+        assert_eq!(
+            candidates[0].address,
+            "0x6e5eaf34c73d1cd0be4e24f923b97cf38e10d1f3"
+        );
+        assert_eq!(
+            chains(
+                "ethereum:pay-0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3@8453/transfer?value=1",
+                false
+            ),
+            [Blockchain::Base]
+        );
+        assert!(chains(
+            "ethereum:0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3@8453",
+            true
+        )
+        .is_empty());
+
+        assert_eq!(
+            chains(
+                "solana:EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v?amount=1",
+                true
+            ),
+            [Blockchain::SolDevnet]
+        );
+        // 'l' isn't base58, and a truncated key doesn't decode to 32 bytes.
+        assert!(chains("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1l", false).is_empty());
+        assert!(chains("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEG", false).is_empty());
+
+        assert_eq!(chains("Alice.near", false), [Blockchain::Near]);
+        assert!(chains("alice.near", true).is_empty());
+        assert_eq!(
+            detect_address("alice.testnet", true)[0].address,
+            "alice.testnet"
+        );
+        assert!(chains("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f", false).is_empty());
+        assert!(chains("bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq", false).is_empty());
+    }
+}
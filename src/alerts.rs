@@ -0,0 +1,285 @@
+//! Balance threshold alerts, e.g. for a gas wallet running dry or a hot wallet holding more
+//! than it should.
+//!
+//! [`BalanceAlerts`] polls the watched wallets and tells an [`AlertSink`] whenever a token
+//! balance crosses into or out of its low/high band. It fires on crossings only, so a wallet
+//! that stays low alerts once, not on every pass; the first pass after startup alerts for
+//! every balance that is already outside its band.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::api::CircleClient;
+use crate::cancel::{run_every, CancellationToken};
+use crate::error::Result;
+use crate::models::ids::{TokenId, WalletId};
+use crate::models::wallet_balance::WalletBalanceQueryParams;
+
+/// The band one token balance of one wallet should stay in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Threshold {
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    /// Balances below this are low.
+    pub low: Option<Decimal>,
+    /// Balances above this are high.
+    pub high: Option<Decimal>,
+}
+
+impl Threshold {
+    pub fn new(wallet_id: WalletId, token_id: TokenId) -> Self {
+        Threshold {
+            wallet_id,
+            token_id,
+            low: None,
+            high: None,
+        }
+    }
+
+    pub fn low(mut self, low: Decimal) -> Self {
+        self.low = Some(low);
+        self
+    }
+
+    pub fn high(mut self, high: Decimal) -> Self {
+        self.high = Some(high);
+        self
+    }
+
+    fn level(&self, balance: Decimal) -> AlertKind {
+        match (self.low, self.high) {
+            (Some(low), _) if balance < low => AlertKind::Low,
+            (_, Some(high)) if balance > high => AlertKind::High,
+            _ => AlertKind::Normal,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AlertKind {
+    Low,
+    High,
+    /// Back inside the band after being low or high.
+    Normal,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceAlert {
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub kind: AlertKind,
+    pub balance: Decimal,
+    pub low: Option<Decimal>,
+    pub high: Option<Decimal>,
+    pub at: DateTime<Utc>,
+}
+
+/// Receives every [`BalanceAlert`], e.g. to page someone or post to a chat channel. Any
+/// `Fn(&BalanceAlert)` closure is a sink.
+pub trait AlertSink: Send + Sync {
+    fn alert(&self, alert: &BalanceAlert);
+}
+
+impl<F: Fn(&BalanceAlert) + Send + Sync> AlertSink for F {
+    fn alert(&self, alert: &BalanceAlert) {
+        self(alert)
+    }
+}
+
+/// What one [`BalanceAlerts::check_once`] found.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertReport {
+    pub alerts: Vec<BalanceAlert>,
+    /// Wallets whose balances couldn't be read, with the reason. They are retried next pass.
+    pub failed_wallets: Vec<(WalletId, String)>,
+}
+
+pub struct BalanceAlerts<'a, K> {
+    client: &'a CircleClient,
+    sink: K,
+    thresholds: Vec<Threshold>,
+    levels: Mutex<HashMap<(WalletId, TokenId), AlertKind>>,
+    interval: Duration,
+    cancellation: CancellationToken,
+}
+
+impl<'a, K: AlertSink> BalanceAlerts<'a, K> {
+    pub fn new(client: &'a CircleClient, sink: K) -> Self {
+        BalanceAlerts {
+            client,
+            sink,
+            thresholds: Vec::new(),
+            levels: Mutex::new(HashMap::new()),
+            interval: Duration::from_secs(60),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Adds or replaces the threshold for `threshold`'s wallet and token.
+    pub fn watch(mut self, threshold: Threshold) -> Self {
+        self.thresholds
+            .retain(|x| (x.wallet_id, x.token_id) != (threshold.wallet_id, threshold.token_id));
+        self.thresholds.push(threshold);
+        self
+    }
+
+    /// Time between passes in [`BalanceAlerts::run`].
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Stops [`BalanceAlerts::run`] once `token` is cancelled, after the current pass.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Reads the balances of every watched wallet once and sends the crossings to the sink.
+    pub async fn check_once(&self) -> AlertReport {
+        let mut wallets = self
+            .thresholds
+            .iter()
+            .map(|x| x.wallet_id)
+            .collect::<Vec<_>>();
+        wallets.sort();
+        wallets.dedup();
+        let mut report = AlertReport::default();
+        for wallet_id in wallets {
+            match self.balances(wallet_id).await {
+                Ok(balances) => report.alerts.extend(self.evaluate(wallet_id, &balances)),
+                Err(err) => report.failed_wallets.push((wallet_id, err.to_string())),
+            }
+        }
+        for alert in &report.alerts {
+            self.sink.alert(alert);
+        }
+        report
+    }
+
+    /// Runs [`BalanceAlerts::check_once`] with [`run_every`].
+    pub async fn run<F: FnMut(AlertReport)>(&self, on_report: F) {
+        run_every(
+            self.interval,
+            &self.cancellation,
+            || self.check_once(),
+            on_report,
+        )
+        .await
+    }
+
+    async fn balances(&self, wallet_id: WalletId) -> Result<HashMap<TokenId, Decimal>> {
+        let response = self
+            .client
+            .get_wallet_balance(wallet_id, WalletBalanceQueryParams::default())
+            .await?;
+        Ok(response
+            .token_balances
+            .into_iter()
+            .map(|x| (x.token.id, x.amount))
+            .collect())
+    }
+
+    fn evaluate(
+        &self,
+        wallet_id: WalletId,
+        balances: &HashMap<TokenId, Decimal>,
+    ) -> Vec<BalanceAlert> {
+        let mut levels = self.levels.lock().unwrap();
+        let mut alerts = Vec::new();
+        for threshold in self.thresholds.iter().filter(|x| x.wallet_id == wallet_id) {
+            // Circle leaves tokens the wallet holds none of out of the response.
+            let balance = balances
+                .get(&threshold.token_id)
+                .copied()
+                .unwrap_or_default();
+            let level = threshold.level(balance);
+            let previous = levels.insert((wallet_id, threshold.token_id), level);
+            if previous.unwrap_or(AlertKind::Normal) != level {
+                alerts.push(BalanceAlert {
+                    wallet_id,
+                    token_id: threshold.token_id,
+                    kind: level,
+                    balance,
+                    low: threshold.low,
+                    high: threshold.high,
+                    at: Utc::now(),
+                });
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    const GAS_TOKEN_ID: &str = "e4f549f9-a910-59b1-b5cd-8f972871f5db";
+
+    fn balances(amount: &str) -> Value {
+        json!({
+            "tokenBalances": [{
+                "amount": amount,
+                "updateDate": "2024-03-01T10:00:00Z",
+                "token": {
+                    "id": GAS_TOKEN_ID,
+                    "blockchain": "MATIC-AMOY",
+                    "isNative": true,
+                    "symbol": "MATIC-AMOY",
+                    "createDate": "2023-11-25T14:26:38Z",
+                    "updateDate": "2023-11-25T14:26:38Z"
+                }
+            }]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_alerts_on_crossings() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let received = received.clone();
+            move |alert: &BalanceAlert| received.lock().unwrap().push(alert.kind)
+        };
+        let wallet_id = WalletId(Uuid::new_v4());
+        let threshold = Threshold::new(wallet_id, TokenId::from_str(GAS_TOKEN_ID).unwrap())
+            .low(Decimal::ONE)
+            .high(Decimal::from(100));
+        let alerts = BalanceAlerts::new(&client, sink).watch(threshold);
+
+        for amount in ["5", "0.5", "0.2", "500"] {
+            transport.push_data(balances(amount));
+            alerts.check_once().await;
+        }
+        transport.push_data(json!({ "tokenBalances": [] }));
+        alerts.check_once().await;
+        transport.push_data(balances("10"));
+        alerts.check_once().await;
+        assert_eq!(
+            *received.lock().unwrap(),
+            [
+                AlertKind::Low,
+                AlertKind::High,
+                AlertKind::Low,
+                AlertKind::Normal
+            ]
+        );
+    }
+}
@@ -0,0 +1,328 @@
+//! ERC-20 allowance monitoring, for platforms that pull payments from allowances their users
+//! approved up front.
+//!
+//! [`AllowanceMonitor`] reads `allowance(owner, spender)` of the watched tokens through
+//! [`CircleClient::query_contract`] and tells an [`AllowanceSink`] when an allowance changes
+//! or crosses its low threshold. The first pass reports allowances that are already low but
+//! no changes, since there's nothing to compare against yet.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::json;
+use web3::types::U256;
+
+use crate::api::CircleClient;
+use crate::cancel::{run_every, CancellationToken};
+use crate::error::{CircleError, Result};
+use crate::models::blockchain::Blockchain;
+use crate::models::contract_query::ContractQueryRequest;
+use crate::models::web3_address::Web3Address;
+
+/// One `(owner, spender, token)` allowance to watch.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowanceWatch {
+    pub blockchain: Blockchain,
+    pub token: Web3Address,
+    pub owner: Web3Address,
+    pub spender: Web3Address,
+    /// The token's decimals, to turn the raw allowance into a token amount.
+    pub decimals: u32,
+    /// Allowances below this are low.
+    pub low: Option<Decimal>,
+}
+
+impl AllowanceWatch {
+    pub fn new(
+        blockchain: Blockchain,
+        token: Web3Address,
+        owner: Web3Address,
+        spender: Web3Address,
+        decimals: u32,
+    ) -> Self {
+        AllowanceWatch {
+            blockchain,
+            token,
+            owner,
+            spender,
+            decimals,
+            low: None,
+        }
+    }
+
+    pub fn low(mut self, low: Decimal) -> Self {
+        self.low = Some(low);
+        self
+    }
+
+    fn key(&self) -> WatchKey {
+        (
+            self.blockchain.clone(),
+            self.token.clone(),
+            self.owner.clone(),
+            self.spender.clone(),
+        )
+    }
+
+    fn is_low(&self, allowance: &Allowance) -> bool {
+        match (self.low, allowance.amount) {
+            (Some(low), Some(amount)) => amount < low,
+            _ => false,
+        }
+    }
+}
+
+/// An allowance as read from the chain.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Allowance {
+    /// In token units. `None` if it's too large for a [`Decimal`], as "unlimited" approvals
+    /// of `2^256 - 1` are.
+    pub amount: Option<Decimal>,
+}
+
+impl Allowance {
+    fn from_raw(raw: U256, decimals: u32) -> Self {
+        let amount = match raw > U256::from(i128::MAX as u128) {
+            true => None,
+            false => Decimal::try_from_i128_with_scale(raw.as_u128() as i128, decimals).ok(),
+        };
+        Allowance { amount }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.amount.is_none()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AllowanceEventKind {
+    /// Different from the previous pass, e.g. after a pull or a new approval.
+    Changed,
+    /// Below the low threshold, after being above it or on the first pass.
+    Low,
+    /// Back above the low threshold.
+    Replenished,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowanceEvent {
+    pub kind: AllowanceEventKind,
+    pub blockchain: Blockchain,
+    pub token: Web3Address,
+    pub owner: Web3Address,
+    pub spender: Web3Address,
+    pub allowance: Allowance,
+    pub previous: Option<Allowance>,
+    pub low: Option<Decimal>,
+    pub at: DateTime<Utc>,
+}
+
+/// Receives every [`AllowanceEvent`]. Any `Fn(&AllowanceEvent)` closure is a sink.
+pub trait AllowanceSink: Send + Sync {
+    fn event(&self, event: &AllowanceEvent);
+}
+
+impl<F: Fn(&AllowanceEvent) + Send + Sync> AllowanceSink for F {
+    fn event(&self, event: &AllowanceEvent) {
+        self(event)
+    }
+}
+
+/// What one [`AllowanceMonitor::check_once`] found.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowanceReport {
+    pub events: Vec<AllowanceEvent>,
+    /// Watches whose allowance couldn't be read, with the reason. They are retried next pass.
+    pub failed: Vec<(AllowanceWatch, String)>,
+}
+
+type WatchKey = (Blockchain, Web3Address, Web3Address, Web3Address);
+
+pub struct AllowanceMonitor<'a, K> {
+    client: &'a CircleClient,
+    sink: K,
+    watches: Vec<AllowanceWatch>,
+    allowances: Mutex<HashMap<WatchKey, Allowance>>,
+    interval: Duration,
+    cancellation: CancellationToken,
+}
+
+impl<'a, K: AllowanceSink> AllowanceMonitor<'a, K> {
+    pub fn new(client: &'a CircleClient, sink: K) -> Self {
+        AllowanceMonitor {
+            client,
+            sink,
+            watches: Vec::new(),
+            allowances: Mutex::new(HashMap::new()),
+            interval: Duration::from_secs(300),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Adds or replaces the watch of `watch`'s owner, spender and token.
+    pub fn watch(mut self, watch: AllowanceWatch) -> Self {
+        self.watches.retain(|x| x.key() != watch.key());
+        self.watches.push(watch);
+        self
+    }
+
+    /// Time between passes in [`AllowanceMonitor::run`].
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Stops [`AllowanceMonitor::run`] once `token` is cancelled, after the current pass.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Reads every watched allowance once and sends the events to the sink.
+    pub async fn check_once(&self) -> AllowanceReport {
+        let mut report = AllowanceReport::default();
+        for watch in &self.watches {
+            match self.allowance(watch).await {
+                Ok(allowance) => report.events.extend(self.evaluate(watch, allowance)),
+                Err(err) => report.failed.push((watch.clone(), err.to_string())),
+            }
+        }
+        for event in &report.events {
+            self.sink.event(event);
+        }
+        report
+    }
+
+    /// Runs [`AllowanceMonitor::check_once`] with [`run_every`].
+    pub async fn run<F: FnMut(AllowanceReport)>(&self, on_report: F) {
+        run_every(
+            self.interval,
+            &self.cancellation,
+            || self.check_once(),
+            on_report,
+        )
+        .await
+    }
+
+    async fn allowance(&self, watch: &AllowanceWatch) -> Result<Allowance> {
+        let request = ContractQueryRequest::new(
+            watch.blockchain.clone(),
+            watch.token.clone(),
+            "allowance(address,address)",
+        )
+        .abi_parameters(vec![
+            json!(watch.owner.to_string()),
+            json!(watch.spender.to_string()),
+        ]);
+        let response = self.client.query_contract(request).await?;
+        let data = response.output_data.ok_or(CircleError::ValueError)?;
+        let data = data.strip_prefix("0x").unwrap_or(&data);
+        if data.is_empty() || data.len() > 64 {
+            return Err(CircleError::ValueError);
+        }
+        let raw = U256::from_str_radix(data, 16).map_err(|_| CircleError::ValueError)?;
+        Ok(Allowance::from_raw(raw, watch.decimals))
+    }
+
+    fn evaluate(&self, watch: &AllowanceWatch, allowance: Allowance) -> Vec<AllowanceEvent> {
+        let previous = self
+            .allowances
+            .lock()
+            .unwrap()
+            .insert(watch.key(), allowance);
+        let was_low = previous.is_some_and(|x| watch.is_low(&x));
+        let mut kinds = Vec::new();
+        if previous.is_some_and(|x| x != allowance) {
+            kinds.push(AllowanceEventKind::Changed);
+        }
+        match (was_low, watch.is_low(&allowance)) {
+            (false, true) => kinds.push(AllowanceEventKind::Low),
+            (true, false) => kinds.push(AllowanceEventKind::Replenished),
+            _ => {}
+        }
+        kinds
+            .into_iter()
+            .map(|kind| AllowanceEvent {
+                kind,
+                blockchain: watch.blockchain.clone(),
+                token: watch.token.clone(),
+                owner: watch.owner.clone(),
+                spender: watch.spender.clone(),
+                allowance,
+                previous,
+                low: watch.low,
+                at: Utc::now(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    fn output(raw: U256) -> serde_json::Value {
+        json!({ "outputData": format!("0x{:064x}", raw) })
+    }
+
+    #[tokio::test]
+    async fn test_allowance_events() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let received = received.clone();
+            move |event: &AllowanceEvent| received.lock().unwrap().push(event.kind)
+        };
+        let address = |x| Web3Address::from_str(x).unwrap();
+        let watch = AllowanceWatch::new(
+            Blockchain::EthSepolia,
+            address("0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238"),
+            address("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3"),
+            address("0x0000000000000000000000000000000000000001"),
+            6,
+        )
+        .low(Decimal::from(100));
+        let monitor = AllowanceMonitor::new(&client, sink).watch(watch);
+
+        // 500 USDC, then 500 again, 50 after a pull, then an unlimited approval.
+        for raw in [
+            U256::from(500_000_000u64),
+            U256::from(500_000_000u64),
+            U256::from(50_000_000u64),
+            U256::MAX,
+        ] {
+            transport.push_data(output(raw));
+            monitor.check_once().await;
+        }
+        assert_eq!(
+            *received.lock().unwrap(),
+            [
+                AllowanceEventKind::Changed,
+                AllowanceEventKind::Low,
+                AllowanceEventKind::Changed,
+                AllowanceEventKind::Replenished
+            ]
+        );
+
+        let body = transport.last_request().unwrap().body.unwrap();
+        assert_eq!(body["abiFunctionSignature"], "allowance(address,address)");
+        assert_eq!(
+            body["abiParameters"][0],
+            "0x6e5eaf34c73d1cd0be4e24f923b97cf38e10d1f3"
+        );
+    }
+}
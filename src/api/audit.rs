@@ -0,0 +1,235 @@
+use chrono::{DateTime, Utc};
+use reqwest::{Method, StatusCode};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::error::{CircleError, Result};
+
+/// The body fields copied into [`AuditRecord::summary`]. Anything else, such as the entity
+/// secret ciphertext or a message to sign, stays out of the trail.
+const SUMMARY_FIELDS: &[&str] = &[
+    "amount",
+    "amounts",
+    "blockchain",
+    "blockchains",
+    "contractAddress",
+    "count",
+    "destination",
+    "destinationAddress",
+    "name",
+    "refId",
+    "source",
+    "tokenAddress",
+    "tokenId",
+    "walletId",
+    "walletIds",
+    "walletSetId",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditPhase {
+    /// Recorded before anything is sent.
+    Started,
+    /// Recorded once the call has finished, retries included.
+    Finished,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Succeeded,
+    Failed {
+        /// The status of the last response, `None` if no response was received at all.
+        status: Option<StatusCode>,
+        error: String,
+    },
+}
+
+/// One mutating call (wallet creation, transfers, signing, ...). The `Started` and `Finished`
+/// records of a call share the same `id`.
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+    pub id: Uuid,
+    pub phase: AuditPhase,
+    pub timestamp: DateTime<Utc>,
+    /// Who made the call: the key id of the API key (`TEST_API_KEY:<key id>:<secret>`), or a
+    /// fingerprint of the key if it isn't in that format. Never the key itself.
+    pub actor: String,
+    pub method: Method,
+    /// The path with ids replaced by `{id}`, e.g. `w3s/developer/transactions/transfer`.
+    pub endpoint: String,
+    /// The full path including ids, e.g. `w3s/developer/wallets/<uuid>/sign/message`.
+    pub path: String,
+    pub idempotency_key: Option<String>,
+    /// What the call was for: the amount, token, destination, wallet, name and similar fields of the
+    /// request body, as an object. Empty for calls without a body.
+    pub summary: Map<String, Value>,
+    /// `None` for `Started` records.
+    pub outcome: Option<AuditOutcome>,
+}
+
+/// Receives an [`AuditRecord`] before and after every call that isn't a `GET`.
+///
+/// An error from the `Started` record aborts the call before anything is sent, so a sink that
+/// can't persist its trail fails closed. Errors from `Finished` records are only logged, since
+/// the call has already happened by then. Making the trail tamper-evident (hash chaining,
+/// append-only storage) is up to the sink.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord) -> Result<()>;
+}
+
+pub(crate) fn actor(api_key: &str) -> String {
+    match api_key.split(':').collect::<Vec<_>>()[..] {
+        [_, key_id, _] if !key_id.is_empty() => key_id.to_string(),
+        _ => format!("sha256:{}", &hex::encode(Sha256::digest(api_key))[..16]),
+    }
+}
+
+fn summary(body: &Value) -> Map<String, Value> {
+    SUMMARY_FIELDS
+        .iter()
+        .filter_map(|x| Some((x.to_string(), body.get(*x)?.clone())))
+        .collect()
+}
+
+impl AuditRecord {
+    pub(crate) fn started(api_key: &str, method: &Method, url: &str, body: &Value) -> Self {
+        let path = url.split('?').next().unwrap_or(url);
+        let path = path.split("/v1/").nth(1).unwrap_or(path);
+        AuditRecord {
+            id: Uuid::new_v4(),
+            phase: AuditPhase::Started,
+            timestamp: Utc::now(),
+            actor: actor(api_key),
+            method: method.clone(),
+            endpoint: super::instrument::endpoint(url).0,
+            path: path.to_string(),
+            idempotency_key: body
+                .get("idempotencyKey")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            summary: summary(body),
+            outcome: None,
+        }
+    }
+
+    pub(crate) fn finished<T>(
+        &self,
+        result: &std::result::Result<T, CircleError>,
+        status: Option<StatusCode>,
+    ) -> Self {
+        let outcome = match result {
+            Ok(_) => AuditOutcome::Succeeded,
+            Err(err) => AuditOutcome::Failed {
+                status,
+                error: err.to_string(),
+            },
+        };
+        AuditRecord {
+            phase: AuditPhase::Finished,
+            timestamp: Utc::now(),
+            outcome: Some(outcome),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[derive(Default)]
+    struct Trail(Mutex<Vec<AuditRecord>>);
+
+    impl AuditSink for Arc<Trail> {
+        fn record(&self, record: &AuditRecord) -> Result<()> {
+            self.0.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+    }
+
+    struct Unavailable;
+
+    impl AuditSink for Unavailable {
+        fn record(&self, _record: &AuditRecord) -> Result<()> {
+            Err(CircleError::ValueError)
+        }
+    }
+
+    #[test]
+    fn test_actor() {
+        assert_eq!(actor("TEST_API_KEY:abc123:secret"), "abc123");
+        assert!(actor("opaque").starts_with("sha256:"));
+        assert!(!actor("opaque").contains("opaque"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let body = serde_json::json!({
+            "amounts": ["1.5"],
+            "destinationAddress": "0xa51c9c604b79a0fadbfed35dd576ca1bce71da0a",
+            "entitySecretCiphertext": "secret",
+            "tokenId": "7adb2b7d-c9cd-5164-b2d4-b73b088274dc",
+            "walletId": "01234567-89ab-cdef-0123-456789abcdef",
+        });
+        let fields = summary(&body);
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields["amounts"], serde_json::json!(["1.5"]));
+        assert!(!fields.contains_key("entitySecretCiphertext"));
+        assert!(summary(&Value::Null).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_audit_sink() {
+        let transport = MockTransport::new();
+        let trail = Arc::new(Trail::default());
+        let client = transport
+            .client_builder()
+            .api_key("TEST_API_KEY:abc123:secret")
+            .audit(trail.clone())
+            .build()
+            .await
+            .unwrap();
+        transport.push_error(StatusCode::BAD_REQUEST, 2, "Invalid parameter");
+
+        let name = "treasury".to_string();
+        assert!(client
+            .create_wallet_set(Uuid::new_v4(), name)
+            .await
+            .is_err());
+        assert!(client.list_wallets(Default::default()).await.is_err());
+
+        let trail = trail.0.lock().unwrap().clone();
+        assert_eq!(trail.len(), 2);
+        assert_eq!(trail[0].phase, AuditPhase::Started);
+        assert_eq!(trail[0].id, trail[1].id);
+        assert_eq!(trail[0].actor, "abc123");
+        assert_eq!(trail[0].endpoint, "w3s/developer/walletSets");
+        assert!(trail[0].idempotency_key.is_some());
+        assert_eq!(trail[0].summary["name"], "treasury");
+        assert!(matches!(
+            trail[1].outcome,
+            Some(AuditOutcome::Failed {
+                status: Some(StatusCode::BAD_REQUEST),
+                ..
+            })
+        ));
+
+        let client = transport
+            .client_builder()
+            .audit(Unavailable)
+            .build()
+            .await
+            .unwrap();
+        let sent = transport.requests().len();
+        let name = "treasury".to_string();
+        assert!(client
+            .create_wallet_set(Uuid::new_v4(), name)
+            .await
+            .is_err());
+        assert_eq!(transport.requests().len(), sent);
+    }
+}
@@ -0,0 +1,51 @@
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::ach_bank_account::{AchBankAccount, AchBankAccountCreateRequestBuilder};
+use crate::models::sepa_bank_account::{
+    SepaBankAccount, SepaBankAccountCreateRequestBuilder, SepaInstructions,
+};
+
+impl CircleClient {
+    pub async fn create_ach_bank_account<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: AchBankAccountCreateRequestBuilder,
+    ) -> Result<AchBankAccount> {
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
+        let url = format!("{}banks/ach", self.base_url);
+        let request = request.build(idempotency_key);
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+
+    pub async fn get_ach_bank_account(&self, bank_account_id: Uuid) -> Result<AchBankAccount> {
+        let url = format!("{}banks/ach/{}", self.base_url, bank_account_id);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+
+    pub async fn create_sepa_bank_account<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: SepaBankAccountCreateRequestBuilder,
+    ) -> Result<SepaBankAccount> {
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
+        let url = format!("{}banks/sepa", self.base_url);
+        let request = request.build(idempotency_key);
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+
+    pub async fn get_sepa_bank_account(&self, bank_account_id: Uuid) -> Result<SepaBankAccount> {
+        let url = format!("{}banks/sepa/{}", self.base_url, bank_account_id);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+
+    pub async fn get_sepa_instructions(&self, bank_account_id: Uuid) -> Result<SepaInstructions> {
+        let url = format!(
+            "{}banks/sepa/{}/instructions",
+            self.base_url, bank_account_id
+        );
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+}
@@ -0,0 +1,391 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use reqwest::Client;
+
+use crate::api::audit::AuditSink;
+use crate::api::cache::ResponseCache;
+use crate::api::circuit_breaker::CircuitBreaker;
+use crate::api::debug_log::{DebugLogger, LoggingTransport};
+use crate::api::environment::Environment;
+use crate::api::failover::BaseUrls;
+use crate::api::idempotency::{IdempotencyPolicy, IdempotencyStore, InMemoryIdempotencyStore};
+use crate::api::inner::ClientInner;
+use crate::api::metrics::MetricsSink;
+use crate::api::middleware::{ClientMiddleware, MiddlewareChain};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::api::pool::PoolConfig;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::api::proxy::ProxyConfig;
+use crate::api::rate_limit::RateLimiter;
+use crate::api::retry::RetryPolicy;
+use crate::api::simulation::SimulationLog;
+use crate::api::tenant::TenantCredentials;
+use crate::api::timeouts::Timeouts;
+use crate::api::transport::{ReqwestTransport, Transport};
+use crate::api::version::{ApiResource, ApiVersion, ApiVersions};
+use crate::api::CircleClient;
+use crate::error::{CircleError, Result};
+
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Default)]
+pub struct CircleClientBuilder {
+    api_key: Option<String>,
+    entity_secret: Option<String>,
+    environment: Environment,
+    fallback_environments: Vec<Environment>,
+    failure_threshold: Option<u32>,
+    failover_cooldown: Option<Duration>,
+    failover_mutating: bool,
+    http_client: Option<Client>,
+    transport: Option<Arc<dyn Transport>>,
+    middleware: MiddlewareChain,
+    application: Option<String>,
+    headers: HeaderMap,
+    timeouts: Timeouts,
+    #[cfg(not(target_arch = "wasm32"))]
+    proxy: Option<ProxyConfig>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool: PoolConfig,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    page_prefetch: usize,
+    circuit_breaker: Option<CircuitBreaker>,
+    response_cache: Option<ResponseCache>,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    debug_logging: Option<DebugLogger>,
+    audit: Option<Arc<dyn AuditSink>>,
+    idempotency: Option<Arc<dyn IdempotencyStore>>,
+    idempotency_policy: IdempotencyPolicy,
+    api_versions: ApiVersions,
+    simulation: Option<SimulationLog>,
+}
+
+impl CircleClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn api_key<S: Into<String>>(mut self, api_key: S) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn entity_secret<S: Into<String>>(mut self, entity_secret: S) -> Self {
+        self.entity_secret = Some(entity_secret.into());
+        self
+    }
+
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Shortcut for [`Environment::Custom`].
+    pub fn base_url(mut self, base_url: &str) -> Result<Self> {
+        self.environment = Environment::custom(base_url)?;
+        Ok(self)
+    }
+
+    /// The API version of every resource without its own, `v1` by default.
+    pub fn api_version(mut self, version: ApiVersion) -> Self {
+        self.api_versions.default = version;
+        self
+    }
+
+    /// Pins `resource` to `version`, whatever the default is.
+    pub fn resource_api_version(mut self, resource: ApiResource, version: ApiVersion) -> Self {
+        self.api_versions.resources.insert(resource, version);
+        self
+    }
+
+    /// Adds a secondary API root (mirror, regional proxy, ...) that read traffic fails over to
+    /// when the ones before it are unhealthy. Fallbacks are tried in the order they're added.
+    pub fn fallback_environment(mut self, environment: Environment) -> Self {
+        self.fallback_environments.push(environment);
+        self
+    }
+
+    /// Consecutive failures after which an endpoint is skipped. Defaults to 1.
+    pub fn failover_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = Some(failure_threshold);
+        self
+    }
+
+    /// How long an unhealthy endpoint is skipped before it's tried again. Defaults to 30s.
+    pub fn failover_cooldown(mut self, cooldown: Duration) -> Self {
+        self.failover_cooldown = Some(cooldown);
+        self
+    }
+
+    /// Also fail over mutating calls. Only enable this when every fallback forwards to the same
+    /// Circle entity, otherwise idempotency keys won't be honored across endpoints.
+    pub fn failover_mutating(mut self, failover_mutating: bool) -> Self {
+        self.failover_mutating = failover_mutating;
+        self
+    }
+
+    /// Uses a pre-configured client (shared connection pool, custom TLS roots, DNS resolver, ...)
+    /// for every call, including the entity public key fetch, instead of creating a new one.
+    pub fn http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Replaces the HTTP layer altogether, e.g. with a different HTTP stack or a test double.
+    /// Takes precedence over [`CircleClientBuilder::http_client`] and the proxy and connect
+    /// timeout settings.
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Identifies the integration in the User-Agent, e.g. `acme-payouts/2.3.1`, which is sent
+    /// as `circle_api/<version> acme-payouts/2.3.1`.
+    pub fn application<S: Into<String>>(mut self, application: S) -> Self {
+        self.application = Some(application.into());
+        self
+    }
+
+    /// Sends a header with every request. Headers set with [`crate::api::RequestOptions`] take
+    /// precedence.
+    pub fn default_header(mut self, name: &str, value: &str) -> Result<Self> {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| CircleError::ValueError)?;
+        let value = HeaderValue::from_str(value).map_err(|_| CircleError::ValueError)?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Reports every call's endpoint, duration, status and retries to `sink`.
+    pub fn metrics<M: MetricsSink + 'static>(mut self, sink: M) -> Self {
+        self.metrics = Some(Arc::new(sink));
+        self
+    }
+
+    /// Records every call that isn't a `GET` to `sink`, before and after it is made.
+    pub fn audit<A: AuditSink + 'static>(mut self, sink: A) -> Self {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Where [`CircleClient::idempotency_key`] keeps its keys. In memory by default; use a
+    /// persistent store to reuse keys across restarts.
+    pub fn idempotency_store<S: IdempotencyStore + 'static>(mut self, store: S) -> Self {
+        self.idempotency = Some(Arc::new(store));
+        self
+    }
+
+    /// Whether mutating calls passed `None` as their idempotency key get a generated one
+    /// (the default) or fail.
+    pub fn idempotency_policy(mut self, policy: IdempotencyPolicy) -> Self {
+        self.idempotency_policy = policy;
+        self
+    }
+
+    /// Simulation mode: calls that would change anything are validated and recorded to `log`
+    /// but never sent, and get a synthetic response instead. Transfers are fee-estimated
+    /// first. Reads still go to Circle, so staging and what-if tooling see real data.
+    pub fn simulate(mut self, log: SimulationLog) -> Self {
+        self.simulation = Some(log);
+        self
+    }
+
+    /// Logs every request and response with secrets redacted, see [`DebugLogger`].
+    pub fn debug_logging(mut self, logger: DebugLogger) -> Self {
+        self.debug_logging = Some(logger);
+        self
+    }
+
+    /// Appends a middleware; they run in the order they're added.
+    pub fn middleware<M: ClientMiddleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Ignored on wasm32, where the browser owns connection setup.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.connect = Some(timeout);
+        self
+    }
+
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.request = Some(timeout);
+        self
+    }
+
+    pub fn deadline(mut self, timeout: Duration) -> Self {
+        self.timeouts.deadline = Some(timeout);
+        self
+    }
+
+    /// Routes all traffic through a proxy. Like the connect timeout, this only applies to HTTP
+    /// clients built by the crate, not to one passed to [`CircleClientBuilder::http_client`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Tunes the connection pool of the HTTP client built by the crate. Like the proxy, this has
+    /// no effect on an injected client or transport.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool(mut self, pool: PoolConfig) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_max_idle_per_host(mut self, max_idle_per_host: usize) -> Self {
+        self.pool.max_idle_per_host = Some(max_idle_per_host);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn rate_limit(self, requests_per_second: f64, burst: u32) -> Self {
+        self.rate_limiter(RateLimiter::new(requests_per_second, burst))
+    }
+
+    /// Uses an existing limiter, e.g. to share one budget between several clients.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// How many pages the `list_*_stream` methods fetch ahead of the one being read, so the
+    /// next page is usually there when it's needed. 0 by default: pages are fetched as the
+    /// stream reaches them.
+    pub fn page_prefetch(mut self, pages: usize) -> Self {
+        self.page_prefetch = pages;
+        self
+    }
+
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Caches slow-changing GETs such as token details. Copies of the client share the cache.
+    pub fn response_cache(mut self, cache: ResponseCache) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    pub async fn build(self) -> Result<CircleClient> {
+        let api_key = self.api_key.ok_or(CircleError::MissingField("api_key"))?;
+        let circle_entity_secret = self
+            .entity_secret
+            .ok_or(CircleError::MissingField("entity_secret"))?;
+        let base_url = self.environment.base_url();
+        let mut base_urls = BaseUrls::new(base_url.clone());
+        for environment in &self.fallback_environments {
+            base_urls.push(environment.base_url());
+        }
+        if let Some(failure_threshold) = self.failure_threshold {
+            base_urls.failure_threshold(failure_threshold);
+        }
+        if let Some(cooldown) = self.failover_cooldown {
+            base_urls.cooldown(cooldown);
+        }
+        base_urls.failover_mutating(self.failover_mutating);
+        let (client, transport) = match (self.transport, self.http_client) {
+            (Some(transport), _) => (None, transport),
+            (None, Some(client)) => (Some(client.clone()), Self::reqwest_transport(client)),
+            (None, None) => {
+                #[allow(unused_mut)]
+                let mut builder = Client::builder();
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    if let Some(connect) = self.timeouts.connect {
+                        builder = builder.connect_timeout(connect);
+                    }
+                    if let Some(proxy) = &self.proxy {
+                        builder = builder.proxy(proxy.to_reqwest()?);
+                    }
+                    builder = self.pool.apply(builder);
+                }
+                let client = builder.build()?;
+                (Some(client.clone()), Self::reqwest_transport(client))
+            }
+        };
+        let transport: Arc<dyn Transport> = match self.debug_logging {
+            Some(logger) => Arc::new(LoggingTransport {
+                inner: transport,
+                logger,
+            }),
+            None => transport,
+        };
+
+        let mut headers = self.headers;
+        if !headers.contains_key(USER_AGENT) {
+            let user_agent = match &self.application {
+                Some(application) => format!("{} {}", DEFAULT_USER_AGENT, application),
+                None => DEFAULT_USER_AGENT.to_string(),
+            };
+            let user_agent =
+                HeaderValue::from_str(&user_agent).map_err(|_| CircleError::ValueError)?;
+            headers.insert(USER_AGENT, user_agent);
+        }
+
+        let public_key = CircleClient::fetch_public_key(
+            transport.as_ref(),
+            &self.middleware,
+            &base_url,
+            &api_key,
+            &headers,
+            self.timeouts.request,
+        )
+        .await?;
+
+        Ok(CircleClient {
+            inner: Arc::new(ClientInner {
+                base_url,
+                base_urls,
+                environment: self.environment,
+                credentials: Arc::new(RwLock::new(TenantCredentials::new(
+                    api_key,
+                    circle_entity_secret,
+                ))),
+                client,
+                transport,
+                middleware: self.middleware,
+                headers,
+                public_key,
+                timeouts: self.timeouts,
+                retry_policy: self.retry_policy,
+                rate_limiter: self.rate_limiter,
+                #[cfg(any(feature = "wallets", feature = "transactions", feature = "payments"))]
+                page_prefetch: self.page_prefetch,
+                circuit_breaker: self.circuit_breaker,
+                request_id_capture: None,
+                raw_capture: None,
+                header_capture: None,
+                token_decimals: Default::default(),
+                response_cache: self.response_cache,
+                metrics: self.metrics,
+                stats: Default::default(),
+                audit: self.audit,
+                idempotency: self
+                    .idempotency
+                    .unwrap_or_else(|| Arc::new(InMemoryIdempotencyStore::default())),
+                idempotency_policy: self.idempotency_policy,
+                api_versions: self.api_versions,
+                simulation: self.simulation,
+            }),
+        })
+    }
+
+    fn reqwest_transport(client: Client) -> Arc<dyn Transport> {
+        Arc::new(ReqwestTransport::new(client))
+    }
+}
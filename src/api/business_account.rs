@@ -0,0 +1,12 @@
+use reqwest::Method;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::business_balance::BusinessBalancesResponse;
+
+impl CircleClient {
+    pub async fn get_business_balances(&self) -> Result<BusinessBalancesResponse> {
+        let url = format!("{}businessAccount/balances", self.base_url);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+}
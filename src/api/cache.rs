@@ -0,0 +1,209 @@
+//! Caching of slow-changing GETs, so hot paths that look up the same token or the entity config
+//! on every request don't call Circle every time.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rsa::sha2::{Digest, Sha256};
+use serde_json::Value;
+
+use crate::api::CircleClient;
+use crate::runtime::Instant;
+
+/// The GETs a [`ResponseCache`] can keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CachedEndpoint {
+    /// `GET /w3s/tokens/{id}`, as used by [`CircleClient::get_token_details`].
+    Tokens,
+    /// `GET /w3s/config/entity`.
+    EntityConfig,
+}
+
+impl CachedEndpoint {
+    fn of(path: &str) -> Option<Self> {
+        match path {
+            "w3s/config/entity" => Some(CachedEndpoint::EntityConfig),
+            _ if path.starts_with("w3s/tokens/") => Some(CachedEndpoint::Tokens),
+            _ => None,
+        }
+    }
+}
+
+/// Where a [`ResponseCache`] keeps responses, e.g. to share them between processes through
+/// Redis. Keys start with the endpoint's name, so whole endpoints can be dropped by prefix.
+pub trait ResponseCacheStore: Send + Sync {
+    /// The value stored for `key`, unless it has expired.
+    fn get(&self, key: &str) -> Option<Value>;
+
+    fn insert(&self, key: &str, value: Value, ttl: Duration);
+
+    /// Drops every key starting with `prefix`.
+    fn remove_prefix(&self, prefix: &str);
+}
+
+impl<S: ResponseCacheStore> ResponseCacheStore for Arc<S> {
+    fn get(&self, key: &str) -> Option<Value> {
+        (**self).get(key)
+    }
+
+    fn insert(&self, key: &str, value: Value, ttl: Duration) {
+        (**self).insert(key, value, ttl)
+    }
+
+    fn remove_prefix(&self, prefix: &str) {
+        (**self).remove_prefix(prefix)
+    }
+}
+
+/// The default [`ResponseCacheStore`], private to the client and its clones.
+#[derive(Debug, Default)]
+pub struct InMemoryResponseCacheStore(Mutex<HashMap<String, (Value, Instant)>>);
+
+impl ResponseCacheStore for InMemoryResponseCacheStore {
+    fn get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.0.lock().unwrap();
+        match entries.get(key) {
+            Some((value, expires)) if Instant::now() < *expires => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: &str, value: Value, ttl: Duration) {
+        let mut entries = self.0.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, (_, expires)| now < *expires);
+        entries.insert(key.to_string(), (value, now + ttl));
+    }
+
+    fn remove_prefix(&self, prefix: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .retain(|key, _| !key.starts_with(prefix));
+    }
+}
+
+/// Which GETs the client caches, for how long, and where. Responses are kept per API key, so
+/// tenants never see each other's. Off unless set with
+/// [`CircleClientBuilder::response_cache`](crate::api::CircleClientBuilder::response_cache).
+#[derive(Clone)]
+pub struct ResponseCache {
+    store: Arc<dyn ResponseCacheStore>,
+    ttls: HashMap<CachedEndpoint, Duration>,
+}
+
+impl ResponseCache {
+    /// Token details for an hour and the entity config for five minutes, in memory.
+    pub fn new() -> Self {
+        ResponseCache {
+            store: Arc::new(InMemoryResponseCacheStore::default()),
+            ttls: HashMap::from([
+                (CachedEndpoint::Tokens, Duration::from_secs(3600)),
+                (CachedEndpoint::EntityConfig, Duration::from_secs(300)),
+            ]),
+        }
+    }
+
+    pub fn store<S: ResponseCacheStore + 'static>(mut self, store: S) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// How long responses of `endpoint` are kept. [`Duration::ZERO`] stops caching it.
+    pub fn ttl(mut self, endpoint: CachedEndpoint, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    /// The key and TTL of a GET of `path` by `api_key`, if it's cached.
+    pub(crate) fn entry(&self, api_key: &str, path: &str) -> Option<(String, Duration)> {
+        let endpoint = CachedEndpoint::of(path)?;
+        let ttl = *self.ttls.get(&endpoint)?;
+        if ttl.is_zero() {
+            return None;
+        }
+        let tenant = &hex::encode(Sha256::digest(api_key.as_bytes()))[..16];
+        Some((format!("{:?}:{}:{}", endpoint, tenant, path), ttl))
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<Value> {
+        self.store.get(key)
+    }
+
+    pub(crate) fn insert(&self, key: &str, value: Value, ttl: Duration) {
+        self.store.insert(key, value, ttl)
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircleClient {
+    /// Drops the cached responses of `endpoint`, for every tenant, e.g. after changing the
+    /// entity config. Does nothing without a [`ResponseCache`].
+    pub fn invalidate_cache(&self, endpoint: CachedEndpoint) {
+        if let Some(cache) = &self.response_cache {
+            cache.store.remove_prefix(&format!("{:?}:", endpoint));
+        }
+    }
+
+    /// Drops every cached response.
+    pub fn clear_cache(&self) {
+        for endpoint in [CachedEndpoint::Tokens, CachedEndpoint::EntityConfig] {
+            self.invalidate_cache(endpoint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_response_cache() {
+        let transport = MockTransport::new();
+        let client = transport
+            .client_builder()
+            .response_cache(ResponseCache::new().ttl(CachedEndpoint::Tokens, Duration::ZERO))
+            .build()
+            .await
+            .unwrap();
+        let calls = transport.requests().len();
+        let config = json!({ "appId": "app" });
+        transport.push_data(config.clone());
+        transport.push_data(config.clone());
+        transport.push_data(config);
+
+        client.get_entity_config().await.unwrap();
+        client.get_entity_config().await.unwrap();
+        assert_eq!(transport.requests().len(), calls + 1);
+
+        // Health checks always go to Circle.
+        client.ping().await.unwrap();
+        assert_eq!(transport.requests().len(), calls + 2);
+
+        client.invalidate_cache(CachedEndpoint::EntityConfig);
+        client.get_entity_config().await.unwrap();
+        assert_eq!(transport.requests().len(), calls + 3);
+
+        // Other tenants and uncached endpoints miss.
+        let cache = ResponseCache::new().ttl(CachedEndpoint::Tokens, Duration::ZERO);
+        assert_ne!(
+            cache.entry("key", "w3s/config/entity").unwrap().0,
+            cache.entry("other", "w3s/config/entity").unwrap().0
+        );
+        assert!(cache.entry("key", "w3s/tokens/abc").is_none());
+        assert!(cache.entry("key", "w3s/wallets").is_none());
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::api::decode::{redact, REDACTED_FIELDS};
+use crate::api::transport::{
+    HttpRequest, HttpResponse, Transport, TransportError, TransportErrorKind, TransportFuture,
+};
+use crate::error::{CircleError, Result};
+
+/// Set to `record` to hit the real API and rewrite cassettes; anything else replays.
+pub const CASSETTE_MODE_ENV: &str = "CIRCLE_CASSETTE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    response: String,
+    #[serde(skip)]
+    used: bool,
+}
+
+/// A [`Transport`] that records live traffic to a JSON cassette, or replays a cassette without
+/// touching the network.
+///
+/// Interactions are matched on method and path (hosts are stripped, so a cassette recorded
+/// against sandbox replays against any base URL), in the order they were recorded. Request
+/// headers aren't stored at all, and entity secret ciphertexts and bank details in request
+/// bodies are replaced with `[REDACTED]`.
+pub struct CassetteTransport {
+    mode: CassetteMode,
+    path: PathBuf,
+    inner: Option<Arc<dyn Transport>>,
+    redacted_fields: Vec<String>,
+    cassette: Mutex<Cassette>,
+}
+
+impl CassetteTransport {
+    /// Forwards to `inner` and writes every interaction to `path`, replacing any previous
+    /// recording.
+    pub fn record<P: AsRef<Path>, T: Transport + 'static>(path: P, inner: T) -> Self {
+        CassetteTransport {
+            mode: CassetteMode::Record,
+            path: path.as_ref().to_path_buf(),
+            inner: Some(Arc::new(inner)),
+            redacted_fields: REDACTED_FIELDS.iter().map(|x| x.to_string()).collect(),
+            cassette: Mutex::new(Cassette::default()),
+        }
+    }
+
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let cassette = serde_json::from_slice(&std::fs::read(path.as_ref())?)?;
+        Ok(CassetteTransport {
+            mode: CassetteMode::Replay,
+            path: path.as_ref().to_path_buf(),
+            inner: None,
+            redacted_fields: REDACTED_FIELDS.iter().map(|x| x.to_string()).collect(),
+            cassette: Mutex::new(cassette),
+        })
+    }
+
+    /// Records through `inner` when [`CASSETTE_MODE_ENV`] is `record`, replays otherwise.
+    pub fn from_env<P: AsRef<Path>, T: Transport + 'static>(path: P, inner: T) -> Result<Self> {
+        match std::env::var(CASSETTE_MODE_ENV).as_deref() {
+            Ok("record") => Ok(Self::record(path, inner)),
+            _ => Self::replay(path),
+        }
+    }
+
+    /// Also redacts `field` wherever it appears in recorded request bodies.
+    pub fn redact<S: Into<String>>(mut self, field: S) -> Self {
+        self.redacted_fields.push(field.into());
+        self
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    fn path_of(url: &str) -> String {
+        match reqwest::Url::parse(url) {
+            Ok(url) => match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            },
+            Err(_) => url.to_string(),
+        }
+    }
+
+    fn replay_one(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let method = request.method.to_string();
+        let path = Self::path_of(&request.url);
+        let mut cassette = self.cassette.lock().unwrap();
+        let interaction = cassette
+            .interactions
+            .iter_mut()
+            .find(|x| !x.used && x.method == method && x.path == path)
+            .ok_or_else(|| {
+                TransportError::new(
+                    TransportErrorKind::Other,
+                    format!("no recorded interaction for {} {}", method, path),
+                )
+            })?;
+        interaction.used = true;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &interaction.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+        Ok(HttpResponse {
+            status: StatusCode::from_u16(interaction.status)
+                .map_err(|_| CircleError::ValueError)?,
+            headers,
+            body: interaction.response.clone().into_bytes(),
+        })
+    }
+
+    fn save(
+        &self,
+        method: &Method,
+        url: &str,
+        body: Option<&[u8]>,
+        response: &HttpResponse,
+    ) -> Result<()> {
+        let body = match body {
+            Some(body) => {
+                let mut body = serde_json::from_slice(body).unwrap_or(Value::Null);
+                redact(&mut body, &self.redacted_fields);
+                Some(body)
+            }
+            None => None,
+        };
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| *name != reqwest::header::SET_COOKIE)
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let mut cassette = self.cassette.lock().unwrap();
+        cassette.interactions.push(Interaction {
+            method: method.to_string(),
+            path: Self::path_of(url),
+            body,
+            status: response.status.as_u16(),
+            headers,
+            response: String::from_utf8_lossy(&response.body).into_owned(),
+            used: false,
+        });
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&*cassette)?)?;
+        Ok(())
+    }
+}
+
+impl Transport for CassetteTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let inner = match (&self.mode, &self.inner) {
+                (CassetteMode::Record, Some(inner)) => inner,
+                _ => return self.replay_one(&request),
+            };
+            let method = request.method.clone();
+            let url = request.url.clone();
+            let body = request.body.clone();
+            let response = inner.send(request).await?;
+            self.save(&method, &url, body.as_deref(), &response)?;
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::decode::REDACTED;
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let path = std::env::temp_dir().join(format!("cassette-{}.json", uuid::Uuid::new_v4()));
+        let mock = MockTransport::new();
+        mock.push_data(serde_json::json!({ "id": "1" }));
+        let recorder = CassetteTransport::record(&path, mock);
+        let request = HttpRequest {
+            method: Method::POST,
+            url: "https://api-sandbox.circle.com/v1/w3s/developer/walletSets".to_string(),
+            headers: HeaderMap::new(),
+            body: Some(br#"{"name":"a","entitySecretCipherText":"secret"}"#.to_vec()),
+            timeout: None,
+        };
+        recorder.send(request.clone()).await.unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("secret"));
+        assert!(saved.contains(REDACTED));
+
+        let replayer = CassetteTransport::replay(&path).unwrap();
+        let mut request = request;
+        request.url = "http://127.0.0.1:1234/v1/w3s/developer/walletSets".to_string();
+        let response = replayer.send(request.clone()).await.unwrap();
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(response.headers.contains_key("x-request-id"));
+        assert!(replayer.send(request).await.is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
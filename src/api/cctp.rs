@@ -0,0 +1,42 @@
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+
+use crate::api::{CircleClient, HttpRequest};
+use crate::error::{CircleError, Result};
+use crate::models::cctp::CctpMessagesResponse;
+
+impl CircleClient {
+    /// Looks up the CCTP messages emitted by a burn transaction on Circle's attestation service.
+    /// Returns `None` while the service hasn't indexed the transaction yet.
+    pub async fn get_cctp_messages(
+        &self,
+        attestation_base_url: &str,
+        source_domain: u32,
+        tx_hash: &str,
+    ) -> Result<Option<CctpMessagesResponse>> {
+        let url = format!(
+            "{}v1/messages/{}/{}",
+            attestation_base_url, source_domain, tx_hash
+        );
+        // The attestation service is public; the API key must not leak to it.
+        let request = HttpRequest {
+            method: Method::GET,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+            timeout: self.timeouts.request,
+        };
+        let response = self.transport.send(request).await?;
+        if response.status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status.is_success() {
+            return Err(CircleError::UnexpectedStatus(
+                super::request_id(&response.headers),
+                response.status,
+            ));
+        }
+        let messages = super::decode::decode(&response.body, None)?;
+        Ok(Some(messages))
+    }
+}
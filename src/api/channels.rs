@@ -0,0 +1,12 @@
+use reqwest::Method;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::channel::Channel;
+
+impl CircleClient {
+    pub async fn list_channels(&self) -> Result<Vec<Channel>> {
+        let url = format!("{}channels", self.base_url);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+}
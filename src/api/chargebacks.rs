@@ -0,0 +1,35 @@
+use futures::Stream;
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::api::paginate::paginate;
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::chargeback::{Chargeback, ChargebackListQueryParams};
+use crate::models::pagination::Paginated;
+
+impl CircleClient {
+    pub async fn list_chargebacks(
+        &self,
+        query_params: ChargebackListQueryParams,
+    ) -> Result<Paginated<Chargeback>> {
+        let url = format!("{}chargebacks", self.base_url);
+        self.send_request(Method::GET, url, Some(query_params))
+            .await
+    }
+
+    /// Every item matching `query_params`, following page cursors as the stream is polled.
+    pub fn list_chargebacks_stream(
+        &self,
+        query_params: ChargebackListQueryParams,
+    ) -> impl Stream<Item = Result<Chargeback>> + '_ {
+        paginate(query_params, self.page_prefetch, move |params| {
+            self.list_chargebacks(params)
+        })
+    }
+
+    pub async fn get_chargeback(&self, chargeback_id: Uuid) -> Result<Chargeback> {
+        let url = format!("{}chargebacks/{}", self.base_url, chargeback_id);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+}
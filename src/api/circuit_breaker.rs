@@ -0,0 +1,140 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::{CircleError, Result};
+use crate::runtime::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { consecutive_failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probe_started: Instant },
+}
+
+/// Stops sending requests after `failure_threshold` consecutive server errors or connection
+/// failures. While open, calls fail immediately with [`CircleError::CircuitOpen`]; after
+/// `open_duration` a single probe request is let through, and its outcome either closes the
+/// circuit again or re-opens it.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Arc<Mutex<BreakerState>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            state: Arc::new(Mutex::new(BreakerState::Closed {
+                consecutive_failures: 0,
+            })),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap() {
+            BreakerState::Closed { .. } => CircuitState::Closed,
+            BreakerState::Open { until } if until <= Instant::now() => CircuitState::HalfOpen,
+            BreakerState::Open { .. } => CircuitState::Open,
+            BreakerState::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    pub(crate) fn allow(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match *state {
+            BreakerState::Closed { .. } => Ok(()),
+            BreakerState::Open { until } if until > now => Err(CircleError::CircuitOpen {
+                retry_in: until - now,
+            }),
+            // A probe that never reported back (e.g. its future was dropped) must not keep the
+            // circuit half-open forever.
+            BreakerState::HalfOpen { probe_started }
+                if now.duration_since(probe_started) < self.open_duration =>
+            {
+                Err(CircleError::CircuitOpen {
+                    retry_in: self.open_duration - now.duration_since(probe_started),
+                })
+            }
+            _ => {
+                *state = BreakerState::HalfOpen { probe_started: now };
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn record_success(&self) {
+        *self.state.lock().unwrap() = BreakerState::Closed {
+            consecutive_failures: 0,
+        };
+    }
+
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        let consecutive_failures = match *state {
+            BreakerState::Closed {
+                consecutive_failures,
+            } => consecutive_failures + 1,
+            _ => self.failure_threshold,
+        };
+        *state = if consecutive_failures >= self.failure_threshold {
+            BreakerState::Open {
+                until: Instant::now() + self.open_duration,
+            }
+        } else {
+            BreakerState::Closed {
+                consecutive_failures,
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.allow().is_ok());
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.allow(),
+            Err(CircleError::CircuitOpen { .. })
+        ));
+    }
+
+    #[test]
+    fn test_half_open_probe() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.allow().is_ok());
+        breaker.record_failure();
+        assert!(breaker.allow().is_ok());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_single_probe_while_half_open() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        breaker.record_failure();
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.allow().is_ok());
+        assert!(breaker.allow().is_err());
+    }
+}
@@ -0,0 +1,16 @@
+use reqwest::Method;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::contract_query::{ContractQueryRequest, ContractQueryResponse};
+
+impl CircleClient {
+    /// Calls a view function without sending a transaction.
+    pub async fn query_contract(
+        &self,
+        request: ContractQueryRequest,
+    ) -> Result<ContractQueryResponse> {
+        let url = format!("{}w3s/contracts/query", self.base_url);
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+}
@@ -0,0 +1,48 @@
+use futures::Stream;
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::api::paginate::paginate;
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::conversion::{
+    Conversion, ConversionCreateRequestBuilder, ConversionListQueryParams,
+};
+use crate::models::pagination::Paginated;
+
+impl CircleClient {
+    pub async fn create_conversion<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: ConversionCreateRequestBuilder,
+    ) -> Result<Conversion> {
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
+        let url = format!("{}conversions", self.base_url);
+        let request = request.build(idempotency_key);
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+
+    pub async fn get_conversion(&self, conversion_id: Uuid) -> Result<Conversion> {
+        let url = format!("{}conversions/{}", self.base_url, conversion_id);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+
+    pub async fn list_conversions(
+        &self,
+        query_params: ConversionListQueryParams,
+    ) -> Result<Paginated<Conversion>> {
+        let url = format!("{}conversions", self.base_url);
+        self.send_request(Method::GET, url, Some(query_params))
+            .await
+    }
+
+    /// Every item matching `query_params`, following page cursors as the stream is polled.
+    pub fn list_conversions_stream(
+        &self,
+        query_params: ConversionListQueryParams,
+    ) -> impl Stream<Item = Result<Conversion>> + '_ {
+        paginate(query_params, self.page_prefetch, move |params| {
+            self.list_conversions(params)
+        })
+    }
+}
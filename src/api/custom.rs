@@ -0,0 +1,126 @@
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::{CircleError, Result};
+
+/// A call to an endpoint the crate doesn't cover yet, sent with
+/// [`CircleClient::custom_request`]. It gets the same authentication, retries, limits and
+/// error handling as the built-in calls.
+#[derive(Debug, Clone)]
+pub struct CustomRequest {
+    method: Method,
+    path: String,
+    query: Option<String>,
+    body: Option<Map<String, Value>>,
+    idempotency_key: Option<Option<Uuid>>,
+    entity_secret: bool,
+}
+
+impl CustomRequest {
+    /// `path` is relative to the API root, e.g. `w3s/wallets`.
+    pub fn new<S: Into<String>>(method: Method, path: S) -> Self {
+        CustomRequest {
+            method,
+            path: path.into().trim_start_matches('/').to_string(),
+            query: None,
+            body: None,
+            idempotency_key: None,
+            entity_secret: false,
+        }
+    }
+
+    /// Anything that serializes to a flat map, e.g. `json!({ "pageSize": 5 })`.
+    pub fn query<Q: Serialize>(mut self, query: &Q) -> Result<Self> {
+        self.query = Some(serde_qs::to_string(query)?);
+        Ok(self)
+    }
+
+    pub fn body(mut self, body: Value) -> Result<Self> {
+        match body {
+            Value::Object(body) => self.body = Some(body),
+            _ => Err(CircleError::Validation(vec![
+                "the body must be a JSON object".to_string(),
+            ]))?,
+        }
+        Ok(self)
+    }
+
+    /// Adds an `idempotencyKey` to the body, generated unless given and the client's
+    /// [`IdempotencyPolicy`](crate::api::IdempotencyPolicy) allows it.
+    pub fn idempotency_key<K: Into<Option<Uuid>>>(mut self, idempotency_key: K) -> Self {
+        self.idempotency_key = Some(idempotency_key.into());
+        self
+    }
+
+    /// Adds a freshly encrypted `entitySecretCiphertext` to the body.
+    pub fn entity_secret(mut self) -> Self {
+        self.entity_secret = true;
+        self
+    }
+}
+
+impl CircleClient {
+    /// Sends `request` and decodes the `data` of the response as `T`; use
+    /// [`serde_json::Value`] to get it untyped.
+    pub async fn custom_request<T: DeserializeOwned>(&self, request: CustomRequest) -> Result<T> {
+        let mut url = format!("{}{}", self.base_url, request.path);
+        if let Some(query) = request.query.filter(|x| !x.is_empty()) {
+            url = format!("{}?{}", url, query);
+        }
+        let mut body = request.body;
+        if let Some(idempotency_key) = request.idempotency_key {
+            let idempotency_key = self.resolve_idempotency_key(idempotency_key)?;
+            body.get_or_insert_with(Map::new).insert(
+                "idempotencyKey".to_string(),
+                idempotency_key.to_string().into(),
+            );
+        }
+        if request.entity_secret {
+            body.get_or_insert_with(Map::new).insert(
+                "entitySecretCiphertext".to_string(),
+                self.entity_secret_ciphertext()?.into(),
+            );
+        }
+        self.send_request(request.method, url, body).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_custom_request() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        transport.push_data(json!({ "staked": "12.5" }));
+        let key = Uuid::new_v4();
+        let request = CustomRequest::new(Method::POST, "/w3s/developer/staking")
+            .query(&json!({ "dryRun": true }))
+            .unwrap()
+            .body(json!({ "amount": "12.5" }))
+            .unwrap()
+            .idempotency_key(key)
+            .entity_secret();
+
+        let response: Value = client.custom_request(request).await.unwrap();
+        assert_eq!(response["staked"], "12.5");
+        let sent = transport.last_request().unwrap();
+        assert_eq!(sent.method, Method::POST);
+        assert!(sent.url.ends_with("/v1/w3s/developer/staking?dryRun=true"));
+        let body = sent.body.unwrap();
+        assert_eq!(body["amount"], "12.5");
+        assert_eq!(body["idempotencyKey"], key.to_string());
+        assert!(body["entitySecretCiphertext"].is_string());
+        assert!(CustomRequest::new(Method::POST, "x")
+            .body(json!([]))
+            .is_err());
+    }
+}
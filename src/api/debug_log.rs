@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use log::debug;
+use serde_json::Value;
+
+use crate::api::decode::{REDACTED, REDACTED_FIELDS};
+use crate::api::transport::{HttpRequest, HttpResponse, Transport, TransportFuture};
+
+/// How much of each body is logged.
+const MAX_BODY_LENGTH: usize = 4096;
+
+/// Logs every attempt at `debug` level under the `circle_api::http` target: method, URL,
+/// status, latency and bodies.
+///
+/// Headers are never logged, so API keys can't leak. Entity secret ciphertexts, bank details
+/// and, unless turned off with [`DebugLogger::redact_addresses`], blockchain addresses are
+/// replaced with `[REDACTED]` in bodies and query strings.
+#[derive(Debug, Clone)]
+pub struct DebugLogger {
+    redacted_fields: Vec<String>,
+    redact_addresses: bool,
+    bodies: bool,
+}
+
+impl Default for DebugLogger {
+    fn default() -> Self {
+        DebugLogger {
+            redacted_fields: REDACTED_FIELDS.iter().map(|x| x.to_string()).collect(),
+            redact_addresses: true,
+            bodies: true,
+        }
+    }
+}
+
+impl DebugLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also redacts `field` wherever it appears.
+    pub fn redact<S: Into<String>>(mut self, field: S) -> Self {
+        self.redacted_fields.push(field.into());
+        self
+    }
+
+    /// Whether fields named `address` or ending in `Address` are redacted. On by default.
+    pub fn redact_addresses(mut self, redact_addresses: bool) -> Self {
+        self.redact_addresses = redact_addresses;
+        self
+    }
+
+    /// Whether bodies are logged at all. On by default.
+    pub fn bodies(mut self, bodies: bool) -> Self {
+        self.bodies = bodies;
+        self
+    }
+
+    fn is_redacted(&self, field: &str) -> bool {
+        self.redacted_fields.iter().any(|x| x == field)
+            || (self.redact_addresses && (field == "address" || field.ends_with("Address")))
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.is_redacted(key) {
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_value(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|x| self.redact_value(x)),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn url(&self, url: &str) -> String {
+        let Some((path, query)) = url.split_once('?') else {
+            return url.to_string();
+        };
+        let query = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) if self.is_redacted(key) => format!("{}={}", key, REDACTED),
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>();
+        format!("{}?{}", path, query.join("&"))
+    }
+
+    pub(crate) fn body(&self, body: Option<&[u8]>) -> String {
+        let body = match body {
+            Some(body) if self.bodies && !body.is_empty() => body,
+            Some(body) if !body.is_empty() => return format!("<{} bytes>", body.len()),
+            _ => return "<empty>".to_string(),
+        };
+        let mut body = match serde_json::from_slice::<Value>(body) {
+            Ok(mut value) => {
+                self.redact_value(&mut value);
+                value.to_string()
+            }
+            Err(_) => return format!("<{} bytes, not JSON>", body.len()),
+        };
+        if body.len() > MAX_BODY_LENGTH {
+            let mut end = MAX_BODY_LENGTH;
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            body.truncate(end);
+            body.push_str("...");
+        }
+        body
+    }
+}
+
+/// Wraps the client's transport when [`crate::api::CircleClientBuilder::debug_logging`] is
+/// set, so the logged latency is that of the attempt alone.
+pub(crate) struct LoggingTransport {
+    pub(crate) inner: Arc<dyn Transport>,
+    pub(crate) logger: DebugLogger,
+}
+
+impl Transport for LoggingTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let method = request.method.clone();
+            let url = self.logger.url(&request.url);
+            debug!(
+                target: "circle_api::http",
+                "--> {} {} {}",
+                method,
+                url,
+                self.logger.body(request.body.as_deref())
+            );
+            let started = crate::runtime::Instant::now();
+            let result = self.inner.send(request).await;
+            let elapsed = started.elapsed();
+            match &result {
+                Ok(HttpResponse { status, body, .. }) => debug!(
+                    target: "circle_api::http",
+                    "<-- {} {} {} in {:?} {}",
+                    status.as_u16(),
+                    method,
+                    url,
+                    elapsed,
+                    self.logger.body(Some(body))
+                ),
+                Err(err) => debug!(
+                    target: "circle_api::http",
+                    "<-- {} {} failed in {:?}: {}",
+                    method,
+                    url,
+                    elapsed,
+                    err
+                ),
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_debug_logger_redacts() {
+        let logger = DebugLogger::new().redact("refId");
+        let body = br#"{"walletId":"w","destinationAddress":"0xabc","refId":"r","entitySecretCiphertext":"c","amounts":["1"]}"#;
+        let body = logger.body(Some(body));
+        assert_eq!(
+            body,
+            r#"{"amounts":["1"],"destinationAddress":"[REDACTED]","entitySecretCiphertext":"[REDACTED]","refId":"[REDACTED]","walletId":"w"}"#
+        );
+        assert_eq!(
+            logger.url("https://api.circle.com/v1/w3s/transactions?address=0xabc&pageSize=5"),
+            "https://api.circle.com/v1/w3s/transactions?address=[REDACTED]&pageSize=5"
+        );
+
+        let logger = DebugLogger::new().redact_addresses(false).bodies(false);
+        assert!(!logger.is_redacted("destinationAddress"));
+        assert_eq!(logger.body(Some(b"{}")), "<2 bytes>");
+        assert_eq!(logger.body(None), "<empty>");
+    }
+}
@@ -0,0 +1,169 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::{CircleError, Result};
+use crate::models::RequestId;
+
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// Fields that are never written to logs, error messages or recorded cassettes.
+pub(crate) const REDACTED_FIELDS: &[&str] = &[
+    "entitySecretCipherText",
+    "entitySecretCiphertext",
+    "plaidProcessorToken",
+    "accountNumber",
+    "iban",
+];
+
+/// How much of the body is kept on a [`DecodeError`].
+const MAX_BODY_LENGTH: usize = 2048;
+
+/// A response that didn't match the crate's model, usually because Circle added or changed a
+/// field. Carries enough context to file a bug without reproducing the call.
+#[derive(Debug)]
+pub struct DecodeError {
+    request_id: Option<RequestId>,
+    path: String,
+    body: String,
+    source: serde_json::Error,
+}
+
+impl DecodeError {
+    pub fn request_id(&self) -> Option<RequestId> {
+        self.request_id
+    }
+
+    /// Where in the body decoding failed, e.g. `data.wallets[3].state`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The response body with secrets redacted, truncated to 2 KiB.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "failed to decode response at {}: {} (body: {})",
+            self.path, self.source, self.body
+        )
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(body: &[u8], request_id: Option<RequestId>) -> Result<T> {
+    let error = |path: String, source: serde_json::Error| {
+        CircleError::DecodeError(DecodeError {
+            request_id,
+            path,
+            body: redacted_body(body),
+            source,
+        })
+    };
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    #[cfg(feature = "strict")]
+    let mut ignored = Vec::new();
+    #[cfg(feature = "strict")]
+    let mut on_ignored = |path: serde_ignored::Path| ignored.push(path.to_string());
+    #[cfg(feature = "strict")]
+    let deserializer = serde_ignored::Deserializer::new(deserializer, &mut on_ignored);
+    let value = serde_path_to_error::deserialize(deserializer)
+        .map_err(|err| error(err.path().to_string(), err.into_inner()))?;
+    #[cfg(feature = "strict")]
+    if let Some(path) = ignored.into_iter().next() {
+        let source = <serde_json::Error as serde::de::Error>::custom("unknown field");
+        return Err(error(path, source));
+    }
+    Ok(value)
+}
+
+fn redacted_body(body: &[u8]) -> String {
+    let mut body = match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            redact(&mut value, REDACTED_FIELDS);
+            value.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    };
+    if body.len() > MAX_BODY_LENGTH {
+        let mut end = MAX_BODY_LENGTH;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+        body.push_str("...");
+    }
+    body
+}
+
+pub(crate) fn redact<S: AsRef<str>>(value: &mut Value, fields: &[S]) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if fields.iter().any(|x| x.as_ref() == key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value, fields);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|x| redact(x, fields)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Wallet {
+        id: String,
+        state: u32,
+    }
+
+    #[test]
+    fn test_decode_error() {
+        let body = br#"{"wallets":[{"id":"a","state":1},{"id":"b","state":"LIVE","iban":"DE89"}]}"#;
+        let err = decode::<std::collections::HashMap<String, Vec<Wallet>>>(body, None).unwrap_err();
+        let CircleError::DecodeError(err) = err else {
+            panic!("unexpected error: {:?}", err);
+        };
+        assert_eq!(err.path(), "wallets[1].state");
+        assert!(err.body().contains("[REDACTED]"));
+        assert!(!err.body().contains("DE89"));
+
+        let body = "x".repeat(MAX_BODY_LENGTH * 2);
+        let err = decode::<Value>(body.as_bytes(), None).unwrap_err();
+        let CircleError::DecodeError(err) = err else {
+            panic!("unexpected error: {:?}", err);
+        };
+        assert_eq!(err.body().len(), MAX_BODY_LENGTH + 3);
+    }
+
+    #[test]
+    #[cfg(feature = "strict")]
+    fn test_strict_rejects_unknown_fields() {
+        let body = br#"{"wallets":[{"id":"a","state":1,"color":"red"}]}"#;
+        let err = decode::<std::collections::HashMap<String, Vec<Wallet>>>(body, None).unwrap_err();
+        let CircleError::DecodeError(err) = err else {
+            panic!("unexpected error: {:?}", err);
+        };
+        assert_eq!(err.path(), "wallets.0.color");
+    }
+}
@@ -0,0 +1,152 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::RequestId;
+
+/// A response together with what the HTTP layer said about it, from
+/// [`CircleClient::envelope`].
+#[derive(Debug, Clone)]
+pub struct Envelope<T> {
+    pub data: T,
+    pub status: StatusCode,
+    pub request_id: Option<RequestId>,
+    pub rate_limit: RateLimit,
+    /// The whole call, including retries and rate limit waits.
+    pub latency: Duration,
+    pub headers: HeaderMap,
+}
+
+impl<T> Envelope<T> {
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Envelope<U> {
+        Envelope {
+            data: f(self.data),
+            status: self.status,
+            request_id: self.request_id,
+            rate_limit: self.rate_limit,
+            latency: self.latency,
+            headers: self.headers,
+        }
+    }
+}
+
+/// The `X-RateLimit-*` headers of a response, where present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// Seconds until the window resets.
+    pub reset: Option<u64>,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let number = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse().ok();
+        RateLimit {
+            limit: number("X-RateLimit-Limit"),
+            remaining: number("X-RateLimit-Remaining"),
+            reset: number("X-RateLimit-Reset"),
+        }
+    }
+}
+
+/// Keeps the status and headers of the last successful response.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HeaderCapture(Arc<Mutex<Option<(StatusCode, HeaderMap)>>>);
+
+impl HeaderCapture {
+    pub(crate) fn set(&self, status: StatusCode, headers: &HeaderMap) {
+        *self.0.lock().unwrap() = Some((status, headers.clone()));
+    }
+
+    fn take(&self) -> Option<(StatusCode, HeaderMap)> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+impl CircleClient {
+    /// Runs `call` on a copy of the client and wraps its result with the metadata of the last
+    /// response:
+    ///
+    /// ```no_run
+    /// # async fn run(client: circle_api::api::CircleClient, id: circle_api::models::ids::WalletId) -> circle_api::error::Result<()> {
+    /// let response = client.envelope(|c| async move { c.get_wallet(id).await }).await?;
+    /// println!("{:?} took {:?}", response.request_id, response.latency);
+    /// let wallet = response.into_inner().wallet;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn envelope<T, F, Fut>(&self, call: F) -> Result<Envelope<T>>
+    where
+        F: FnOnce(CircleClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let capture = HeaderCapture::default();
+        let client = self.modified(|inner| inner.header_capture = Some(capture.clone()));
+        let started = crate::runtime::Instant::now();
+        let data = call(client).await?;
+        let latency = started.elapsed();
+        let (status, headers) = capture.take().unwrap_or_default();
+        Ok(Envelope {
+            data,
+            status,
+            request_id: super::request_id(&headers),
+            rate_limit: RateLimit::from_headers(&headers),
+            latency,
+            headers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::{HttpResponse, MockTransport};
+
+    #[tokio::test]
+    async fn test_envelope() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let request_id = uuid::Uuid::new_v4();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Request-Id",
+            HeaderValue::from_str(&request_id.to_string()).unwrap(),
+        );
+        headers.insert("X-RateLimit-Limit", HeaderValue::from_static("10"));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("7"));
+        transport.push_response(HttpResponse {
+            status: StatusCode::OK,
+            headers,
+            body: json!({ "data": [] }).to_string().into_bytes(),
+        });
+
+        let response = client
+            .envelope(|c| async move { c.list_channels().await })
+            .await
+            .unwrap();
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(response.request_id, Some(request_id));
+        assert_eq!(
+            response.rate_limit,
+            RateLimit {
+                limit: Some(10),
+                remaining: Some(7),
+                reset: None
+            }
+        );
+        assert!(response.into_inner().is_empty());
+    }
+}
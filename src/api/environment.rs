@@ -0,0 +1,55 @@
+use reqwest::Url;
+
+use crate::error::{CircleError, Result};
+
+pub const PRODUCTION_BASE_URL: &str = "https://api.circle.com/v1/";
+pub const SANDBOX_BASE_URL: &str = "https://api-sandbox.circle.com/v1/";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Environment {
+    #[default]
+    Production,
+    Sandbox,
+    /// Any other API root, such as a local mock server. The URL should include the version
+    /// prefix, e.g. `http://localhost:8080/v1/`.
+    Custom(Url),
+}
+
+impl Environment {
+    pub fn custom(base_url: &str) -> Result<Self> {
+        let url = Url::parse(base_url).map_err(|_| CircleError::ValueError)?;
+        Ok(Environment::Custom(url))
+    }
+
+    pub fn base_url(&self) -> String {
+        match self {
+            Environment::Production => PRODUCTION_BASE_URL.to_string(),
+            Environment::Sandbox => SANDBOX_BASE_URL.to_string(),
+            Environment::Custom(url) => {
+                let mut base_url = url.to_string();
+                if !base_url.ends_with('/') {
+                    base_url.push('/');
+                }
+                base_url
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_base_url() {
+        assert_eq!(Environment::default().base_url(), PRODUCTION_BASE_URL);
+        assert_eq!(Environment::Sandbox.base_url(), SANDBOX_BASE_URL);
+        assert_eq!(
+            Environment::custom("http://localhost:8080/v1")
+                .unwrap()
+                .base_url(),
+            "http://localhost:8080/v1/"
+        );
+        assert!(Environment::custom("not a url").is_err());
+    }
+}
@@ -0,0 +1,120 @@
+/// Error codes from Circle's error envelope. Codes without a variant come back as `Unknown`,
+/// so matching on this never fails for codes Circle adds later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircleErrorCode {
+    /// `-1`: something went wrong on Circle's side.
+    InternalError,
+    ParameterMissing,
+    ParameterInvalid,
+    Forbidden,
+    Unauthorized,
+    /// `9`: the request can be retried as-is.
+    Retry,
+    CustomerSuspended,
+    /// `11`: the resource is still being processed.
+    Pending,
+    InvalidNumberFormat,
+    /// `155101`: the destination isn't valid on the transfer's blockchain, e.g. an address for
+    /// another chain.
+    InvalidDestinationChain,
+    /// `156004`.
+    WalletNotFound,
+    Unknown(i64),
+}
+
+/// The group of Circle's error reference a code belongs to, for handling codes that have no
+/// variant of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCodeFamily {
+    /// Codes below 1000, shared by all of Circle's APIs.
+    General,
+    /// `155xxx`: Programmable Wallets request errors, e.g. an unsupported blockchain.
+    Request,
+    /// `156xxx`: wallet and wallet set errors.
+    Wallet,
+    /// `177xxx`: transaction errors.
+    Transaction,
+    Other,
+}
+
+impl CircleErrorCode {
+    pub fn code(&self) -> i64 {
+        match self {
+            CircleErrorCode::InternalError => -1,
+            CircleErrorCode::ParameterMissing => 1,
+            CircleErrorCode::ParameterInvalid => 2,
+            CircleErrorCode::Forbidden => 3,
+            CircleErrorCode::Unauthorized => 4,
+            CircleErrorCode::Retry => 9,
+            CircleErrorCode::CustomerSuspended => 10,
+            CircleErrorCode::Pending => 11,
+            CircleErrorCode::InvalidNumberFormat => 12,
+            CircleErrorCode::InvalidDestinationChain => 155101,
+            CircleErrorCode::WalletNotFound => 156004,
+            CircleErrorCode::Unknown(code) => *code,
+        }
+    }
+
+    pub fn family(&self) -> ErrorCodeFamily {
+        match self.code() {
+            code if code < 1000 => ErrorCodeFamily::General,
+            155000..=155999 => ErrorCodeFamily::Request,
+            156000..=156999 => ErrorCodeFamily::Wallet,
+            177000..=177999 => ErrorCodeFamily::Transaction,
+            _ => ErrorCodeFamily::Other,
+        }
+    }
+}
+
+impl From<i64> for CircleErrorCode {
+    fn from(code: i64) -> Self {
+        match code {
+            -1 => CircleErrorCode::InternalError,
+            1 => CircleErrorCode::ParameterMissing,
+            2 => CircleErrorCode::ParameterInvalid,
+            3 => CircleErrorCode::Forbidden,
+            4 => CircleErrorCode::Unauthorized,
+            9 => CircleErrorCode::Retry,
+            10 => CircleErrorCode::CustomerSuspended,
+            11 => CircleErrorCode::Pending,
+            12 => CircleErrorCode::InvalidNumberFormat,
+            155101 => CircleErrorCode::InvalidDestinationChain,
+            156004 => CircleErrorCode::WalletNotFound,
+            code => CircleErrorCode::Unknown(code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_error_code_round_trip() {
+        for code in [-1, 1, 2, 3, 4, 9, 10, 11, 12, 155101, 156004, 177001] {
+            assert_eq!(CircleErrorCode::from(code).code(), code);
+        }
+        assert_eq!(CircleErrorCode::from(2), CircleErrorCode::ParameterInvalid);
+        assert_eq!(
+            CircleErrorCode::from(177001),
+            CircleErrorCode::Unknown(177001)
+        );
+        assert_eq!(
+            CircleErrorCode::from(155101),
+            CircleErrorCode::InvalidDestinationChain
+        );
+
+        let families =
+            [-1, 155101, 156001, 177001, 999999].map(|x| CircleErrorCode::from(x).family());
+        assert_eq!(
+            families,
+            [
+                ErrorCodeFamily::General,
+                ErrorCodeFamily::Request,
+                ErrorCodeFamily::Wallet,
+                ErrorCodeFamily::Transaction,
+                ErrorCodeFamily::Other
+            ]
+        );
+    }
+}
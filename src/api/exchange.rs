@@ -0,0 +1,12 @@
+use reqwest::Method;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::exchange_rate::{CurrencyPair, ExchangeRate};
+
+impl CircleClient {
+    pub async fn get_exchange_rates(&self, pair: CurrencyPair) -> Result<ExchangeRate> {
+        let url = format!("{}exchange/rates/{}", self.base_url, pair);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Method;
+
+use crate::api::version;
+use crate::runtime::Instant;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+/// An ordered list of API roots. Requests go to the first healthy one; an endpoint that fails
+/// `failure_threshold` times in a row is skipped for `cooldown` before being tried again.
+/// Only reads fail over unless mutating calls are explicitly allowed.
+#[derive(Debug, Clone)]
+pub struct BaseUrls {
+    urls: Vec<String>,
+    health: Arc<Mutex<Vec<EndpointHealth>>>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    failover_mutating: bool,
+}
+
+impl BaseUrls {
+    pub(crate) fn new(primary: String) -> Self {
+        BaseUrls {
+            urls: vec![primary],
+            health: Arc::new(Mutex::new(vec![EndpointHealth::default()])),
+            failure_threshold: 1,
+            cooldown: Duration::from_secs(30),
+            failover_mutating: false,
+        }
+    }
+
+    pub(crate) fn push(&mut self, url: String) {
+        self.urls.push(url);
+        self.health.lock().unwrap().push(EndpointHealth::default());
+    }
+
+    pub(crate) fn failure_threshold(&mut self, failure_threshold: u32) {
+        self.failure_threshold = failure_threshold.max(1);
+    }
+
+    pub(crate) fn cooldown(&mut self, cooldown: Duration) {
+        self.cooldown = cooldown;
+    }
+
+    pub(crate) fn failover_mutating(&mut self, failover_mutating: bool) {
+        self.failover_mutating = failover_mutating;
+    }
+
+    pub fn primary(&self) -> &str {
+        &self.urls[0]
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Index of the endpoint to use for the next attempt of a `method` call.
+    pub(crate) fn select(&self, method: &Method) -> usize {
+        if self.urls.len() == 1 || (*method != Method::GET && !self.failover_mutating) {
+            return 0;
+        }
+        let now = Instant::now();
+        let health = self.health.lock().unwrap();
+        health
+            .iter()
+            .position(|x| x.unhealthy_until.map_or(true, |until| until <= now))
+            .unwrap_or_else(|| {
+                // Everything is cooling down: use whichever endpoint recovers first.
+                health
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, x)| x.unhealthy_until)
+                    .map(|(index, _)| index)
+                    .unwrap_or(0)
+            })
+    }
+
+    pub(crate) fn record(&self, index: usize, success: bool) {
+        let mut health = self.health.lock().unwrap();
+        let Some(endpoint) = health.get_mut(index) else {
+            return;
+        };
+        if success {
+            *endpoint = EndpointHealth::default();
+        } else {
+            endpoint.consecutive_failures += 1;
+            if endpoint.consecutive_failures >= self.failure_threshold {
+                endpoint.unhealthy_until = Some(Instant::now() + self.cooldown);
+            }
+        }
+    }
+
+    /// Moves a URL built against the primary root, on any API version, onto the endpoint at
+    /// `index`.
+    pub(crate) fn rebase(&self, url: &str, index: usize) -> String {
+        match (index, url.strip_prefix(version::root(self.primary()))) {
+            (0, _) | (_, None) => url.to_string(),
+            (_, Some(path)) => format!("{}{}", version::root(&self.urls[index]), path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn base_urls() -> BaseUrls {
+        let mut base_urls = BaseUrls::new("https://api.circle.com/v1/".to_string());
+        base_urls.push("https://mirror.internal/v1/".to_string());
+        base_urls
+    }
+
+    #[test]
+    fn test_fails_over_reads() {
+        let base_urls = base_urls();
+        assert_eq!(base_urls.select(&Method::GET), 0);
+        base_urls.record(0, false);
+        assert_eq!(base_urls.select(&Method::GET), 1);
+        assert_eq!(base_urls.select(&Method::POST), 0);
+        assert_eq!(
+            base_urls.rebase("https://api.circle.com/v1/w3s/wallets", 1),
+            "https://mirror.internal/v1/w3s/wallets"
+        );
+        base_urls.record(0, true);
+        assert_eq!(base_urls.select(&Method::GET), 0);
+    }
+
+    #[test]
+    fn test_all_unhealthy() {
+        let mut base_urls = base_urls();
+        base_urls.failover_mutating(true);
+        base_urls.record(1, false);
+        base_urls.record(0, false);
+        assert_eq!(base_urls.select(&Method::POST), 1);
+    }
+}
@@ -0,0 +1,17 @@
+use reqwest::Method;
+use serde_json::Value;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::faucet::FaucetRequest;
+
+impl CircleClient {
+    /// Sends testnet tokens to an address. Only available in sandbox and rate limited by
+    /// Circle.
+    pub async fn request_testnet_tokens(&self, request: FaucetRequest) -> Result<()> {
+        let url = format!("{}faucet/drips", self.base_url);
+        self.send_request::<Option<Value>>(Method::POST, url, Some(request))
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::StatusCode;
+
+use crate::api::transport::{
+    HttpRequest, HttpResponse, Transport, TransportError, TransportErrorKind, TransportFuture,
+};
+
+/// How many faults a [`FaultInjectingTransport`] has injected so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InjectedFaults {
+    pub delayed: u64,
+    pub server_errors: u64,
+    pub rate_limits: u64,
+    pub connection_resets: u64,
+    pub malformed_bodies: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Fault {
+    ServerError,
+    RateLimit,
+    ConnectionReset,
+    MalformedBody,
+}
+
+/// A [`Transport`] wrapper that makes calls fail at configurable rates, for chaos-testing code
+/// built on the client: added latency, `503`s, `429`s, connection resets and truncated
+/// response bodies.
+///
+/// Each request is delayed with the latency probability, then gets at most one failure, picked
+/// by a single roll against the failure probabilities in the order above. Server errors, rate
+/// limits and resets never reach `inner`; a malformed body replaces the body of a real
+/// response, so the call *did* happen. Clones share the on/off switch, the random generator
+/// and the counters.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use circle_api::api::{FaultInjectingTransport, ReqwestTransport};
+/// let transport = FaultInjectingTransport::new(ReqwestTransport::new(reqwest::Client::new()))
+///     .latency(0.2, Duration::from_millis(500))
+///     .server_errors(0.05)
+///     .rate_limits(0.05, Duration::from_secs(1))
+///     .seed(7);
+/// ```
+#[derive(Clone)]
+pub struct FaultInjectingTransport {
+    inner: Arc<dyn Transport>,
+    latency: (f64, Duration),
+    server_errors: f64,
+    rate_limits: (f64, Duration),
+    connection_resets: f64,
+    malformed_bodies: f64,
+    enabled: Arc<AtomicBool>,
+    rng: Arc<Mutex<StdRng>>,
+    injected: Arc<Mutex<InjectedFaults>>,
+}
+
+impl FaultInjectingTransport {
+    /// Wraps `inner` without injecting anything yet.
+    pub fn new<T: Transport + 'static>(inner: T) -> Self {
+        FaultInjectingTransport {
+            inner: Arc::new(inner),
+            latency: (0.0, Duration::ZERO),
+            server_errors: 0.0,
+            rate_limits: (0.0, Duration::ZERO),
+            connection_resets: 0.0,
+            malformed_bodies: 0.0,
+            enabled: Arc::new(AtomicBool::new(true)),
+            rng: Arc::new(Mutex::new(StdRng::from_entropy())),
+            injected: Default::default(),
+        }
+    }
+
+    /// Delays a `probability` share of requests by `delay`.
+    pub fn latency(mut self, probability: f64, delay: Duration) -> Self {
+        self.latency = (probability.clamp(0.0, 1.0), delay);
+        self
+    }
+
+    /// Answers a `probability` share of requests with `503 Service Unavailable`.
+    pub fn server_errors(mut self, probability: f64) -> Self {
+        self.server_errors = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Answers a `probability` share of requests with `429 Too Many Requests` and a
+    /// `Retry-After` of `retry_after`.
+    pub fn rate_limits(mut self, probability: f64, retry_after: Duration) -> Self {
+        self.rate_limits = (probability.clamp(0.0, 1.0), retry_after);
+        self
+    }
+
+    /// Fails a `probability` share of requests with a connection error.
+    pub fn connection_resets(mut self, probability: f64) -> Self {
+        self.connection_resets = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Truncates the response body of a `probability` share of requests.
+    pub fn malformed_bodies(mut self, probability: f64) -> Self {
+        self.malformed_bodies = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Makes the sequence of faults reproducible.
+    pub fn seed(self, seed: u64) -> Self {
+        *self.rng.lock().unwrap() = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Turns injection on or off, e.g. to let the client build before the chaos starts.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn injected(&self) -> InjectedFaults {
+        self.injected.lock().unwrap().clone()
+    }
+
+    fn roll(&self) -> (bool, Option<Fault>) {
+        if !self.enabled.load(Ordering::SeqCst) {
+            return (false, None);
+        }
+        let mut rng = self.rng.lock().unwrap();
+        let delay = rng.gen::<f64>() < self.latency.0;
+        let mut roll = rng.gen::<f64>();
+        let mut fault = None;
+        for (probability, candidate) in [
+            (self.server_errors, Fault::ServerError),
+            (self.rate_limits.0, Fault::RateLimit),
+            (self.connection_resets, Fault::ConnectionReset),
+            (self.malformed_bodies, Fault::MalformedBody),
+        ] {
+            if roll < probability {
+                fault = Some(candidate);
+                break;
+            }
+            roll -= probability;
+        }
+        (delay, fault)
+    }
+
+    fn count(&self, update: impl FnOnce(&mut InjectedFaults)) {
+        update(&mut self.injected.lock().unwrap());
+    }
+}
+
+impl Transport for FaultInjectingTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let (delay, fault) = self.roll();
+            if delay {
+                self.count(|x| x.delayed += 1);
+                crate::runtime::sleep(self.latency.1).await;
+            }
+            match fault {
+                Some(Fault::ServerError) => {
+                    self.count(|x| x.server_errors += 1);
+                    Ok(HttpResponse {
+                        status: StatusCode::SERVICE_UNAVAILABLE,
+                        headers: HeaderMap::new(),
+                        body: b"<html>503 Service Unavailable</html>".to_vec(),
+                    })
+                }
+                Some(Fault::RateLimit) => {
+                    self.count(|x| x.rate_limits += 1);
+                    let mut headers = HeaderMap::new();
+                    headers.insert(RETRY_AFTER, HeaderValue::from(self.rate_limits.1.as_secs()));
+                    Ok(HttpResponse {
+                        status: StatusCode::TOO_MANY_REQUESTS,
+                        headers,
+                        body: Vec::new(),
+                    })
+                }
+                Some(Fault::ConnectionReset) => {
+                    self.count(|x| x.connection_resets += 1);
+                    Err(TransportError::new(
+                        TransportErrorKind::Connect,
+                        "connection reset by peer (injected)",
+                    ))?
+                }
+                Some(Fault::MalformedBody) => {
+                    let mut response = self.inner.send(request).await?;
+                    self.count(|x| x.malformed_bodies += 1);
+                    let half = response.body.len() / 2;
+                    response.body.truncate(half.max(1));
+                    Ok(response)
+                }
+                None => self.inner.send(request).await,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::{CircleClient, Environment, MockTransport};
+    use crate::error::CircleError;
+
+    #[tokio::test]
+    async fn test_injected_faults() {
+        let mock = MockTransport::new();
+        let transport = FaultInjectingTransport::new(mock.clone()).seed(1);
+        transport.set_enabled(false);
+        let client = |transport: FaultInjectingTransport| {
+            CircleClient::builder()
+                .api_key("key")
+                .entity_secret(hex::encode("test"))
+                .environment(Environment::Sandbox)
+                .transport(transport)
+                .build()
+        };
+
+        let failing = client(transport.clone().server_errors(1.0)).await.unwrap();
+        transport.set_enabled(true);
+        let err = failing
+            .get_wallet_set(uuid::Uuid::nil().into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CircleError::UnexpectedStatus(None, _)) && err.is_retryable());
+
+        let resetting = client(transport.clone().connection_resets(1.0)).await;
+        assert!(matches!(resetting, Err(CircleError::TransportError(_))));
+
+        transport.set_enabled(false);
+        let malformed = client(transport.clone().malformed_bodies(1.0))
+            .await
+            .unwrap();
+        transport.set_enabled(true);
+        mock.push_data(json!({ "walletSet": { "id": uuid::Uuid::nil() } }));
+        assert!(malformed
+            .get_wallet_set(uuid::Uuid::nil().into())
+            .await
+            .is_err());
+        assert!(mock.requests().len() == 1);
+
+        assert_eq!(
+            transport.injected(),
+            InjectedFaults {
+                server_errors: 1,
+                connection_resets: 1,
+                malformed_bodies: 1,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use reqwest::Method;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::entity_config::EntityConfigResponse;
+
+/// The outcome of [`CircleClient::ping`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Health {
+    pub latency: Duration,
+    /// `false` if Circle rejected the API key.
+    pub authenticated: bool,
+}
+
+impl CircleClient {
+    pub async fn get_entity_config(&self) -> Result<EntityConfigResponse> {
+        let url = format!("{}w3s/config/entity", self.base_url);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+
+    /// Makes one cheap authenticated call, for readiness probes and startup checks. A rejected
+    /// API key comes back as `authenticated: false`; anything else that goes wrong (network,
+    /// timeouts, Circle being down) is returned as an error.
+    pub async fn ping(&self) -> Result<Health> {
+        let started = crate::runtime::Instant::now();
+        // Never from the response cache, which would hide a revoked key or an outage.
+        let url = format!("{}w3s/config/entity", self.base_url);
+        let config = self.send_uncached::<EntityConfigResponse>(Method::GET, url, None::<()>);
+        let authenticated = match config.await {
+            Ok(_) => true,
+            Err(err) if err.is_auth_error() => false,
+            Err(err) => return Err(err),
+        };
+        Ok(Health {
+            latency: started.elapsed(),
+            authenticated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_ping() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+
+        transport.push_data(json!({ "appId": "app" }));
+        assert!(client.ping().await.unwrap().authenticated);
+        let request = transport.last_request().unwrap();
+        assert!(request.url.ends_with("w3s/config/entity"));
+
+        transport.push_error(StatusCode::UNAUTHORIZED, 401, "Malformed authorization");
+        assert!(!client.ping().await.unwrap().authenticated);
+
+        transport.push_error(StatusCode::BAD_REQUEST, 2, "Invalid parameter");
+        assert!(client.ping().await.is_err());
+    }
+}
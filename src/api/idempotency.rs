@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use log::debug;
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::api::{CircleClient, RetryPolicy};
+use crate::error::{CircleError, Result};
+
+/// What mutating calls do when they're given `None` instead of an idempotency key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdempotencyPolicy {
+    /// Use a fresh random key. Retries of that call reuse it, but calling again is a new
+    /// operation, so this suits scripts more than services.
+    #[default]
+    AlwaysGenerate,
+    /// Fail with [`CircleError::MissingField`], so every mutation has to pick its key.
+    Require,
+}
+
+/// Remembers the idempotency key generated for each operation, so a retried operation reuses
+/// it even after a restart. Operations are identities chosen by the caller, e.g.
+/// `payout:<row id>`.
+pub trait IdempotencyStore: Send + Sync {
+    /// The key stored for `operation`, or `key` once it has been stored. Must be atomic, so
+    /// concurrent callers for the same operation all get the same key.
+    fn get_or_insert<'a>(&'a self, operation: &'a str, key: Uuid) -> BoxFuture<'a, Result<Uuid>>;
+
+    /// Forgets `operation`, e.g. once it completed and may legitimately run again.
+    fn remove<'a>(&'a self, operation: &'a str) -> BoxFuture<'a, Result<()>>;
+}
+
+impl<S: IdempotencyStore> IdempotencyStore for Arc<S> {
+    fn get_or_insert<'a>(&'a self, operation: &'a str, key: Uuid) -> BoxFuture<'a, Result<Uuid>> {
+        (**self).get_or_insert(operation, key)
+    }
+
+    fn remove<'a>(&'a self, operation: &'a str) -> BoxFuture<'a, Result<()>> {
+        (**self).remove(operation)
+    }
+}
+
+/// The default [`IdempotencyStore`]. Keys survive retries within the process, not restarts.
+#[derive(Debug, Default)]
+pub struct InMemoryIdempotencyStore(Mutex<HashMap<String, Uuid>>);
+
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    fn get_or_insert<'a>(&'a self, operation: &'a str, key: Uuid) -> BoxFuture<'a, Result<Uuid>> {
+        Box::pin(async move {
+            let mut keys = self.0.lock().unwrap();
+            Ok(*keys.entry(operation.to_string()).or_insert(key))
+        })
+    }
+
+    fn remove<'a>(&'a self, operation: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.0.lock().unwrap().remove(operation);
+            Ok(())
+        })
+    }
+}
+
+/// A mutating request bound to one idempotency key, so it can be resubmitted until Circle
+/// answers without ever running twice.
+#[derive(Debug, Clone)]
+pub struct Idempotent<R> {
+    key: Uuid,
+    request: R,
+    retry_policy: RetryPolicy,
+}
+
+impl<R: Clone> Idempotent<R> {
+    /// Retries up to 5 attempts, starting at 1s backoff.
+    pub fn new(key: Uuid, request: R) -> Self {
+        Idempotent {
+            key,
+            request,
+            retry_policy: RetryPolicy::new(5, Duration::from_secs(1)),
+        }
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn key(&self) -> Uuid {
+        self.key
+    }
+
+    pub fn request(&self) -> &R {
+        &self.request
+    }
+
+    /// Calls `send` with the key and a copy of the request until it succeeds or fails for a
+    /// reason retrying won't fix. A 409 means Circle already has an operation under the key,
+    /// typically an earlier attempt whose response got lost, so its result is fetched with
+    /// `recover` instead, e.g. by looking the transaction up by its `ref_id`.
+    pub async fn submit_with_retries<T, S, SFut, C, CFut>(&self, send: S, recover: C) -> Result<T>
+    where
+        S: Fn(Uuid, R) -> SFut,
+        SFut: Future<Output = Result<T>>,
+        C: FnOnce(Uuid) -> CFut,
+        CFut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match send(self.key, self.request.clone()).await {
+                Err(err) if err.status() == Some(StatusCode::CONFLICT) => {
+                    debug!("{} was already submitted, recovering its result", self.key);
+                    return recover(self.key).await;
+                }
+                Err(err) if err.is_retryable() && attempt < self.retry_policy.max_attempts() => {
+                    debug!(
+                        "submitting {} failed on attempt {}: {}",
+                        self.key, attempt, err
+                    );
+                    crate::runtime::sleep(self.retry_policy.backoff(attempt)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl CircleClient {
+    /// The idempotency key for `operation`, generated and stored the first time it's asked
+    /// for. Pass it to a mutating call to make retrying the whole operation safe:
+    /// `client.create_transfer_transaction(client.idempotency_key("payout:17").await?, request)`.
+    pub async fn idempotency_key(&self, operation: &str) -> Result<Uuid> {
+        self.idempotency
+            .get_or_insert(operation, Uuid::new_v4())
+            .await
+    }
+
+    pub(crate) fn resolve_idempotency_key(&self, key: Option<Uuid>) -> Result<Uuid> {
+        match (key, self.idempotency_policy) {
+            (Some(key), _) => Ok(key),
+            (None, IdempotencyPolicy::AlwaysGenerate) => Ok(Uuid::new_v4()),
+            (None, IdempotencyPolicy::Require) => Err(CircleError::MissingField("idempotency_key")),
+        }
+    }
+
+    /// Forgets the key of `operation`, so the next [`CircleClient::idempotency_key`] for it
+    /// starts a new operation.
+    pub async fn forget_idempotency_key(&self, operation: &str) -> Result<()> {
+        self.idempotency.remove(operation).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::{MockTransport, TransportErrorKind};
+
+    #[tokio::test]
+    async fn test_idempotency_key_reuse() {
+        let store = Arc::new(InMemoryIdempotencyStore::default());
+        let client = |store: Arc<InMemoryIdempotencyStore>| {
+            MockTransport::new()
+                .client_builder()
+                .idempotency_store(store)
+                .build()
+        };
+        let first = client(store.clone()).await.unwrap();
+        let key = first.idempotency_key("payout:17").await.unwrap();
+        assert_eq!(first.idempotency_key("payout:17").await.unwrap(), key);
+        assert_ne!(first.idempotency_key("payout:18").await.unwrap(), key);
+
+        // A new client on the same store, as after a restart.
+        let second = client(store).await.unwrap();
+        assert_eq!(second.idempotency_key("payout:17").await.unwrap(), key);
+        second.forget_idempotency_key("payout:17").await.unwrap();
+        assert_ne!(second.idempotency_key("payout:17").await.unwrap(), key);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_policy() {
+        let transport = MockTransport::new();
+        let client = |policy| {
+            transport
+                .client_builder()
+                .idempotency_policy(policy)
+                .build()
+        };
+        let strict = client(IdempotencyPolicy::Require).await.unwrap();
+        assert!(matches!(
+            strict.create_wallet_set(None, "treasury".to_string()).await,
+            Err(CircleError::MissingField("idempotency_key"))
+        ));
+        #[cfg(feature = "transactions")]
+        assert!(matches!(
+            strict.cancel_transaction(Default::default(), None).await,
+            Err(CircleError::MissingField("idempotency_key"))
+        ));
+        assert!(transport.requests().is_empty());
+
+        let relaxed = client(IdempotencyPolicy::AlwaysGenerate).await.unwrap();
+        transport.push_data(serde_json::json!({ "walletSet": {
+            "id": Uuid::new_v4(),
+            "custodyType": "DEVELOPER",
+            "name": "treasury",
+            "createDate": "2024-03-01T10:00:00Z",
+            "updateDate": "2024-03-01T10:00:00Z"
+        } }));
+        relaxed
+            .create_wallet_set(None, "treasury".to_string())
+            .await
+            .unwrap();
+        let body = transport.requests().pop().unwrap().body.unwrap();
+        assert!(Uuid::parse_str(body["idempotencyKey"].as_str().unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_submit_with_retries() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        transport
+            .push_transport_error(TransportErrorKind::Connect)
+            .push_error(StatusCode::CONFLICT, 409, "duplicate idempotency key");
+
+        let submission = Idempotent::new(Uuid::new_v4(), "treasury".to_string())
+            .retry_policy(RetryPolicy::new(3, Duration::ZERO));
+        let recovered = submission
+            .submit_with_retries(
+                |key, name| {
+                    let client = client.clone();
+                    async move { client.create_wallet_set(key, name).await.map(|_| None) }
+                },
+                |key| async move { Ok(Some(key)) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(recovered, Some(submission.key()));
+
+        let keys: Vec<_> = transport
+            .requests()
+            .into_iter()
+            .map(|x| x.body.unwrap()["idempotencyKey"].clone())
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.iter().all(|x| *x == submission.key().to_string()));
+    }
+}
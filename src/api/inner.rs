@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use rsa::RsaPublicKey;
+
+use crate::api::envelope::HeaderCapture;
+use crate::api::middleware::MiddlewareChain;
+use crate::api::stats::StatsCollector;
+use crate::api::version::ApiVersions;
+use crate::api::{
+    AuditSink, BaseUrls, CircuitBreaker, Environment, IdempotencyPolicy, IdempotencyStore,
+    MetricsSink, RateLimiter, RawCapture, RequestIdCapture, ResponseCache, RetryPolicy,
+    SimulationLog, TenantCredentials, Timeouts, Transport,
+};
+use crate::models::ids::TokenId;
+
+/// Everything a [`CircleClient`](crate::api::CircleClient) holds, behind one `Arc` so that
+/// cloning the client is cheap. Public only so it can be the client's `Deref` target; the
+/// module is private, so nothing outside `api` can name it.
+#[derive(Clone)]
+pub struct ClientInner {
+    pub(super) base_url: String,
+    pub(super) base_urls: BaseUrls,
+    pub(super) environment: Environment,
+    pub(super) credentials: Arc<RwLock<TenantCredentials>>,
+    pub(super) client: Option<Client>,
+    pub(super) transport: Arc<dyn Transport>,
+    pub(super) middleware: MiddlewareChain,
+    pub(super) headers: HeaderMap,
+    pub(super) public_key: RsaPublicKey,
+    pub(super) timeouts: Timeouts,
+    pub(super) retry_policy: RetryPolicy,
+    pub(super) rate_limiter: Option<RateLimiter>,
+    #[cfg(any(feature = "wallets", feature = "transactions", feature = "payments"))]
+    pub(super) page_prefetch: usize,
+    pub(super) circuit_breaker: Option<CircuitBreaker>,
+    pub(super) request_id_capture: Option<RequestIdCapture>,
+    pub(super) raw_capture: Option<RawCapture>,
+    pub(super) header_capture: Option<HeaderCapture>,
+    pub(super) token_decimals: Arc<RwLock<HashMap<TokenId, Option<u32>>>>,
+    pub(super) response_cache: Option<ResponseCache>,
+    pub(super) metrics: Option<Arc<dyn MetricsSink>>,
+    pub(super) stats: Arc<StatsCollector>,
+    pub(super) audit: Option<Arc<dyn AuditSink>>,
+    pub(super) idempotency: Arc<dyn IdempotencyStore>,
+    pub(super) idempotency_policy: IdempotencyPolicy,
+    pub(super) api_versions: ApiVersions,
+    pub(super) simulation: Option<SimulationLog>,
+}
@@ -0,0 +1,188 @@
+//! `tracing` spans for API calls. Without the `tracing` feature the `record_*` functions are
+//! no-ops.
+//!
+//! Spans carry the endpoint with ids replaced by `{id}`, the ids themselves, the status, the
+//! number of attempts and Circle's `X-Request-Id`. Headers and bodies are never recorded, so
+//! API keys and entity secret ciphertexts can't end up in traces.
+//!
+//! With the `otel` feature each attempt also carries the span's OpenTelemetry context, injected
+//! with the global text map propagator (usually a `traceparent` header).
+
+use reqwest::header::HeaderMap;
+use reqwest::Method;
+use serde_json::Value;
+
+use crate::api::transport::HttpResponse;
+use crate::error::Result;
+
+#[cfg(feature = "tracing")]
+pub(crate) fn request_span(method: &Method, url: &str) -> tracing::Span {
+    let span = tracing::info_span!(
+        "circle.request",
+        otel.kind = "client",
+        http.method = %method,
+        endpoint = tracing::field::Empty,
+        wallet_id = tracing::field::Empty,
+        wallet_set_id = tracing::field::Empty,
+        transaction_id = tracing::field::Empty,
+        resource_id = tracing::field::Empty,
+        http.status_code = tracing::field::Empty,
+        attempts = tracing::field::Empty,
+        request_id = tracing::field::Empty,
+    );
+    let (endpoint, ids) = endpoint(url);
+    span.record("endpoint", endpoint.as_str());
+    for (field, id) in ids {
+        span.record(field, id);
+    }
+    span
+}
+
+/// `https://api.circle.com/v1/w3s/wallets/<uuid>/balances?x=y` becomes
+/// `w3s/wallets/{id}/balances`, with the uuid recorded as `wallet_id`.
+pub(crate) fn endpoint(url: &str) -> (String, Vec<(&'static str, &str)>) {
+    let path = url.split('?').next().unwrap_or(url);
+    let path = crate::api::version::path(path);
+    let mut endpoint = Vec::new();
+    let mut ids = Vec::new();
+    let mut previous = "";
+    for segment in path.split('/') {
+        if uuid::Uuid::parse_str(segment).is_err() {
+            endpoint.push(segment);
+            previous = segment;
+            continue;
+        }
+        endpoint.push("{id}");
+        let field = match previous {
+            "wallets" => "wallet_id",
+            "walletSets" => "wallet_set_id",
+            "transactions" => "transaction_id",
+            _ => "resource_id",
+        };
+        ids.push((field, segment));
+    }
+    (endpoint.join("/"), ids)
+}
+
+/// Adds the current span's OpenTelemetry context to `headers`.
+pub(crate) fn propagate(headers: &mut HeaderMap) {
+    #[cfg(feature = "otel")]
+    {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let context = tracing::Span::current().context();
+        opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut HeaderInjector(headers))
+        });
+    }
+    #[cfg(not(feature = "otel"))]
+    let _ = headers;
+}
+
+#[cfg(feature = "otel")]
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+#[cfg(feature = "otel")]
+impl opentelemetry::propagation::Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        let name = reqwest::header::HeaderName::from_bytes(key.as_bytes());
+        let value = reqwest::header::HeaderValue::from_str(&value);
+        if let (Ok(name), Ok(value)) = (name, value) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// Records ids from a request body, e.g. the source wallet of a transfer.
+pub(crate) fn record_body(body: &Value) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+        for (key, field) in [("walletId", "wallet_id"), ("walletSetId", "wallet_set_id")] {
+            if let Some(id) = body.get(key).and_then(Value::as_str) {
+                span.record(field, id);
+            }
+        }
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = body;
+}
+
+pub(crate) fn record_attempt(method: &Method, attempt: u32, result: &Result<HttpResponse>) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+        span.record("attempts", attempt);
+        match result {
+            Ok(response) => {
+                span.record("http.status_code", response.status.as_u16());
+                if let Some(request_id) = super::request_id(&response.headers) {
+                    span.record("request_id", tracing::field::display(request_id));
+                }
+                tracing::debug!(%method, status = response.status.as_u16(), attempt, "response");
+            }
+            Err(err) => tracing::debug!(%method, attempt, error = %err, "transport error"),
+        }
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (method, attempt, result);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        let id = "ce714f5b-0d8e-4062-9454-61aa1154869b";
+        let url = format!(
+            "https://api.circle.com/v1/w3s/wallets/{}/balances?pageSize=5",
+            id
+        );
+        assert_eq!(
+            endpoint(&url),
+            (
+                "w3s/wallets/{id}/balances".to_string(),
+                vec![("wallet_id", id)]
+            )
+        );
+        let url = format!("https://api.circle.com/v1/settlements/{}", id);
+        assert_eq!(
+            endpoint(&url),
+            ("settlements/{id}".to_string(), vec![("resource_id", id)])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "otel")]
+    fn test_propagate_trace_context() {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        opentelemetry::global::set_text_map_propagator(
+            opentelemetry_sdk::propagation::TraceContextPropagator::new(),
+        );
+        let tracer = opentelemetry::trace::noop::NoopTracer::new();
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        let parent = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+
+        let mut headers = HeaderMap::new();
+        tracing::subscriber::with_default(subscriber, || {
+            let span = request_span(&Method::GET, "https://api.circle.com/v1/w3s/wallets");
+            span.set_parent(opentelemetry::Context::new().with_remote_span_context(parent));
+            span.in_scope(|| propagate(&mut headers));
+        });
+        let traceparent = headers["traceparent"].to_str().unwrap();
+        assert!(traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+
+use crate::error::CircleError;
+
+/// One finished API call, retries included.
+#[derive(Debug)]
+pub struct CallMetrics<'a> {
+    pub method: &'a Method,
+    /// The path with ids replaced by `{id}`, e.g. `w3s/wallets/{id}/balances`, so it can be
+    /// used as a metric label without blowing up cardinality.
+    pub endpoint: &'a str,
+    pub duration: Duration,
+    /// The status of the last response, `None` if no response was received at all.
+    pub status: Option<StatusCode>,
+    pub attempts: u32,
+    /// How many `429 Too Many Requests` responses were waited out along the way.
+    pub rate_limited: u32,
+    pub error: Option<&'a CircleError>,
+}
+
+impl CallMetrics<'_> {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Receives [`CallMetrics`] for every call, to be forwarded to the metrics system of choice.
+/// Called on the request path, so implementations should only update counters.
+pub trait MetricsSink: Send + Sync {
+    fn record(&self, metrics: &CallMetrics<'_>);
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CallStats {
+    pub(crate) status: Option<StatusCode>,
+    pub(crate) attempts: u32,
+    pub(crate) rate_limited: u32,
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::{MockTransport, RetryPolicy, TransportErrorKind};
+
+    type Recorded = (String, Option<StatusCode>, u32, bool);
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<Recorded>>);
+
+    impl MetricsSink for Arc<Recorder> {
+        fn record(&self, metrics: &CallMetrics<'_>) {
+            self.0.lock().unwrap().push((
+                metrics.endpoint.to_string(),
+                metrics.status,
+                metrics.attempts,
+                metrics.is_success(),
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_sink() {
+        let transport = MockTransport::new();
+        let recorder = Arc::new(Recorder::default());
+        let client = transport
+            .client_builder()
+            .retry_policy(RetryPolicy::new(2, Duration::ZERO).jitter(false))
+            .metrics(recorder.clone())
+            .build()
+            .await
+            .unwrap();
+        transport
+            .push_transport_error(TransportErrorKind::Connect)
+            .push_error(StatusCode::BAD_REQUEST, 2, "API parameter invalid");
+
+        assert!(client.get_wallet(Uuid::new_v4().into()).await.is_err());
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![(
+                "w3s/wallets/{id}".to_string(),
+                Some(StatusCode::BAD_REQUEST),
+                2,
+                false
+            )]
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use crate::api::transport::{HttpRequest, HttpResponse};
+use crate::error::{CircleError, Result};
+
+/// Hooks run around every attempt, after retries, rate limiting and failover have picked the
+/// endpoint and before the transport sends it. Middleware runs in registration order.
+pub trait ClientMiddleware: Send + Sync {
+    /// Mutates the outgoing request, e.g. to add headers. Returning an error aborts the call
+    /// without sending anything.
+    fn on_request(&self, _request: &mut HttpRequest) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_response(&self, _request: &HttpRequest, _response: &HttpResponse) {}
+
+    /// Called when the transport fails before a response was received.
+    fn on_error(&self, _request: &HttpRequest, _error: &CircleError) {}
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareChain(Vec<Arc<dyn ClientMiddleware>>);
+
+impl MiddlewareChain {
+    pub(crate) fn push(&mut self, middleware: Arc<dyn ClientMiddleware>) {
+        self.0.push(middleware);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn on_request(&self, request: &mut HttpRequest) -> Result<()> {
+        self.0.iter().try_for_each(|x| x.on_request(request))
+    }
+
+    pub(crate) fn on_result(&self, request: &HttpRequest, result: &Result<HttpResponse>) {
+        match result {
+            Ok(response) => self.0.iter().for_each(|x| x.on_response(request, response)),
+            Err(error) => self.0.iter().for_each(|x| x.on_error(request, error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use reqwest::header::HeaderValue;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    struct ExperimentFlag;
+
+    impl ClientMiddleware for ExperimentFlag {
+        fn on_request(&self, request: &mut HttpRequest) -> Result<()> {
+            request
+                .headers
+                .insert("X-Experiment", HeaderValue::from_static("new-fees"));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct CountStatuses(AtomicUsize);
+
+    impl ClientMiddleware for Arc<CountStatuses> {
+        fn on_response(&self, _request: &HttpRequest, response: &HttpResponse) {
+            if response.status.is_client_error() {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_chain() {
+        let transport = MockTransport::new();
+        let counter = Arc::new(CountStatuses::default());
+        let client = transport
+            .client_builder()
+            .middleware(ExperimentFlag)
+            .middleware(counter.clone())
+            .build()
+            .await
+            .unwrap();
+        transport.push_error(
+            reqwest::StatusCode::NOT_FOUND,
+            156004,
+            "Cannot find the wallet",
+        );
+
+        assert!(client.get_wallet(Uuid::new_v4().into()).await.is_err());
+        let request = transport.last_request().unwrap();
+        assert_eq!(request.headers["X-Experiment"], "new-fees");
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    }
+}
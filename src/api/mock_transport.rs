@@ -0,0 +1,368 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::api::transport::{
+    HttpRequest, HttpResponse, Transport, TransportError, TransportErrorKind, TransportFuture,
+};
+
+/// Public key served for the entity public key fetch, so a client can be built offline.
+pub const MOCK_PUBLIC_KEY: &str = "-----BEGIN RSA PUBLIC KEY-----\nMIICIjANBgkqhkiG9w0BAQEFAAOCAg8AMIICCgKCAgEAxDiWHMTzDfIMeLVw4BGT\nOnhVv/jjccrcHFMtm0ShbOb8bu0b/hvtN2oEdWx2RTdNT7AvntB9R9vCv60lZrk0\nZtfR8p2lew++NKAfyEeqTfL8dpfjhPtTZWLjdKG9SzkN66SRXBz5fNae4qaDHG3N\nI8PtYmwRnpfy6VzpcdwOGQxv2nGmgT4AKD/A1wl+7W2KruUPlWaGRNsSiFVceNTR\nYWll5OsRM0BB9YLkwDAFm27e+XmISJlapSmD8Gqx3i5ZvpwINboj1JiEaqMe/bAs\nASYHR73qz7G/B9p7nSc6tKr3SToXivZqDC47NLa81JZuyHyc7U5r+pdcTXOCsa+T\nTS0Y+fEZZ5rOQO3nI3voDULvf1yDvWsJTJW8qi3RjtGlR3P3M0JwONF0xZUwtSal\nMOLWwNjZrC33LIuGoD4M+43/y62xkdXIE4CHXTo3annRPnktkdYxTVfIYUXH8JDA\ng7++dIE4ZaN41Eg2mWCt3SSry9BqrMhEcY7YyuVyzJnv59cGCi5sDnQHGlXs1xJG\n/5QSyhID9+J2RRtu4sZ+5aLIvcIkMsNhul0mbfTRr34f9MGqYv9mkuzHUC/ppykG\nOv1ZJ0PWMIX4WCMXLKSi5Ii4Eayrev4BZk6WtXnvgX+EY9j+/85o+XgvyaX1Z7hE\nPBYZ9E8aCK/7kzIK4tgXviECAwEAAQ==\n-----END RSA PUBLIC KEY-----\n";
+
+const PUBLIC_KEY_PATH: &str = "w3s/config/entity/publicKey";
+
+/// A request as seen by [`MockTransport`].
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub url: String,
+    pub path: String,
+    pub headers: HeaderMap,
+    pub body: Option<Value>,
+}
+
+enum MockResponse {
+    Http(HttpResponse),
+    TransportError(TransportErrorKind),
+}
+
+#[derive(Default)]
+struct State {
+    responses: VecDeque<MockResponse>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// An in-memory [`Transport`] that answers with canned responses, in the order they were
+/// queued, and records every request for later assertions.
+///
+/// The entity public key fetch done by [`crate::api::CircleClientBuilder::build`] is answered
+/// with [`MOCK_PUBLIC_KEY`] and isn't recorded, so tests only see their own calls. Clones share
+/// the same queue and recordings.
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a successful response wrapping `data` in Circle's `{"data": ...}` envelope.
+    pub fn push_data<T: Serialize>(&self, data: T) -> &Self {
+        let body = json!({ "data": data });
+        self.push_json(StatusCode::OK, body)
+    }
+
+    /// Queues a Circle error response.
+    pub fn push_error(&self, status: StatusCode, code: i64, message: &str) -> &Self {
+        let body = json!({ "code": code, "message": message, "errors": [] });
+        self.push_json(status, body)
+    }
+
+    pub fn push_json(&self, status: StatusCode, body: Value) -> &Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Request-Id",
+            HeaderValue::from_str(&Uuid::new_v4().to_string()).unwrap(),
+        );
+        self.push_response(HttpResponse {
+            status,
+            headers,
+            body: body.to_string().into_bytes(),
+        })
+    }
+
+    pub fn push_response(&self, response: HttpResponse) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push_back(MockResponse::Http(response));
+        self
+    }
+
+    /// Queues a failure before any response is received, e.g. a connection refused.
+    pub fn push_transport_error(&self, kind: TransportErrorKind) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push_back(MockResponse::TransportError(kind));
+        self
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn last_request(&self) -> Option<RecordedRequest> {
+        self.state.lock().unwrap().requests.last().cloned()
+    }
+
+    /// Number of queued responses that haven't been consumed yet.
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().responses.len()
+    }
+
+    fn public_key_response() -> HttpResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Request-Id",
+            HeaderValue::from_str(&Uuid::new_v4().to_string()).unwrap(),
+        );
+        let body = json!({ "data": { "publicKey": MOCK_PUBLIC_KEY } });
+        HttpResponse {
+            status: StatusCode::OK,
+            headers,
+            body: body.to_string().into_bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl MockTransport {
+    /// A sandbox client builder answered by this transport, for tests to customise.
+    pub(crate) fn client_builder(&self) -> crate::api::CircleClientBuilder {
+        crate::api::CircleClient::builder()
+            .api_key("key")
+            .entity_secret(hex::encode("test"))
+            .environment(crate::api::Environment::Sandbox)
+            .transport(self.clone())
+    }
+
+    /// A sandbox client answered by this transport.
+    pub(crate) async fn client(&self) -> crate::api::CircleClient {
+        self.client_builder().build().await.unwrap()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            if request.url.ends_with(PUBLIC_KEY_PATH) {
+                return Ok(Self::public_key_response());
+            }
+            let path = reqwest::Url::parse(&request.url)
+                .map(|url| url.path().to_string())
+                .unwrap_or_else(|_| request.url.clone());
+            let body = match &request.body {
+                Some(body) => Some(serde_json::from_slice(body)?),
+                None => None,
+            };
+            let mut state = self.state.lock().unwrap();
+            state.requests.push(RecordedRequest {
+                method: request.method,
+                url: request.url,
+                path,
+                headers: request.headers,
+                body,
+            });
+            match state.responses.pop_front() {
+                Some(MockResponse::Http(response)) => Ok(response),
+                Some(MockResponse::TransportError(kind)) => {
+                    Err(TransportError::new(kind, "mock transport error"))?
+                }
+                None => Err(TransportError::new(
+                    TransportErrorKind::Other,
+                    "no mock response queued",
+                ))?,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::{CircleClient, RetryPolicy};
+    use crate::error::CircleError;
+    use crate::models::ids::{TokenId, WalletId, WalletSetId};
+    use crate::models::transaction_transfer_create::TransactionTransferCreateRequestBuilder;
+    use crate::models::wallet_list::WalletListQueryParams;
+    use crate::models::wallet_set::WalletSetsQueryParams;
+    use crate::models::web3_address::Web3Address;
+    use futures::TryStreamExt;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    async fn client(transport: &MockTransport) -> CircleClient {
+        transport
+            .client_builder()
+            .retry_policy(RetryPolicy::new(2, std::time::Duration::ZERO).jitter(false))
+            .build()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_records_requests() {
+        let transport = MockTransport::new();
+        let client = client(&transport).await;
+        transport.push_data(json!({
+            "walletSet": {
+                "id": "0068d5a4-eb64-4399-8441-a9af33af80a0",
+                "custodyType": "DEVELOPER",
+                "name": "test_wallet_set",
+                "updateDate": "2023-11-25T14:26:38Z",
+                "createDate": "2023-11-25T14:26:38Z"
+            }
+        }));
+
+        let response = client
+            .create_wallet_set(Uuid::new_v4(), "test_wallet_set".to_string())
+            .await
+            .unwrap();
+        assert_eq!(response.wallet_set.name, "test_wallet_set");
+
+        let request = transport.last_request().unwrap();
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.path, "/v1/w3s/developer/walletSets");
+        assert_eq!(request.body.unwrap()["name"], "test_wallet_set");
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_transport_errors() {
+        let transport = MockTransport::new();
+        let client = client(&transport).await;
+        transport
+            .push_transport_error(TransportErrorKind::Connect)
+            .push_error(StatusCode::BAD_REQUEST, 2, "API parameter invalid");
+
+        let err = client.get_wallet(Uuid::new_v4().into()).await.unwrap_err();
+        assert!(matches!(err, CircleError::ApiError(_, ref err) if err.code() == 2));
+        assert_eq!(transport.requests().len(), 2);
+        assert_eq!(transport.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_retries_use_fresh_ciphertext() {
+        let transport = MockTransport::new();
+        let client = client(&transport).await;
+        transport
+            .push_error(StatusCode::INTERNAL_SERVER_ERROR, -1, "Internal error")
+            .push_data(json!({
+                "walletSet": {
+                    "id": "0068d5a4-eb64-4399-8441-a9af33af80a0",
+                    "custodyType": "DEVELOPER",
+                    "name": "retried",
+                    "updateDate": "2023-11-25T14:26:38Z",
+                    "createDate": "2023-11-25T14:26:38Z"
+                }
+            }));
+
+        client
+            .create_wallet_set(Uuid::new_v4(), "retried".to_string())
+            .await
+            .unwrap();
+        let bodies: Vec<_> = transport
+            .requests()
+            .into_iter()
+            .map(|x| x.body.unwrap())
+            .collect();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0]["idempotencyKey"], bodies[1]["idempotencyKey"]);
+        assert!(bodies[0]["entitySecretCipherText"].is_string());
+        assert_ne!(
+            bodies[0]["entitySecretCipherText"],
+            bodies[1]["entitySecretCipherText"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_params_in_query_string() {
+        let transport = MockTransport::new();
+        let client = client(&transport).await;
+        let wallet_set_id = WalletSetId(Uuid::new_v4());
+        transport.push_data(json!({ "wallets": [] }));
+
+        let params = WalletListQueryParams::default()
+            .wallet_set_id(wallet_set_id)
+            .page_size(5)
+            .unwrap();
+        let page = client.list_wallets(params).await.unwrap();
+        assert!(page.is_empty());
+        assert!(page.next_cursor().is_none());
+
+        let request = transport.last_request().unwrap();
+        assert!(request.body.is_none());
+        assert!(request.url.ends_with(&format!(
+            "/w3s/wallets?walletSetId={}&pageSize=5",
+            wallet_set_id
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_list_stream_follows_cursors() {
+        let transport = MockTransport::new();
+        let client = client(&transport).await;
+        let ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let wallet_set = |id: &Uuid| {
+            json!({
+                "id": id,
+                "custodyType": "DEVELOPER",
+                "updateDate": "2023-11-25T14:26:38Z",
+                "createDate": "2023-11-25T14:26:38Z"
+            })
+        };
+        transport
+            .push_data(json!({ "walletSets": [wallet_set(&ids[0]), wallet_set(&ids[1])] }))
+            .push_data(json!({ "walletSets": [wallet_set(&ids[2])] }));
+
+        let params = WalletSetsQueryParams::new().page_size(2).unwrap();
+        let wallet_sets = client
+            .list_wallet_sets_stream(params)
+            .map_ok(|x| x.id)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(wallet_sets, ids);
+
+        // The short second page ends the stream without a third request.
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1]
+            .url
+            .ends_with(&format!("?pageAfter={}&pageSize=2", ids[1])));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_amounts_beyond_token_decimals() {
+        let transport = MockTransport::new();
+        let client = client(&transport).await;
+        transport.push_data(json!({
+            "token": {
+                "id": "7adb2b7d-c9cd-5164-b2d4-b73b088274dc",
+                "blockchain": "MATIC-MUMBAI",
+                "decimals": 6,
+                "isNative": false,
+                "updateDate": "2023-11-25T14:26:38Z",
+                "createDate": "2023-11-25T14:26:38Z"
+            }
+        }));
+
+        let token_id = TokenId::from_str("7adb2b7d-c9cd-5164-b2d4-b73b088274dc").unwrap();
+        let address = Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        for amount in [Decimal::new(10000001, 7), Decimal::new(1, 8)] {
+            let request = TransactionTransferCreateRequestBuilder::new(
+                address.clone(),
+                token_id,
+                WalletId(Uuid::new_v4()),
+                amount,
+            );
+            let err = client
+                .create_transfer_transaction(Uuid::new_v4(), request)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, CircleError::Validation(_)), "{:?}", err);
+        }
+        // The decimals are looked up once and nothing was submitted.
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
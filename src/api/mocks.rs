@@ -0,0 +1,34 @@
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::chargeback::Chargeback;
+use crate::models::mock::{
+    MockAchAccountRequest, MockAchAccountResponse, MockChargebackRequest, MockWirePaymentRequest,
+    MockWirePaymentResponse,
+};
+
+impl CircleClient {
+    pub async fn create_mock_wire_payment(
+        &self,
+        request: MockWirePaymentRequest,
+    ) -> Result<MockWirePaymentResponse> {
+        let url = format!("{}mocks/payments/wire", self.base_url);
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+
+    pub async fn create_mock_chargeback(&self, payment_id: Uuid) -> Result<Chargeback> {
+        let url = format!("{}mocks/cards/chargebacks", self.base_url);
+        let request = MockChargebackRequest::new(payment_id);
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+
+    pub async fn create_mock_ach_account(
+        &self,
+        request: MockAchAccountRequest,
+    ) -> Result<MockAchAccountResponse> {
+        let url = format!("{}mocks/ach/accounts", self.base_url);
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+}
@@ -1,5 +1,7 @@
-use log::debug;
-use reqwest::{Client, Method, Response};
+use log::{debug, warn};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, Method, StatusCode};
+use rsa::pkcs1::DecodeRsaPublicKey;
 use rsa::pkcs8::DecodePublicKey;
 use rsa::sha2::Sha256;
 use rsa::{Oaep, RsaPublicKey};
@@ -7,16 +9,145 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::error::CircleError;
 use crate::error::Result;
 use crate::models::public_key::PublicKeyResponse;
+use crate::models::RequestId;
+use inner::ClientInner;
+use metrics::CallStats;
 
+pub use audit::{AuditOutcome, AuditPhase, AuditRecord, AuditSink};
+pub use builder::CircleClientBuilder;
+pub use cache::{CachedEndpoint, InMemoryResponseCacheStore, ResponseCache, ResponseCacheStore};
+#[cfg(any(test, feature = "test-util"))]
+pub use cassette::{CassetteMode, CassetteTransport, CASSETTE_MODE_ENV};
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
+pub use custom::CustomRequest;
+pub use debug_log::DebugLogger;
+pub use decode::DecodeError;
+pub use envelope::{Envelope, RateLimit};
+pub use environment::{Environment, PRODUCTION_BASE_URL, SANDBOX_BASE_URL};
+pub use error_code::{CircleErrorCode, ErrorCodeFamily};
+pub use failover::BaseUrls;
+#[cfg(any(test, feature = "test-util"))]
+pub use fault_injection::{FaultInjectingTransport, InjectedFaults};
+pub use health::Health;
+pub use idempotency::{IdempotencyPolicy, IdempotencyStore, Idempotent, InMemoryIdempotencyStore};
+pub use metrics::{CallMetrics, MetricsSink};
+pub use middleware::ClientMiddleware;
+#[cfg(any(test, feature = "test-util"))]
+pub use mock_transport::{MockTransport, RecordedRequest, MOCK_PUBLIC_KEY};
+pub use namespaces::Tokens;
+#[cfg(feature = "transactions")]
+pub use namespaces::Transactions;
+#[cfg(feature = "webhooks")]
+pub use namespaces::Webhooks;
+#[cfg(feature = "wallets")]
+pub use namespaces::{WalletSets, Wallets};
+pub use options::{RequestIdCapture, RequestOptions};
+#[cfg(not(target_arch = "wasm32"))]
+pub use pool::PoolConfig;
+#[cfg(feature = "prometheus")]
+pub use prometheus::PrometheusMetrics;
+#[cfg(not(target_arch = "wasm32"))]
+pub use proxy::ProxyConfig;
+pub use rate_limit::RateLimiter;
+pub use raw::RawCapture;
+pub use retry::RetryPolicy;
+pub use simulation::{SimulatedCall, SimulationLog};
+pub use stats::{ClientStats, EndpointStats};
+pub use tenant::TenantCredentials;
+pub use timeouts::Timeouts;
+pub(crate) use transport::is_transient;
+pub use transport::{
+    HttpRequest, HttpResponse, ReqwestTransport, Transport, TransportError, TransportErrorKind,
+    TransportFuture,
+};
+pub use version::{ApiResource, ApiVersion};
+
+mod audit;
+#[cfg(feature = "payments")]
+mod bank_accounts;
+mod builder;
+#[cfg(feature = "payments")]
+mod business_account;
+mod cache;
+#[cfg(any(test, feature = "test-util"))]
+mod cassette;
+#[cfg(feature = "contracts")]
+mod cctp;
+#[cfg(feature = "payments")]
+mod channels;
+#[cfg(feature = "payments")]
+mod chargebacks;
+mod circuit_breaker;
+#[cfg(feature = "contracts")]
+mod contracts;
+#[cfg(feature = "payments")]
+mod conversions;
+mod custom;
+mod debug_log;
+mod decode;
+mod envelope;
+mod environment;
+mod error_code;
+#[cfg(feature = "payments")]
+mod exchange;
+mod failover;
+#[cfg(feature = "sandbox")]
+mod faucet;
+#[cfg(any(test, feature = "test-util"))]
+mod fault_injection;
+mod health;
+mod idempotency;
+mod inner;
+mod instrument;
+mod metrics;
+mod middleware;
+#[cfg(any(test, feature = "test-util"))]
+mod mock_transport;
+#[cfg(all(feature = "sandbox", feature = "payments"))]
+mod mocks;
+mod namespaces;
+mod options;
+#[cfg(any(feature = "wallets", feature = "transactions", feature = "payments"))]
+mod paginate;
+#[cfg(not(target_arch = "wasm32"))]
+mod pool;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+#[cfg(not(target_arch = "wasm32"))]
+mod proxy;
+mod rate_limit;
+mod raw;
+#[cfg(feature = "transactions")]
+mod refunds;
+mod retry;
+#[cfg(feature = "payments")]
+mod returns;
+#[cfg(feature = "payments")]
+mod settlements;
+#[cfg(feature = "wallets")]
 mod signing;
+mod simulation;
+mod stats;
+#[cfg(feature = "webhooks")]
+mod subscriptions;
+mod tenant;
+mod timeouts;
 mod token_lookup;
+#[cfg(feature = "transactions")]
 mod transactions;
+mod transport;
+mod version;
+#[cfg(feature = "wallets")]
 mod wallet_sets;
+#[cfg(feature = "wallets")]
 mod wallets;
 
 #[derive(Deserialize, Debug)]
@@ -28,11 +159,49 @@ struct ApiSuccess<T> {
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiError {
+    #[serde(skip)]
+    status: Option<StatusCode>,
     code: i64,
     message: String,
+    #[serde(default)]
     errors: Vec<ApiErrorConduit>,
 }
 
+impl ApiError {
+    /// The HTTP status the error was returned with. Only `None` for errors deserialized by hand.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    pub fn code(&self) -> i64 {
+        self.code
+    }
+
+    pub fn error_code(&self) -> CircleErrorCode {
+        CircleErrorCode::from(self.code)
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ApiErrorTypes> {
+        self.errors.iter().map(|conduit| &conduit.error)
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)?;
+        if let Some(status) = self.status {
+            write!(f, ", HTTP {}", status)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
 #[derive(Deserialize, Debug)]
 struct ApiErrorConduit {
     #[serde(flatten)]
@@ -52,52 +221,211 @@ pub enum ApiErrorTypes {
     #[serde(rename_all = "camelCase")]
     UnknownError {
         #[serde(flatten)]
-        data: HashMap<String, String>,
+        data: HashMap<String, Value>,
     },
 }
 
+impl ApiErrorTypes {
+    /// The request field the error refers to, e.g. `amounts` or `destinationAddress`.
+    pub fn location(&self) -> Option<&str> {
+        match self {
+            ApiErrorTypes::InvalidValue { location, .. } => Some(location),
+            ApiErrorTypes::UnknownError { data } => data.get("location").and_then(Value::as_str),
+        }
+    }
+}
+
+/// The client is a handle to shared state: cloning it is cheap, and clones share the
+/// connection pool, rate limiter, circuit breaker and credentials. It is `Send + Sync`, so it
+/// can be put straight into e.g. axum state or moved into spawned tasks.
+#[derive(Clone)]
 pub struct CircleClient {
-    base_url: String,
-    api_key: String,
-    circle_entity_secret: String,
-    client: Client,
-    public_key: RsaPublicKey,
+    inner: Arc<ClientInner>,
+}
+
+// Fails to compile if a field ever makes the client lose these.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync + Clone + 'static>() {}
+    assert_send_sync::<CircleClient>();
+};
+
+impl Deref for CircleClient {
+    type Target = ClientInner;
+
+    fn deref(&self) -> &ClientInner {
+        &self.inner
+    }
 }
 
 impl CircleClient {
     pub async fn new(api_key: String, circle_entity_secret: String) -> Result<Self> {
-        let base_url = "https://api.circle.com/v1/";
-        let client = Client::new();
+        Self::builder()
+            .api_key(api_key)
+            .entity_secret(circle_entity_secret)
+            .build()
+            .await
+    }
+
+    pub fn builder() -> CircleClientBuilder {
+        CircleClientBuilder::new()
+    }
+
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    /// Request counts and latencies per endpoint plus the rate limiter and circuit breaker
+    /// state, e.g. for an admin or health endpoint.
+    pub fn stats(&self) -> ClientStats {
+        ClientStats {
+            endpoints: self.stats.endpoints(),
+            rate_limiter_saturation: self.rate_limiter.as_ref().map(RateLimiter::saturation),
+            circuit_state: self.circuit_breaker.as_ref().map(CircuitBreaker::state),
+        }
+    }
+
+    /// The limiter every request of this client and its copies waits on, if there is one.
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.inner.rate_limiter.as_ref()
+    }
 
-        let public_key = Self::fetch_public_key(&client, base_url, &api_key).await?;
+    /// Returns a copy of the client sharing the same connection pool, with the request
+    /// timeout and deadline overridden. The connect timeout is fixed when the client is built.
+    pub fn with_timeouts(&self, timeouts: Timeouts) -> Self {
+        self.modified(|inner| {
+            inner.timeouts.request = timeouts.request.or(inner.timeouts.request);
+            inner.timeouts.deadline = timeouts.deadline.or(inner.timeouts.deadline);
+        })
+    }
+
+    /// Returns a copy of the client sharing the same connection pool and limits, with
+    /// `options` applied to every call made through it:
+    ///
+    /// ```no_run
+    /// # async fn run(client: circle_api::api::CircleClient, id: circle_api::models::ids::WalletId) -> circle_api::error::Result<()> {
+    /// use std::time::Duration;
+    /// use circle_api::api::{RequestOptions, RetryPolicy};
+    ///
+    /// let dashboard = client.with_options(
+    ///     RequestOptions::new()
+    ///         .timeout(Duration::from_secs(2))
+    ///         .retry_policy(RetryPolicy::none()),
+    /// );
+    /// let wallet = dashboard.get_wallet(id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        self.with_timeouts(Timeouts {
+            connect: None,
+            request: options.timeout,
+            deadline: options.deadline,
+        })
+        .modified(|inner| {
+            if let Some(retry_policy) = options.retry_policy {
+                inner.retry_policy = retry_policy;
+            }
+            inner.headers.extend(options.headers);
+            if let Some(capture) = options.request_id {
+                inner.request_id_capture = Some(capture);
+            }
+            if let Some(capture) = options.raw {
+                inner.raw_capture = Some(capture);
+            }
+        })
+    }
+
+    /// A copy of the client with `update` applied, leaving this one and its other clones alone.
+    fn modified<F: FnOnce(&mut ClientInner)>(&self, update: F) -> Self {
+        let mut inner = ClientInner::clone(&self.inner);
+        update(&mut inner);
+        CircleClient {
+            inner: Arc::new(inner),
+        }
+    }
+
+    /// Swaps the API key used by this client and every copy made from it (with
+    /// [`CircleClient::with_options`], [`CircleClient::with_timeouts`] or `clone`). Attempts
+    /// already on the wire finish with the old key; the next one uses the new key.
+    pub fn set_api_key<S: Into<String>>(&self, api_key: S) {
+        self.credentials.write().unwrap().api_key = api_key.into();
+    }
+
+    /// Swaps the entity secret after it was rotated in the Circle console. Like
+    /// [`CircleClient::set_api_key`], this applies to all copies of the client.
+    pub fn set_entity_secret<S: Into<String>>(&self, entity_secret: S) {
+        self.credentials.write().unwrap().entity_secret = entity_secret.into();
+    }
+
+    pub fn set_credentials(&self, credentials: TenantCredentials) {
+        *self.credentials.write().unwrap() = credentials;
+    }
 
-        Ok(CircleClient {
-            base_url: base_url.to_string(),
-            api_key,
-            circle_entity_secret,
-            client,
-            public_key,
+    fn entity_secret_ciphertext(&self) -> Result<String> {
+        let credentials = self.credentials.read().unwrap();
+        encrypt_entity_secret(&self.public_key, &credentials.entity_secret)
+    }
+
+    pub fn base_urls(&self) -> &BaseUrls {
+        &self.base_urls
+    }
+
+    /// The underlying HTTP client, for sharing its connection pool with other code. `None` when
+    /// the client was built with a custom [`Transport`].
+    pub fn http_client(&self) -> Option<&Client> {
+        self.client.as_ref()
+    }
+
+    pub fn transport(&self) -> &Arc<dyn Transport> {
+        &self.transport
+    }
+
+    fn http_request(
+        method: Method,
+        url: String,
+        api_key: &str,
+        extra_headers: &HeaderMap,
+        body: Option<Vec<u8>>,
+        timeout: Option<Duration>,
+    ) -> Result<HttpRequest> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let authorization = HeaderValue::from_str(&format!("Bearer {}", api_key))
+            .map_err(|_| CircleError::ValueError)?;
+        headers.insert(AUTHORIZATION, authorization);
+        headers.extend(extra_headers.clone());
+        Ok(HttpRequest {
+            method,
+            url,
+            headers,
+            body,
+            timeout,
         })
     }
 
     async fn fetch_public_key(
-        client: &Client,
+        transport: &dyn Transport,
+        middleware: &middleware::MiddlewareChain,
         base_url: &str,
         api_key: &str,
+        headers: &HeaderMap,
+        timeout: Option<Duration>,
     ) -> Result<RsaPublicKey> {
         let url = format!("{}w3s/config/entity/publicKey", base_url);
-        let res = client
-            .get(&url)
-            .header("Content-Type", "application/json")
-            .bearer_auth(api_key)
-            .send()
-            .await?;
-
-        let public_key_response: PublicKeyResponse = Self::parse_response(res).await?;
-        let public_key =
-            RsaPublicKey::from_public_key_pem(&public_key_response.public_key.replace("RSA ", ""))
-                .unwrap();
-        Ok(public_key)
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let request =
+                Self::http_request(Method::GET, url.clone(), api_key, headers, None, timeout)?;
+            let res = Self::send_through(transport, middleware, request).await?;
+            let public_key_response: PublicKeyResponse = Self::parse_response(res)?;
+            match parse_public_key(&public_key_response.public_key) {
+                Err(CircleError::InvalidPublicKey) if attempt == 1 => {
+                    debug!("could not parse the entity public key, fetching it again");
+                }
+                result => return result,
+            }
+        }
     }
 
     async fn send_request<T: DeserializeOwned>(
@@ -106,37 +434,266 @@ impl CircleClient {
         url: String,
         body: Option<impl Serialize>,
     ) -> Result<T> {
-        let request = self
-            .client
-            .request(method, &url)
-            .bearer_auth(&self.api_key)
-            .json(&body);
+        let cached = match &self.response_cache {
+            Some(cache) if method == Method::GET && body.is_none() => {
+                let api_key = self.credentials.read().unwrap().api_key.clone();
+                cache
+                    .entry(&api_key, version::path(&url))
+                    .map(|(key, ttl)| (cache, key, ttl))
+            }
+            _ => None,
+        };
+        let Some((cache, key, ttl)) = cached else {
+            return self.send_uncached(method, url, body).await;
+        };
+        let value = match cache.get(&key) {
+            Some(value) => value,
+            None => {
+                let value: Value = self.send_uncached(method, url, body).await?;
+                cache.insert(&key, value.clone(), ttl);
+                value
+            }
+        };
+        Ok(serde_json::from_value(value)?)
+    }
 
-        let response = request.send().await?;
-        Self::parse_response(response).await
+    /// [`CircleClient::send_request`] without the [`ResponseCache`].
+    async fn send_uncached<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: String,
+        body: Option<impl Serialize>,
+    ) -> Result<T> {
+        if let Some(log) = &self.simulation {
+            if simulation::is_simulated(&method, &url) {
+                return self.simulate(log, method, url, body).await;
+            }
+        }
+        #[cfg(feature = "tracing")]
+        let span = instrument::request_span(&method, &url);
+        let audit = match &self.audit {
+            Some(sink) if method != Method::GET => {
+                let api_key = self.credentials.read().unwrap().api_key.clone();
+                let body = serde_json::to_value(&body)?;
+                let record = audit::AuditRecord::started(&api_key, &method, &url, &body);
+                sink.record(&record)?;
+                Some((sink, record))
+            }
+            _ => None,
+        };
+        let started = crate::runtime::Instant::now();
+        let mut stats = CallStats::default();
+        let response = async {
+            let send = self.send_with_retries(method.clone(), url.clone(), body, &mut stats);
+            match self.timeouts.deadline {
+                Some(deadline) => crate::runtime::timeout(deadline, send)
+                    .await
+                    .map_err(|_| CircleError::DeadlineExceeded(deadline))?,
+                None => send.await,
+            }
+        };
+        #[cfg(feature = "tracing")]
+        let response = tracing::Instrument::instrument(response, span);
+        let response = response.await;
+        let call = CallMetrics {
+            method: &method,
+            endpoint: &instrument::endpoint(&url).0,
+            duration: started.elapsed(),
+            status: stats.status,
+            attempts: stats.attempts,
+            rate_limited: stats.rate_limited,
+            error: response.as_ref().err(),
+        };
+        self.stats.record(&call);
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&call);
+        }
+        if let Some((sink, record)) = audit {
+            if let Err(err) = sink.record(&record.finished(&response, stats.status)) {
+                warn!(
+                    "failed to record audit trail for {} {}: {}",
+                    method, url, err
+                );
+            }
+        }
+        response
+    }
+
+    async fn send_with_retries<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: String,
+        body: Option<impl Serialize>,
+        stats: &mut CallStats,
+    ) -> Result<T> {
+        // GET parameters go in the query string; Circle ignores a GET body.
+        let (url, body) = match (&method, body) {
+            (&Method::GET, Some(query)) => match serde_qs::to_string(&query)? {
+                query if query.is_empty() => (url, None),
+                query => (format!("{}?{}", url, query), None),
+            },
+            (_, body) => (url, body),
+        };
+        let body = serde_json::to_value(&body)?;
+        instrument::record_body(&body);
+        let idempotent = body.get("idempotencyKey").is_some();
+        let mut body = match body {
+            Value::Null => None,
+            body => Some(body),
+        };
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            // Circle rejects a ciphertext it has seen before, so retries need a fresh one.
+            if let Some(Value::Object(body)) = body.as_mut().filter(|_| attempt > 1) {
+                for field in ["entitySecretCiphertext", "entitySecretCipherText"] {
+                    if let Some(ciphertext) = body.get_mut(field) {
+                        *ciphertext = self.entity_secret_ciphertext()?.into();
+                    }
+                }
+            }
+            let payload = body.as_ref().map(serde_json::to_vec).transpose()?;
+            if let Some(circuit_breaker) = &self.circuit_breaker {
+                circuit_breaker.allow()?;
+            }
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            let endpoint = self.base_urls.select(&method);
+            let api_key = self.credentials.read().unwrap().api_key.clone();
+            let mut request = Self::http_request(
+                method.clone(),
+                self.base_urls.rebase(&url, endpoint),
+                &api_key,
+                &self.headers,
+                payload,
+                self.timeouts.request,
+            )?;
+            instrument::propagate(&mut request.headers);
+
+            let result =
+                Self::send_through(self.transport.as_ref(), &self.middleware, request).await;
+            instrument::record_attempt(&method, attempt, &result);
+            stats.attempts = attempt;
+            stats.status = result.as_ref().ok().map(|x| x.status);
+            if let (Some(capture), Ok(response)) = (&self.request_id_capture, &result) {
+                capture.set(request_id(&response.headers));
+            }
+            let retryable = match &result {
+                Ok(response) => response.status.is_server_error(),
+                Err(err) => transport::is_transient(err),
+            };
+            self.base_urls.record(endpoint, !retryable);
+            if let Some(circuit_breaker) = &self.circuit_breaker {
+                if retryable {
+                    circuit_breaker.record_failure();
+                } else {
+                    circuit_breaker.record_success();
+                }
+            }
+            if let Ok(response) = &result {
+                if response.status == StatusCode::TOO_MANY_REQUESTS {
+                    let retry_after = retry::parse_retry_after(&response.headers);
+                    match self.retry_policy.rate_limit_wait(attempt, retry_after) {
+                        Some(wait) => {
+                            stats.rate_limited += 1;
+                            debug!("rate limited on {} {}, waiting {:?}", method, url, wait);
+                            crate::runtime::sleep(wait).await;
+                            continue;
+                        }
+                        None => Err(CircleError::RateLimited { retry_after })?,
+                    }
+                }
+            }
+            if retryable && self.retry_policy.should_retry(&method, idempotent, attempt) {
+                let backoff = self.retry_policy.backoff(attempt);
+                debug!(
+                    "retrying {} {} in {:?} (attempt {})",
+                    method, url, backoff, attempt
+                );
+                crate::runtime::sleep(backoff).await;
+                continue;
+            }
+            let response = result?;
+            if let Some(capture) = &self.raw_capture {
+                capture.record(&response);
+            }
+            if let (Some(capture), true) = (&self.header_capture, response.status.is_success()) {
+                capture.set(response.status, &response.headers);
+            }
+            return Self::parse_response(response);
+        }
+    }
+
+    async fn send_through(
+        transport: &dyn Transport,
+        middleware: &middleware::MiddlewareChain,
+        mut request: HttpRequest,
+    ) -> Result<HttpResponse> {
+        if middleware.is_empty() {
+            return transport.send(request).await;
+        }
+        middleware.on_request(&mut request)?;
+        let sent = request.clone();
+        let result = transport.send(request).await;
+        middleware.on_result(&sent, &result);
+        result
     }
 
-    async fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T> {
+    fn parse_response<T: DeserializeOwned>(response: HttpResponse) -> Result<T> {
+        if !response.status.is_success() {
+            return Err(Self::parse_error(response));
+        }
+
         let request_id = response
-            .headers()
+            .headers
             .get("X-Request-Id")
             .ok_or(CircleError::MissingRequestId)?;
         let request_id = request_id.to_str()?;
         let request_id = Uuid::parse_str(request_id)?;
 
-        let status = response.status();
-        let json_value: Value = response.json().await?;
-        debug!("json_value: {:?}", json_value);
-        if status.is_success() {
-            let json_obj = serde_json::from_value::<ApiSuccess<T>>(json_value)?;
-            Ok(json_obj.data)
-        } else {
-            let json_obj = serde_json::from_value::<ApiError>(json_value)?;
-            Err(CircleError::ApiError(request_id, json_obj))?
+        let body = match response.body.is_empty() {
+            true => &b"{}"[..],
+            false => &response.body[..],
+        };
+        debug!("response body: {}", String::from_utf8_lossy(body));
+        let json_obj = decode::decode::<ApiSuccess<T>>(body, Some(request_id))?;
+        Ok(json_obj.data)
+    }
+
+    /// Gateways and load balancers in front of Circle answer with HTML or empty bodies and no
+    /// request id; those surface as [`CircleError::UnexpectedStatus`] instead of a parse error.
+    fn parse_error(response: HttpResponse) -> CircleError {
+        debug!("error body: {}", String::from_utf8_lossy(&response.body));
+        let request_id = request_id(&response.headers);
+        match (
+            request_id,
+            serde_json::from_slice::<ApiError>(&response.body),
+        ) {
+            (Some(request_id), Ok(mut error)) => {
+                error.status = Some(response.status);
+                CircleError::ApiError(request_id, error)
+            }
+            _ => CircleError::UnexpectedStatus(request_id, response.status),
         }
     }
 }
 
+/// Circle labels its key `RSA PUBLIC KEY` (PKCS#1) while the body is a SubjectPublicKeyInfo,
+/// so accept either encoding under either label.
+fn parse_public_key(pem: &str) -> Result<RsaPublicKey> {
+    let spki = pem.trim().replace("RSA PUBLIC KEY", "PUBLIC KEY");
+    let pkcs1 = spki.replace("PUBLIC KEY", "RSA PUBLIC KEY");
+    RsaPublicKey::from_public_key_pem(&spki)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(&pkcs1))
+        .map_err(|_| CircleError::InvalidPublicKey)
+}
+
+fn request_id(headers: &HeaderMap) -> Option<RequestId> {
+    let request_id = headers.get("X-Request-Id")?.to_str().ok()?;
+    Uuid::parse_str(request_id).ok()
+}
+
 pub fn encrypt_entity_secret(public_key: &RsaPublicKey, entity_secret: &str) -> Result<String> {
     let entity_secret = hex::decode(entity_secret)?;
     let padding = Oaep::new::<Sha256>();
@@ -146,6 +703,10 @@ pub fn encrypt_entity_secret(public_key: &RsaPublicKey, entity_secret: &str) ->
 
 #[cfg(test)]
 mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rsa::pkcs1::{EncodeRsaPublicKey, LineEnding};
+
     use crate::models::wallet_set::CreateWalletSetResponse;
 
     use super::*;
@@ -210,4 +771,190 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_parse_error_response() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Request-Id",
+            HeaderValue::from_static("2f4b8a4c-5a3e-4b61-8a0e-3b6f2b8e4c1d"),
+        );
+        let body = serde_json::json!({
+            "code": 2,
+            "message": "API parameter invalid",
+            "errors": [{
+                "error": "value_must_be_positive",
+                "location": "amounts",
+                "invalidValue": 0,
+                "constraints": { "min": 1 }
+            }]
+        });
+        let response = HttpResponse {
+            status: StatusCode::BAD_REQUEST,
+            headers: headers.clone(),
+            body: serde_json::to_vec(&body).unwrap(),
+        };
+        let err = CircleClient::parse_response::<Value>(response).unwrap_err();
+        let CircleError::ApiError(_, err) = err else {
+            panic!("unexpected error: {:?}", err);
+        };
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(err.code(), 2);
+        assert_eq!(
+            err.errors().map(|x| x.location()).collect::<Vec<_>>(),
+            vec![Some("amounts")]
+        );
+
+        let response = HttpResponse {
+            status: StatusCode::NOT_FOUND,
+            headers,
+            body: br#"{"code":-1,"message":"Not found"}"#.to_vec(),
+        };
+        let err = CircleClient::parse_response::<Value>(response).unwrap_err();
+        assert!(matches!(err, CircleError::ApiError(_, ref err) if err.errors().count() == 0));
+
+        let response = HttpResponse {
+            status: StatusCode::BAD_GATEWAY,
+            headers: HeaderMap::new(),
+            body: b"<html>502 Bad Gateway</html>".to_vec(),
+        };
+        let err = CircleClient::parse_response::<Value>(response).unwrap_err();
+        assert!(matches!(
+            err,
+            CircleError::UnexpectedStatus(None, StatusCode::BAD_GATEWAY)
+        ));
+    }
+
+    /// Answers public key fetches, with a corrupt key for the first `corrupt` of them.
+    #[derive(Default)]
+    struct PublicKeyTransport {
+        corrupt: usize,
+        fetches: AtomicUsize,
+    }
+
+    impl Transport for PublicKeyTransport {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(async move {
+                let fetch = self.fetches.fetch_add(1, Ordering::SeqCst);
+                assert_eq!(
+                    request.url,
+                    format!("{}w3s/config/entity/publicKey", SANDBOX_BASE_URL)
+                );
+                assert_eq!(request.headers[AUTHORIZATION], "Bearer key");
+                let mut headers = HeaderMap::new();
+                headers.insert(
+                    "X-Request-Id",
+                    HeaderValue::from_static("2f4b8a4c-5a3e-4b61-8a0e-3b6f2b8e4c1d"),
+                );
+                let public_key = match fetch < self.corrupt {
+                    true => "-----BEGIN RSA PUBLIC KEY-----\nAAAA\n-----END RSA PUBLIC KEY-----\n",
+                    false => PUBLIC_RSA_KEY_STR,
+                };
+                let body = serde_json::json!({ "data": { "publicKey": public_key } });
+                Ok(HttpResponse {
+                    status: StatusCode::OK,
+                    headers,
+                    body: serde_json::to_vec(&body)?,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_with_custom_transport() {
+        let client = CircleClient::builder()
+            .api_key("key")
+            .entity_secret(hex::encode("test"))
+            .environment(Environment::Sandbox)
+            .transport(PublicKeyTransport::default())
+            .build()
+            .await
+            .unwrap();
+        assert!(client.http_client().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_state() {
+        let client = CircleClient::builder()
+            .api_key("key")
+            .entity_secret(hex::encode("test"))
+            .environment(Environment::Sandbox)
+            .transport(PublicKeyTransport::default())
+            .build()
+            .await
+            .unwrap();
+        let clone = client.clone();
+        assert!(Arc::ptr_eq(&client.inner, &clone.inner));
+        let environment = tokio::spawn(async move { clone.environment().clone() })
+            .await
+            .unwrap();
+        assert_eq!(environment, Environment::Sandbox);
+
+        let patient = client.with_timeouts(Timeouts::new().request(Duration::from_secs(60)));
+        assert!(!Arc::ptr_eq(&client.inner, &patient.inner));
+        assert!(Arc::ptr_eq(&client.stats, &patient.stats));
+        assert_eq!(patient.timeouts.request, Some(Duration::from_secs(60)));
+        assert_ne!(client.timeouts.request, patient.timeouts.request);
+    }
+
+    #[test]
+    fn test_parse_public_key() {
+        let public_key = parse_public_key(PUBLIC_RSA_KEY_STR).unwrap();
+        let spki = PUBLIC_RSA_KEY_STR.replace("RSA ", "");
+        assert_eq!(parse_public_key(&spki).unwrap(), public_key);
+
+        let pkcs1 = public_key.to_pkcs1_pem(LineEnding::LF).unwrap();
+        assert!(pkcs1.starts_with("-----BEGIN RSA PUBLIC KEY-----"));
+        assert_eq!(parse_public_key(&pkcs1).unwrap(), public_key);
+
+        assert!(matches!(
+            parse_public_key("not a key"),
+            Err(CircleError::InvalidPublicKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_refetch_corrupt_public_key() {
+        let builder = || {
+            CircleClient::builder()
+                .api_key("key")
+                .entity_secret(hex::encode("test"))
+                .environment(Environment::Sandbox)
+        };
+        let transport = PublicKeyTransport {
+            corrupt: 1,
+            ..Default::default()
+        };
+        assert!(builder().transport(transport).build().await.is_ok());
+
+        let transport = PublicKeyTransport {
+            corrupt: 2,
+            ..Default::default()
+        };
+        assert!(matches!(
+            builder().transport(transport).build().await,
+            Err(CircleError::InvalidPublicKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_and_default_headers() {
+        let transport = MockTransport::new();
+        let client = transport
+            .client_builder()
+            .application("acme-payouts/2.3.1")
+            .default_header("X-Integration", "acme")
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        transport.push_data(serde_json::json!([]));
+        client.list_channels().await.unwrap();
+
+        let request = transport.last_request().unwrap();
+        let user_agent = request.headers["User-Agent"].to_str().unwrap();
+        assert!(user_agent.starts_with("circle_api/"));
+        assert!(user_agent.ends_with(" acme-payouts/2.3.1"));
+        assert_eq!(request.headers["X-Integration"], "acme");
+    }
 }
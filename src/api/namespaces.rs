@@ -0,0 +1,379 @@
+//! Handles grouping the client's calls by resource, e.g. `client.wallets().get(id)`. They
+//! borrow the client, so they are free to create and share its transport, rate limiter and
+//! credentials. The flat methods on [`CircleClient`] stay available.
+
+#[cfg(any(feature = "wallets", feature = "transactions"))]
+use futures::Stream;
+#[cfg(any(feature = "wallets", feature = "transactions", feature = "webhooks"))]
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+#[cfg(feature = "wallets")]
+use crate::models::blockchain::Blockchain;
+use crate::models::ids::TokenId;
+#[cfg(feature = "transactions")]
+use crate::models::ids::TransactionId;
+#[cfg(feature = "wallets")]
+use crate::models::ids::{WalletId, WalletSetId};
+#[cfg(feature = "webhooks")]
+use crate::models::subscription::{PaymentsSubscription, Subscription};
+use crate::models::token_get::TokenGetResponse;
+#[cfg(feature = "transactions")]
+use crate::models::transaction::{Transaction, TxType};
+#[cfg(feature = "transactions")]
+use crate::models::transaction_accelerate::TransactionAccelerateResponse;
+#[cfg(feature = "transactions")]
+use crate::models::transaction_cancel::TransactionCancelResponse;
+#[cfg(feature = "contracts")]
+use crate::models::transaction_contract_execution::{
+    TransactionContractExecutionRequestBuilder, TransactionContractExecutionResponse,
+};
+#[cfg(feature = "transactions")]
+use crate::models::transaction_get::TransactionGetResponse;
+#[cfg(feature = "transactions")]
+use crate::models::transaction_list::{TransactionListQueryParams, TransactionListResponse};
+#[cfg(feature = "transactions")]
+use crate::models::transaction_transfer_create::{
+    TransactionTransferCreateRequestBuilder, TransactionTransferCreateResponse,
+};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_balance::{WalletBalanceQueryParams, WalletBalanceResponse};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_create::{WalletCreateRequestBuilder, WalletCreateResponse};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_detail::WalletDetail;
+#[cfg(feature = "wallets")]
+use crate::models::wallet_get::WalletGetResponse;
+#[cfg(feature = "wallets")]
+use crate::models::wallet_list::{WalletListQueryParams, WalletListResponse};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_nfts::{WalletNftsQueryParams, WalletNftsResponse};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_set::{
+    CreateWalletSetRequestBuilder, CreateWalletSetResponse, GetWalletSetResponse,
+    UpdateWalletSetResponse, WalletSet, WalletSetsQueryParams, WalletSetsResponse,
+};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_update::{WalletUpdateRequest, WalletUpdateResponse};
+
+impl CircleClient {
+    #[cfg(feature = "wallets")]
+    pub fn wallet_sets(&self) -> WalletSets<'_> {
+        WalletSets(self)
+    }
+
+    #[cfg(feature = "wallets")]
+    pub fn wallets(&self) -> Wallets<'_> {
+        Wallets(self)
+    }
+
+    #[cfg(feature = "transactions")]
+    pub fn transactions(&self) -> Transactions<'_> {
+        Transactions(self)
+    }
+
+    pub fn tokens(&self) -> Tokens<'_> {
+        Tokens(self)
+    }
+
+    /// Notification subscriptions of both product families.
+    #[cfg(feature = "webhooks")]
+    pub fn webhooks(&self) -> Webhooks<'_> {
+        Webhooks(self)
+    }
+}
+
+#[cfg(feature = "wallets")]
+#[derive(Clone, Copy)]
+pub struct WalletSets<'a>(&'a CircleClient);
+
+#[cfg(feature = "wallets")]
+impl<'a> WalletSets<'a> {
+    pub async fn list(&self, query_params: WalletSetsQueryParams) -> Result<WalletSetsResponse> {
+        self.0.list_wallet_sets(query_params).await
+    }
+
+    pub fn list_stream(
+        &self,
+        query_params: WalletSetsQueryParams,
+    ) -> impl Stream<Item = Result<WalletSet>> + 'a {
+        self.0.list_wallet_sets_stream(query_params)
+    }
+
+    pub async fn create<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        name: String,
+    ) -> Result<CreateWalletSetResponse> {
+        self.0.create_wallet_set(idempotency_key, name).await
+    }
+
+    pub async fn create_with<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: CreateWalletSetRequestBuilder,
+    ) -> Result<CreateWalletSetResponse> {
+        self.0
+            .create_wallet_set_with(idempotency_key, request)
+            .await
+    }
+
+    pub async fn update(
+        &self,
+        wallet_set_id: WalletSetId,
+        name: String,
+    ) -> Result<UpdateWalletSetResponse> {
+        self.0.update_wallet_set(wallet_set_id, name).await
+    }
+
+    pub async fn get(&self, wallet_set_id: WalletSetId) -> Result<GetWalletSetResponse> {
+        self.0.get_wallet_set(wallet_set_id).await
+    }
+}
+
+#[cfg(feature = "wallets")]
+#[derive(Clone, Copy)]
+pub struct Wallets<'a>(&'a CircleClient);
+
+#[cfg(feature = "wallets")]
+impl<'a> Wallets<'a> {
+    pub async fn create<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        wallet_set_id: WalletSetId,
+        blockchains: Vec<Blockchain>,
+        count: u32,
+    ) -> Result<WalletCreateResponse> {
+        self.0
+            .create_wallet(idempotency_key, wallet_set_id, blockchains, count)
+            .await
+    }
+
+    pub async fn create_with<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: WalletCreateRequestBuilder,
+    ) -> Result<WalletCreateResponse> {
+        self.0.create_wallet_with(idempotency_key, request).await
+    }
+
+    pub async fn list(&self, query_params: WalletListQueryParams) -> Result<WalletListResponse> {
+        self.0.list_wallets(query_params).await
+    }
+
+    pub fn list_stream(
+        &self,
+        query_params: WalletListQueryParams,
+    ) -> impl Stream<Item = Result<WalletDetail>> + 'a {
+        self.0.list_wallets_stream(query_params)
+    }
+
+    pub async fn get(&self, wallet_id: WalletId) -> Result<WalletGetResponse> {
+        self.0.get_wallet(wallet_id).await
+    }
+
+    pub async fn update(
+        &self,
+        wallet_id: WalletId,
+        request: WalletUpdateRequest,
+    ) -> Result<WalletUpdateResponse> {
+        self.0.update_wallet(wallet_id, request).await
+    }
+
+    pub async fn balances(
+        &self,
+        wallet_id: WalletId,
+        query_params: WalletBalanceQueryParams,
+    ) -> Result<WalletBalanceResponse> {
+        self.0.get_wallet_balance(wallet_id, query_params).await
+    }
+
+    pub async fn nfts(
+        &self,
+        wallet_id: WalletId,
+        query_params: WalletNftsQueryParams,
+    ) -> Result<WalletNftsResponse> {
+        self.0.get_wallet_nfts(wallet_id, query_params).await
+    }
+}
+
+#[cfg(feature = "transactions")]
+#[derive(Clone, Copy)]
+pub struct Transactions<'a>(&'a CircleClient);
+
+#[cfg(feature = "transactions")]
+impl<'a> Transactions<'a> {
+    pub async fn transfer<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: TransactionTransferCreateRequestBuilder,
+    ) -> Result<TransactionTransferCreateResponse> {
+        self.0
+            .create_transfer_transaction(idempotency_key, request)
+            .await
+    }
+
+    #[cfg(feature = "contracts")]
+    pub async fn execute_contract<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: TransactionContractExecutionRequestBuilder,
+    ) -> Result<TransactionContractExecutionResponse> {
+        self.0
+            .create_contract_execution_transaction(idempotency_key, request)
+            .await
+    }
+
+    pub async fn accelerate<K: Into<Option<Uuid>>>(
+        &self,
+        transaction_id: TransactionId,
+        idempotency_key: K,
+    ) -> Result<TransactionAccelerateResponse> {
+        self.0
+            .accelerate_transaction(transaction_id, idempotency_key)
+            .await
+    }
+
+    pub async fn cancel<K: Into<Option<Uuid>>>(
+        &self,
+        transaction_id: TransactionId,
+        idempotency_key: K,
+    ) -> Result<TransactionCancelResponse> {
+        self.0
+            .cancel_transaction(transaction_id, idempotency_key)
+            .await
+    }
+
+    pub async fn list(
+        &self,
+        query_params: TransactionListQueryParams,
+    ) -> Result<TransactionListResponse> {
+        self.0.list_transactions(query_params).await
+    }
+
+    pub fn list_stream(
+        &self,
+        query_params: TransactionListQueryParams,
+    ) -> impl Stream<Item = Result<Transaction>> + 'a {
+        self.0.list_transactions_stream(query_params)
+    }
+
+    /// See [`CircleClient::list_transactions_stream_concurrent`].
+    pub fn list_stream_concurrent(
+        &self,
+        query_params: TransactionListQueryParams,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<Transaction>> + 'a {
+        self.0
+            .list_transactions_stream_concurrent(query_params, concurrency)
+    }
+
+    pub async fn get(
+        &self,
+        transaction_id: TransactionId,
+        tx_type: Option<TxType>,
+    ) -> Result<TransactionGetResponse> {
+        self.0.get_transaction(transaction_id, tx_type).await
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Tokens<'a>(&'a CircleClient);
+
+impl<'a> Tokens<'a> {
+    pub async fn get(&self, token_id: TokenId) -> Result<TokenGetResponse> {
+        self.0.get_token_details(token_id).await
+    }
+
+    /// See [`CircleClient::token_decimals`].
+    pub async fn decimals(&self, token_id: TokenId) -> Result<Option<u32>> {
+        self.0.token_decimals(token_id).await
+    }
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Copy)]
+pub struct Webhooks<'a>(&'a CircleClient);
+
+#[cfg(feature = "webhooks")]
+impl<'a> Webhooks<'a> {
+    /// Subscribes `endpoint` to Web3 Services notifications, all of them if
+    /// `notification_types` is `None`.
+    pub async fn create(
+        &self,
+        endpoint: String,
+        notification_types: Option<Vec<String>>,
+    ) -> Result<Subscription> {
+        self.0
+            .create_subscription(endpoint, notification_types)
+            .await
+    }
+
+    pub async fn list(&self) -> Result<Vec<Subscription>> {
+        self.0.list_subscriptions().await
+    }
+
+    pub async fn get(&self, subscription_id: Uuid) -> Result<Subscription> {
+        self.0.get_subscription(subscription_id).await
+    }
+
+    pub async fn delete(&self, subscription_id: Uuid) -> Result<()> {
+        self.0.delete_subscription(subscription_id).await
+    }
+
+    /// Subscribes `endpoint` to payments notifications.
+    pub async fn create_payments(&self, endpoint: String) -> Result<PaymentsSubscription> {
+        self.0.create_payments_subscription(endpoint).await
+    }
+
+    pub async fn list_payments(&self) -> Result<Vec<PaymentsSubscription>> {
+        self.0.list_payments_subscriptions().await
+    }
+
+    pub async fn delete_payments(&self, subscription_id: Uuid) -> Result<()> {
+        self.0.delete_payments_subscription(subscription_id).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_namespaces_share_the_client() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let wallet_set_id = Uuid::new_v4();
+        transport.push_data(json!({ "walletSet": {
+            "id": wallet_set_id,
+            "custodyType": "DEVELOPER",
+            "name": "treasury",
+            "createDate": "2024-03-01T10:00:00Z",
+            "updateDate": "2024-03-01T10:00:00Z"
+        } }));
+        transport.push_data(json!([]));
+        let wallet_set = client
+            .wallet_sets()
+            .get(wallet_set_id.into())
+            .await
+            .unwrap();
+        assert_eq!(wallet_set.wallet_set.id, wallet_set_id);
+        assert!(client.webhooks().list().await.unwrap().is_empty());
+        let paths = transport
+            .requests()
+            .into_iter()
+            .map(|x| x.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                format!("/v1/w3s/walletSets/{}", wallet_set_id),
+                "/v1/w3s/subscriptions".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::api::retry::RetryPolicy;
+use crate::api::RawCapture;
+use crate::error::{CircleError, Result};
+use crate::models::RequestId;
+
+/// Overrides for a subset of calls, applied with [`crate::api::CircleClient::with_options`].
+/// Unset fields keep the client's configuration.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) deadline: Option<Duration>,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    pub(crate) headers: HeaderMap,
+    pub(crate) request_id: Option<RequestIdCapture>,
+    pub(crate) raw: Option<RawCapture>,
+}
+
+/// Receives the `X-Request-Id` of the last response to a call made with
+/// [`RequestOptions::capture_request_id`], successful or not.
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdCapture(Arc<Mutex<Option<RequestId>>>);
+
+impl RequestIdCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> Option<RequestId> {
+        *self.0.lock().unwrap()
+    }
+
+    pub(crate) fn set(&self, request_id: Option<RequestId>) {
+        *self.0.lock().unwrap() = request_id;
+    }
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time allowed for a single HTTP attempt.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Upper bound for the whole call, including retries and backoff.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Adds a header to every request, replacing any value the client would send for it.
+    pub fn header(mut self, name: &str, value: &str) -> Result<Self> {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| CircleError::ValueError)?;
+        let value = HeaderValue::from_str(value).map_err(|_| CircleError::ValueError)?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Records the request id of each response into `capture`. Use a separate capture (and
+    /// handle) per concurrent call, since every call made through the handle overwrites it.
+    pub fn capture_request_id(mut self, capture: &RequestIdCapture) -> Self {
+        self.request_id = Some(capture.clone());
+        self
+    }
+
+    /// Records the raw `data` of each response into `capture`, see [`CircleClient::raw`] for a
+    /// shorthand.
+    ///
+    /// [`CircleClient::raw`]: crate::api::CircleClient::raw
+    pub fn capture_raw(mut self, capture: &RawCapture) -> Self {
+        self.raw = Some(capture.clone());
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::StatusCode;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::{MockTransport, TransportErrorKind};
+
+    #[tokio::test]
+    async fn test_with_options() {
+        let transport = MockTransport::new();
+        let client = transport
+            .client_builder()
+            .retry_policy(RetryPolicy::new(3, Duration::ZERO))
+            .build()
+            .await
+            .unwrap();
+        let options = RequestOptions::new()
+            .retry_policy(RetryPolicy::none())
+            .header("X-Trace", "abc")
+            .unwrap();
+        transport.push_transport_error(TransportErrorKind::Connect);
+
+        assert!(client
+            .with_options(options)
+            .get_wallet(Uuid::new_v4().into())
+            .await
+            .is_err());
+        assert_eq!(transport.requests().len(), 1);
+        assert_eq!(transport.last_request().unwrap().headers["X-Trace"], "abc");
+        assert!(RequestOptions::new().header("bad header", "x").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_capture_request_id() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let capture = RequestIdCapture::new();
+        let client = client.with_options(RequestOptions::new().capture_request_id(&capture));
+
+        transport.push_error(StatusCode::NOT_FOUND, 156004, "Cannot find the wallet.");
+        let err = client.get_wallet(Uuid::new_v4().into()).await.unwrap_err();
+        assert!(err.request_id().is_some());
+        assert_eq!(capture.get(), err.request_id());
+
+        transport.push_data(serde_json::json!([]));
+        client.list_channels().await.unwrap();
+        assert!(capture.get().is_some());
+        assert_ne!(capture.get(), err.request_id());
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::vec;
+
+use futures::stream::{self, Fuse, FusedStream, Stream, StreamExt};
+
+use crate::error::Result;
+#[cfg(feature = "transactions")]
+use crate::models::pagination::SortOrder;
+use crate::models::pagination::{ListQueryParams, PageCursor, Paginated};
+
+/// Follows `page_after` cursors from `params` until a page comes back empty or short, yielding
+/// the pages. Stops at the first error.
+fn pages<P, T, F, Fut>(params: P, fetch: F) -> impl Stream<Item = Result<Vec<T>>>
+where
+    P: ListQueryParams,
+    T: PageCursor,
+    F: Fn(P) -> Fut,
+    Fut: Future<Output = Result<Paginated<T>>>,
+{
+    stream::try_unfold((Some(params), fetch), |(params, fetch)| async move {
+        let Some(mut params) = params else {
+            return Result::Ok(None);
+        };
+        let page = fetch(params.clone()).await?;
+        let page_size = params.page_params().size();
+        let short = page_size.is_some_and(|x| page.len() < x as usize);
+        let next = match page.next_page(params.page_params()) {
+            Some(next) if !short => {
+                params.set_page_params(next);
+                Some(params)
+            }
+            _ => None,
+        };
+        Ok(Some((page.into_items(), (next, fetch))))
+    })
+}
+
+/// Yields the items of a stream of pages one by one, fetching up to `prefetch` pages ahead of
+/// the one being read.
+struct Prefetch<S, T> {
+    pages: Pin<Box<Fuse<S>>>,
+    buffer: VecDeque<Result<Vec<T>>>,
+    current: vec::IntoIter<T>,
+    prefetch: usize,
+}
+
+impl<S: Stream<Item = Result<Vec<T>>>, T> Prefetch<S, T> {
+    fn new(pages: S, prefetch: usize) -> Self {
+        Prefetch {
+            pages: Box::pin(pages.fuse()),
+            buffer: VecDeque::new(),
+            current: Vec::new().into_iter(),
+            prefetch,
+        }
+    }
+
+    /// Polls for pages until `prefetch` are buffered besides the one being read, or none is
+    /// ready yet.
+    fn fill(&mut self, cx: &mut Context<'_>) {
+        let wanted = self.prefetch + usize::from(self.current.len() == 0);
+        while self.buffer.len() < wanted && !self.pages.is_terminated() {
+            match self.pages.as_mut().poll_next(cx) {
+                Poll::Ready(Some(page)) => self.buffer.push_back(page),
+                Poll::Ready(None) | Poll::Pending => break,
+            }
+        }
+    }
+}
+
+impl<S: Stream<Item = Result<Vec<T>>>, T: Unpin> Stream for Prefetch<S, T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            this.fill(cx);
+            if let Some(item) = this.current.next() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            match this.buffer.pop_front() {
+                Some(Ok(page)) => this.current = page.into_iter(),
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None if this.pages.is_terminated() => return Poll::Ready(None),
+                None => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Follows `page_after` cursors from `params` until a page comes back empty or short, yielding
+/// the items one by one and fetching up to `prefetch` pages ahead. Stops at the first error.
+pub(crate) fn paginate<P, T, F, Fut>(
+    params: P,
+    prefetch: usize,
+    fetch: F,
+) -> impl Stream<Item = Result<T>>
+where
+    P: ListQueryParams,
+    T: PageCursor + Unpin,
+    F: Fn(P) -> Fut,
+    Fut: Future<Output = Result<Paginated<T>>>,
+{
+    Prefetch::new(pages(params, fetch), prefetch)
+}
+
+/// Reads scans one after the other while polling up to `concurrency` of them at once.
+#[cfg(feature = "transactions")]
+struct Concurrent<S, T> {
+    scans: VecDeque<Prefetch<S, T>>,
+    concurrency: usize,
+}
+
+#[cfg(feature = "transactions")]
+impl<S: Stream<Item = Result<Vec<T>>>, T: Unpin> Stream for Concurrent<S, T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            for scan in this.scans.iter_mut().take(this.concurrency).skip(1) {
+                scan.fill(cx);
+            }
+            let Some(scan) = this.scans.front_mut() else {
+                return Poll::Ready(None);
+            };
+            match Pin::new(scan).poll_next(cx) {
+                Poll::Ready(None) => drop(this.scans.pop_front()),
+                poll => return poll,
+            }
+        }
+    }
+}
+
+/// Like [`paginate`], but cuts the params' time range into `concurrency` windows and walks up to
+/// that many cursor chains at once. Items come back in the same order as a sequential scan:
+/// the first window is streamed as it arrives, while each later one buffers its first page plus
+/// up to `prefetch` more until its turn. Every request still goes through the client's rate
+/// limiter.
+#[cfg(feature = "transactions")]
+pub(crate) fn paginate_concurrent<P, T, F, Fut>(
+    params: P,
+    concurrency: usize,
+    prefetch: usize,
+    fetch: F,
+) -> impl Stream<Item = Result<T>>
+where
+    P: ListQueryParams,
+    T: PageCursor + Unpin,
+    F: Fn(P) -> Fut + Clone,
+    Fut: Future<Output = Result<Paginated<T>>>,
+{
+    let concurrency = concurrency.max(1);
+    let mut windows = params.window().split(concurrency);
+    if params.page_params().sort_order() != Some(SortOrder::Asc) {
+        windows.reverse();
+    }
+    let scans = windows.into_iter().map(|window| {
+        let mut params = params.clone();
+        params.set_window(window);
+        Prefetch::new(pages(params, fetch.clone()), prefetch)
+    });
+    Concurrent {
+        scans: scans.collect(),
+        concurrency,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use chrono::{Duration, TimeZone, Utc};
+    use futures::future::{self, FutureExt};
+    use futures::TryStreamExt;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::pagination::PageParams;
+    use crate::models::time_range::TimeRange;
+    use crate::models::wallet_set::WalletSetsQueryParams;
+
+    struct Item(Uuid, i64);
+
+    impl PageCursor for Item {
+        fn cursor(&self) -> Uuid {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paginate_concurrent() {
+        let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let range = TimeRange::between(from, from + Duration::seconds(3)).unwrap();
+        let params = WalletSetsQueryParams::new()
+            .time_range(range)
+            .page_size(1)
+            .unwrap();
+
+        // Each window has a single item, tagged with the window's start.
+        let first_page = PageParams::new().page_size(1).unwrap();
+        let fetch = |params: WalletSetsQueryParams| {
+            let start = params.window().start().unwrap() - from;
+            let items = match params.page_params() == &first_page {
+                true => vec![Item(Uuid::new_v4(), start.num_seconds())],
+                false => Vec::new(),
+            };
+            async move { Ok(Paginated { items }) }
+        };
+        let items = paginate_concurrent(params.clone(), 3, 0, fetch)
+            .map_ok(|x| x.1)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(items, vec![2, 1, 0]);
+
+        // The newest window is streamed before it's finished, even if its next page hangs.
+        let fetch = |params: WalletSetsQueryParams| {
+            let start = params.window().start().unwrap() - from;
+            let first = params.page_params() == &first_page;
+            async move {
+                if !first && start.num_seconds() == 2 {
+                    future::pending::<()>().await;
+                }
+                let items = match first {
+                    true => vec![Item(Uuid::new_v4(), start.num_seconds())],
+                    false => Vec::new(),
+                };
+                Ok(Paginated { items })
+            }
+        };
+        let mut items = Box::pin(paginate_concurrent(params, 3, 0, fetch));
+        let first = items.next().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(first.1, 2);
+        assert!(items.next().now_or_never().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_paginate_prefetch() {
+        let params = WalletSetsQueryParams::new().page_size(1).unwrap();
+        let fetched = AtomicUsize::new(0);
+        let fetch = |_| {
+            let page = fetched.fetch_add(1, Ordering::SeqCst);
+            let items = match page < 5 {
+                true => vec![Item(Uuid::new_v4(), page as i64)],
+                false => Vec::new(),
+            };
+            async move { Ok(Paginated { items }) }
+        };
+        let mut items = Box::pin(paginate(params, 2, fetch));
+        assert_eq!(items.next().await.unwrap().unwrap().1, 0);
+        // The page being read and two more.
+        assert_eq!(fetched.load(Ordering::SeqCst), 3);
+        let rest = items.map_ok(|x| x.1).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(rest, [1, 2, 3, 4]);
+    }
+}
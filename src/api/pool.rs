@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use reqwest::ClientBuilder;
+
+/// Connection pool tuning for the HTTP client built by the crate. High-throughput deployments
+/// usually want more idle connections per host than reqwest keeps by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept around before it is closed.
+    pub idle_timeout: Option<Duration>,
+    /// Interval for TCP keep-alive probes on open connections.
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl PoolConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_idle_per_host(mut self, value: usize) -> Self {
+        self.max_idle_per_host = Some(value);
+        self
+    }
+
+    pub fn idle_timeout(mut self, value: Duration) -> Self {
+        self.idle_timeout = Some(value);
+        self
+    }
+
+    pub fn tcp_keepalive(mut self, value: Duration) -> Self {
+        self.tcp_keepalive = Some(value);
+        self
+    }
+
+    pub(crate) fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(max_idle_per_host) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle_per_host);
+        }
+        if let Some(idle_timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(idle_timeout);
+        }
+        if let Some(tcp_keepalive) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(tcp_keepalive);
+        }
+        builder
+    }
+}
@@ -0,0 +1,118 @@
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+
+use crate::api::metrics::{CallMetrics, MetricsSink};
+use crate::error::Result;
+
+/// Ready-made Prometheus metrics for [`crate::api::CircleClientBuilder::metrics`]:
+///
+/// - `circle_requests_total{method, endpoint, status}`, with `status` set to `error` when no
+///   response was received
+/// - `circle_request_duration_seconds{method, endpoint}`, retries included
+/// - `circle_rate_limited_total{method, endpoint}`, one per `429` waited out
+///
+/// The collectors are cheap to clone and share, so the same value can be registered and
+/// handed to the client.
+#[derive(Clone)]
+pub struct PrometheusMetrics {
+    requests: IntCounterVec,
+    duration: HistogramVec,
+    rate_limited: IntCounterVec,
+}
+
+impl PrometheusMetrics {
+    pub fn new() -> Result<Self> {
+        let requests = IntCounterVec::new(
+            Opts::new("circle_requests_total", "Circle API calls"),
+            &["method", "endpoint", "status"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "circle_request_duration_seconds",
+                "Circle API call latency, retries included",
+            ),
+            &["method", "endpoint"],
+        )?;
+        let rate_limited = IntCounterVec::new(
+            Opts::new(
+                "circle_rate_limited_total",
+                "Rate-limited responses from Circle",
+            ),
+            &["method", "endpoint"],
+        )?;
+        Ok(PrometheusMetrics {
+            requests,
+            duration,
+            rate_limited,
+        })
+    }
+
+    /// Registers the collectors with `registry`, e.g. `prometheus::default_registry()`.
+    pub fn register(&self, registry: &Registry) -> Result<()> {
+        registry.register(Box::new(self.requests.clone()))?;
+        registry.register(Box::new(self.duration.clone()))?;
+        registry.register(Box::new(self.rate_limited.clone()))?;
+        Ok(())
+    }
+}
+
+impl MetricsSink for PrometheusMetrics {
+    fn record(&self, metrics: &CallMetrics<'_>) {
+        let method = metrics.method.as_str();
+        let status = match metrics.status {
+            Some(status) => status.as_str().to_string(),
+            None => "error".to_string(),
+        };
+        self.requests
+            .with_label_values(&[method, metrics.endpoint, &status])
+            .inc();
+        self.duration
+            .with_label_values(&[method, metrics.endpoint])
+            .observe(metrics.duration.as_secs_f64());
+        if metrics.rate_limited > 0 {
+            self.rate_limited
+                .with_label_values(&[method, metrics.endpoint])
+                .inc_by(metrics.rate_limited as u64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use reqwest::{Method, StatusCode};
+
+    use super::*;
+
+    #[test]
+    fn test_prometheus_metrics() {
+        let registry = Registry::new();
+        let metrics = PrometheusMetrics::new().unwrap();
+        metrics.register(&registry).unwrap();
+        metrics.record(&CallMetrics {
+            method: &Method::GET,
+            endpoint: "w3s/wallets/{id}",
+            duration: Duration::from_millis(20),
+            status: Some(StatusCode::OK),
+            attempts: 2,
+            rate_limited: 1,
+            error: None,
+        });
+
+        let families = registry.gather();
+        let names = families.iter().map(|x| x.get_name()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "circle_rate_limited_total",
+                "circle_request_duration_seconds",
+                "circle_requests_total"
+            ]
+        );
+        let requests = &families[2].get_metric()[0];
+        assert_eq!(requests.get_counter().get_value(), 1.0);
+        assert!(registry
+            .register(Box::new(metrics.requests.clone()))
+            .is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use reqwest::{NoProxy, Proxy};
+
+use crate::error::Result;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    url: String,
+    credentials: Option<(String, String)>,
+    no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Proxies both HTTP and HTTPS traffic through `url`, e.g. `http://proxy.internal:3128`.
+    pub fn new<S: Into<String>>(url: S) -> Self {
+        ProxyConfig {
+            url: url.into(),
+            credentials: None,
+            no_proxy: Vec::new(),
+        }
+    }
+
+    pub fn basic_auth<S: Into<String>>(mut self, username: S, password: S) -> Self {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Hosts, domains (`.example.com`) or CIDR ranges that bypass the proxy.
+    pub fn no_proxy<S: Into<String>>(mut self, host: S) -> Self {
+        self.no_proxy.push(host.into());
+        self
+    }
+
+    pub(crate) fn to_reqwest(&self) -> Result<Proxy> {
+        let mut proxy = Proxy::all(&self.url)?;
+        if let Some((username, password)) = &self.credentials {
+            proxy = proxy.basic_auth(username, password);
+        }
+        if !self.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(",")));
+        }
+        Ok(proxy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_proxy_config() {
+        let proxy = ProxyConfig::new("http://proxy.internal:3128")
+            .basic_auth("user", "pass")
+            .no_proxy("localhost")
+            .no_proxy(".internal");
+        assert!(proxy.to_reqwest().is_ok());
+        assert!(ProxyConfig::new("not a url").to_reqwest().is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::runtime::Instant;
+
+/// A token bucket shared by every clone of the client it is attached to. Requests take a token
+/// before each attempt; when the bucket is empty they wait for their turn instead of sending
+/// and collecting a 429.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        RateLimiter {
+            requests_per_second: requests_per_second.max(f64::MIN_POSITIVE),
+            burst,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: burst,
+                last_refill: Instant::now(),
+            })),
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(self.burst);
+        bucket.last_refill = now;
+    }
+
+    /// Takes a token if one is available right now.
+    pub fn try_acquire(&self) -> bool {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reserves a token and waits until it becomes valid. Reservations are handed out in call
+    /// order, so concurrent callers are served roughly first-come first-served.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+            self.refill(&mut bucket);
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        };
+        crate::runtime::sleep(wait).await;
+    }
+
+    /// Share of the burst capacity currently in use, from 0.0 (idle) to 1.0 (empty bucket).
+    /// Values above 1.0 mean callers are queued waiting for tokens.
+    pub fn saturation(&self) -> f64 {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        1.0 - bucket.tokens / self.burst
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_burst() {
+        let limiter = RateLimiter::new(0.001, 2);
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+        assert!(limiter.saturation() > 0.99);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_refill() {
+        let limiter = RateLimiter::new(100.0, 1);
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(15));
+    }
+}
@@ -0,0 +1,83 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+use crate::api::{CircleClient, HttpResponse, RequestOptions};
+use crate::error::Result;
+
+/// Receives the `data` of the last response to a call made with
+/// [`RequestOptions::capture_raw`], as Circle sent it. Fields the models don't cover yet are
+/// still in there. Paginated streams overwrite it with every page.
+#[derive(Debug, Clone, Default)]
+pub struct RawCapture(Arc<Mutex<Option<Value>>>);
+
+impl RawCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> Option<Value> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn take(&self) -> Option<Value> {
+        self.0.lock().unwrap().take()
+    }
+
+    /// Keeps the `data` of a successful response and clears the capture on any other.
+    pub(crate) fn record(&self, response: &HttpResponse) {
+        let data = match response.status.is_success() {
+            true => serde_json::from_slice::<Value>(&response.body)
+                .ok()
+                .and_then(|mut x| x.get_mut("data").map(Value::take)),
+            false => None,
+        };
+        *self.0.lock().unwrap() = data;
+    }
+}
+
+impl CircleClient {
+    /// Runs `call` on a copy of the client and returns its result together with the raw
+    /// `data` of the last response, `Value::Null` if there was none:
+    ///
+    /// ```no_run
+    /// # async fn run(client: circle_api::api::CircleClient, id: circle_api::models::ids::WalletId) -> circle_api::error::Result<()> {
+    /// let (wallet, raw) = client.raw(|c| async move { c.get_wallet(id).await }).await?;
+    /// let sca_core = raw["wallet"]["scaCore"].as_str();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw<T, F, Fut>(&self, call: F) -> Result<(T, Value)>
+    where
+        F: FnOnce(CircleClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let capture = RawCapture::new();
+        let client = self.with_options(RequestOptions::new().capture_raw(&capture));
+        let response = call(client).await?;
+        Ok((response, capture.take().unwrap_or(Value::Null)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_raw() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let data = json!([{ "id": "wire-1", "type": "wire", "name": "primary" }]);
+        transport.push_data(data.clone());
+
+        let (channels, raw) = client
+            .raw(|c| async move { c.list_channels().await })
+            .await
+            .unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(raw, data);
+    }
+}
@@ -0,0 +1,186 @@
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::{CircleError, Result};
+use crate::models::ids::TransactionId;
+use crate::models::refund::{Refund, RefundOptions};
+use crate::models::transaction::{Transaction, TxType};
+use crate::models::transaction_state::TransactionState;
+use crate::models::transaction_transfer_create::TransactionTransferCreateRequestBuilder;
+use crate::runtime::Instant;
+
+impl CircleClient {
+    /// Returns a completed inbound transfer to its sender from the wallet that received it,
+    /// then waits for the refund to complete or the options' completion timeout to run out.
+    pub async fn refund_transaction(
+        &self,
+        original_transaction_id: TransactionId,
+        options: RefundOptions,
+    ) -> Result<Refund> {
+        let original = self
+            .get_transaction(original_transaction_id, None)
+            .await?
+            .transaction;
+        let mut refund = refund_for(&original, &options)?;
+
+        let request = TransactionTransferCreateRequestBuilder::new(
+            refund.destination.clone(),
+            refund.token_id,
+            refund.wallet_id,
+            refund.amount,
+        )
+        .fee_level(options.fee_level)
+        .ref_id(refund.ref_id.clone());
+        let response = self
+            .create_transfer_transaction(refund.idempotency_key, request)
+            .await?;
+        refund.transaction_id = response.id;
+        refund.state = response.state;
+
+        let deadline = Instant::now() + options.completion_timeout;
+        while !refund.is_complete() && !refund.is_failed() && Instant::now() < deadline {
+            crate::runtime::sleep(options.poll_interval).await;
+            let transaction = self
+                .get_transaction(refund.transaction_id, None)
+                .await?
+                .transaction;
+            refund.state = transaction.state;
+            refund.tx_hash = transaction.tx_hash;
+            refund.error_reason = transaction.error_reason;
+        }
+        Ok(refund)
+    }
+}
+
+/// The refund `original` calls for, before it is submitted.
+fn refund_for(original: &Transaction, options: &RefundOptions) -> Result<Refund> {
+    if original.transaction_type != TxType::Inbound || original.state != TransactionState::Complete
+    {
+        return Err(CircleError::InvalidState(format!(
+            "transaction {} is not a completed inbound transfer",
+            original.id
+        )));
+    }
+    let wallet_id = original
+        .wallet_id
+        .ok_or(CircleError::MissingField("walletId"))?;
+    let token_id = original
+        .token_id
+        .ok_or(CircleError::MissingField("tokenId"))?;
+    let destination = match &options.destination {
+        Some(destination) => destination.clone(),
+        None => original
+            .source_address
+            .clone()
+            .ok_or(CircleError::MissingField("sourceAddress"))?,
+    };
+    let received = original
+        .amounts
+        .as_ref()
+        .ok_or(CircleError::MissingField("amounts"))?
+        .iter()
+        .sum::<Decimal>();
+    let amount = options.amount.unwrap_or(received);
+    if amount <= Decimal::ZERO || amount > received {
+        return Err(CircleError::Validation(vec![format!(
+            "refund amount must be positive and at most {}, got {}",
+            received, amount
+        )]));
+    }
+    let idempotency_key = options.idempotency_key.unwrap_or_else(|| {
+        Uuid::new_v5(
+            &original.id.into(),
+            format!("refund:{}", amount.normalize()).as_bytes(),
+        )
+    });
+    Ok(Refund {
+        original_transaction_id: original.id,
+        transaction_id: TransactionId::default(),
+        wallet_id,
+        token_id,
+        destination,
+        amount,
+        ref_id: format!("refund:{}", original.id),
+        idempotency_key,
+        state: TransactionState::Initiated,
+        tx_hash: None,
+        error_reason: None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use crate::api::MockTransport;
+    use crate::models::ids::TransactionId;
+    use crate::models::refund::RefundOptions;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_refund_transaction() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let original_id = TransactionId(Uuid::new_v4());
+        let original = json!({
+            "transaction": {
+                "id": original_id,
+                "blockchain": "MATIC-AMOY",
+                "walletId": "ce714f5b-0d8e-4062-9454-61aa1154869b",
+                "tokenId": "7adb2b7d-c9cd-5164-b2d4-b73b088274dc",
+                "sourceAddress": "0x6e5eaf34c73d1cd0be4e24f923b97cf38e10d1f3",
+                "amounts": ["25"],
+                "state": "COMPLETE",
+                "transactionType": "INBOUND",
+                "createDate": "2024-03-01T10:00:00Z",
+                "updateDate": "2024-03-01T10:00:00Z"
+            }
+        });
+        let refund_id = Uuid::new_v4();
+        transport.push_data(original.clone());
+        transport.push_data(json!({ "id": refund_id, "state": "INITIATED" }));
+        transport.push_data(json!({
+            "transaction": {
+                "id": refund_id,
+                "blockchain": "MATIC-AMOY",
+                "state": "COMPLETE",
+                "txHash": "0xabc",
+                "transactionType": "OUTBOUND",
+                "createDate": "2024-03-01T11:00:00Z",
+                "updateDate": "2024-03-01T11:00:00Z"
+            }
+        }));
+
+        let options = RefundOptions::new()
+            .amount(Decimal::from(10))
+            .poll_interval(Duration::ZERO);
+        let refund = client
+            .refund_transaction(original_id, options)
+            .await
+            .unwrap();
+        assert!(refund.is_complete());
+        assert_eq!(refund.transaction_id, refund_id);
+        assert_eq!(refund.tx_hash.as_deref(), Some("0xabc"));
+
+        let transfer = transport.requests().into_iter().nth(1).unwrap();
+        let body = transfer.body.unwrap();
+        assert_eq!(body["amounts"], json!(["10"]));
+        assert_eq!(body["refId"], json!(format!("refund:{}", original_id)));
+        assert_eq!(
+            body["destinationAddress"],
+            json!("0x6e5eaf34c73d1cd0be4e24f923b97cf38e10d1f3")
+        );
+        assert_eq!(body["idempotencyKey"], json!(refund.idempotency_key));
+
+        transport.push_data(original);
+        let options = RefundOptions::new().amount(Decimal::from(30));
+        assert!(client
+            .refund_transaction(original_id, options)
+            .await
+            .is_err());
+    }
+}
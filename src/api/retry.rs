@@ -0,0 +1,223 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::Method;
+
+/// Controls how failed calls are retried. Only server errors (5xx) and connection failures are
+/// retried. `GET`, `PUT` and `DELETE` are always safe to repeat; `POST` calls are only retried
+/// when their body carries an idempotency key, which is resent unchanged on every attempt so
+/// Circle deduplicates the request.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: u32,
+    jitter: bool,
+    retry_mutating: bool,
+    wait_on_rate_limit: bool,
+    max_rate_limit_wait: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2,
+            jitter: true,
+            retry_mutating: true,
+            wait_on_rate_limit: true,
+            max_rate_limit_wait: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, initial_backoff: Duration) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+            ..Self::default()
+        }
+    }
+
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier.max(1);
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Whether `POST` calls with an idempotency key are retried. Enabled by default.
+    pub fn retry_mutating(mut self, retry_mutating: bool) -> Self {
+        self.retry_mutating = retry_mutating;
+        self
+    }
+
+    /// Whether a 429 response is retried after the delay Circle asks for in `Retry-After` (or
+    /// the regular backoff if the header is missing). When disabled, or when the requested delay
+    /// exceeds `max_rate_limit_wait`, the call fails with [`CircleError::RateLimited`].
+    ///
+    /// [`CircleError::RateLimited`]: crate::error::CircleError::RateLimited
+    pub fn wait_on_rate_limit(mut self, wait_on_rate_limit: bool) -> Self {
+        self.wait_on_rate_limit = wait_on_rate_limit;
+        self
+    }
+
+    pub fn max_rate_limit_wait(mut self, max_rate_limit_wait: Duration) -> Self {
+        self.max_rate_limit_wait = max_rate_limit_wait;
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub(crate) fn should_retry(&self, method: &Method, idempotent: bool, attempt: u32) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        match *method {
+            Method::GET | Method::HEAD | Method::PUT | Method::DELETE => true,
+            _ => self.retry_mutating && idempotent,
+        }
+    }
+
+    /// Rate-limited requests were never processed, so they can be retried regardless of method.
+    pub(crate) fn rate_limit_wait(
+        &self,
+        attempt: u32,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if !self.wait_on_rate_limit || attempt >= self.max_attempts {
+            return None;
+        }
+        match retry_after {
+            Some(retry_after) if retry_after > self.max_rate_limit_wait => None,
+            Some(retry_after) => Some(retry_after),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    /// The delay before the attempt following `attempt` (1-based). With jitter enabled the delay
+    /// is drawn uniformly from the upper half of the exponential backoff.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .checked_pow(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        let backoff = self
+            .initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        if self.jitter && !backoff.is_zero() {
+            let half = backoff / 2;
+            half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Parses `Retry-After` as either delay-seconds or an HTTP date.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    Some((date - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exponential_backoff() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(350))
+            .jitter(false);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100));
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff >= Duration::from_millis(100) && backoff <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        let policy =
+            RetryPolicy::new(3, Duration::ZERO).max_rate_limit_wait(Duration::from_secs(10));
+        assert_eq!(
+            policy.rate_limit_wait(1, Some(Duration::from_secs(2))),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.rate_limit_wait(1, Some(Duration::from_secs(20))),
+            None
+        );
+        assert_eq!(
+            policy.rate_limit_wait(3, Some(Duration::from_secs(2))),
+            None
+        );
+        assert_eq!(
+            policy
+                .wait_on_rate_limit(false)
+                .rate_limit_wait(1, Some(Duration::from_secs(2))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let policy = RetryPolicy::new(3, Duration::ZERO);
+        assert!(policy.should_retry(&Method::GET, false, 1));
+        assert!(!policy.should_retry(&Method::GET, false, 3));
+        assert!(!policy.should_retry(&Method::POST, false, 1));
+        assert!(policy.should_retry(&Method::POST, true, 1));
+        assert!(!policy
+            .retry_mutating(false)
+            .should_retry(&Method::POST, true, 1));
+    }
+}
@@ -0,0 +1,29 @@
+use futures::Stream;
+use reqwest::Method;
+
+use crate::api::paginate::paginate;
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::pagination::Paginated;
+use crate::models::payout_return::{PayoutReturn, PayoutReturnListQueryParams};
+
+impl CircleClient {
+    pub async fn list_payout_returns(
+        &self,
+        query_params: PayoutReturnListQueryParams,
+    ) -> Result<Paginated<PayoutReturn>> {
+        let url = format!("{}returns", self.base_url);
+        self.send_request(Method::GET, url, Some(query_params))
+            .await
+    }
+
+    /// Every item matching `query_params`, following page cursors as the stream is polled.
+    pub fn list_payout_returns_stream(
+        &self,
+        query_params: PayoutReturnListQueryParams,
+    ) -> impl Stream<Item = Result<PayoutReturn>> + '_ {
+        paginate(query_params, self.page_prefetch, move |params| {
+            self.list_payout_returns(params)
+        })
+    }
+}
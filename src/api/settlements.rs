@@ -0,0 +1,35 @@
+use futures::Stream;
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::api::paginate::paginate;
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::pagination::Paginated;
+use crate::models::settlement::{Settlement, SettlementListQueryParams};
+
+impl CircleClient {
+    pub async fn list_settlements(
+        &self,
+        query_params: SettlementListQueryParams,
+    ) -> Result<Paginated<Settlement>> {
+        let url = format!("{}settlements", self.base_url);
+        self.send_request(Method::GET, url, Some(query_params))
+            .await
+    }
+
+    /// Every item matching `query_params`, following page cursors as the stream is polled.
+    pub fn list_settlements_stream(
+        &self,
+        query_params: SettlementListQueryParams,
+    ) -> impl Stream<Item = Result<Settlement>> + '_ {
+        paginate(query_params, self.page_prefetch, move |params| {
+            self.list_settlements(params)
+        })
+    }
+
+    pub async fn get_settlement(&self, settlement_id: Uuid) -> Result<Settlement> {
+        let url = format!("{}settlements/{}", self.base_url, settlement_id);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+}
@@ -1,10 +1,12 @@
-use crate::api::{encrypt_entity_secret, CircleClient};
+use crate::api::CircleClient;
 use crate::error::Result;
+use crate::models::ids::WalletId;
 use crate::models::signing_message::{SignMessageRequestBuilder, SignMessageResponse};
+#[cfg(not(target_arch = "wasm32"))]
 use crate::models::web3_address::Web3Address;
 use crate::models::web3_signature::Web3Signature;
 use reqwest::Method;
-use uuid::Uuid;
+#[cfg(not(target_arch = "wasm32"))]
 use web3::signing::{keccak256, recover};
 
 impl CircleClient {
@@ -19,10 +21,7 @@ impl CircleClient {
                 url,
                 Some(
                     request
-                        .entity_secret_ciphertext(encrypt_entity_secret(
-                            &self.public_key,
-                            &self.circle_entity_secret,
-                        )?)
+                        .entity_secret_ciphertext(self.entity_secret_ciphertext()?)
                         .build()?,
                 ),
             )
@@ -32,7 +31,7 @@ impl CircleClient {
 
     pub async fn sign_ethereum_message(
         &self,
-        wallet_id: Uuid,
+        wallet_id: WalletId,
         message: String,
     ) -> Result<Web3Signature> {
         let response = self
@@ -46,6 +45,8 @@ impl CircleClient {
         Ok(response.signature)
     }
 
+    /// Not available on wasm32, where secp256k1 recovery isn't built.
+    #[cfg(not(target_arch = "wasm32"))]
     pub async fn verify_ethereum_message(
         &self,
         wallet_address: Web3Address,
@@ -0,0 +1,222 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use log::info;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::api::metrics::CallStats;
+use crate::api::version;
+use crate::api::{ApiResource, CircleClient};
+use crate::error::{CircleError, Result};
+
+/// Simulated ids are v5 of the idempotency key in this namespace, so retries get the same id.
+const SIMULATION_NAMESPACE: Uuid = Uuid::from_u128(0x6d1e_0c3a_54b8_4f0e_9a51_2c7e_8f43_d9b6);
+
+/// POSTs that don't change anything and still go to Circle in simulation mode.
+const READ_ONLY_PATHS: [&str; 3] = ["/estimateFee", "/validateAddress", "/contracts/query"];
+
+/// A mutating call that simulation mode answered instead of Circle.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedCall {
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    /// E.g. `w3s/developer/transactions/transfer`.
+    pub path: String,
+    /// The request body without the entity secret ciphertext.
+    pub request: Value,
+    /// Circle's fee estimate, for transfers it could estimate.
+    pub fee_estimate: Option<Value>,
+    /// The synthetic response the caller got.
+    pub response: Value,
+}
+
+/// Every call simulated by a client built with
+/// [`CircleClientBuilder::simulate`](crate::api::CircleClientBuilder::simulate). Clones share
+/// the log.
+#[derive(Debug, Clone, Default)]
+pub struct SimulationLog(Arc<Mutex<Vec<SimulatedCall>>>);
+
+impl SimulationLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn calls(&self) -> Vec<SimulatedCall> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+pub(crate) fn is_simulated(method: &Method, url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or(url);
+    *method != Method::GET && !READ_ONLY_PATHS.iter().any(|x| path.ends_with(x))
+}
+
+impl CircleClient {
+    /// Whether mutating calls are simulated instead of sent to Circle.
+    pub fn is_simulated(&self) -> bool {
+        self.simulation.is_some()
+    }
+
+    /// Answers a mutating call with a synthetic response built from the request: the request
+    /// fields, a fresh `id`, `state: INITIATED` and `status: pending`. Calls whose response
+    /// needs more than that fail with [`CircleError::InvalidState`].
+    pub(crate) async fn simulate<T: DeserializeOwned>(
+        &self,
+        log: &SimulationLog,
+        method: Method,
+        url: String,
+        body: Option<impl Serialize>,
+    ) -> Result<T> {
+        let mut request = match serde_json::to_value(&body)? {
+            Value::Object(request) => request,
+            _ => Map::new(),
+        };
+        request.remove("entitySecretCiphertext");
+        let path = version::path(&url).to_string();
+        let fee_estimate = match path.as_str() {
+            "w3s/developer/transactions/transfer" => self.estimate_transfer_fee(&request).await,
+            _ => None,
+        };
+
+        let id = match request.get("idempotencyKey").and_then(Value::as_str) {
+            Some(key) => Uuid::new_v5(&SIMULATION_NAMESPACE, key.as_bytes()),
+            None => Uuid::new_v4(),
+        };
+        let now = Utc::now();
+        let mut response = request.clone();
+        response.remove("idempotencyKey");
+        response.extend([
+            ("id".to_string(), json!(id)),
+            ("state".to_string(), json!("INITIATED")),
+            ("status".to_string(), json!("pending")),
+            ("createDate".to_string(), json!(now)),
+            ("updateDate".to_string(), json!(now)),
+        ]);
+        let response = Value::Object(response);
+        info!("simulated {} {} as {}", method, path, id);
+        log.0.lock().unwrap().push(SimulatedCall {
+            timestamp: now,
+            method: method.to_string(),
+            path: path.clone(),
+            request: Value::Object(request),
+            fee_estimate,
+            response: response.clone(),
+        });
+        serde_json::from_value(response).map_err(|err| {
+            CircleError::InvalidState(format!("can't simulate {} {}: {}", method, path, err))
+        })
+    }
+
+    async fn estimate_transfer_fee(&self, request: &Map<String, Value>) -> Option<Value> {
+        let url = format!(
+            "{}w3s/transactions/transfer/estimateFee",
+            self.base_url_for(ApiResource::Transactions)
+        );
+        let estimate = [
+            "amounts",
+            "destinationAddress",
+            "nftTokenIds",
+            "tokenId",
+            "walletId",
+        ]
+        .iter()
+        .filter_map(|x| Some((x.to_string(), request.get(*x)?.clone())))
+        .collect::<Map<_, _>>();
+        let result = self
+            .send_with_retries::<Value>(
+                Method::POST,
+                url,
+                Some(estimate),
+                &mut CallStats::default(),
+            )
+            .await;
+        match result {
+            Ok(estimate) => Some(estimate),
+            Err(err) => {
+                info!(
+                    "could not estimate the fee of a simulated transfer: {}",
+                    err
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use rust_decimal::Decimal;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+    use crate::models::ids::{TokenId, WalletId};
+    use crate::models::transaction_transfer_create::TransactionTransferCreateRequestBuilder;
+    use crate::models::web3_address::Web3Address;
+
+    #[tokio::test]
+    async fn test_simulated_transfer() {
+        let transport = MockTransport::new();
+        let log = SimulationLog::new();
+        let client = transport
+            .client_builder()
+            .simulate(log.clone())
+            .build()
+            .await
+            .unwrap();
+        transport.push_data(json!({ "medium": { "networkFee": "0.0021" } }));
+        let destination =
+            Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let request = |amount| {
+            TransactionTransferCreateRequestBuilder::new(
+                destination.clone(),
+                TokenId(Uuid::new_v4()),
+                WalletId(Uuid::new_v4()),
+                amount,
+            )
+        };
+        let key = Uuid::new_v4();
+        let response = client
+            .create_transfer_transaction(key, request(Decimal::from(2)))
+            .await
+            .unwrap();
+
+        // Only the fee estimate reached Circle.
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].url.ends_with("/estimateFee"));
+        assert_eq!(requests[0].body.as_ref().unwrap()["amounts"], json!(["2"]));
+        let calls = log.calls();
+        assert_eq!(calls[0].response["id"], json!(response.id));
+        assert_eq!(
+            calls[0].fee_estimate.as_ref().unwrap()["medium"]["networkFee"],
+            "0.0021"
+        );
+        assert!(calls[0].request.get("entitySecretCiphertext").is_none());
+
+        // Validation still runs, and the same key simulates the same id.
+        assert!(client
+            .create_transfer_transaction(key, request(Decimal::from(-1)))
+            .await
+            .is_err());
+        transport.push_error(reqwest::StatusCode::BAD_REQUEST, 2, "invalid");
+        let again = client
+            .create_transfer_transaction(key, request(Decimal::from(2)))
+            .await
+            .unwrap();
+        assert_eq!(again.id, response.id);
+        assert!(log.calls()[1].fee_estimate.is_none());
+    }
+}
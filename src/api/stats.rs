@@ -0,0 +1,138 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::api::circuit_breaker::CircuitState;
+use crate::api::metrics::CallMetrics;
+
+/// How many recent latencies per endpoint the percentiles are computed from.
+const LATENCY_WINDOW: usize = 512;
+
+/// A point-in-time view of a client's health, from [`crate::api::CircleClient::stats`].
+/// Counters cover every call made since the client was built, across all its clones.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientStats {
+    /// Keyed by method and templated endpoint, e.g. `GET w3s/wallets/{id}`.
+    pub endpoints: BTreeMap<String, EndpointStats>,
+    /// See [`crate::api::RateLimiter::saturation`]; `None` without a rate limiter.
+    pub rate_limiter_saturation: Option<f64>,
+    /// `None` without a circuit breaker.
+    pub circuit_state: Option<CircuitState>,
+}
+
+impl ClientStats {
+    pub fn requests(&self) -> u64 {
+        self.endpoints.values().map(|x| x.requests).sum()
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.endpoints.values().map(|x| x.errors).sum()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointStats {
+    pub requests: u64,
+    pub errors: u64,
+    pub rate_limited: u64,
+    /// Over the last 512 calls, retries included.
+    pub p50: Option<Duration>,
+    pub p95: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    requests: u64,
+    errors: u64,
+    rate_limited: u64,
+    latencies: VecDeque<Duration>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StatsCollector(Mutex<HashMap<String, Entry>>);
+
+impl StatsCollector {
+    pub(crate) fn record(&self, metrics: &CallMetrics<'_>) {
+        let key = format!("{} {}", metrics.method, metrics.endpoint);
+        let mut entries = self.0.lock().unwrap();
+        let entry = entries.entry(key).or_default();
+        entry.requests += 1;
+        entry.errors += u64::from(!metrics.is_success());
+        entry.rate_limited += u64::from(metrics.rate_limited);
+        if entry.latencies.len() == LATENCY_WINDOW {
+            entry.latencies.pop_front();
+        }
+        entry.latencies.push_back(metrics.duration);
+    }
+
+    pub(crate) fn endpoints(&self) -> BTreeMap<String, EndpointStats> {
+        let entries = self.0.lock().unwrap();
+        entries
+            .iter()
+            .map(|(key, entry)| {
+                let mut latencies = entry.latencies.iter().copied().collect::<Vec<_>>();
+                latencies.sort_unstable();
+                let stats = EndpointStats {
+                    requests: entry.requests,
+                    errors: entry.errors,
+                    rate_limited: entry.rate_limited,
+                    p50: percentile(&latencies, 50),
+                    p95: percentile(&latencies, 95),
+                };
+                (key.clone(), stats)
+            })
+            .collect()
+    }
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], percent: usize) -> Option<Duration> {
+    let rank = (sorted.len() * percent + 99) / 100;
+    sorted.get(rank.max(1) - 1).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::{CircuitBreaker, MockTransport, RateLimiter};
+
+    #[test]
+    fn test_percentile() {
+        let samples = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+        assert_eq!(percentile(&samples, 50), Some(Duration::from_millis(50)));
+        assert_eq!(percentile(&samples, 95), Some(Duration::from_millis(95)));
+        assert_eq!(
+            percentile(&samples[..1], 95),
+            Some(Duration::from_millis(1))
+        );
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[tokio::test]
+    async fn test_client_stats() {
+        let transport = MockTransport::new();
+        let client = transport
+            .client_builder()
+            .rate_limiter(RateLimiter::new(10.0, 10))
+            .circuit_breaker(CircuitBreaker::new(5, Duration::from_secs(30)))
+            .build()
+            .await
+            .unwrap();
+        transport.push_error(
+            reqwest::StatusCode::NOT_FOUND,
+            156004,
+            "Cannot find the wallet",
+        );
+        assert!(client.get_wallet(Uuid::new_v4().into()).await.is_err());
+
+        let stats = client.stats();
+        let wallet = &stats.endpoints["GET w3s/wallets/{id}"];
+        assert_eq!((wallet.requests, wallet.errors), (1, 1));
+        assert!(wallet.p50.is_some());
+        assert_eq!((stats.requests(), stats.errors()), (1, 1));
+        assert_eq!(stats.circuit_state, Some(CircuitState::Closed));
+        assert!(stats.rate_limiter_saturation.unwrap() > 0.0);
+    }
+}
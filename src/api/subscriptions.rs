@@ -0,0 +1,80 @@
+use reqwest::Method;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::api::{ApiResource, CircleClient};
+use crate::error::Result;
+use crate::models::subscription::{
+    PaymentsSubscription, PaymentsSubscriptionCreateRequest, Subscription,
+    SubscriptionCreateRequest,
+};
+
+impl CircleClient {
+    pub async fn create_subscription(
+        &self,
+        endpoint: String,
+        notification_types: Option<Vec<String>>,
+    ) -> Result<Subscription> {
+        let url = format!(
+            "{}w3s/subscriptions",
+            self.base_url_for(ApiResource::Webhooks)
+        );
+        let request = match notification_types {
+            Some(types) => SubscriptionCreateRequest::new(endpoint).notification_types(types),
+            None => SubscriptionCreateRequest::new(endpoint),
+        };
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+
+    pub async fn list_subscriptions(&self) -> Result<Vec<Subscription>> {
+        let url = format!(
+            "{}w3s/subscriptions",
+            self.base_url_for(ApiResource::Webhooks)
+        );
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+
+    pub async fn get_subscription(&self, subscription_id: Uuid) -> Result<Subscription> {
+        let url = format!(
+            "{}w3s/subscriptions/{}",
+            self.base_url_for(ApiResource::Webhooks),
+            subscription_id
+        );
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+
+    pub async fn delete_subscription(&self, subscription_id: Uuid) -> Result<()> {
+        let url = format!(
+            "{}w3s/subscriptions/{}",
+            self.base_url_for(ApiResource::Webhooks),
+            subscription_id
+        );
+        self.send_request::<Option<Value>>(Method::DELETE, url, None::<()>)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_payments_subscription(
+        &self,
+        endpoint: String,
+    ) -> Result<PaymentsSubscription> {
+        let url = format!("{}notifications/subscriptions", self.base_url);
+        let request = PaymentsSubscriptionCreateRequest::new(endpoint);
+        self.send_request(Method::POST, url, Some(request)).await
+    }
+
+    pub async fn list_payments_subscriptions(&self) -> Result<Vec<PaymentsSubscription>> {
+        let url = format!("{}notifications/subscriptions", self.base_url);
+        self.send_request(Method::GET, url, None::<()>).await
+    }
+
+    pub async fn delete_payments_subscription(&self, subscription_id: Uuid) -> Result<()> {
+        let url = format!(
+            "{}notifications/subscriptions/{}",
+            self.base_url, subscription_id
+        );
+        self.send_request::<Option<Value>>(Method::DELETE, url, None::<()>)
+            .await?;
+        Ok(())
+    }
+}
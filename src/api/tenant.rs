@@ -0,0 +1,101 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, RwLock};
+
+use crate::api::CircleClient;
+use crate::error::Result;
+
+/// API credentials of one Circle entity.
+#[derive(Clone)]
+pub struct TenantCredentials {
+    pub(crate) api_key: String,
+    pub(crate) entity_secret: String,
+}
+
+impl TenantCredentials {
+    pub fn new<S: Into<String>, T: Into<String>>(api_key: S, entity_secret: T) -> Self {
+        TenantCredentials {
+            api_key: api_key.into(),
+            entity_secret: entity_secret.into(),
+        }
+    }
+}
+
+impl Debug for TenantCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TenantCredentials")
+            .field("api_key", &"[REDACTED]")
+            .field("entity_secret", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl CircleClient {
+    /// Returns a handle that makes calls as another entity. It shares this client's transport
+    /// (and connection pool), environment, retry policy, middleware, rate limiter and circuit
+    /// breaker; only the credentials and the entity public key differ. The public key is
+    /// fetched once here, so keep the handle around rather than calling this per request.
+    pub async fn for_tenant(&self, credentials: TenantCredentials) -> Result<CircleClient> {
+        let public_key = Self::fetch_public_key(
+            self.transport.as_ref(),
+            &self.middleware,
+            &self.base_url,
+            &credentials.api_key,
+            &self.headers,
+            self.timeouts.request,
+        )
+        .await?;
+        Ok(self.modified(|inner| {
+            inner.credentials = Arc::new(RwLock::new(credentials));
+            inner.public_key = public_key;
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_for_tenant() {
+        let transport = MockTransport::new();
+        let client = transport
+            .client_builder()
+            .api_key("platform")
+            .build()
+            .await
+            .unwrap();
+        let tenant = client
+            .for_tenant(TenantCredentials::new("tenant", hex::encode("other")))
+            .await
+            .unwrap();
+        transport.push_data(serde_json::json!([]));
+        transport.push_data(serde_json::json!([]));
+
+        tenant.list_channels().await.unwrap();
+        let request = transport.last_request().unwrap();
+        assert_eq!(request.headers["Authorization"], "Bearer tenant");
+        client.list_channels().await.unwrap();
+        let request = transport.last_request().unwrap();
+        assert_eq!(request.headers["Authorization"], "Bearer platform");
+        assert!(!format!("{:?}", TenantCredentials::new("a", "b")).contains('b'));
+    }
+
+    #[tokio::test]
+    async fn test_set_api_key() {
+        let transport = MockTransport::new();
+        let client = transport
+            .client_builder()
+            .api_key("old")
+            .build()
+            .await
+            .unwrap();
+        let copy = client.with_timeouts(Default::default());
+        client.set_api_key("new");
+        transport.push_data(serde_json::json!([]));
+
+        copy.list_channels().await.unwrap();
+        let request = transport.last_request().unwrap();
+        assert_eq!(request.headers["Authorization"], "Bearer new");
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Time allowed to establish a TCP/TLS connection. Only applied to HTTP clients built by
+    /// the crate; an injected client keeps its own connect timeout.
+    pub connect: Option<Duration>,
+    /// Time allowed for a single HTTP attempt, from sending the request to reading the body.
+    pub request: Option<Duration>,
+    /// Upper bound for a whole call, including retries and backoff.
+    pub deadline: Option<Duration>,
+}
+
+impl Timeouts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn connect(mut self, value: Duration) -> Self {
+        self.connect = Some(value);
+        self
+    }
+
+    pub fn request(mut self, value: Duration) -> Self {
+        self.request = Some(value);
+        self
+    }
+
+    pub fn deadline(mut self, value: Duration) -> Self {
+        self.deadline = Some(value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::{CircleClient, Environment, FaultInjectingTransport, MockTransport};
+    use crate::error::CircleError;
+
+    #[tokio::test]
+    async fn test_deadline_exceeded() {
+        let mock = MockTransport::new();
+        let transport = FaultInjectingTransport::new(mock.clone())
+            .latency(1.0, Duration::from_secs(60))
+            .seed(1);
+        transport.set_enabled(false);
+        let client = CircleClient::builder()
+            .api_key("key")
+            .entity_secret(hex::encode("test"))
+            .environment(Environment::Sandbox)
+            .transport(transport.clone())
+            .timeouts(Timeouts::new().deadline(Duration::from_millis(50)))
+            .build()
+            .await
+            .unwrap();
+        transport.set_enabled(true);
+
+        mock.push_data(json!({ "appId": "app" }));
+        let err = client.get_entity_config().await.unwrap_err();
+        assert!(matches!(
+            err,
+            CircleError::DeadlineExceeded(deadline) if deadline == Duration::from_millis(50)
+        ));
+        assert_eq!(transport.injected().delayed, 1);
+    }
+}
@@ -1,15 +1,32 @@
-use crate::api::CircleClient;
+use crate::api::{ApiResource, CircleClient};
+use crate::models::ids::TokenId;
 
+use crate::error::Result;
 use crate::models::token_get::TokenGetResponse;
 use reqwest::Method;
-use uuid::Uuid;
 
 impl CircleClient {
-    pub async fn get_token_details(
-        &self,
-        token_id: Uuid,
-    ) -> crate::error::Result<TokenGetResponse> {
-        let url = format!("{}w3s/tokens/{}", self.base_url, token_id);
+    pub async fn get_token_details(&self, token_id: TokenId) -> Result<TokenGetResponse> {
+        let url = format!(
+            "{}w3s/tokens/{}",
+            self.base_url_for(ApiResource::Tokens),
+            token_id
+        );
         self.send_request(Method::GET, url, None::<()>).await
     }
+
+    /// The number of decimals `token_id` supports, or `None` if Circle doesn't report one.
+    /// Looked up once per client and shared by its clones.
+    pub async fn token_decimals(&self, token_id: TokenId) -> Result<Option<u32>> {
+        if let Some(decimals) = self.token_decimals.read().unwrap().get(&token_id) {
+            return Ok(*decimals);
+        }
+        let token = self.get_token_details(token_id).await?.token;
+        let decimals = token.decimals.and_then(|x| u32::try_from(x).ok());
+        self.token_decimals
+            .write()
+            .unwrap()
+            .insert(token_id, decimals);
+        Ok(decimals)
+    }
 }
@@ -1,16 +1,24 @@
+use futures::Stream;
 use reqwest::Method;
 
 use uuid::Uuid;
 
-use crate::api::{encrypt_entity_secret, CircleClient};
+use crate::api::paginate::{paginate, paginate_concurrent};
+use crate::api::{ApiResource, CircleClient};
+use crate::models::ids::TransactionId;
 
 use crate::error::Result;
 use crate::models::auth::Auth;
+use crate::models::transaction::Transaction;
 use crate::models::transaction::TxType;
 use crate::models::transaction_accelerate::{
     TransactionAccelerateRequest, TransactionAccelerateResponse,
 };
 use crate::models::transaction_cancel::{TransactionCancelRequest, TransactionCancelResponse};
+#[cfg(feature = "contracts")]
+use crate::models::transaction_contract_execution::{
+    TransactionContractExecutionRequestBuilder, TransactionContractExecutionResponse,
+};
 use crate::models::transaction_get::TransactionGetResponse;
 use crate::models::transaction_list::{TransactionListQueryParams, TransactionListResponse};
 use crate::models::transaction_transfer_create::{
@@ -18,71 +26,118 @@ use crate::models::transaction_transfer_create::{
 };
 
 impl CircleClient {
-    pub async fn create_transfer_transaction(
+    pub async fn create_transfer_transaction<K: Into<Option<Uuid>>>(
         &self,
-        idempotency_key: Uuid,
+        idempotency_key: K,
         request: TransactionTransferCreateRequestBuilder,
     ) -> Result<TransactionTransferCreateResponse> {
-        let url = format!("{}w3s/developer/transactions/transfer", self.base_url);
-        let request = request.build(Auth::new(
-            idempotency_key,
-            encrypt_entity_secret(&self.public_key, &self.circle_entity_secret)?,
-        ));
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
+        let url = format!(
+            "{}w3s/developer/transactions/transfer",
+            self.base_url_for(ApiResource::Transactions)
+        );
+        request.validate()?;
+        if let Some(token_id) = request.fractional_token_id() {
+            if let Some(decimals) = self.token_decimals(token_id).await? {
+                request.validate_decimals(decimals)?;
+            }
+        }
+        let request = request.build(Auth::new(idempotency_key, self.entity_secret_ciphertext()?));
         let response = self.send_request(Method::POST, url, Some(request)).await?;
         Ok(response)
     }
 
-    pub async fn accelerate_transaction(
+    pub async fn accelerate_transaction<K: Into<Option<Uuid>>>(
         &self,
-        transaction_id: String,
-        idempotency_key: Uuid,
+        transaction_id: TransactionId,
+        idempotency_key: K,
     ) -> Result<TransactionAccelerateResponse> {
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
         let url = format!(
             "{}w3s/developer/transactions/{}/accelerate",
-            self.base_url, transaction_id
+            self.base_url_for(ApiResource::Transactions),
+            transaction_id
         );
-        let request = TransactionAccelerateRequest {
-            auth: Auth::new(
-                idempotency_key,
-                encrypt_entity_secret(&self.public_key, &self.circle_entity_secret)?,
-            ),
-        };
+        let auth = Auth::new(idempotency_key, self.entity_secret_ciphertext()?);
+        let request = TransactionAccelerateRequest::new(auth);
         let response = self.send_request(Method::POST, url, Some(request)).await?;
         Ok(response)
     }
 
-    pub async fn cancel_transaction(
+    pub async fn cancel_transaction<K: Into<Option<Uuid>>>(
         &self,
-        transaction_id: Uuid,
+        transaction_id: TransactionId,
+        idempotency_key: K,
     ) -> Result<TransactionCancelResponse> {
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
         let url = format!(
             "{}w3s/developer/transactions/{}/cancel",
-            self.base_url, transaction_id
+            self.base_url_for(ApiResource::Transactions),
+            transaction_id
         );
-        let request = TransactionCancelRequest {
-            auth: Auth::new(
-                Uuid::new_v4(),
-                encrypt_entity_secret(&self.public_key, &self.circle_entity_secret)?,
-            ),
-        };
+        let auth = Auth::new(idempotency_key, self.entity_secret_ciphertext()?);
+        let request = TransactionCancelRequest::new(auth);
         let response = self.send_request(Method::POST, url, Some(request)).await?;
         Ok(response)
     }
 
-    // TODO: create a contract execution transaction
+    #[cfg(feature = "contracts")]
+    pub async fn create_contract_execution_transaction<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: TransactionContractExecutionRequestBuilder,
+    ) -> Result<TransactionContractExecutionResponse> {
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
+        let url = format!(
+            "{}w3s/developer/transactions/contractExecution",
+            self.base_url_for(ApiResource::Transactions)
+        );
+        request.validate()?;
+        let request = request.build(Auth::new(idempotency_key, self.entity_secret_ciphertext()?));
+        self.send_request(Method::POST, url, Some(request)).await
+    }
 
     pub async fn list_transactions(
         &self,
         query_params: TransactionListQueryParams,
     ) -> Result<TransactionListResponse> {
-        let url = format!("{}w3s/transactions", self.base_url);
+        let url = format!(
+            "{}w3s/transactions",
+            self.base_url_for(ApiResource::Transactions)
+        );
         self.send_request(Method::GET, url, Some(query_params))
             .await
     }
 
+    /// Every item matching `query_params`, following page cursors as the stream is polled.
+    pub fn list_transactions_stream(
+        &self,
+        query_params: TransactionListQueryParams,
+    ) -> impl Stream<Item = Result<Transaction>> + '_ {
+        paginate(query_params, self.page_prefetch, move |params| {
+            self.list_transactions(params)
+        })
+    }
+
+    /// [`Self::list_transactions_stream`] for full-history scans: the query's time range is cut
+    /// into `concurrency` windows that are fetched in parallel. Needs a start (`from`) on the
+    /// time range to have anything to cut; without one this is a plain sequential scan.
+    pub fn list_transactions_stream_concurrent(
+        &self,
+        query_params: TransactionListQueryParams,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<Transaction>> + '_ {
+        paginate_concurrent(
+            query_params,
+            concurrency,
+            self.page_prefetch,
+            move |params| self.list_transactions(params),
+        )
+    }
+
     pub async fn get_transaction(
         &self,
-        transaction_id: Uuid,
+        transaction_id: TransactionId,
         tx_type: Option<TxType>,
     ) -> Result<TransactionGetResponse> {
         let tx_type_param = match tx_type {
@@ -91,7 +146,9 @@ impl CircleClient {
         };
         let url = format!(
             "{}w3s/transactions/{}{}",
-            self.base_url, transaction_id, tx_type_param
+            self.base_url_for(ApiResource::Transactions),
+            transaction_id,
+            tx_type_param
         );
         self.send_request(Method::GET, url, None::<()>).await
     }
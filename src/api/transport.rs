@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, StatusCode};
+
+use crate::error::{CircleError, Result};
+
+/// A fully prepared request: the URL already points at the chosen endpoint and the
+/// authorization and content-type headers are set.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// Boxed future returned by [`Transport::send`]. Not `Send` on wasm32, where reqwest's fetch
+/// backend is single-threaded.
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportFuture<'a> = futures::future::BoxFuture<'a, Result<HttpResponse>>;
+#[cfg(target_arch = "wasm32")]
+pub type TransportFuture<'a> = futures::future::LocalBoxFuture<'a, Result<HttpResponse>>;
+
+/// The HTTP layer under [`crate::api::CircleClient`]. Retries, rate limiting, failover and the
+/// circuit breaker all sit above it, so an implementation only has to move bytes.
+///
+/// Connection failures and timeouts should be reported as [`TransportError`]s of the matching
+/// kind (or as the underlying `reqwest::Error`), since those are what the client retries.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    Connect,
+    Timeout,
+    Other,
+}
+
+#[derive(Debug)]
+pub struct TransportError {
+    kind: TransportErrorKind,
+    source: Box<dyn Error + Send + Sync>,
+}
+
+impl TransportError {
+    pub fn new<E: Into<Box<dyn Error + Send + Sync>>>(kind: TransportErrorKind, source: E) -> Self {
+        TransportError {
+            kind,
+            source: source.into(),
+        }
+    }
+
+    pub fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+}
+
+impl Display for TransportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} transport error: {}", self.kind, self.source)
+    }
+}
+
+impl Error for TransportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Whether a failed send is worth retrying (and counts against endpoint health).
+pub(crate) fn is_transient(err: &CircleError) -> bool {
+    match err {
+        #[cfg(not(target_arch = "wasm32"))]
+        CircleError::UnknownRequestError(err) => err.is_connect() || err.is_timeout(),
+        #[cfg(target_arch = "wasm32")]
+        CircleError::UnknownRequestError(err) => err.is_timeout(),
+        CircleError::TransportError(err) => err.kind != TransportErrorKind::Other,
+        _ => false,
+    }
+}
+
+/// The default transport, backed by a reqwest [`Client`].
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: Client) -> Self {
+        ReqwestTransport { client }
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, &request.url)
+                .headers(request.headers);
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+            // The fetch backend has no per-request timeout, so race the whole exchange instead.
+            #[cfg(target_arch = "wasm32")]
+            if let Some(timeout) = request.timeout {
+                return crate::runtime::timeout(timeout, Self::execute(builder))
+                    .await
+                    .map_err(|_| {
+                        TransportError::new(TransportErrorKind::Timeout, "request timed out")
+                    })?;
+            }
+            Self::execute(builder).await
+        })
+    }
+}
+
+impl ReqwestTransport {
+    async fn execute(builder: reqwest::RequestBuilder) -> Result<HttpResponse> {
+        let response = builder.send().await?;
+        Ok(HttpResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
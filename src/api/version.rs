@@ -0,0 +1,158 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::CircleClient;
+
+/// Version prefix of the API paths, e.g. `v1` in `https://api.circle.com/v1/w3s/wallets`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+            ApiVersion::V2 => "v2",
+        }
+    }
+}
+
+/// The resources Circle versions separately, one per namespace handle of the client.
+/// Everything else, including the payments endpoints, stays on `v1`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiResource {
+    WalletSets,
+    Wallets,
+    Transactions,
+    Tokens,
+    Webhooks,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ApiVersions {
+    pub(crate) default: ApiVersion,
+    pub(crate) resources: HashMap<ApiResource, ApiVersion>,
+}
+
+impl ApiVersions {
+    fn get(&self, resource: ApiResource) -> ApiVersion {
+        self.resources
+            .get(&resource)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// `base_url` with its trailing `v1/` replaced by `version`. Custom roots without a version
+/// prefix are left alone.
+pub(crate) fn versioned(base_url: &str, version: ApiVersion) -> Cow<'_, str> {
+    match (version, base_url.strip_suffix("v1/")) {
+        (ApiVersion::V1, _) | (_, None) => Cow::Borrowed(base_url),
+        (_, Some(root)) => Cow::Owned(format!("{}{}/", root, version.as_str())),
+    }
+}
+
+/// `base_url` without its version prefix.
+pub(crate) fn root(base_url: &str) -> &str {
+    ApiVersion::ALL
+        .iter()
+        .find_map(|x| base_url.strip_suffix(&format!("{}/", x.as_str())))
+        .unwrap_or(base_url)
+}
+
+/// The part of `url` after the version prefix, or all of it if there is none.
+pub(crate) fn path(url: &str) -> &str {
+    ApiVersion::ALL
+        .iter()
+        .find_map(|x| url.split_once(&format!("/{}/", x.as_str())))
+        .map_or(url, |(_, path)| path)
+}
+
+impl CircleClient {
+    pub fn api_version(&self, resource: ApiResource) -> ApiVersion {
+        self.api_versions.get(resource)
+    }
+
+    /// Returns a copy of the client that calls `resource` on `version`, so that moving to a new
+    /// version can be staged one resource and one caller at a time.
+    pub fn with_api_version(&self, resource: ApiResource, version: ApiVersion) -> Self {
+        self.modified(|inner| {
+            inner.api_versions.resources.insert(resource, version);
+        })
+    }
+
+    /// The API root for calls on `resource`.
+    pub(crate) fn base_url_for(&self, resource: ApiResource) -> Cow<'_, str> {
+        versioned(&self.base_url, self.api_version(resource))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+    use crate::models::transaction_list::TransactionListQueryParams;
+
+    #[tokio::test]
+    async fn test_api_versions() {
+        let transport = MockTransport::new();
+        let client = transport
+            .client_builder()
+            .resource_api_version(ApiResource::Transactions, ApiVersion::V2)
+            .build()
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            transport.push_data(json!({ "transactions": [] }));
+        }
+        transport.push_data(json!([]));
+
+        let staged = client.with_api_version(ApiResource::Transactions, ApiVersion::V1);
+        let query = TransactionListQueryParams::new;
+        client.list_transactions(query()).await.unwrap();
+        staged.list_transactions(query()).await.unwrap();
+        client.transactions().list(query()).await.unwrap();
+        client.webhooks().list().await.unwrap();
+        let paths = transport
+            .requests()
+            .into_iter()
+            .map(|x| x.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "/v2/w3s/transactions",
+                "/v1/w3s/transactions",
+                "/v2/w3s/transactions",
+                "/v1/w3s/subscriptions"
+            ]
+        );
+        assert_eq!(client.api_version(ApiResource::Wallets), ApiVersion::V1);
+
+        let base_url = "https://api.circle.com/v1/";
+        assert_eq!(
+            versioned(base_url, ApiVersion::V2),
+            "https://api.circle.com/v2/"
+        );
+        assert_eq!(
+            versioned("http://localhost/", ApiVersion::V2),
+            "http://localhost/"
+        );
+        assert_eq!(
+            root("https://api.circle.com/v2/"),
+            "https://api.circle.com/"
+        );
+        assert_eq!(path("https://api.circle.com/v2/w3s/wallets"), "w3s/wallets");
+    }
+}
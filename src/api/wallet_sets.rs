@@ -1,13 +1,17 @@
 use crate::error::Result;
+use crate::models::ids::WalletSetId;
+use crate::models::wallet_set::WalletSet;
+use futures::Stream;
 use reqwest::Method;
 
-use crate::api::{encrypt_entity_secret, CircleClient};
+use crate::api::paginate::paginate;
+use crate::api::{ApiResource, CircleClient};
 use crate::models::auth::Auth;
 use uuid::Uuid;
 
 use crate::models::wallet_set::{
-    CreateWalletSetRequest, CreateWalletSetResponse, GetWalletSetResponse, UpdateWalletSetRequest,
-    UpdateWalletSetResponse, WalletSetsQueryParams, WalletSetsResponse,
+    CreateWalletSetRequestBuilder, CreateWalletSetResponse, GetWalletSetResponse,
+    UpdateWalletSetRequest, UpdateWalletSetResponse, WalletSetsQueryParams, WalletSetsResponse,
 };
 
 impl CircleClient {
@@ -15,42 +19,75 @@ impl CircleClient {
         &self,
         query_params: WalletSetsQueryParams,
     ) -> Result<WalletSetsResponse> {
-        let url = format!("{}w3s/walletSets", self.base_url);
+        let url = format!(
+            "{}w3s/walletSets",
+            self.base_url_for(ApiResource::WalletSets)
+        );
         self.send_request(Method::GET, url, Some(query_params))
             .await
     }
 
-    pub async fn create_wallet_set(
+    /// Every item matching `query_params`, following page cursors as the stream is polled.
+    pub fn list_wallet_sets_stream(
+        &self,
+        query_params: WalletSetsQueryParams,
+    ) -> impl Stream<Item = Result<WalletSet>> + '_ {
+        paginate(query_params, self.page_prefetch, move |params| {
+            self.list_wallet_sets(params)
+        })
+    }
+
+    pub async fn create_wallet_set<K: Into<Option<Uuid>>>(
         &self,
-        idempotency_key: Uuid,
+        idempotency_key: K,
         name: String,
     ) -> Result<CreateWalletSetResponse> {
-        let url = format!("{}w3s/developer/walletSets", self.base_url);
-        let request = CreateWalletSetRequest {
-            auth: Auth::new(
-                idempotency_key,
-                encrypt_entity_secret(&self.public_key, &self.circle_entity_secret)?,
-            ),
-            name,
-        };
+        self.create_wallet_set_with(idempotency_key, CreateWalletSetRequestBuilder::new(name))
+            .await
+    }
+
+    pub async fn create_wallet_set_with<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: CreateWalletSetRequestBuilder,
+    ) -> Result<CreateWalletSetResponse> {
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
+        let url = format!(
+            "{}w3s/developer/walletSets",
+            self.base_url_for(ApiResource::WalletSets)
+        );
+        let request = request.build(Auth::new(idempotency_key, self.entity_secret_ciphertext()?));
         self.send_request(Method::POST, url, Some(request)).await
     }
 
     pub async fn update_wallet_set(
         &self,
-        wallet_set_id: Uuid,
+        wallet_set_id: WalletSetId,
         name: String,
+    ) -> Result<UpdateWalletSetResponse> {
+        self.update_wallet_set_with(wallet_set_id, UpdateWalletSetRequest::new(name))
+            .await
+    }
+
+    pub async fn update_wallet_set_with(
+        &self,
+        wallet_set_id: WalletSetId,
+        request: UpdateWalletSetRequest,
     ) -> Result<UpdateWalletSetResponse> {
         let url = format!(
             "{}w3s/developer/walletSets/{}",
-            self.base_url, wallet_set_id
+            self.base_url_for(ApiResource::WalletSets),
+            wallet_set_id
         );
-        let request = UpdateWalletSetRequest { name };
         self.send_request(Method::PUT, url, Some(request)).await
     }
 
-    pub async fn get_wallet_set(&self, wallet_set_id: Uuid) -> Result<GetWalletSetResponse> {
-        let url = format!("{}w3s/walletSets/{}", self.base_url, wallet_set_id);
+    pub async fn get_wallet_set(&self, wallet_set_id: WalletSetId) -> Result<GetWalletSetResponse> {
+        let url = format!(
+            "{}w3s/walletSets/{}",
+            self.base_url_for(ApiResource::WalletSets),
+            wallet_set_id
+        );
         self.send_request(Method::GET, url, None::<()>).await
     }
 }
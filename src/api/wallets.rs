@@ -1,35 +1,47 @@
+use futures::Stream;
 use reqwest::Method;
 use uuid::Uuid;
 
-use crate::api::{encrypt_entity_secret, CircleClient};
+use crate::api::paginate::paginate;
+use crate::api::{ApiResource, CircleClient};
 use crate::error::Result;
 use crate::models::blockchain::Blockchain;
+use crate::models::ids::{WalletId, WalletSetId};
 use crate::models::wallet_balance::{WalletBalanceQueryParams, WalletBalanceResponse};
-use crate::models::wallet_create::{WalletCreateRequest, WalletCreateResponse};
+use crate::models::wallet_create::{WalletCreateRequestBuilder, WalletCreateResponse};
+use crate::models::wallet_detail::WalletDetail;
 use crate::models::wallet_get::WalletGetResponse;
 use crate::models::wallet_list::{WalletListQueryParams, WalletListResponse};
 use crate::models::wallet_nfts::{WalletNftsQueryParams, WalletNftsResponse};
 use crate::models::wallet_update::{WalletUpdateRequest, WalletUpdateResponse};
 
 impl CircleClient {
-    pub async fn create_wallet(
+    pub async fn create_wallet<K: Into<Option<Uuid>>>(
         &self,
-        idempotency_key: Uuid,
-        wallet_set_id: Uuid,
+        idempotency_key: K,
+        wallet_set_id: WalletSetId,
         blockchains: Vec<Blockchain>,
         count: u32,
     ) -> Result<WalletCreateResponse> {
-        let url = format!("{}w3s/developer/wallets", self.base_url);
-        let request = WalletCreateRequest {
+        self.create_wallet_with(
             idempotency_key,
-            entity_secret_cipher_text: encrypt_entity_secret(
-                &self.public_key,
-                &self.circle_entity_secret,
-            )?,
-            wallet_set_id,
-            blockchains,
-            count,
-        };
+            WalletCreateRequestBuilder::new(wallet_set_id, blockchains).count(count),
+        )
+        .await
+    }
+
+    pub async fn create_wallet_with<K: Into<Option<Uuid>>>(
+        &self,
+        idempotency_key: K,
+        request: WalletCreateRequestBuilder,
+    ) -> Result<WalletCreateResponse> {
+        let idempotency_key = self.resolve_idempotency_key(idempotency_key.into())?;
+        let url = format!(
+            "{}w3s/developer/wallets",
+            self.base_url_for(ApiResource::Wallets)
+        );
+        let request = request.build(idempotency_key, self.entity_secret_ciphertext()?);
+        request.validate()?;
         self.send_request(Method::POST, url, Some(request)).await
     }
 
@@ -37,42 +49,68 @@ impl CircleClient {
         &self,
         query_params: WalletListQueryParams,
     ) -> Result<WalletListResponse> {
-        let url = format!("{}w3s/wallets", self.base_url);
+        let url = format!("{}w3s/wallets", self.base_url_for(ApiResource::Wallets));
         self.send_request(Method::GET, url, Some(query_params))
             .await
     }
 
-    pub async fn get_wallet(&self, wallet_id: Uuid) -> Result<WalletGetResponse> {
-        let url = format!("{}w3s/wallets/{}", self.base_url, wallet_id);
+    /// Every item matching `query_params`, following page cursors as the stream is polled.
+    pub fn list_wallets_stream(
+        &self,
+        query_params: WalletListQueryParams,
+    ) -> impl Stream<Item = Result<WalletDetail>> + '_ {
+        paginate(query_params, self.page_prefetch, move |params| {
+            self.list_wallets(params)
+        })
+    }
+
+    pub async fn get_wallet(&self, wallet_id: WalletId) -> Result<WalletGetResponse> {
+        let url = format!(
+            "{}w3s/wallets/{}",
+            self.base_url_for(ApiResource::Wallets),
+            wallet_id
+        );
         self.send_request(Method::GET, url, None::<()>).await
     }
 
     pub async fn update_wallet(
         &self,
-        wallet_id: Uuid,
+        wallet_id: WalletId,
         query_params: WalletUpdateRequest,
     ) -> Result<WalletUpdateResponse> {
-        let url = format!("{}w3s/wallets/{}", self.base_url, wallet_id);
+        let url = format!(
+            "{}w3s/wallets/{}",
+            self.base_url_for(ApiResource::Wallets),
+            wallet_id
+        );
         self.send_request(Method::PUT, url, Some(query_params))
             .await
     }
 
     pub async fn get_wallet_balance(
         &self,
-        wallet_id: Uuid,
+        wallet_id: WalletId,
         query_params: WalletBalanceQueryParams,
     ) -> Result<WalletBalanceResponse> {
-        let url = format!("{}w3s/wallets/{}/balances", self.base_url, wallet_id);
+        let url = format!(
+            "{}w3s/wallets/{}/balances",
+            self.base_url_for(ApiResource::Wallets),
+            wallet_id
+        );
         self.send_request(Method::GET, url, Some(query_params))
             .await
     }
 
     pub async fn get_wallet_nfts(
         &self,
-        wallet_id: Uuid,
+        wallet_id: WalletId,
         query_params: WalletNftsQueryParams,
     ) -> Result<WalletNftsResponse> {
-        let url = format!("{}w3s/wallets/{}/nfts", self.base_url, wallet_id);
+        let url = format!(
+            "{}w3s/wallets/{}/nfts",
+            self.base_url_for(ApiResource::Wallets),
+            wallet_id
+        );
         self.send_request(Method::GET, url, Some(query_params))
             .await
     }
@@ -0,0 +1,407 @@
+//! `circle`: the main wallet operations from the command line, for runbooks and poking at the
+//! sandbox. Credentials come from flags or `CIRCLE_API_KEY` / `CIRCLE_ENTITY_SECRET`.
+
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use futures::{StreamExt, TryStreamExt};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use circle_api::api::{CircleClient, Environment};
+use circle_api::error::Result;
+use circle_api::models::blockchain::Blockchain;
+use circle_api::models::faucet::FaucetRequest;
+use circle_api::models::ids::{TokenId, TransactionId, WalletId, WalletSetId};
+use circle_api::models::transaction::Transaction;
+use circle_api::models::transaction_transfer_create::{
+    FeeLevel, TransactionTransferCreateRequestBuilder,
+};
+use circle_api::models::wallet_balance::WalletBalanceQueryParams;
+use circle_api::models::wallet_detail::WalletDetail;
+use circle_api::models::wallet_list::WalletListQueryParams;
+use circle_api::models::web3_address::Web3Address;
+
+#[derive(Parser)]
+#[command(
+    name = "circle",
+    about = "Circle programmable wallets from the command line"
+)]
+struct Cli {
+    #[arg(long, env = "CIRCLE_API_KEY", hide_env_values = true)]
+    api_key: String,
+    #[arg(long, env = "CIRCLE_ENTITY_SECRET", hide_env_values = true)]
+    entity_secret: String,
+    #[arg(long, env = "CIRCLE_ENVIRONMENT", value_enum, default_value_t = Network::Sandbox)]
+    environment: Network,
+    #[arg(long, short, value_enum, default_value_t = Format::Table)]
+    output: Format,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Network {
+    Sandbox,
+    Production,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a wallet set
+    CreateWalletSet {
+        name: String,
+        #[command(flatten)]
+        idempotency: Idempotency,
+    },
+    /// Create wallets in a wallet set
+    CreateWallets {
+        #[arg(long)]
+        wallet_set: WalletSetId,
+        /// e.g. `MATIC-AMOY`; repeat for several chains
+        #[arg(long = "blockchain", required = true, value_parser = blockchain)]
+        blockchains: Vec<Blockchain>,
+        #[arg(long, default_value_t = 1)]
+        count: u32,
+        #[command(flatten)]
+        idempotency: Idempotency,
+    },
+    /// List wallets, newest first
+    Wallets {
+        #[arg(long)]
+        wallet_set: Option<WalletSetId>,
+        #[arg(long, value_parser = blockchain)]
+        blockchain: Option<Blockchain>,
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Token balances of a wallet
+    Balances { wallet: WalletId },
+    /// Transfer tokens out of a wallet
+    Transfer {
+        #[arg(long)]
+        wallet: WalletId,
+        #[arg(long)]
+        token: TokenId,
+        #[arg(long)]
+        to: String,
+        #[arg(long)]
+        amount: Decimal,
+        #[arg(long, value_enum, default_value_t = Fee::Medium)]
+        fee_level: Fee,
+        #[command(flatten)]
+        idempotency: Idempotency,
+    },
+    /// Show a transaction
+    Transaction { id: TransactionId },
+    /// Request testnet tokens (sandbox only)
+    Faucet {
+        address: String,
+        #[arg(long, value_parser = blockchain)]
+        blockchain: Blockchain,
+        #[arg(long)]
+        native: bool,
+        #[arg(long)]
+        usdc: bool,
+        #[arg(long)]
+        eurc: bool,
+    },
+}
+
+#[derive(Args)]
+struct Idempotency {
+    /// Reuse a key to safely retry a command; a new one is generated otherwise
+    #[arg(long)]
+    idempotency_key: Option<Uuid>,
+}
+
+impl Idempotency {
+    fn key(&self) -> Uuid {
+        self.idempotency_key.unwrap_or_else(Uuid::new_v4)
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Fee {
+    Low,
+    Medium,
+    High,
+}
+
+impl From<Fee> for FeeLevel {
+    fn from(value: Fee) -> Self {
+        match value {
+            Fee::Low => FeeLevel::Low,
+            Fee::Medium => FeeLevel::Medium,
+            Fee::High => FeeLevel::High,
+        }
+    }
+}
+
+fn blockchain(value: &str) -> std::result::Result<Blockchain, String> {
+    match serde_json::from_value(Value::String(value.to_uppercase())) {
+        Ok(Blockchain::Unknown(_)) | Err(_) => Err(format!("unknown blockchain {}", value)),
+        Ok(blockchain) => Ok(blockchain),
+    }
+}
+
+/// Rows of named columns, printed as an aligned table or as a JSON array of objects.
+struct Output {
+    columns: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+impl Output {
+    fn new(columns: &'static [&'static str]) -> Self {
+        Output {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    fn row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    fn print(&self, format: Format) {
+        if format == Format::Json {
+            let rows = self
+                .rows
+                .iter()
+                .map(|row| {
+                    let fields = self.columns.iter().zip(row);
+                    let fields = fields
+                        .map(|(column, value)| (column.to_string(), Value::String(value.clone())));
+                    Value::Object(fields.collect::<Map<_, _>>())
+                })
+                .collect::<Vec<_>>();
+            println!("{}", Value::Array(rows));
+            return;
+        }
+        let mut widths = self.columns.iter().map(|x| x.len()).collect::<Vec<_>>();
+        for row in &self.rows {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(value.chars().count());
+            }
+        }
+        let line = |values: Vec<&str>| {
+            let cells = values.iter().zip(&widths);
+            let cells = cells.map(|(value, width)| format!("{:width$}", value, width = width));
+            println!("{}", cells.collect::<Vec<_>>().join("  ").trim_end());
+        };
+        line(self.columns.to_vec());
+        for row in &self.rows {
+            line(row.iter().map(String::as_str).collect());
+        }
+    }
+}
+
+/// The JSON spelling of an enum or address, e.g. `MATIC-AMOY`.
+fn text<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(value)) => value,
+        Ok(value) => value.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+const WALLET_COLUMNS: &[&str] = &["id", "blockchain", "address", "state", "walletSetId"];
+
+fn wallet_row(wallet: &WalletDetail) -> Vec<String> {
+    vec![
+        wallet.id.to_string(),
+        text(&wallet.blockchain),
+        text(&wallet.address),
+        text(&wallet.state),
+        wallet.wallet_set_id.to_string(),
+    ]
+}
+
+fn transaction_rows(transaction: &Transaction) -> Output {
+    let mut output = Output::new(&["field", "value"]);
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    let fields = [
+        ("id", transaction.id.to_string()),
+        ("state", text(&transaction.state)),
+        ("blockchain", text(&transaction.blockchain)),
+        (
+            "amounts",
+            optional(transaction.amounts.as_ref().map(|x| {
+                let amounts = x.iter().map(Decimal::to_string);
+                amounts.collect::<Vec<_>>().join(", ")
+            })),
+        ),
+        (
+            "source",
+            optional(transaction.source_address.as_ref().map(text)),
+        ),
+        (
+            "destination",
+            optional(transaction.destination_address.as_ref().map(text)),
+        ),
+        ("txHash", optional(transaction.tx_hash.clone())),
+        (
+            "networkFee",
+            optional(transaction.network_fee.map(|x| x.to_string())),
+        ),
+        ("errorReason", optional(transaction.error_reason.clone())),
+        ("createDate", transaction.create_date.to_rfc3339()),
+    ];
+    for (field, value) in fields {
+        output.row(vec![field.to_string(), value]);
+    }
+    output
+}
+
+async fn run(cli: Cli) -> Result<Output> {
+    let environment = match cli.environment {
+        Network::Sandbox => Environment::Sandbox,
+        Network::Production => Environment::Production,
+    };
+    let client = CircleClient::builder()
+        .api_key(cli.api_key)
+        .entity_secret(cli.entity_secret)
+        .environment(environment)
+        .application("circle-cli")
+        .build()
+        .await?;
+
+    let output = match cli.command {
+        Command::CreateWalletSet { name, idempotency } => {
+            let response = client.create_wallet_set(idempotency.key(), name).await?;
+            let wallet_set = response.wallet_set;
+            let mut output = Output::new(&["id", "name", "custodyType"]);
+            output.row(vec![
+                wallet_set.id.to_string(),
+                wallet_set.name,
+                text(&wallet_set.custody_type),
+            ]);
+            output
+        }
+        Command::CreateWallets {
+            wallet_set,
+            blockchains,
+            count,
+            idempotency,
+        } => {
+            let response = client
+                .create_wallet(idempotency.key(), wallet_set, blockchains, count)
+                .await?;
+            let mut output = Output::new(WALLET_COLUMNS);
+            response
+                .wallets
+                .iter()
+                .for_each(|x| output.row(wallet_row(x)));
+            output
+        }
+        Command::Wallets {
+            wallet_set,
+            blockchain,
+            limit,
+        } => {
+            let mut params = WalletListQueryParams::default();
+            if let Some(wallet_set) = wallet_set {
+                params = params.wallet_set_id(wallet_set);
+            }
+            if let Some(blockchain) = blockchain {
+                params = params.blockchain(blockchain);
+            }
+            let wallets = client
+                .list_wallets_stream(params)
+                .take(limit)
+                .try_collect::<Vec<_>>()
+                .await?;
+            let mut output = Output::new(WALLET_COLUMNS);
+            wallets.iter().for_each(|x| output.row(wallet_row(x)));
+            output
+        }
+        Command::Balances { wallet } => {
+            let response = client
+                .get_wallet_balance(wallet, WalletBalanceQueryParams::default())
+                .await?;
+            let mut output = Output::new(&["symbol", "amount", "blockchain", "tokenId"]);
+            for balance in response.token_balances {
+                output.row(vec![
+                    balance.token.symbol.unwrap_or_default(),
+                    balance.amount.to_string(),
+                    text(&balance.token.blockchain),
+                    balance.token.id.to_string(),
+                ]);
+            }
+            output
+        }
+        Command::Transfer {
+            wallet,
+            token,
+            to,
+            amount,
+            fee_level,
+            idempotency,
+        } => {
+            let destination = to.parse::<Web3Address>()?;
+            let request =
+                TransactionTransferCreateRequestBuilder::new(destination, token, wallet, amount)
+                    .fee_level(fee_level.into());
+            let response = client
+                .create_transfer_transaction(idempotency.key(), request)
+                .await?;
+            let mut output = Output::new(&["id", "state"]);
+            output.row(vec![response.id.to_string(), text(&response.state)]);
+            output
+        }
+        Command::Transaction { id } => {
+            let response = client.get_transaction(id, None).await?;
+            transaction_rows(&response.transaction)
+        }
+        Command::Faucet {
+            address,
+            blockchain,
+            native,
+            usdc,
+            eurc,
+        } => {
+            let mut request = FaucetRequest::new(address.parse()?, blockchain);
+            if native {
+                request = request.native();
+            }
+            if usdc {
+                request = request.usdc();
+            }
+            if eurc {
+                request = request.eurc();
+            }
+            client.request_testnet_tokens(request).await?;
+            let mut output = Output::new(&["address", "status"]);
+            output.row(vec![address, "requested".to_string()]);
+            output
+        }
+    };
+    Ok(output)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let format = cli.output;
+    match run(cli).await {
+        Ok(output) => {
+            output.print(format);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            if let Some(request_id) = err.request_id() {
+                eprintln!("request id: {}", request_id);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! A synchronous wrapper around [`crate::api::CircleClient`] for CLI tools and scripts that
+//! don't run a tokio runtime of their own.
+//!
+//! Each client owns a current-thread runtime and blocks on it for every call, so it must not be
+//! used from inside an async context.
+
+use std::sync::Arc;
+
+use tokio::runtime::Runtime;
+#[cfg(any(
+    feature = "wallets",
+    feature = "transactions",
+    feature = "webhooks",
+    feature = "payments"
+))]
+use uuid::Uuid;
+
+use crate::api::{self, CircleClientBuilder};
+use crate::error::Result;
+#[cfg(feature = "payments")]
+use crate::models::ach_bank_account::{AchBankAccount, AchBankAccountCreateRequestBuilder};
+#[cfg(feature = "wallets")]
+use crate::models::blockchain::Blockchain;
+#[cfg(feature = "payments")]
+use crate::models::business_balance::BusinessBalancesResponse;
+#[cfg(feature = "contracts")]
+use crate::models::cctp::CctpMessagesResponse;
+#[cfg(feature = "payments")]
+use crate::models::channel::Channel;
+#[cfg(feature = "payments")]
+use crate::models::chargeback::{Chargeback, ChargebackListQueryParams};
+#[cfg(feature = "contracts")]
+use crate::models::contract_query::{ContractQueryRequest, ContractQueryResponse};
+#[cfg(feature = "payments")]
+use crate::models::conversion::{
+    Conversion, ConversionCreateRequestBuilder, ConversionListQueryParams,
+};
+use crate::models::entity_config::EntityConfigResponse;
+#[cfg(feature = "payments")]
+use crate::models::exchange_rate::{CurrencyPair, ExchangeRate};
+#[cfg(feature = "sandbox")]
+use crate::models::faucet::FaucetRequest;
+use crate::models::ids::TokenId;
+#[cfg(feature = "transactions")]
+use crate::models::ids::TransactionId;
+#[cfg(feature = "wallets")]
+use crate::models::ids::{WalletId, WalletSetId};
+#[cfg(all(feature = "sandbox", feature = "payments"))]
+use crate::models::mock::{
+    MockAchAccountRequest, MockAchAccountResponse, MockWirePaymentRequest, MockWirePaymentResponse,
+};
+#[cfg(feature = "payments")]
+use crate::models::pagination::Paginated;
+#[cfg(feature = "payments")]
+use crate::models::payout_return::{PayoutReturn, PayoutReturnListQueryParams};
+#[cfg(feature = "transactions")]
+use crate::models::refund::{Refund, RefundOptions};
+#[cfg(feature = "payments")]
+use crate::models::sepa_bank_account::{
+    SepaBankAccount, SepaBankAccountCreateRequestBuilder, SepaInstructions,
+};
+#[cfg(feature = "payments")]
+use crate::models::settlement::{Settlement, SettlementListQueryParams};
+#[cfg(feature = "wallets")]
+use crate::models::signing_message::{SignMessageRequestBuilder, SignMessageResponse};
+#[cfg(feature = "webhooks")]
+use crate::models::subscription::{PaymentsSubscription, Subscription};
+use crate::models::token_get::TokenGetResponse;
+#[cfg(feature = "transactions")]
+use crate::models::transaction::TxType;
+#[cfg(feature = "transactions")]
+use crate::models::transaction_accelerate::TransactionAccelerateResponse;
+#[cfg(feature = "transactions")]
+use crate::models::transaction_cancel::TransactionCancelResponse;
+#[cfg(feature = "contracts")]
+use crate::models::transaction_contract_execution::{
+    TransactionContractExecutionRequestBuilder, TransactionContractExecutionResponse,
+};
+#[cfg(feature = "transactions")]
+use crate::models::transaction_get::TransactionGetResponse;
+#[cfg(feature = "transactions")]
+use crate::models::transaction_list::{TransactionListQueryParams, TransactionListResponse};
+#[cfg(feature = "transactions")]
+use crate::models::transaction_transfer_create::{
+    TransactionTransferCreateRequestBuilder, TransactionTransferCreateResponse,
+};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_balance::{WalletBalanceQueryParams, WalletBalanceResponse};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_create::WalletCreateResponse;
+#[cfg(feature = "wallets")]
+use crate::models::wallet_get::WalletGetResponse;
+#[cfg(feature = "wallets")]
+use crate::models::wallet_list::{WalletListQueryParams, WalletListResponse};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_nfts::{WalletNftsQueryParams, WalletNftsResponse};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_set::{
+    CreateWalletSetResponse, GetWalletSetResponse, UpdateWalletSetResponse, WalletSetsQueryParams,
+    WalletSetsResponse,
+};
+#[cfg(feature = "wallets")]
+use crate::models::wallet_update::{WalletUpdateRequest, WalletUpdateResponse};
+#[cfg(feature = "wallets")]
+use crate::models::web3_address::Web3Address;
+#[cfg(feature = "wallets")]
+use crate::models::web3_signature::Web3Signature;
+
+#[derive(Clone)]
+pub struct CircleClient {
+    inner: api::CircleClient,
+    runtime: Arc<Runtime>,
+}
+
+/// Mirrors async methods of [`api::CircleClient`] as blocking ones with the same signature.
+macro_rules! blocking {
+    ($($(#[$attr:meta])* fn $name:ident(&self $(, $arg:ident: $ty:ty)*) -> $ret:ty;)*) => {
+        impl CircleClient {
+            $(
+                $(#[$attr])*
+                pub fn $name(&self $(, $arg: $ty)*) -> Result<$ret> {
+                    self.runtime.block_on(self.inner.$name($($arg),*))
+                }
+            )*
+        }
+    };
+}
+
+impl CircleClient {
+    pub fn new(api_key: String, circle_entity_secret: String) -> Result<Self> {
+        Self::from_builder(
+            api::CircleClient::builder()
+                .api_key(api_key)
+                .entity_secret(circle_entity_secret),
+        )
+    }
+
+    pub fn from_builder(builder: CircleClientBuilder) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let inner = runtime.block_on(builder.build())?;
+        Ok(CircleClient {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The async client underneath, e.g. for calls this wrapper doesn't cover.
+    pub fn as_async(&self) -> &api::CircleClient {
+        &self.inner
+    }
+}
+
+blocking! {
+    #[cfg(feature = "payments")]
+    fn create_ach_bank_account(
+        &self,
+        idempotency_key: impl Into<Option<Uuid>>,
+        request: AchBankAccountCreateRequestBuilder
+    ) -> AchBankAccount;
+    #[cfg(feature = "payments")]
+    fn get_ach_bank_account(&self, bank_account_id: Uuid) -> AchBankAccount;
+    #[cfg(feature = "payments")]
+    fn create_sepa_bank_account(
+        &self,
+        idempotency_key: impl Into<Option<Uuid>>,
+        request: SepaBankAccountCreateRequestBuilder
+    ) -> SepaBankAccount;
+    #[cfg(feature = "payments")]
+    fn get_sepa_bank_account(&self, bank_account_id: Uuid) -> SepaBankAccount;
+    #[cfg(feature = "payments")]
+    fn get_sepa_instructions(&self, bank_account_id: Uuid) -> SepaInstructions;
+    #[cfg(feature = "payments")]
+    fn get_business_balances(&self) -> BusinessBalancesResponse;
+    #[cfg(feature = "contracts")]
+    fn get_cctp_messages(
+        &self,
+        attestation_base_url: &str,
+        source_domain: u32,
+        tx_hash: &str
+    ) -> Option<CctpMessagesResponse>;
+    #[cfg(feature = "payments")]
+    fn list_channels(&self) -> Vec<Channel>;
+    #[cfg(feature = "payments")]
+    fn list_chargebacks(&self, query_params: ChargebackListQueryParams) -> Paginated<Chargeback>;
+    #[cfg(feature = "payments")]
+    fn get_chargeback(&self, chargeback_id: Uuid) -> Chargeback;
+    #[cfg(feature = "payments")]
+    fn create_conversion(
+        &self,
+        idempotency_key: impl Into<Option<Uuid>>,
+        request: ConversionCreateRequestBuilder
+    ) -> Conversion;
+    #[cfg(feature = "payments")]
+    fn get_conversion(&self, conversion_id: Uuid) -> Conversion;
+    #[cfg(feature = "payments")]
+    fn list_conversions(&self, query_params: ConversionListQueryParams) -> Paginated<Conversion>;
+    #[cfg(feature = "payments")]
+    fn get_exchange_rates(&self, pair: CurrencyPair) -> ExchangeRate;
+    #[cfg(all(feature = "sandbox", feature = "payments"))]
+    fn create_mock_wire_payment(&self, request: MockWirePaymentRequest) -> MockWirePaymentResponse;
+    #[cfg(all(feature = "sandbox", feature = "payments"))]
+    fn create_mock_chargeback(&self, payment_id: Uuid) -> Chargeback;
+    #[cfg(all(feature = "sandbox", feature = "payments"))]
+    fn create_mock_ach_account(&self, request: MockAchAccountRequest) -> MockAchAccountResponse;
+    #[cfg(feature = "sandbox")]
+    fn request_testnet_tokens(&self, request: FaucetRequest) -> ();
+    #[cfg(feature = "payments")]
+    fn list_payout_returns(&self, query_params: PayoutReturnListQueryParams) -> Paginated<PayoutReturn>;
+    #[cfg(feature = "payments")]
+    fn list_settlements(&self, query_params: SettlementListQueryParams) -> Paginated<Settlement>;
+    #[cfg(feature = "payments")]
+    fn get_settlement(&self, settlement_id: Uuid) -> Settlement;
+    #[cfg(feature = "wallets")]
+    fn sign_message(&self, request: SignMessageRequestBuilder) -> SignMessageResponse;
+    #[cfg(feature = "wallets")]
+    fn sign_ethereum_message(&self, wallet_id: WalletId, message: String) -> Web3Signature;
+    #[cfg(feature = "wallets")]
+    fn verify_ethereum_message(
+        &self,
+        wallet_address: Web3Address,
+        message: String,
+        signature: Web3Signature
+    ) -> bool;
+    #[cfg(feature = "webhooks")]
+    fn create_subscription(
+        &self,
+        endpoint: String,
+        notification_types: Option<Vec<String>>
+    ) -> Subscription;
+    #[cfg(feature = "webhooks")]
+    fn list_subscriptions(&self) -> Vec<Subscription>;
+    #[cfg(feature = "webhooks")]
+    fn get_subscription(&self, subscription_id: Uuid) -> Subscription;
+    #[cfg(feature = "webhooks")]
+    fn delete_subscription(&self, subscription_id: Uuid) -> ();
+    #[cfg(feature = "webhooks")]
+    fn create_payments_subscription(&self, endpoint: String) -> PaymentsSubscription;
+    #[cfg(feature = "webhooks")]
+    fn list_payments_subscriptions(&self) -> Vec<PaymentsSubscription>;
+    #[cfg(feature = "webhooks")]
+    fn delete_payments_subscription(&self, subscription_id: Uuid) -> ();
+    fn get_entity_config(&self) -> EntityConfigResponse;
+    fn ping(&self) -> api::Health;
+    fn get_token_details(&self, token_id: TokenId) -> TokenGetResponse;
+    fn token_decimals(&self, token_id: TokenId) -> Option<u32>;
+    #[cfg(feature = "transactions")]
+    fn create_transfer_transaction(
+        &self,
+        idempotency_key: impl Into<Option<Uuid>>,
+        request: TransactionTransferCreateRequestBuilder
+    ) -> TransactionTransferCreateResponse;
+    #[cfg(feature = "transactions")]
+    fn accelerate_transaction(
+        &self,
+        transaction_id: TransactionId,
+        idempotency_key: impl Into<Option<Uuid>>
+    ) -> TransactionAccelerateResponse;
+    #[cfg(feature = "transactions")]
+    fn cancel_transaction(
+        &self,
+        transaction_id: TransactionId,
+        idempotency_key: impl Into<Option<Uuid>>
+    ) -> TransactionCancelResponse;
+    #[cfg(feature = "contracts")]
+    fn query_contract(&self, request: ContractQueryRequest) -> ContractQueryResponse;
+    #[cfg(feature = "contracts")]
+    fn create_contract_execution_transaction(
+        &self,
+        idempotency_key: impl Into<Option<Uuid>>,
+        request: TransactionContractExecutionRequestBuilder
+    ) -> TransactionContractExecutionResponse;
+    #[cfg(feature = "transactions")]
+    fn list_transactions(&self, query_params: TransactionListQueryParams) -> TransactionListResponse;
+    #[cfg(feature = "transactions")]
+    fn get_transaction(
+        &self,
+        transaction_id: TransactionId,
+        tx_type: Option<TxType>
+    ) -> TransactionGetResponse;
+    #[cfg(feature = "transactions")]
+    fn refund_transaction(&self, original_transaction_id: TransactionId, options: RefundOptions) -> Refund;
+    #[cfg(feature = "wallets")]
+    fn list_wallet_sets(&self, query_params: WalletSetsQueryParams) -> WalletSetsResponse;
+    #[cfg(feature = "wallets")]
+    fn create_wallet_set(&self, idempotency_key: impl Into<Option<Uuid>>, name: String) -> CreateWalletSetResponse;
+    #[cfg(feature = "wallets")]
+    fn update_wallet_set(&self, wallet_set_id: WalletSetId, name: String) -> UpdateWalletSetResponse;
+    #[cfg(feature = "wallets")]
+    fn get_wallet_set(&self, wallet_set_id: WalletSetId) -> GetWalletSetResponse;
+    #[cfg(feature = "wallets")]
+    fn create_wallet(
+        &self,
+        idempotency_key: impl Into<Option<Uuid>>,
+        wallet_set_id: WalletSetId,
+        blockchains: Vec<Blockchain>,
+        count: u32
+    ) -> WalletCreateResponse;
+    #[cfg(feature = "wallets")]
+    fn list_wallets(&self, query_params: WalletListQueryParams) -> WalletListResponse;
+    #[cfg(feature = "wallets")]
+    fn get_wallet(&self, wallet_id: WalletId) -> WalletGetResponse;
+    #[cfg(feature = "wallets")]
+    fn update_wallet(
+        &self,
+        wallet_id: WalletId,
+        query_params: WalletUpdateRequest
+    ) -> WalletUpdateResponse;
+    #[cfg(feature = "wallets")]
+    fn get_wallet_balance(
+        &self,
+        wallet_id: WalletId,
+        query_params: WalletBalanceQueryParams
+    ) -> WalletBalanceResponse;
+    #[cfg(feature = "wallets")]
+    fn get_wallet_nfts(
+        &self,
+        wallet_id: WalletId,
+        query_params: WalletNftsQueryParams
+    ) -> WalletNftsResponse;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[test]
+    fn test_blocking_calls() {
+        let transport = MockTransport::new();
+        let builder = transport.client_builder();
+        let client = CircleClient::from_builder(builder).unwrap();
+        transport.push_data(serde_json::json!([]));
+        assert!(client.list_channels().unwrap().is_empty());
+        assert_eq!(transport.last_request().unwrap().path, "/v1/channels");
+    }
+}
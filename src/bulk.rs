@@ -0,0 +1,354 @@
+//! Large queues of mixed operations (balance reads, transfers, lookups) run alongside live
+//! traffic, e.g. by nightly jobs.
+//!
+//! [`BulkScheduler`] runs queued operations highest priority class first, and only while the
+//! client's [`RateLimiter`] has more than [`BulkScheduler::headroom`] of its burst to spare,
+//! so the requests of the rest of the service don't queue behind the job.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::Serialize;
+
+use crate::api::{CircleClient, RateLimiter};
+use crate::cancel::CancellationToken;
+use crate::error::Result;
+use crate::runtime::Instant;
+
+/// How often a scheduler waiting for headroom checks the client's limiter again.
+const HEADROOM_POLL: Duration = Duration::from_millis(50);
+
+struct Queued<'a, T> {
+    priority: i32,
+    sequence: u64,
+    class: String,
+    id: String,
+    operation: BoxFuture<'a, Result<T>>,
+}
+
+impl<T> Queued<'_, T> {
+    /// Highest priority first, then first queued first.
+    fn rank(&self) -> (i32, Reverse<u64>) {
+        (self.priority, Reverse(self.sequence))
+    }
+}
+
+impl<T> PartialEq for Queued<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank() == other.rank()
+    }
+}
+
+impl<T> Eq for Queued<'_, T> {}
+
+impl<T> PartialOrd for Queued<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Queued<'_, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+/// How far one class of operations has got.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassProgress {
+    pub queued: usize,
+    pub in_flight: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl ClassProgress {
+    pub fn total(&self) -> usize {
+        self.queued + self.in_flight + self.succeeded + self.failed
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkProgress {
+    pub classes: BTreeMap<String, ClassProgress>,
+    /// Since the first [`BulkScheduler::run`] started.
+    pub elapsed: Duration,
+    /// At the pace so far. `None` until an operation has finished.
+    pub eta: Option<Duration>,
+}
+
+impl BulkProgress {
+    fn sum(&self, count: impl Fn(&ClassProgress) -> usize) -> usize {
+        self.classes.values().map(count).sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.sum(ClassProgress::total)
+    }
+
+    pub fn finished(&self) -> usize {
+        self.sum(|x| x.succeeded + x.failed)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.sum(|x| x.failed)
+    }
+
+    /// Queued or in flight.
+    pub fn remaining(&self) -> usize {
+        self.sum(|x| x.queued + x.in_flight)
+    }
+}
+
+/// One operation's outcome.
+#[derive(Debug)]
+pub struct BulkResult<T> {
+    pub class: String,
+    pub id: String,
+    pub result: Result<T>,
+}
+
+/// What one [`BulkScheduler::run`] did, in the order operations finished.
+#[derive(Debug)]
+pub struct BulkReport<T> {
+    pub results: Vec<BulkResult<T>>,
+    pub progress: BulkProgress,
+}
+
+struct State<'a, T> {
+    queue: BinaryHeap<Queued<'a, T>>,
+    sequence: u64,
+    classes: BTreeMap<String, ClassProgress>,
+    started: Option<Instant>,
+    finished: usize,
+}
+
+pub struct BulkScheduler<'a, T> {
+    client: &'a CircleClient,
+    priorities: HashMap<String, i32>,
+    concurrency: usize,
+    headroom: f64,
+    rate_limiter: Option<RateLimiter>,
+    cancellation: CancellationToken,
+    state: Mutex<State<'a, T>>,
+}
+
+impl<'a, T: Send + 'a> BulkScheduler<'a, T> {
+    pub fn new(client: &'a CircleClient) -> Self {
+        BulkScheduler {
+            client,
+            priorities: HashMap::new(),
+            concurrency: 4,
+            headroom: 0.25,
+            rate_limiter: None,
+            cancellation: CancellationToken::new(),
+            state: Mutex::new(State {
+                queue: BinaryHeap::new(),
+                sequence: 0,
+                classes: BTreeMap::new(),
+                started: None,
+                finished: 0,
+            }),
+        }
+    }
+
+    /// Operations of higher priority classes run first, even if they were queued later.
+    /// Classes without a priority have 0.
+    pub fn class<S: Into<String>>(mut self, class: S, priority: i32) -> Self {
+        self.priorities.insert(class.into(), priority);
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Share of the client's rate limiter burst left to other traffic, from 0.0 to 1.0.
+    /// Operations wait while the limiter is busier than that. Ignored if the client has no
+    /// limiter.
+    pub fn headroom(mut self, headroom: f64) -> Self {
+        self.headroom = headroom.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Caps the job itself at `requests_per_second`, on top of the client's limiter.
+    pub fn rate_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(requests_per_second, burst));
+        self
+    }
+
+    /// Stops [`BulkScheduler::run`] from starting operations once `token` is cancelled.
+    /// Operations in flight finish; queued ones stay queued for the next run.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Queues `operation`, which doesn't start until the scheduler runs it. Operations can be
+    /// queued while [`BulkScheduler::run`] is going, e.g. payouts arriving mid-job.
+    pub fn push<C, I, F>(&self, class: C, id: I, operation: F)
+    where
+        C: Into<String>,
+        I: Into<String>,
+        F: Future<Output = Result<T>> + Send + 'a,
+    {
+        let class = class.into();
+        let mut state = self.state.lock().unwrap();
+        state.sequence += 1;
+        state.classes.entry(class.clone()).or_default().queued += 1;
+        let queued = Queued {
+            priority: self.priorities.get(&class).copied().unwrap_or_default(),
+            sequence: state.sequence,
+            class,
+            id: id.into(),
+            operation: Box::pin(operation),
+        };
+        state.queue.push(queued);
+    }
+
+    pub fn progress(&self) -> BulkProgress {
+        let state = self.state.lock().unwrap();
+        let elapsed = state.started.map(|x| x.elapsed()).unwrap_or_default();
+        let remaining: usize = state.classes.values().map(|x| x.queued + x.in_flight).sum();
+        let eta = match state.finished {
+            0 => None,
+            finished => Some(elapsed.mul_f64(remaining as f64 / finished as f64)),
+        };
+        BulkProgress {
+            classes: state.classes.clone(),
+            elapsed,
+            eta,
+        }
+    }
+
+    /// Runs queued operations until the queue is empty or the scheduler is cancelled, handing
+    /// the progress to `on_progress` after each one finishes.
+    pub async fn run<F: FnMut(&BulkProgress)>(&self, mut on_progress: F) -> BulkReport<T> {
+        self.state
+            .lock()
+            .unwrap()
+            .started
+            .get_or_insert_with(Instant::now);
+        let mut in_flight = FuturesUnordered::new();
+        let mut results = Vec::new();
+        loop {
+            while in_flight.len() < self.concurrency && !self.cancellation.is_cancelled() {
+                let Some(next) = self.next() else {
+                    break;
+                };
+                in_flight.push(async move {
+                    self.wait_for_headroom().await;
+                    let result = next.operation.await;
+                    BulkResult {
+                        class: next.class,
+                        id: next.id,
+                        result,
+                    }
+                });
+            }
+            let Some(result) = in_flight.next().await else {
+                break;
+            };
+            self.finish(&result);
+            results.push(result);
+            on_progress(&self.progress());
+        }
+        BulkReport {
+            results,
+            progress: self.progress(),
+        }
+    }
+
+    fn next(&self) -> Option<Queued<'a, T>> {
+        let mut state = self.state.lock().unwrap();
+        let next = state.queue.pop()?;
+        let class = state.classes.entry(next.class.clone()).or_default();
+        class.queued -= 1;
+        class.in_flight += 1;
+        Some(next)
+    }
+
+    fn finish(&self, result: &BulkResult<T>) {
+        let mut state = self.state.lock().unwrap();
+        state.finished += 1;
+        let class = state.classes.entry(result.class.clone()).or_default();
+        class.in_flight -= 1;
+        match result.result {
+            Ok(_) => class.succeeded += 1,
+            Err(_) => class.failed += 1,
+        }
+    }
+
+    async fn wait_for_headroom(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+        let Some(client_limiter) = self.client.rate_limiter() else {
+            return;
+        };
+        while client_limiter.saturation() > 1.0 - self.headroom {
+            if self.cancellation.sleep(HEADROOM_POLL).await {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+    use crate::error::CircleError;
+
+    #[tokio::test]
+    async fn test_bulk_scheduler() {
+        let transport = MockTransport::new();
+        let client = transport
+            .client_builder()
+            .rate_limit(1000.0, 4)
+            .build()
+            .await
+            .unwrap();
+        let scheduler = BulkScheduler::new(&client)
+            .class("payouts", 10)
+            .class("analytics", -10)
+            .concurrency(1);
+
+        for id in ["a1", "a2"] {
+            scheduler.push("analytics", id, async { Ok(()) });
+        }
+        transport.push_data(json!({ "appId": "app" }));
+        scheduler.push("lookups", "config", async {
+            client.get_entity_config().await.map(drop)
+        });
+        scheduler.push("payouts", "p1", async { Ok(()) });
+        scheduler.push("payouts", "p2", async { Err(CircleError::ValueError) });
+        assert_eq!(scheduler.progress().total(), 5);
+        assert_eq!(scheduler.progress().eta, None);
+
+        let mut seen = Vec::new();
+        let report = scheduler
+            .run(|progress| seen.push(progress.finished()))
+            .await;
+        let order: Vec<_> = report.results.iter().map(|x| x.id.as_str()).collect();
+        assert_eq!(order, ["p1", "p2", "config", "a1", "a2"]);
+        assert_eq!(seen, [1, 2, 3, 4, 5]);
+
+        let progress = report.progress;
+        assert_eq!(progress.failed(), 1);
+        assert_eq!(progress.remaining(), 0);
+        assert_eq!(progress.eta, Some(Duration::ZERO));
+        assert_eq!(progress.classes["payouts"].succeeded, 1);
+        assert_eq!(progress.classes["lookups"].succeeded, 1);
+    }
+}
@@ -0,0 +1,171 @@
+//! Cooperative cancellation for the long-running helpers ([`crate::payouts::BatchRunner`],
+//! [`crate::cctp::CctpTransfer::run_until_cancelled`]).
+//!
+//! Cancelling never aborts a request that is already on the wire: helpers finish the step they
+//! are in and then stop, leaving anything that was submitted but not confirmed in a pending
+//! state that can be resumed later with the same idempotency keys.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use futures::future::{select, Either};
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    next_waiter: AtomicU64,
+    /// The wakers of pending [`Cancelled`] futures, by waiter id.
+    wakers: Mutex<HashMap<u64, Waker>>,
+}
+
+/// A cheaply cloneable cancellation flag. All clones observe the same cancellation.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        let wakers = std::mem::take(&mut *self.inner.wakers.lock().unwrap());
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once [`CancellationToken::cancel`] has been called on any clone.
+    pub fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled {
+            token: self,
+            waiter: self.inner.next_waiter.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Sleeps for `duration` unless cancelled first. Returns `true` if the token was cancelled.
+    pub(crate) async fn sleep(&self, duration: Duration) -> bool {
+        let sleep = std::pin::pin!(crate::runtime::sleep(duration));
+        match select(self.cancelled(), sleep).await {
+            Either::Left(_) => true,
+            Either::Right(_) => false,
+        }
+    }
+}
+
+/// Runs `pass` every `interval` until `token` is cancelled, handing each pass's result to
+/// `on_report`. The first pass starts right away, and a pass in progress always finishes.
+pub async fn run_every<T, P, F, R>(
+    interval: Duration,
+    token: &CancellationToken,
+    mut pass: P,
+    mut on_report: R,
+) where
+    P: FnMut() -> F,
+    F: Future<Output = T>,
+    R: FnMut(T),
+{
+    loop {
+        on_report(pass().await);
+        if token.is_cancelled() || token.sleep(interval).await {
+            return;
+        }
+    }
+}
+
+pub struct Cancelled<'a> {
+    token: &'a CancellationToken,
+    waiter: u64,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let mut wakers = self.token.inner.wakers.lock().unwrap();
+        // Re-check under the lock so a concurrent cancel() can't slip in between.
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        match wakers.get_mut(&self.waiter) {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            Some(waker) => *waker = cx.waker().clone(),
+            None => {
+                wakers.insert(self.waiter, cx.waker().clone());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled<'_> {
+    fn drop(&mut self) {
+        self.token.inner.wakers.lock().unwrap().remove(&self.waiter);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        assert!(!token.sleep(Duration::from_millis(1)).await);
+
+        let clone = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            clone.cancel();
+        });
+        assert!(token.sleep(Duration::from_secs(60)).await);
+        assert!(token.is_cancelled());
+        token.cancelled().await;
+    }
+
+    #[tokio::test]
+    async fn test_run_every() {
+        let token = CancellationToken::new();
+        let mut passes = 0;
+        let mut reports = Vec::new();
+        run_every(
+            Duration::from_millis(1),
+            &token,
+            || {
+                passes += 1;
+                if passes == 3 {
+                    token.cancel();
+                }
+                futures::future::ready(passes)
+            },
+            |x| reports.push(x),
+        )
+        .await;
+        assert_eq!(reports, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_waiters_are_forgotten() {
+        let token = CancellationToken::new();
+        let mut waiting = Box::pin(token.cancelled());
+        assert!(futures::poll!(waiting.as_mut()).is_pending());
+        assert!(futures::poll!(waiting.as_mut()).is_pending());
+        assert_eq!(token.inner.wakers.lock().unwrap().len(), 1);
+        drop(waiting);
+        assert!(token.inner.wakers.lock().unwrap().is_empty());
+    }
+}
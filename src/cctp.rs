@@ -0,0 +1,448 @@
+//! Cross-chain USDC transfers between developer-controlled wallets using Circle's
+//! Cross-Chain Transfer Protocol: approve and burn on the source chain, wait for the
+//! attestation, then mint on the destination chain.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use web3::types::Address;
+
+use crate::api::CircleClient;
+use crate::cancel::CancellationToken;
+use crate::error::{CircleError, Result};
+use crate::models::blockchain::Blockchain;
+use crate::models::ids::{TransactionId, WalletId};
+use crate::models::transaction::TxType;
+use crate::models::transaction_contract_execution::TransactionContractExecutionRequestBuilder;
+use crate::models::transaction_state::TransactionState;
+use crate::models::transaction_transfer_create::FeeLevel;
+use crate::models::web3_address::Web3Address;
+
+pub const ATTESTATION_API_URL: &str = "https://iris-api.circle.com/";
+pub const ATTESTATION_SANDBOX_API_URL: &str = "https://iris-api-sandbox.circle.com/";
+
+const USDC_DECIMALS: u32 = 6;
+
+pub struct CctpContracts {
+    pub usdc: &'static str,
+    pub token_messenger: &'static str,
+    pub message_transmitter: &'static str,
+}
+
+/// The CCTP domain of `blockchain`, or `None` for chains this crate has no CCTP support for.
+pub fn cctp_domain(blockchain: &Blockchain) -> Option<u32> {
+    match blockchain {
+        Blockchain::Eth | Blockchain::EthGoerli => Some(0),
+        Blockchain::Avax | Blockchain::AvaxFuji => Some(1),
+        Blockchain::Matic | Blockchain::MaticMumbai => Some(7),
+        _ => None,
+    }
+}
+
+pub fn cctp_contracts(blockchain: &Blockchain) -> Option<CctpContracts> {
+    let contracts = match blockchain {
+        Blockchain::Eth => CctpContracts {
+            usdc: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            token_messenger: "0xBd3fa81B58Ba92a82136038B25aDec7066af3155",
+            message_transmitter: "0x0a992d191DEeC32aFe36203Ad87D7d289a738F81",
+        },
+        Blockchain::EthGoerli => CctpContracts {
+            usdc: "0x07865c6E87B9F70255377e024ace6630C1Eaa37F",
+            token_messenger: "0xd0c3da58f55358142b8d3e06c1c30c5c6114efe8",
+            message_transmitter: "0x26413e8157cd32011e726065a5462e97dd4d03d9",
+        },
+        Blockchain::Avax => CctpContracts {
+            usdc: "0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E",
+            token_messenger: "0x6B25532e1060CE10cc3B0A99e5683b91BFDe6982",
+            message_transmitter: "0x8186359aF5F57FbB40c6b14A588d2A59C0C29880",
+        },
+        Blockchain::AvaxFuji => CctpContracts {
+            usdc: "0x5425890298aed601595a70AB815c96711a31Bc65",
+            token_messenger: "0xeb08f243e5d3fcff26a9e38ae5520a669f4019d0",
+            message_transmitter: "0xa9fb1b3009dcb79e2fe346c16a604b8fa8ae0a79",
+        },
+        Blockchain::Matic => CctpContracts {
+            usdc: "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359",
+            token_messenger: "0x9daF8c91AEFAE50b9c0E69629D3F6Ca40cA3B3FE",
+            message_transmitter: "0xF3be9355363857F3e001be68856A2f96b4C39bA9",
+        },
+        Blockchain::MaticMumbai => CctpContracts {
+            usdc: "0x9999f7Fea5938fD3b1E26A12c3f2fb024e194f97",
+            token_messenger: "0x9f3B8679c73C2Fef8b59B4f3444d4e156fb70AA5",
+            message_transmitter: "0xe09A679F56207EF33F5b9d8fb4499Ec00792eA73",
+        },
+        _ => return None,
+    };
+    Some(contracts)
+}
+
+fn domain(blockchain: &Blockchain) -> Result<u32> {
+    cctp_domain(blockchain).ok_or(CircleError::ValueError)
+}
+
+fn contracts(blockchain: &Blockchain) -> Result<CctpContracts> {
+    cctp_contracts(blockchain).ok_or(CircleError::ValueError)
+}
+
+fn parse_address(value: &str) -> Result<Web3Address> {
+    let address =
+        Address::from_str(value.trim_start_matches("0x")).map_err(|_| CircleError::ValueError)?;
+    Ok(Web3Address::from(address))
+}
+
+fn address_to_bytes32(address: &Web3Address) -> String {
+    let address: Address = address.clone().into();
+    format!("0x{:0>64}", hex::encode(address.as_bytes()))
+}
+
+fn to_usdc_units(amount: Decimal) -> Result<u64> {
+    let units = amount
+        .checked_mul(Decimal::from(10u64.pow(USDC_DECIMALS)))
+        .ok_or(CircleError::ValueError)?;
+    if units.fract() != Decimal::ZERO || units <= Decimal::ZERO {
+        Err(CircleError::ValueError)?
+    }
+    units.to_u64().ok_or(CircleError::ValueError)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CctpTransferState {
+    Pending,
+    Approving {
+        approve_tx_id: TransactionId,
+    },
+    Burning {
+        burn_tx_id: TransactionId,
+    },
+    AwaitingAttestation {
+        burn_tx_hash: String,
+    },
+    Minting {
+        mint_tx_id: TransactionId,
+    },
+    Complete {
+        mint_tx_id: TransactionId,
+        mint_tx_hash: Option<String>,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+impl CctpTransferState {
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            CctpTransferState::Complete { .. } | CctpTransferState::Failed { .. }
+        )
+    }
+}
+
+enum TxProgress {
+    Pending,
+    Done(Option<String>),
+    Failed(String),
+}
+
+/// A resumable CCTP transfer. Every step reuses the idempotency keys generated when the transfer
+/// was created, so calling [`CctpTransfer::step`] again after an error never submits twice.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CctpTransfer {
+    source_wallet_id: WalletId,
+    source_blockchain: Blockchain,
+    destination_wallet_id: WalletId,
+    destination_blockchain: Blockchain,
+    amount: u64,
+    fee_level: FeeLevel,
+    approve_idempotency_key: Uuid,
+    burn_idempotency_key: Uuid,
+    mint_idempotency_key: Uuid,
+    state: CctpTransferState,
+}
+
+impl CctpTransfer {
+    pub fn new(
+        source_wallet_id: WalletId,
+        source_blockchain: Blockchain,
+        destination_wallet_id: WalletId,
+        destination_blockchain: Blockchain,
+        amount: Decimal,
+    ) -> Result<Self> {
+        if source_blockchain == destination_blockchain
+            || cctp_domain(&source_blockchain).is_none()
+            || cctp_domain(&destination_blockchain).is_none()
+            || source_blockchain.is_testnet() != destination_blockchain.is_testnet()
+        {
+            Err(CircleError::ValueError)?
+        }
+        Ok(CctpTransfer {
+            source_wallet_id,
+            source_blockchain,
+            destination_wallet_id,
+            destination_blockchain,
+            amount: to_usdc_units(amount)?,
+            fee_level: FeeLevel::Medium,
+            approve_idempotency_key: Uuid::new_v4(),
+            burn_idempotency_key: Uuid::new_v4(),
+            mint_idempotency_key: Uuid::new_v4(),
+            state: CctpTransferState::Pending,
+        })
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee_level = fee_level;
+        self
+    }
+
+    pub fn state(&self) -> &CctpTransferState {
+        &self.state
+    }
+
+    /// Advances the transfer by at most one stage without blocking on chain progress.
+    pub async fn step(&mut self, client: &CircleClient) -> Result<&CctpTransferState> {
+        let next = match &self.state {
+            CctpTransferState::Pending => CctpTransferState::Approving {
+                approve_tx_id: self.approve(client).await?,
+            },
+            CctpTransferState::Approving { approve_tx_id } => {
+                match Self::progress(client, *approve_tx_id).await? {
+                    TxProgress::Pending => return Ok(&self.state),
+                    TxProgress::Done(_) => CctpTransferState::Burning {
+                        burn_tx_id: self.burn(client).await?,
+                    },
+                    TxProgress::Failed(reason) => CctpTransferState::Failed { reason },
+                }
+            }
+            CctpTransferState::Burning { burn_tx_id } => {
+                match Self::progress(client, *burn_tx_id).await? {
+                    TxProgress::Pending => return Ok(&self.state),
+                    TxProgress::Done(Some(burn_tx_hash)) => {
+                        CctpTransferState::AwaitingAttestation { burn_tx_hash }
+                    }
+                    // Without the hash there is nothing to fetch the attestation for.
+                    TxProgress::Done(None) => CctpTransferState::Failed {
+                        reason: "burn completed without tx hash".to_string(),
+                    },
+                    TxProgress::Failed(reason) => CctpTransferState::Failed { reason },
+                }
+            }
+            CctpTransferState::AwaitingAttestation { burn_tx_hash } => {
+                let attestation_url = if self.source_blockchain.is_testnet() {
+                    ATTESTATION_SANDBOX_API_URL
+                } else {
+                    ATTESTATION_API_URL
+                };
+                let messages = client
+                    .get_cctp_messages(
+                        attestation_url,
+                        domain(&self.source_blockchain)?,
+                        burn_tx_hash,
+                    )
+                    .await?;
+                let message = messages.and_then(|x| x.messages.into_iter().next());
+                match message {
+                    Some(message) if message.is_attested() => CctpTransferState::Minting {
+                        mint_tx_id: self
+                            .mint(client, message.message, message.attestation)
+                            .await?,
+                    },
+                    _ => return Ok(&self.state),
+                }
+            }
+            CctpTransferState::Minting { mint_tx_id } => {
+                match Self::progress(client, *mint_tx_id).await? {
+                    TxProgress::Pending => return Ok(&self.state),
+                    TxProgress::Done(mint_tx_hash) => CctpTransferState::Complete {
+                        mint_tx_id: *mint_tx_id,
+                        mint_tx_hash,
+                    },
+                    TxProgress::Failed(reason) => CctpTransferState::Failed { reason },
+                }
+            }
+            CctpTransferState::Complete { .. } | CctpTransferState::Failed { .. } => {
+                return Ok(&self.state)
+            }
+        };
+        self.state = next;
+        Ok(&self.state)
+    }
+
+    /// Drives the transfer to a terminal state, polling every `poll_interval`.
+    pub async fn run(
+        &mut self,
+        client: &CircleClient,
+        poll_interval: Duration,
+    ) -> Result<&CctpTransferState> {
+        while !self.step(client).await?.is_terminal() {
+            crate::runtime::sleep(poll_interval).await;
+        }
+        Ok(&self.state)
+    }
+
+    /// Like [`CctpTransfer::run`], but returns early once `token` is cancelled. A step that is
+    /// already running is finished first, so the transfer can be persisted and resumed later.
+    pub async fn run_until_cancelled(
+        &mut self,
+        client: &CircleClient,
+        poll_interval: Duration,
+        token: &CancellationToken,
+    ) -> Result<&CctpTransferState> {
+        while !token.is_cancelled() && !self.step(client).await?.is_terminal() {
+            if token.sleep(poll_interval).await {
+                break;
+            }
+        }
+        Ok(&self.state)
+    }
+
+    async fn approve(&self, client: &CircleClient) -> Result<TransactionId> {
+        let contracts = contracts(&self.source_blockchain)?;
+        let request = TransactionContractExecutionRequestBuilder::new(
+            self.source_wallet_id,
+            parse_address(contracts.usdc)?,
+            "approve(address,uint256)".to_string(),
+            vec![
+                Value::from(contracts.token_messenger),
+                Value::from(self.amount.to_string()),
+            ],
+        )
+        .fee_level(self.fee_level);
+        let response = client
+            .create_contract_execution_transaction(self.approve_idempotency_key, request)
+            .await?;
+        Ok(response.id)
+    }
+
+    async fn burn(&self, client: &CircleClient) -> Result<TransactionId> {
+        let contracts = contracts(&self.source_blockchain)?;
+        let destination_wallet = client.get_wallet(self.destination_wallet_id).await?.wallet;
+        let request = TransactionContractExecutionRequestBuilder::new(
+            self.source_wallet_id,
+            parse_address(contracts.token_messenger)?,
+            "depositForBurn(uint256,uint32,bytes32,address)".to_string(),
+            vec![
+                Value::from(self.amount.to_string()),
+                Value::from(domain(&self.destination_blockchain)?.to_string()),
+                Value::from(address_to_bytes32(&destination_wallet.address)),
+                Value::from(contracts.usdc),
+            ],
+        )
+        .fee_level(self.fee_level);
+        let response = client
+            .create_contract_execution_transaction(self.burn_idempotency_key, request)
+            .await?;
+        Ok(response.id)
+    }
+
+    async fn mint(
+        &self,
+        client: &CircleClient,
+        message: String,
+        attestation: String,
+    ) -> Result<TransactionId> {
+        let contracts = contracts(&self.destination_blockchain)?;
+        let request = TransactionContractExecutionRequestBuilder::new(
+            self.destination_wallet_id,
+            parse_address(contracts.message_transmitter)?,
+            "receiveMessage(bytes,bytes)".to_string(),
+            vec![Value::from(message), Value::from(attestation)],
+        )
+        .fee_level(self.fee_level);
+        let response = client
+            .create_contract_execution_transaction(self.mint_idempotency_key, request)
+            .await?;
+        Ok(response.id)
+    }
+
+    async fn progress(client: &CircleClient, transaction_id: TransactionId) -> Result<TxProgress> {
+        let transaction = client
+            .get_transaction(transaction_id, None::<TxType>)
+            .await?
+            .transaction;
+        Ok(match transaction.state {
+            TransactionState::Complete => TxProgress::Done(transaction.tx_hash),
+            TransactionState::Failed | TransactionState::Denied | TransactionState::Cancelled => {
+                TxProgress::Failed(
+                    transaction
+                        .error_reason
+                        .unwrap_or_else(|| format!("{:?}", transaction.state)),
+                )
+            }
+            _ => TxProgress::Pending,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[test]
+    fn test_to_usdc_units() {
+        assert_eq!(to_usdc_units(Decimal::new(125, 1)).unwrap(), 12_500_000);
+        assert_eq!(to_usdc_units(Decimal::new(1, 6)).unwrap(), 1);
+        assert!(to_usdc_units(Decimal::new(1, 7)).is_err());
+        assert!(to_usdc_units(Decimal::NEGATIVE_ONE).is_err());
+    }
+
+    #[test]
+    fn test_address_to_bytes32() {
+        let address = parse_address("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+        assert_eq!(
+            address_to_bytes32(&address),
+            "0x000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_burn_without_tx_hash_fails() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let burn_tx_id = TransactionId(Uuid::new_v4());
+        let mut transfer = CctpTransfer::new(
+            WalletId(Uuid::new_v4()),
+            Blockchain::EthGoerli,
+            WalletId(Uuid::new_v4()),
+            Blockchain::AvaxFuji,
+            Decimal::ONE,
+        )
+        .unwrap();
+        transfer.state = CctpTransferState::Burning { burn_tx_id };
+        transport.push_data(json!({
+            "transaction": {
+                "id": burn_tx_id,
+                "blockchain": "ETH-GOERLI",
+                "state": "COMPLETE",
+                "transactionType": "OUTBOUND",
+                "createDate": "2024-03-01T11:00:00Z",
+                "updateDate": "2024-03-01T11:00:00Z"
+            }
+        }));
+        let state = transfer.step(&client).await.unwrap();
+        assert!(
+            matches!(state, CctpTransferState::Failed { reason } if reason.contains("tx hash"))
+        );
+    }
+
+    #[test]
+    fn test_rejects_mixed_networks() {
+        let result = CctpTransfer::new(
+            WalletId(Uuid::new_v4()),
+            Blockchain::EthGoerli,
+            WalletId(Uuid::new_v4()),
+            Blockchain::Avax,
+            Decimal::ONE,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! Accepting payments into developer-controlled wallets: a [`PaymentIntent`] says which wallet
+//! should receive how much of which token by when, and inbound transfers are counted against
+//! it as they arrive, from webhooks or by polling.
+//!
+//! Each open intent needs a wallet of its own (e.g. from [`crate::deposits::DepositManager`]),
+//! since transfers are attributed to intents by the receiving wallet alone.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::cancel::CancellationToken;
+use crate::error::Result;
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::notification::NotificationEvent;
+use crate::models::transaction::{Transaction, TxType};
+use crate::models::transaction_list::TransactionQuery;
+use crate::models::transaction_state::TransactionState;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentStatus {
+    /// Nothing received yet.
+    Pending,
+    /// Something was received, but less than the amount due. Final once the intent expires.
+    PartiallyPaid,
+    /// At least the amount due, minus the underpayment tolerance, was received in time.
+    Paid,
+    /// Nothing was received in time.
+    Expired,
+}
+
+/// How far the received total may be from the amount due.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Tolerance {
+    /// Shortfall still accepted as paid, e.g. to absorb rounding by the payer's wallet.
+    pub under: Decimal,
+    /// Excess not reported by [`PaymentIntent::overpaid`].
+    pub over: Decimal,
+}
+
+/// A payment the merchant expects. Serializable, so it can be stored between polls or
+/// webhooks.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentIntent {
+    pub id: Uuid,
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub amount: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub tolerance: Tolerance,
+    status: PaymentStatus,
+    received: Decimal,
+    transactions: Vec<TransactionId>,
+}
+
+impl PaymentIntent {
+    pub fn new(
+        wallet_id: WalletId,
+        token_id: TokenId,
+        amount: Decimal,
+        ttl: chrono::Duration,
+    ) -> Self {
+        let created_at = Utc::now();
+        PaymentIntent {
+            id: Uuid::new_v4(),
+            wallet_id,
+            token_id,
+            amount,
+            created_at,
+            expires_at: created_at + ttl,
+            tolerance: Tolerance::default(),
+            status: PaymentStatus::Pending,
+            received: Decimal::ZERO,
+            transactions: Vec::new(),
+        }
+    }
+
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn status(&self) -> PaymentStatus {
+        self.status
+    }
+
+    pub fn is_final(&self) -> bool {
+        match self.status {
+            PaymentStatus::Paid | PaymentStatus::Expired => true,
+            PaymentStatus::PartiallyPaid => self.is_expired(Utc::now()),
+            PaymentStatus::Pending => false,
+        }
+    }
+
+    pub fn received(&self) -> Decimal {
+        self.received
+    }
+
+    /// What is still due, ignoring the tolerance.
+    pub fn remaining(&self) -> Decimal {
+        (self.amount - self.received).max(Decimal::ZERO)
+    }
+
+    /// How much more than the amount due plus the overpayment tolerance was received, e.g. to
+    /// refund.
+    pub fn overpaid(&self) -> Decimal {
+        (self.received - self.amount - self.tolerance.over).max(Decimal::ZERO)
+    }
+
+    /// The transactions counted towards this intent.
+    pub fn transactions(&self) -> &[TransactionId] {
+        &self.transactions
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Counts `transaction` if it is a completed inbound transfer of the right token into the
+    /// intent's wallet that arrived before expiry and wasn't counted yet. Transfers arriving
+    /// after the intent is paid still count, so [`PaymentIntent::overpaid`] sees them. Returns
+    /// whether it was counted.
+    pub fn apply_transaction(&mut self, transaction: &Transaction) -> bool {
+        let applies = transaction.transaction_type == TxType::Inbound
+            && transaction.state == TransactionState::Complete
+            && transaction.wallet_id == Some(self.wallet_id)
+            && transaction.token_id == Some(self.token_id)
+            && transaction.create_date < self.expires_at
+            && !self.transactions.contains(&transaction.id);
+        let amount = transaction
+            .amounts
+            .as_ref()
+            .map(|amounts| amounts.iter().sum::<Decimal>());
+        let Some(amount) = amount.filter(|_| applies) else {
+            return false;
+        };
+        self.received += amount;
+        self.transactions.push(transaction.id);
+        self.refresh(Utc::now());
+        true
+    }
+
+    /// [`PaymentIntent::apply_transaction`] for a `transactions.inbound` webhook.
+    pub fn apply_notification(&mut self, event: &NotificationEvent) -> bool {
+        match event {
+            NotificationEvent::TransactionInbound(transaction) => {
+                self.apply_transaction(transaction)
+            }
+            _ => false,
+        }
+    }
+
+    /// Re-evaluates the status at `now`, e.g. to expire intents nobody paid.
+    pub fn refresh(&mut self, now: DateTime<Utc>) -> PaymentStatus {
+        self.status = if self.received >= self.amount - self.tolerance.under {
+            PaymentStatus::Paid
+        } else if self.received > Decimal::ZERO {
+            PaymentStatus::PartiallyPaid
+        } else if self.is_expired(now) {
+            PaymentStatus::Expired
+        } else {
+            PaymentStatus::Pending
+        };
+        self.status
+    }
+}
+
+/// Polls Circle for transfers into intents' wallets, for deployments without webhooks.
+pub struct Checkout<'a> {
+    client: &'a CircleClient,
+    poll_interval: Duration,
+    cancellation: CancellationToken,
+}
+
+impl<'a> Checkout<'a> {
+    pub fn new(client: &'a CircleClient) -> Self {
+        Checkout {
+            client,
+            poll_interval: Duration::from_secs(10),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Stops [`Checkout::watch`] once `token` is cancelled.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Looks up completed inbound transfers into the intent's wallet once and applies them.
+    pub async fn poll(&self, intent: &mut PaymentIntent) -> Result<PaymentStatus> {
+        let query = TransactionQuery::new()
+            .wallet(intent.wallet_id)
+            .inbound()
+            .state(TransactionState::Complete)
+            .since(intent.created_at)
+            .until(intent.expires_at);
+        let transactions = self
+            .client
+            .list_transactions_stream(query.build())
+            .try_collect::<Vec<_>>()
+            .await?;
+        for transaction in &transactions {
+            intent.apply_transaction(transaction);
+        }
+        Ok(intent.refresh(Utc::now()))
+    }
+
+    /// Polls until the intent is paid, partially paid at expiry, or expired, or until
+    /// cancelled.
+    pub async fn watch(&self, intent: &mut PaymentIntent) -> Result<PaymentStatus> {
+        loop {
+            let status = self.poll(intent).await?;
+            if intent.is_final() || self.cancellation.sleep(self.poll_interval).await {
+                return Ok(status);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use super::*;
+
+    const WALLET_ID: &str = "ce714f5b-0d8e-4062-9454-61aa1154869b";
+    const TOKEN_ID: &str = "7adb2b7d-c9cd-5164-b2d4-b73b088274dc";
+
+    fn transfer(amount: &str) -> Transaction {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "blockchain": "MATIC-AMOY",
+            "walletId": WALLET_ID,
+            "tokenId": TOKEN_ID,
+            "amounts": [amount],
+            "state": "COMPLETE",
+            "transactionType": "INBOUND",
+            "createDate": Utc::now(),
+            "updateDate": Utc::now()
+        }))
+        .unwrap()
+    }
+
+    fn intent() -> PaymentIntent {
+        let wallet_id = WalletId::from_str(WALLET_ID).unwrap();
+        let token_id = TokenId::from_str(TOKEN_ID).unwrap();
+        PaymentIntent::new(
+            wallet_id,
+            token_id,
+            Decimal::from(100),
+            chrono::Duration::minutes(15),
+        )
+        .tolerance(Tolerance {
+            under: Decimal::new(1, 2),
+            over: Decimal::ONE,
+        })
+    }
+
+    #[test]
+    fn test_payment_intent() {
+        let mut intent = intent();
+        let first = transfer("60");
+        assert!(intent.apply_transaction(&first));
+        assert!(!intent.apply_transaction(&first));
+        assert_eq!(intent.status(), PaymentStatus::PartiallyPaid);
+        assert_eq!(intent.remaining(), Decimal::from(40));
+
+        assert!(intent.apply_transaction(&transfer("39.99")));
+        assert_eq!(intent.status(), PaymentStatus::Paid);
+        assert!(intent.is_final());
+        assert_eq!(intent.overpaid(), Decimal::ZERO);
+        assert!(intent.apply_transaction(&transfer("5")));
+        assert_eq!(intent.status(), PaymentStatus::Paid);
+        assert_eq!(intent.overpaid(), Decimal::new(399, 2));
+
+        let mut intent = self::intent();
+        intent.apply_transaction(&transfer("105"));
+        assert_eq!(intent.overpaid(), Decimal::from(4));
+
+        let mut intent = self::intent();
+        let later = intent.expires_at + chrono::Duration::seconds(1);
+        assert_eq!(intent.refresh(later), PaymentStatus::Expired);
+        let stored = serde_json::to_string(&intent).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PaymentIntent>(&stored).unwrap(),
+            intent
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! Client and subsystem settings from the environment or a TOML file, so applications don't
+//! each have to read and check them by hand.
+//!
+//! A `circle.toml` (with the `config-file` feature) looks like this; only the credentials are
+//! required:
+//!
+//! ```toml
+//! api_key = "TEST_API_KEY:..."
+//! entity_secret = "..."
+//! environment = "sandbox"        # "production" (default), "sandbox" or an API root URL
+//! webhook_secret = "..."
+//! request_timeout_secs = 30
+//!
+//! [retry]
+//! max_attempts = 3
+//! initial_backoff_ms = 500
+//!
+//! [rate_limit]
+//! requests_per_second = 10.0
+//! burst = 20
+//!
+//! [[sweep]]
+//! token_id = "7adb2b7d-c9cd-5164-b2d4-b73b088274dc"
+//! threshold = "100"
+//! reserve = "0"
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::api::{CircleClient, CircleClientBuilder, Environment, RetryPolicy};
+use crate::error::{CircleError, Result};
+use crate::models::ids::TokenId;
+#[cfg(all(feature = "transactions", feature = "wallets"))]
+use crate::sweep::SweepPolicy;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    #[serde(default = "default_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: Option<u64>,
+}
+
+fn default_backoff_ms() -> u64 {
+    500
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_second: f64,
+    pub burst: u32,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SweepConfig {
+    pub token_id: TokenId,
+    pub threshold: Decimal,
+    #[serde(default)]
+    pub reserve: Decimal,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CircleConfig {
+    pub api_key: String,
+    /// Hex-encoded, as shown in the Circle console.
+    pub entity_secret: String,
+    /// `production`, `sandbox`, or an API root URL such as `http://localhost:8080/v1/`.
+    #[serde(default = "default_environment")]
+    pub environment: String,
+    /// For the application to verify webhook deliveries with; the client doesn't use it.
+    pub webhook_secret: Option<String>,
+    pub request_timeout_secs: Option<u64>,
+    pub retry: Option<RetryConfig>,
+    pub rate_limit: Option<RateLimitConfig>,
+    #[serde(default)]
+    pub sweep: Vec<SweepConfig>,
+}
+
+fn default_environment() -> String {
+    "production".to_string()
+}
+
+impl fmt::Debug for CircleConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircleConfig")
+            .field("api_key", &"<redacted>")
+            .field("entity_secret", &"<redacted>")
+            .field("environment", &self.environment)
+            .field(
+                "webhook_secret",
+                &self.webhook_secret.as_ref().map(|_| "<redacted>"),
+            )
+            .field("request_timeout_secs", &self.request_timeout_secs)
+            .field("retry", &self.retry)
+            .field("rate_limit", &self.rate_limit)
+            .field("sweep", &self.sweep)
+            .finish()
+    }
+}
+
+impl CircleConfig {
+    /// Reads the settings from these variables:
+    ///
+    /// - `CIRCLE_API_KEY`, `CIRCLE_ENTITY_SECRET` (required)
+    /// - `CIRCLE_ENVIRONMENT`, `CIRCLE_WEBHOOK_SECRET`, `CIRCLE_REQUEST_TIMEOUT_SECS`
+    /// - `CIRCLE_RETRY_MAX_ATTEMPTS`, `CIRCLE_RETRY_INITIAL_BACKOFF_MS`,
+    ///   `CIRCLE_RETRY_MAX_BACKOFF_MS`
+    /// - `CIRCLE_RATE_LIMIT_RPS`, `CIRCLE_RATE_LIMIT_BURST`
+    ///
+    /// Sweep policies only come from files. Errors name every variable that is missing or
+    /// can't be parsed.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Reads and validates a TOML file such as `circle.toml`.
+    #[cfg(feature = "config-file")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        Self::from_toml(&text)
+            .map_err(|err| CircleError::ConfigError(format!("{}: {}", path.display(), err)))
+    }
+
+    #[cfg(feature = "config-file")]
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let config: CircleConfig =
+            toml::from_str(text).map_err(|err| CircleError::ConfigError(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_vars<I: IntoIterator<Item = (String, String)>>(vars: I) -> Result<Self> {
+        let vars = vars
+            .into_iter()
+            .filter(|(key, _)| key.starts_with("CIRCLE_"))
+            .collect::<HashMap<_, _>>();
+        let mut errors = Vec::new();
+        let mut required = |name: &str| match vars.get(name) {
+            Some(value) => value.clone(),
+            None => {
+                errors.push(format!("{} is not set", name));
+                String::new()
+            }
+        };
+        let api_key = required("CIRCLE_API_KEY");
+        let entity_secret = required("CIRCLE_ENTITY_SECRET");
+        let request_timeout_secs = parse_var(&vars, "CIRCLE_REQUEST_TIMEOUT_SECS", &mut errors);
+        let max_attempts = parse_var(&vars, "CIRCLE_RETRY_MAX_ATTEMPTS", &mut errors);
+        let initial_backoff_ms = parse_var(&vars, "CIRCLE_RETRY_INITIAL_BACKOFF_MS", &mut errors);
+        let max_backoff_ms = parse_var(&vars, "CIRCLE_RETRY_MAX_BACKOFF_MS", &mut errors);
+        let requests_per_second = parse_var(&vars, "CIRCLE_RATE_LIMIT_RPS", &mut errors);
+        let burst = parse_var(&vars, "CIRCLE_RATE_LIMIT_BURST", &mut errors);
+        if !errors.is_empty() {
+            return Err(CircleError::ConfigError(errors.join("; ")));
+        }
+
+        let config = CircleConfig {
+            api_key,
+            entity_secret,
+            environment: vars
+                .get("CIRCLE_ENVIRONMENT")
+                .cloned()
+                .unwrap_or_else(default_environment),
+            webhook_secret: vars.get("CIRCLE_WEBHOOK_SECRET").cloned(),
+            request_timeout_secs,
+            retry: max_attempts.map(|max_attempts| RetryConfig {
+                max_attempts,
+                initial_backoff_ms: initial_backoff_ms.unwrap_or_else(default_backoff_ms),
+                max_backoff_ms,
+            }),
+            rate_limit: requests_per_second.map(|requests_per_second| RateLimitConfig {
+                requests_per_second,
+                burst: burst.unwrap_or(1),
+            }),
+            sweep: Vec::new(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the values serde can't, collecting every problem into one error.
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+        if self.api_key.is_empty() {
+            errors.push("api_key is empty".to_string());
+        }
+        if hex::decode(&self.entity_secret).is_err() {
+            errors.push("entity_secret is not hex".to_string());
+        }
+        if let Err(err) = self.environment() {
+            errors.push(err);
+        }
+        if let Some(retry) = &self.retry {
+            if retry.max_attempts == 0 {
+                errors.push("retry.max_attempts must be at least 1".to_string());
+            }
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            if rate_limit.requests_per_second.is_nan() || rate_limit.requests_per_second <= 0.0 {
+                errors.push("rate_limit.requests_per_second must be positive".to_string());
+            }
+        }
+        for (i, sweep) in self.sweep.iter().enumerate() {
+            if sweep.threshold.is_sign_negative() || sweep.reserve.is_sign_negative() {
+                errors.push(format!("sweep[{}]: amounts can't be negative", i));
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(CircleError::ConfigError(errors.join("; "))),
+        }
+    }
+
+    fn environment(&self) -> std::result::Result<Environment, String> {
+        match self.environment.to_ascii_lowercase().as_str() {
+            "production" => Ok(Environment::Production),
+            "sandbox" => Ok(Environment::Sandbox),
+            _ => Environment::custom(&self.environment).map_err(|_| {
+                format!(
+                    "environment {:?} is not production, sandbox or a URL",
+                    self.environment
+                )
+            }),
+        }
+    }
+
+    /// A builder with the credentials, environment, timeout, retry policy and rate limit set.
+    pub fn client_builder(&self) -> Result<CircleClientBuilder> {
+        let environment = self.environment().map_err(CircleError::ConfigError)?;
+        let mut builder = CircleClient::builder()
+            .api_key(&self.api_key)
+            .entity_secret(&self.entity_secret)
+            .environment(environment);
+        if let Some(timeout) = self.request_timeout_secs {
+            builder = builder.request_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(retry) = &self.retry {
+            let mut policy = RetryPolicy::new(
+                retry.max_attempts,
+                Duration::from_millis(retry.initial_backoff_ms),
+            );
+            if let Some(max_backoff) = retry.max_backoff_ms {
+                policy = policy.max_backoff(Duration::from_millis(max_backoff));
+            }
+            builder = builder.retry_policy(policy);
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            builder = builder.rate_limit(rate_limit.requests_per_second, rate_limit.burst);
+        }
+        Ok(builder)
+    }
+
+    pub async fn build_client(&self) -> Result<CircleClient> {
+        self.client_builder()?.build().await
+    }
+
+    #[cfg(all(feature = "transactions", feature = "wallets"))]
+    pub fn sweep_policies(&self) -> Vec<SweepPolicy> {
+        self.sweep
+            .iter()
+            .map(|x| SweepPolicy::new(x.token_id, x.threshold).reserve(x.reserve))
+            .collect()
+    }
+}
+
+fn parse_var<T: FromStr>(
+    vars: &HashMap<String, String>,
+    name: &str,
+    errors: &mut Vec<String>,
+) -> Option<T>
+where
+    T::Err: fmt::Display,
+{
+    let value = vars.get(name)?;
+    match value.trim().parse() {
+        Ok(value) => Some(value),
+        Err(err) => {
+            errors.push(format!("{}={:?}: {}", name, value, err));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let config = CircleConfig::from_vars(vars(&[
+            ("CIRCLE_API_KEY", "TEST_API_KEY:id:secret"),
+            ("CIRCLE_ENTITY_SECRET", "abcd"),
+            ("CIRCLE_ENVIRONMENT", "Sandbox"),
+            ("CIRCLE_RETRY_MAX_ATTEMPTS", "3"),
+            ("CIRCLE_RATE_LIMIT_RPS", "10"),
+            ("HOME", "/root"),
+        ]))
+        .unwrap();
+        assert_eq!(config.environment(), Ok(Environment::Sandbox));
+        assert_eq!(config.retry.as_ref().unwrap().initial_backoff_ms, 500);
+        assert_eq!(config.rate_limit.as_ref().unwrap().burst, 1);
+        assert!(!format!("{:?}", config).contains("TEST_API_KEY"));
+
+        let err = CircleConfig::from_vars(vars(&[
+            ("CIRCLE_ENTITY_SECRET", "xyz"),
+            ("CIRCLE_RETRY_MAX_ATTEMPTS", "three"),
+        ]))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("CIRCLE_API_KEY is not set"), "{}", err);
+        assert!(
+            err.contains("CIRCLE_RETRY_MAX_ATTEMPTS=\"three\""),
+            "{}",
+            err
+        );
+    }
+
+    #[cfg(feature = "config-file")]
+    #[test]
+    fn test_from_toml() {
+        let config = CircleConfig::from_toml(
+            r#"
+            api_key = "key"
+            entity_secret = "abcd"
+            environment = "http://localhost:8080/v1/"
+
+            [[sweep]]
+            token_id = "7adb2b7d-c9cd-5164-b2d4-b73b088274dc"
+            threshold = "100"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(config.environment(), Ok(Environment::Custom(_))));
+        assert_eq!(config.sweep_policies()[0].threshold, Decimal::from(100));
+
+        let err = CircleConfig::from_toml("api_key = \"key\"\nentity_secret = \"ab\"\nretries = 3")
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("unknown field `retries`"),
+            "{}",
+            err
+        );
+    }
+}
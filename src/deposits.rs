@@ -0,0 +1,264 @@
+//! Per-user deposit addresses: a Circle wallet is assigned to each user and chain the first
+//! time it's asked for, and inbound transfers to it are matched back to the user.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use futures::future::BoxFuture;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::{CircleError, Result};
+use crate::models::blockchain::Blockchain;
+use crate::models::ids::{TokenId, TransactionId, WalletId, WalletSetId};
+use crate::models::notification::NotificationEvent;
+use crate::models::transaction::{Transaction, TxType};
+use crate::models::transaction_state::TransactionState;
+use crate::models::web3_address::Web3Address;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositAddress {
+    pub user_id: String,
+    pub blockchain: Blockchain,
+    pub wallet_id: WalletId,
+    pub address: Web3Address,
+}
+
+/// Where assignments are kept, usually the application's database. Assignments are never
+/// changed once inserted.
+pub trait DepositStore: Send + Sync {
+    fn get<'a>(
+        &'a self,
+        user_id: &'a str,
+        blockchain: &'a Blockchain,
+    ) -> BoxFuture<'a, Result<Option<DepositAddress>>>;
+
+    fn find_by_wallet(&self, wallet_id: WalletId) -> BoxFuture<'_, Result<Option<DepositAddress>>>;
+
+    /// Called after a wallet was created for a user. Inserting an assignment that is already
+    /// stored must succeed.
+    fn insert<'a>(&'a self, assignment: &'a DepositAddress) -> BoxFuture<'a, Result<()>>;
+}
+
+/// A [`DepositStore`] that forgets everything on restart, for tests and prototypes.
+#[derive(Debug, Default)]
+pub struct InMemoryDepositStore(Mutex<HashMap<(String, Blockchain), DepositAddress>>);
+
+impl DepositStore for InMemoryDepositStore {
+    fn get<'a>(
+        &'a self,
+        user_id: &'a str,
+        blockchain: &'a Blockchain,
+    ) -> BoxFuture<'a, Result<Option<DepositAddress>>> {
+        Box::pin(async move {
+            let key = (user_id.to_string(), blockchain.clone());
+            Ok(self.0.lock().unwrap().get(&key).cloned())
+        })
+    }
+
+    fn find_by_wallet(&self, wallet_id: WalletId) -> BoxFuture<'_, Result<Option<DepositAddress>>> {
+        Box::pin(async move {
+            let assignments = self.0.lock().unwrap();
+            Ok(assignments
+                .values()
+                .find(|x| x.wallet_id == wallet_id)
+                .cloned())
+        })
+    }
+
+    fn insert<'a>(&'a self, assignment: &'a DepositAddress) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let key = (assignment.user_id.clone(), assignment.blockchain.clone());
+            self.0.lock().unwrap().insert(key, assignment.clone());
+            Ok(())
+        })
+    }
+}
+
+/// An inbound transfer to a user's deposit address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositDetected {
+    pub user_id: String,
+    pub wallet_id: WalletId,
+    pub blockchain: Blockchain,
+    pub transaction_id: TransactionId,
+    pub token_id: Option<TokenId>,
+    pub amount: Option<Decimal>,
+    pub tx_hash: Option<String>,
+    pub state: TransactionState,
+}
+
+impl DepositDetected {
+    /// Whether the funds have arrived. Earlier states are worth showing as pending, but
+    /// shouldn't be credited.
+    pub fn is_complete(&self) -> bool {
+        self.state == TransactionState::Complete
+    }
+}
+
+pub struct DepositManager<S> {
+    client: CircleClient,
+    wallet_set_id: WalletSetId,
+    store: S,
+}
+
+impl<S: DepositStore> DepositManager<S> {
+    /// Creates deposit wallets in `wallet_set_id`.
+    pub fn new(client: CircleClient, wallet_set_id: WalletSetId, store: S) -> Self {
+        DepositManager {
+            client,
+            wallet_set_id,
+            store,
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// The idempotency key used to create the wallet for `user_id` on `blockchain`. Derived
+    /// from both, so concurrent or retried assignments for the same user get the same wallet.
+    pub fn idempotency_key(&self, user_id: &str, blockchain: &Blockchain) -> Uuid {
+        let chain = serde_json::to_string(blockchain).unwrap_or_default();
+        let name = format!("deposit:{}:{}", user_id, chain);
+        Uuid::new_v5(&self.wallet_set_id.as_uuid(), name.as_bytes())
+    }
+
+    /// The deposit address of `user_id` on `blockchain`, creating a wallet if the user has
+    /// none yet.
+    pub async fn assign(&self, user_id: &str, blockchain: Blockchain) -> Result<DepositAddress> {
+        if let Some(assignment) = self.store.get(user_id, &blockchain).await? {
+            return Ok(assignment);
+        }
+        let idempotency_key = self.idempotency_key(user_id, &blockchain);
+        let response = self
+            .client
+            .create_wallet(idempotency_key, self.wallet_set_id, vec![blockchain], 1)
+            .await?;
+        let wallet = response
+            .wallets
+            .into_iter()
+            .next()
+            .ok_or(CircleError::MissingField("wallets"))?;
+        let assignment = DepositAddress {
+            user_id: user_id.to_string(),
+            blockchain: wallet.blockchain,
+            wallet_id: wallet.id,
+            address: wallet.address,
+        };
+        self.store.insert(&assignment).await?;
+        Ok(assignment)
+    }
+
+    /// Matches an inbound transaction to the user owning the receiving wallet. Outbound
+    /// transactions and transfers to wallets that aren't deposit addresses give `None`.
+    pub async fn match_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Option<DepositDetected>> {
+        if transaction.transaction_type != TxType::Inbound {
+            return Ok(None);
+        }
+        let Some(wallet_id) = transaction.wallet_id else {
+            return Ok(None);
+        };
+        let Some(assignment) = self.store.find_by_wallet(wallet_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(DepositDetected {
+            user_id: assignment.user_id,
+            wallet_id,
+            blockchain: transaction.blockchain.clone(),
+            transaction_id: transaction.id,
+            token_id: transaction.token_id,
+            amount: transaction
+                .amounts
+                .as_ref()
+                .and_then(|x| x.first())
+                .copied(),
+            tx_hash: transaction.tx_hash.clone(),
+            state: transaction.state.clone(),
+        }))
+    }
+
+    /// [`DepositManager::match_transaction`] for a `transactions.inbound` webhook.
+    pub async fn match_notification(
+        &self,
+        event: &NotificationEvent,
+    ) -> Result<Option<DepositDetected>> {
+        match event {
+            NotificationEvent::TransactionInbound(transaction) => {
+                self.match_transaction(transaction).await
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_deposit_manager() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let wallet_set_id = WalletSetId(Uuid::new_v4());
+        let wallet_id = "ce714f5b-0d8e-4062-9454-61aa1154869b";
+        transport.push_data(json!({
+            "wallets": [{
+                "id": wallet_id,
+                "state": "LIVE",
+                "walletSetId": wallet_set_id,
+                "custodyType": "DEVELOPER",
+                "address": "0xf5c83e5fede8456929d0f90e8c541dcac3d63835",
+                "blockchain": "MATIC-AMOY",
+                "updateDate": "2023-08-03T19:33:14Z",
+                "createDate": "2023-08-03T19:33:14Z"
+            }]
+        }));
+
+        let manager = DepositManager::new(client, wallet_set_id, InMemoryDepositStore::default());
+        let assignment = manager
+            .assign("alice", Blockchain::MaticAmoy)
+            .await
+            .unwrap();
+        assert_eq!(assignment.wallet_id.to_string(), wallet_id);
+        let again = manager
+            .assign("alice", Blockchain::MaticAmoy)
+            .await
+            .unwrap();
+        assert_eq!(again, assignment);
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        let key = manager.idempotency_key("alice", &Blockchain::MaticAmoy);
+        assert_eq!(
+            requests[0].body.as_ref().unwrap()["idempotencyKey"],
+            json!(key)
+        );
+
+        let notification = json!({
+            "notificationType": "transactions.inbound",
+            "notification": {
+                "id": "c4d1da72-111e-4d52-bdbf-2e74a2d803d5",
+                "blockchain": "MATIC-AMOY",
+                "walletId": wallet_id,
+                "tokenId": "7adb2b7d-c9cd-5164-b2d4-b73b088274dc",
+                "amounts": ["12.5"],
+                "state": "COMPLETE",
+                "transactionType": "INBOUND",
+                "createDate": "2023-08-03T19:33:14Z",
+                "updateDate": "2023-08-03T19:33:14Z"
+            }
+        });
+        let event = NotificationEvent::parse(&notification.to_string()).unwrap();
+        let deposit = manager.match_notification(&event).await.unwrap().unwrap();
+        assert_eq!(deposit.user_id, "alice");
+        assert_eq!(deposit.amount, Some(Decimal::new(125, 1)));
+        assert!(deposit.is_complete());
+    }
+}
@@ -1,79 +1,226 @@
-use std::error::Error;
-use std::fmt::{Display, Formatter};
+use reqwest::StatusCode;
+use thiserror::Error;
 
-use crate::api::ApiError;
+use crate::api::{ApiError, CircleErrorCode, DecodeError, TransportError};
 use crate::models::RequestId;
+#[cfg(feature = "transactions")]
+use crate::policies::LimitExceeded;
 
+/// Returned by every fallible call in the crate. `CircleError` is `Send + Sync + 'static`, so
+/// `?` converts it into `anyhow::Error` (or `Box<dyn Error>`) in application code.
 pub type Result<T> = std::result::Result<T, CircleError>;
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum CircleError {
+    #[error("Circle API error (request id {0}): {1}")]
     ApiError(RequestId, ApiError),
+    #[error("invalid value")]
     ValueError,
+    #[error("response is missing the X-Request-Id header")]
     MissingRequestId,
+    #[error("missing required field `{0}`")]
     MissingField(&'static str),
-    RequestIdIsNotAValidString(reqwest::header::ToStrError),
-    RequestIdIsNotAValidUuid(uuid::Error),
-    UnknownRequestError(reqwest::Error),
-    FromHexError(hex::FromHexError),
-    RsaError(rsa::errors::Error),
-    SerdeQsError(serde_qs::Error),
-    SerdeJsonError(serde_json::Error),
-    Web3SigningRecoveryError(web3::signing::RecoveryError),
+    #[error("invalid request: {}", .0.join("; "))]
+    Validation(Vec<String>),
+    #[error("can't combine amounts of token {0} and token {1}")]
+    TokenMismatch(uuid::Uuid, uuid::Uuid),
+    #[error("token amount out of range: {0}")]
+    AmountOutOfRange(String),
+    #[error("X-Request-Id is not a valid string")]
+    RequestIdIsNotAValidString(#[from] reqwest::header::ToStrError),
+    #[error("X-Request-Id is not a valid UUID")]
+    RequestIdIsNotAValidUuid(#[from] uuid::Error),
+    #[error("HTTP request failed")]
+    UnknownRequestError(#[from] reqwest::Error),
+    #[error(transparent)]
+    TransportError(#[from] TransportError),
+    #[error("unexpected HTTP status {1}")]
+    UnexpectedStatus(Option<RequestId>, reqwest::StatusCode),
+    #[error("invalid hex")]
+    FromHexError(#[from] hex::FromHexError),
+    #[error("Circle returned an entity public key that could not be parsed")]
+    InvalidPublicKey,
+    #[error("RSA error")]
+    RsaError(#[from] rsa::errors::Error),
+    #[error("failed to encode query string")]
+    SerdeQsError(#[from] serde_qs::Error),
+    #[error("JSON error")]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    DecodeError(#[from] DecodeError),
+    #[error("failed to recover the signer")]
+    Web3SigningRecoveryError(#[from] web3::signing::RecoveryError),
+    #[error("CSV error")]
+    #[cfg(feature = "transactions")]
+    CsvError(#[from] csv::Error),
+    #[error("I/O error")]
+    IoError(#[from] std::io::Error),
+    #[cfg(feature = "prometheus")]
+    #[error("Prometheus error")]
+    PrometheusError(#[from] prometheus::Error),
+    #[cfg(feature = "sqlx")]
+    #[error("database error")]
+    SqlError(#[from] sqlx::Error),
+    #[cfg(feature = "sqlx")]
+    #[error("database migration failed")]
+    MigrateError(#[from] sqlx::migrate::MigrateError),
+    #[error("deadline of {0:?} exceeded")]
+    DeadlineExceeded(std::time::Duration),
+    #[error("circuit breaker is open, retry in {retry_in:?}")]
+    CircuitOpen { retry_in: std::time::Duration },
+    #[error("rate limited by Circle (retry after {retry_after:?})")]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+    },
+    #[error("failed to publish event: {0}")]
+    PublishError(String),
+    #[error("invalid state: {0}")]
+    InvalidState(String),
+    #[error("invalid configuration: {0}")]
+    ConfigError(String),
+    #[cfg(feature = "transactions")]
+    #[error(transparent)]
+    LimitExceeded(#[from] LimitExceeded),
 }
 
-impl Display for CircleError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "CircleError({:?})", self)
+impl CircleError {
+    /// The HTTP status of the failed response, if the error came from one.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            CircleError::ApiError(_, err) => err.status(),
+            CircleError::UnexpectedStatus(_, status) => Some(*status),
+            CircleError::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            _ => None,
+        }
     }
-}
 
-impl Error for CircleError {}
+    /// Circle's `X-Request-Id` for the failed call. Include it when contacting Circle support.
+    pub fn request_id(&self) -> Option<RequestId> {
+        match self {
+            CircleError::ApiError(request_id, _) => Some(*request_id),
+            CircleError::UnexpectedStatus(request_id, _) => *request_id,
+            CircleError::DecodeError(err) => err.request_id(),
+            _ => None,
+        }
+    }
 
-impl From<reqwest::header::ToStrError> for CircleError {
-    fn from(err: reqwest::header::ToStrError) -> Self {
-        CircleError::RequestIdIsNotAValidString(err)
+    fn error_code(&self) -> Option<CircleErrorCode> {
+        match self {
+            CircleError::ApiError(_, err) => Some(err.error_code()),
+            _ => None,
+        }
     }
-}
 
-impl From<uuid::Error> for CircleError {
-    fn from(err: uuid::Error) -> Self {
-        CircleError::RequestIdIsNotAValidUuid(err)
+    /// Whether sending the same request again later may succeed: server errors, rate limits,
+    /// an open circuit and connection-level failures.
+    pub fn is_retryable(&self) -> bool {
+        if crate::api::is_transient(self) || self.is_rate_limited() {
+            return true;
+        }
+        if matches!(self, CircleError::CircuitOpen { .. }) {
+            return true;
+        }
+        self.error_code() == Some(CircleErrorCode::Retry)
+            || self.status().is_some_and(|x| x.is_server_error())
     }
-}
 
-impl From<reqwest::Error> for CircleError {
-    fn from(err: reqwest::Error) -> Self {
-        CircleError::UnknownRequestError(err)
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
     }
-}
 
-impl From<hex::FromHexError> for CircleError {
-    fn from(err: hex::FromHexError) -> Self {
-        CircleError::FromHexError(err)
+    /// Bad or missing API key, or a key without access to the resource.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self.error_code(),
+            Some(CircleErrorCode::Unauthorized | CircleErrorCode::Forbidden)
+        ) || matches!(
+            self.status(),
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+        )
     }
-}
 
-impl From<rsa::errors::Error> for CircleError {
-    fn from(err: rsa::errors::Error) -> Self {
-        CircleError::RsaError(err)
+    /// The request itself was wrong and retrying it unchanged will never work, e.g. a missing
+    /// or malformed parameter.
+    pub fn is_client_bug(&self) -> bool {
+        match self {
+            CircleError::ValueError
+            | CircleError::MissingField(_)
+            | CircleError::Validation(_)
+            | CircleError::TokenMismatch(..)
+            | CircleError::AmountOutOfRange(_)
+            | CircleError::SerdeQsError(_) => true,
+            _ => {
+                matches!(
+                    self.error_code(),
+                    Some(
+                        CircleErrorCode::ParameterMissing
+                            | CircleErrorCode::ParameterInvalid
+                            | CircleErrorCode::InvalidNumberFormat
+                    )
+                ) || matches!(
+                    self.status(),
+                    Some(StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY)
+                )
+            }
+        }
     }
 }
 
-impl From<serde_qs::Error> for CircleError {
-    fn from(err: serde_qs::Error) -> Self {
-        CircleError::SerdeQsError(err)
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn api_error(code: i64) -> CircleError {
+        let body = format!(r#"{{"code":{},"message":"","errors":[]}}"#, code);
+        CircleError::ApiError(Uuid::nil(), serde_json::from_str(&body).unwrap())
     }
-}
 
-impl From<serde_json::Error> for CircleError {
-    fn from(err: serde_json::Error) -> Self {
-        CircleError::SerdeJsonError(err)
+    #[test]
+    fn test_classification() {
+        assert!(api_error(2).is_client_bug());
+        assert!(!api_error(2).is_retryable());
+        assert!(api_error(4).is_auth_error());
+        assert!(api_error(9).is_retryable());
+        assert_eq!(api_error(9).request_id(), Some(Uuid::nil()));
+
+        let err = CircleError::RateLimited { retry_after: None };
+        assert!(err.is_rate_limited() && err.is_retryable());
+        assert!(CircleError::UnexpectedStatus(None, StatusCode::BAD_GATEWAY).is_retryable());
+        assert!(CircleError::UnexpectedStatus(None, StatusCode::FORBIDDEN).is_auth_error());
+        assert!(CircleError::MissingField("api_key").is_client_bug());
+        assert!(!CircleError::MissingRequestId.is_retryable());
+    }
+
+    #[test]
+    fn test_display_and_source() {
+        assert_eq!(
+            api_error(2).to_string(),
+            format!("Circle API error (request id {}):  (code 2)", Uuid::nil())
+        );
+        let err = CircleError::from(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no such file",
+        ));
+        assert_eq!(err.to_string(), "I/O error");
+        assert_eq!(
+            std::error::Error::source(&err).unwrap().to_string(),
+            "no such file"
+        );
     }
-}
 
-impl From<web3::signing::RecoveryError> for CircleError {
-    fn from(err: web3::signing::RecoveryError) -> Self {
-        CircleError::Web3SigningRecoveryError(err)
+    #[test]
+    fn test_anyhow_conversion() {
+        fn run() -> anyhow::Result<()> {
+            Err(CircleError::MissingField("api_key"))?;
+            Ok(())
+        }
+        let err = run().unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CircleError>(),
+            Some(CircleError::MissingField("api_key"))
+        ));
     }
 }
@@ -0,0 +1,236 @@
+//! Fanning webhook notifications out to other services, so one webhook endpoint can be the
+//! single ingress point for Circle events.
+//!
+//! Hand the body of every verified webhook request to [`EventBridge::dispatch`], which
+//! publishes it to each configured [`EventSink`]: an in-process [`BroadcastSink`], or with the
+//! `nats`/`kafka` features a NATS subject or Kafka topic.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use futures::future::{join_all, BoxFuture};
+use serde::Deserialize;
+
+#[cfg(any(feature = "nats", feature = "kafka"))]
+use crate::error::CircleError;
+use crate::error::Result;
+use crate::models::notification::NotificationEvent;
+
+/// One webhook notification, kept as the raw JSON Circle sent so consumers can parse it with
+/// whatever they like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookEvent {
+    /// E.g. `transactions.inbound`.
+    pub notification_type: String,
+    pub notification_id: Option<String>,
+    pub body: String,
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope {
+    notification_type: String,
+    notification_id: Option<String>,
+}
+
+impl WebhookEvent {
+    /// Reads the notification type and id from `body`; the rest isn't parsed.
+    pub fn parse<S: Into<String>>(body: S) -> Result<Self> {
+        let body = body.into();
+        let envelope = serde_json::from_str::<Envelope>(&body)?;
+        Ok(WebhookEvent {
+            notification_type: envelope.notification_type,
+            notification_id: envelope.notification_id,
+            body,
+            received_at: Utc::now(),
+        })
+    }
+
+    pub fn event(&self) -> Result<NotificationEvent> {
+        NotificationEvent::parse(&self.body)
+    }
+}
+
+/// Somewhere to publish webhook events. Return an error only if the event may have been lost,
+/// so the webhook can answer with an error and Circle delivers it again.
+pub trait EventSink: Send + Sync {
+    fn publish<'a>(&'a self, event: &'a WebhookEvent) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Publishes every event to a set of sinks.
+#[derive(Clone, Default)]
+pub struct EventBridge {
+    sinks: Vec<Arc<dyn EventSink>>,
+}
+
+impl EventBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sink<S: EventSink + 'static>(mut self, sink: S) -> Self {
+        self.sinks.push(Arc::new(sink));
+        self
+    }
+
+    /// Publishes `body` to every sink concurrently. Fails with the first error once all sinks
+    /// have been tried, which makes Circle retry the delivery, so consumers should expect
+    /// duplicates.
+    pub async fn dispatch<S: Into<String>>(&self, body: S) -> Result<WebhookEvent> {
+        let event = WebhookEvent::parse(body)?;
+        let results = join_all(self.sinks.iter().map(|x| x.publish(&event))).await;
+        results.into_iter().collect::<Result<Vec<()>>>()?;
+        Ok(event)
+    }
+}
+
+/// Fans events out to in-process subscribers over a [`tokio::sync::broadcast`] channel.
+/// Subscribers that fall behind by more than the capacity miss events.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct BroadcastSink(tokio::sync::broadcast::Sender<WebhookEvent>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl BroadcastSink {
+    pub fn new(capacity: usize) -> Self {
+        BroadcastSink(tokio::sync::broadcast::channel(capacity.max(1)).0)
+    }
+
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<WebhookEvent> {
+        self.0.subscribe()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl EventSink for BroadcastSink {
+    fn publish<'a>(&'a self, event: &'a WebhookEvent) -> BoxFuture<'a, Result<()>> {
+        // No subscribers isn't an error: nobody is interested yet.
+        let _ = self.0.send(event.clone());
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Publishes each event to the subject `<prefix>.<notification type>`, e.g.
+/// `circle.transactions.inbound`.
+#[cfg(feature = "nats")]
+pub struct NatsSink {
+    client: async_nats::Client,
+    prefix: String,
+}
+
+#[cfg(feature = "nats")]
+impl NatsSink {
+    pub fn new<S: Into<String>>(client: async_nats::Client, prefix: S) -> Self {
+        NatsSink {
+            client,
+            prefix: prefix.into(),
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+impl EventSink for NatsSink {
+    fn publish<'a>(&'a self, event: &'a WebhookEvent) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let subject = format!("{}.{}", self.prefix, event.notification_type);
+            self.client
+                .publish(subject, event.body.clone().into())
+                .await
+                .map_err(|err| CircleError::PublishError(err.to_string()))?;
+            // Publishing only queues the message; wait until the server has it.
+            self.client
+                .flush()
+                .await
+                .map_err(|err| CircleError::PublishError(err.to_string()))
+        })
+    }
+}
+
+/// Produces each event to one partition of a Kafka topic, keyed by notification type and with
+/// a `notification-type` header.
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    partition: rskafka::client::partition::PartitionClient,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    pub async fn new<S: Into<String> + Send>(
+        client: &rskafka::client::Client,
+        topic: S,
+        partition: i32,
+    ) -> Result<Self> {
+        let partition = client
+            .partition_client(
+                topic,
+                partition,
+                rskafka::client::partition::UnknownTopicHandling::Retry,
+            )
+            .await
+            .map_err(|err| CircleError::PublishError(err.to_string()))?;
+        Ok(KafkaSink { partition })
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl EventSink for KafkaSink {
+    fn publish<'a>(&'a self, event: &'a WebhookEvent) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let record = rskafka::record::Record {
+                key: Some(event.notification_type.clone().into_bytes()),
+                value: Some(event.body.clone().into_bytes()),
+                headers: [(
+                    "notification-type".to_string(),
+                    event.notification_type.clone().into_bytes(),
+                )]
+                .into(),
+                timestamp: event.received_at,
+            };
+            self.partition
+                .produce(
+                    vec![record],
+                    rskafka::client::partition::Compression::NoCompression,
+                )
+                .await
+                .map_err(|err| CircleError::PublishError(err.to_string()))?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+    use crate::error::CircleError;
+
+    struct Failing;
+
+    impl EventSink for Failing {
+        fn publish<'a>(&'a self, _: &'a WebhookEvent) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async { Err(CircleError::PublishError("broker down".to_string())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let broadcast = BroadcastSink::new(16);
+        let mut subscriber = broadcast.subscribe();
+        let bridge = EventBridge::new().sink(broadcast);
+        let body = r#"{"notificationType":"challenges.initialize","notificationId":"n-1","notification":{}}"#;
+        let event = bridge.dispatch(body).await.unwrap();
+        assert_eq!(event.notification_id.as_deref(), Some("n-1"));
+        let received = subscriber.recv().await.unwrap();
+        assert_eq!(received, event);
+        assert_eq!(
+            received.event().unwrap().notification_type(),
+            "challenges.initialize"
+        );
+
+        // The broadcast still gets it, but the webhook should be retried.
+        let bridge = bridge.sink(Failing);
+        assert!(bridge.dispatch(body).await.is_err());
+        assert_eq!(subscriber.recv().await.unwrap().body, body);
+        assert!(bridge.dispatch("not json").await.is_err());
+    }
+}
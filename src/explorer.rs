@@ -0,0 +1,153 @@
+//! Block explorer links for transactions, addresses and tokens, e.g. to deep-link from
+//! notification emails and dashboards.
+
+use std::collections::HashMap;
+
+use crate::models::blockchain::Blockchain;
+
+/// URL templates of one block explorer, with `{}` where the hash, address or token goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explorer {
+    pub transaction: String,
+    pub address: String,
+    pub token: String,
+}
+
+impl Explorer {
+    /// An Etherscan-style explorer at `base_url`, e.g. `https://etherscan.io`.
+    pub fn etherscan_style(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Explorer {
+            transaction: format!("{}/tx/{{}}", base_url),
+            address: format!("{}/address/{{}}", base_url),
+            token: format!("{}/token/{{}}", base_url),
+        }
+    }
+
+    pub fn transaction_url(&self, tx_hash: &str) -> String {
+        self.transaction.replace("{}", tx_hash)
+    }
+
+    pub fn address_url(&self, address: &str) -> String {
+        self.address.replace("{}", address)
+    }
+
+    /// `token` is the token's contract address, or mint on Solana.
+    pub fn token_url(&self, token: &str) -> String {
+        self.token.replace("{}", token)
+    }
+}
+
+/// The explorer used for `blockchain` unless [`Explorers`] says otherwise.
+pub fn default_explorer(blockchain: &Blockchain) -> Option<Explorer> {
+    let etherscan_style = |base_url| Some(Explorer::etherscan_style(base_url));
+    match blockchain {
+        Blockchain::Eth => etherscan_style("https://etherscan.io"),
+        Blockchain::EthSepolia => etherscan_style("https://sepolia.etherscan.io"),
+        Blockchain::EthGoerli => etherscan_style("https://goerli.etherscan.io"),
+        Blockchain::Avax => etherscan_style("https://snowtrace.io"),
+        Blockchain::AvaxFuji => etherscan_style("https://testnet.snowtrace.io"),
+        Blockchain::Matic => etherscan_style("https://polygonscan.com"),
+        Blockchain::MaticAmoy => etherscan_style("https://amoy.polygonscan.com"),
+        Blockchain::MaticMumbai => etherscan_style("https://mumbai.polygonscan.com"),
+        Blockchain::Arb => etherscan_style("https://arbiscan.io"),
+        Blockchain::ArbSepolia => etherscan_style("https://sepolia.arbiscan.io"),
+        Blockchain::Base => etherscan_style("https://basescan.org"),
+        Blockchain::BaseSepolia => etherscan_style("https://sepolia.basescan.org"),
+        Blockchain::Sol => Some(solscan("")),
+        Blockchain::SolDevnet => Some(solscan("?cluster=devnet")),
+        Blockchain::Near => Some(nearblocks("https://nearblocks.io")),
+        Blockchain::NearTestnet => Some(nearblocks("https://testnet.nearblocks.io")),
+        Blockchain::Unknown(_) => None,
+    }
+}
+
+fn solscan(cluster: &str) -> Explorer {
+    Explorer {
+        transaction: format!("https://solscan.io/tx/{{}}{}", cluster),
+        address: format!("https://solscan.io/account/{{}}{}", cluster),
+        token: format!("https://solscan.io/token/{{}}{}", cluster),
+    }
+}
+
+fn nearblocks(base_url: &str) -> Explorer {
+    Explorer {
+        transaction: format!("{}/txns/{{}}", base_url),
+        ..Explorer::etherscan_style(base_url)
+    }
+}
+
+/// The explorer of every chain: the defaults, with any chain's replaced by
+/// [`Explorers::explorer`], e.g. for a self-hosted Blockscout.
+#[derive(Debug, Clone, Default)]
+pub struct Explorers {
+    custom: HashMap<Blockchain, Explorer>,
+}
+
+impl Explorers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn explorer(mut self, blockchain: Blockchain, explorer: Explorer) -> Self {
+        self.custom.insert(blockchain, explorer);
+        self
+    }
+
+    pub fn get(&self, blockchain: &Blockchain) -> Option<Explorer> {
+        match self.custom.get(blockchain) {
+            Some(explorer) => Some(explorer.clone()),
+            None => default_explorer(blockchain),
+        }
+    }
+
+    pub fn transaction_url(&self, blockchain: &Blockchain, tx_hash: &str) -> Option<String> {
+        Some(self.get(blockchain)?.transaction_url(tx_hash))
+    }
+
+    pub fn address_url(&self, blockchain: &Blockchain, address: &str) -> Option<String> {
+        Some(self.get(blockchain)?.address_url(address))
+    }
+
+    pub fn token_url(&self, blockchain: &Blockchain, token: &str) -> Option<String> {
+        Some(self.get(blockchain)?.token_url(token))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_explorer_urls() {
+        let explorers = Explorers::new().explorer(
+            Blockchain::Base,
+            Explorer::etherscan_style("https://base.blockscout.com/"),
+        );
+        assert_eq!(
+            explorers
+                .transaction_url(&Blockchain::Base, "0xabc")
+                .unwrap(),
+            "https://base.blockscout.com/tx/0xabc"
+        );
+        assert_eq!(
+            explorers
+                .address_url(&Blockchain::MaticAmoy, "0x01")
+                .unwrap(),
+            "https://amoy.polygonscan.com/address/0x01"
+        );
+        assert_eq!(
+            explorers.token_url(&Blockchain::SolDevnet, "mint").unwrap(),
+            "https://solscan.io/token/mint?cluster=devnet"
+        );
+        assert_eq!(
+            explorers
+                .transaction_url(&Blockchain::Near, "hash")
+                .unwrap(),
+            "https://nearblocks.io/txns/hash"
+        );
+        assert!(explorers
+            .transaction_url(&Blockchain::Unknown("APTOS".to_string()), "0x1")
+            .is_none());
+    }
+}
@@ -0,0 +1,324 @@
+//! Transaction history exports for accounting hand-offs: walks the transaction list and writes
+//! one normalized row per transaction as CSV or JSON Lines to any [`AsyncWrite`].
+
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use futures::io::{AsyncWrite, AsyncWriteExt};
+use futures::stream::{Stream, StreamExt};
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::transaction::Transaction;
+use crate::models::transaction_list::TransactionListQueryParams;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Id,
+    CreateDate,
+    UpdateDate,
+    State,
+    Blockchain,
+    Operation,
+    TransactionType,
+    Amounts,
+    AmountInUsd,
+    TokenId,
+    WalletId,
+    SourceAddress,
+    DestinationAddress,
+    TxHash,
+    NetworkFee,
+    RefId,
+    ErrorReason,
+}
+
+impl Column {
+    pub const DEFAULT: &'static [Column] = &[
+        Column::Id,
+        Column::CreateDate,
+        Column::State,
+        Column::Blockchain,
+        Column::TransactionType,
+        Column::Amounts,
+        Column::TokenId,
+        Column::WalletId,
+        Column::SourceAddress,
+        Column::DestinationAddress,
+        Column::TxHash,
+        Column::NetworkFee,
+        Column::RefId,
+    ];
+
+    /// The header (CSV) or key (JSON Lines) of the column, matching Circle's field name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::CreateDate => "createDate",
+            Column::UpdateDate => "updateDate",
+            Column::State => "state",
+            Column::Blockchain => "blockchain",
+            Column::Operation => "operation",
+            Column::TransactionType => "transactionType",
+            Column::Amounts => "amounts",
+            Column::AmountInUsd => "amountInUSD",
+            Column::TokenId => "tokenId",
+            Column::WalletId => "walletId",
+            Column::SourceAddress => "sourceAddress",
+            Column::DestinationAddress => "destinationAddress",
+            Column::TxHash => "txHash",
+            Column::NetworkFee => "networkFee",
+            Column::RefId => "refId",
+            Column::ErrorReason => "errorReason",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmountFormat {
+    /// Amounts exactly as Circle returned them.
+    #[default]
+    AsIs,
+    /// Rounded half-even to a fixed number of decimal places, e.g. `2` for `12.50`.
+    Fixed(u32),
+}
+
+/// Multiple amounts (batched NFT transfers) are joined with `;` in a single cell.
+pub struct TransactionExport {
+    format: ExportFormat,
+    columns: Vec<Column>,
+    amount_format: AmountFormat,
+    timezone: FixedOffset,
+}
+
+impl TransactionExport {
+    pub fn new(format: ExportFormat) -> Self {
+        TransactionExport {
+            format,
+            columns: Column::DEFAULT.to_vec(),
+            amount_format: AmountFormat::default(),
+            timezone: FixedOffset::east_opt(0).unwrap(),
+        }
+    }
+
+    pub fn columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn amount_format(mut self, amount_format: AmountFormat) -> Self {
+        self.amount_format = amount_format;
+        self
+    }
+
+    /// Timestamps are written as RFC 3339 in this offset. UTC by default.
+    pub fn timezone(mut self, timezone: FixedOffset) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Exports every transaction matching `query_params`. Returns the number of rows written.
+    pub async fn export<W>(
+        &self,
+        client: &CircleClient,
+        query_params: TransactionListQueryParams,
+        writer: &mut W,
+    ) -> Result<usize>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.write(client.list_transactions_stream(query_params), writer)
+            .await
+    }
+
+    /// Writes `transactions` in order, stopping at the first error. Returns the number of rows
+    /// written.
+    pub async fn write<S, W>(&self, transactions: S, writer: &mut W) -> Result<usize>
+    where
+        S: Stream<Item = Result<Transaction>>,
+        W: AsyncWrite + Unpin,
+    {
+        if self.format == ExportFormat::Csv {
+            let header = self.columns.iter().map(Column::name);
+            writer.write_all(&csv_line(header)?).await?;
+        }
+        let mut transactions = std::pin::pin!(transactions);
+        let mut rows = 0;
+        while let Some(transaction) = transactions.next().await {
+            let transaction = transaction?;
+            let line = match self.format {
+                ExportFormat::Csv => {
+                    let cells = self.columns.iter().map(|x| self.cell(&transaction, *x));
+                    csv_line(cells.map(Option::unwrap_or_default))?
+                }
+                ExportFormat::JsonLines => {
+                    let fields = self.columns.iter().map(|x| {
+                        let value = match x {
+                            Column::Amounts => transaction.amounts.as_ref().map(|amounts| {
+                                amounts
+                                    .iter()
+                                    .map(|x| Value::String(self.amount(*x)))
+                                    .collect()
+                            }),
+                            _ => self.cell(&transaction, *x).map(Value::String),
+                        };
+                        (x.name(), value.unwrap_or_default())
+                    });
+                    json_line(fields)?
+                }
+            };
+            writer.write_all(&line).await?;
+            rows += 1;
+        }
+        writer.flush().await?;
+        Ok(rows)
+    }
+
+    fn cell(&self, transaction: &Transaction, column: Column) -> Option<String> {
+        match column {
+            Column::Id => Some(transaction.id.to_string()),
+            Column::CreateDate => Some(self.timestamp(transaction.create_date)),
+            Column::UpdateDate => Some(self.timestamp(transaction.update_date)),
+            Column::State => text(&transaction.state),
+            Column::Blockchain => text(&transaction.blockchain),
+            Column::Operation => transaction.operation.as_ref().and_then(text),
+            Column::TransactionType => text(&transaction.transaction_type),
+            Column::Amounts => transaction.amounts.as_ref().map(|amounts| {
+                let amounts = amounts.iter().map(|x| self.amount(*x));
+                amounts.collect::<Vec<_>>().join(";")
+            }),
+            Column::AmountInUsd => transaction.amount_in_usd.map(|x| self.amount(x)),
+            Column::TokenId => transaction.token_id.map(|x| x.to_string()),
+            Column::WalletId => transaction.wallet_id.map(|x| x.to_string()),
+            Column::SourceAddress => transaction.source_address.as_ref().and_then(text),
+            Column::DestinationAddress => transaction.destination_address.as_ref().and_then(text),
+            Column::TxHash => transaction.tx_hash.clone(),
+            Column::NetworkFee => transaction.network_fee.map(|x| self.amount(x)),
+            Column::RefId => transaction.ref_id.clone(),
+            Column::ErrorReason => transaction.error_reason.clone(),
+        }
+    }
+
+    fn amount(&self, amount: Decimal) -> String {
+        match self.amount_format {
+            AmountFormat::Fixed(places) => {
+                format!("{:.*}", places as usize, amount.round_dp(places))
+            }
+            AmountFormat::AsIs => amount.to_string(),
+        }
+    }
+
+    fn timestamp(&self, value: DateTime<Utc>) -> String {
+        value
+            .with_timezone(&self.timezone)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+}
+
+/// The string form of an enum or address, as it appears in Circle's JSON.
+pub(crate) fn text<T: serde::Serialize>(value: &T) -> Option<String> {
+    match serde_json::to_value(value) {
+        Ok(Value::String(x)) => Some(x),
+        _ => None,
+    }
+}
+
+fn csv_line<I, S>(cells: I) -> Result<Vec<u8>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(cells)?;
+    writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(err.into_error()).into())
+}
+
+/// An object with `fields` in the given order, which a `serde_json::Map` wouldn't keep.
+fn json_line<'a, I>(fields: I) -> Result<Vec<u8>>
+where
+    I: IntoIterator<Item = (&'a str, Value)>,
+{
+    let mut line = vec![b'{'];
+    for (i, (name, value)) in fields.into_iter().enumerate() {
+        if i > 0 {
+            line.push(b',');
+        }
+        serde_json::to_writer(&mut line, name)?;
+        line.push(b':');
+        serde_json::to_writer(&mut line, &value)?;
+    }
+    line.extend_from_slice(b"}\n");
+    Ok(line)
+}
+
+#[cfg(test)]
+mod test {
+    use futures::stream;
+    use serde_json::json;
+
+    use super::*;
+
+    fn transaction(amount: &str, ref_id: Option<&str>) -> Transaction {
+        serde_json::from_value(json!({
+            "id": "c4d1da72-111e-4d52-bdbf-2e74a2d803d5",
+            "amounts": [amount],
+            "blockchain": "MATIC-AMOY",
+            "createDate": "2024-01-10T09:15:32Z",
+            "updateDate": "2024-01-10T09:16:00Z",
+            "state": "COMPLETE",
+            "transactionType": "OUTBOUND",
+            "refId": ref_id,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_csv_export() {
+        let transactions = vec![
+            Ok(transaction("12.5", Some("payout, march"))),
+            Ok(transaction("0.125", None)),
+        ];
+        let export = TransactionExport::new(ExportFormat::Csv)
+            .columns(vec![Column::CreateDate, Column::Amounts, Column::RefId])
+            .amount_format(AmountFormat::Fixed(2))
+            .timezone(FixedOffset::east_opt(2 * 3600).unwrap());
+        let mut out = Vec::new();
+        let rows = export
+            .write(stream::iter(transactions), &mut out)
+            .await
+            .unwrap();
+        assert_eq!(rows, 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "createDate,amounts,refId\n\
+             2024-01-10T11:15:32+02:00,12.50,\"payout, march\"\n\
+             2024-01-10T11:15:32+02:00,0.12,\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_lines_export() {
+        let export = TransactionExport::new(ExportFormat::JsonLines).columns(vec![
+            Column::State,
+            Column::Amounts,
+            Column::TxHash,
+        ]);
+        let mut out = Vec::new();
+        export
+            .write(stream::iter(vec![Ok(transaction("1", None))]), &mut out)
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"state\":\"COMPLETE\",\"amounts\":[\"1\"],\"txHash\":null}\n"
+        );
+    }
+}
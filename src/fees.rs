@@ -0,0 +1,206 @@
+//! Network fees paid over a period, broken down by chain, wallet, day and fee level, so gas
+//! costs can be attributed to whatever a wallet or transaction belongs to.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::export::text;
+use crate::models::fee::FeeLevel;
+use crate::models::ids::WalletId;
+use crate::models::time_range::TimeRange;
+use crate::models::transaction::Transaction;
+use crate::models::transaction_list::TransactionQuery;
+
+/// The fees of a group of transactions. Native fees are per chain, since they are in different
+/// currencies; the USD value adds up across chains.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTotals {
+    pub transactions: u64,
+    /// Keyed by blockchain, e.g. `MATIC-AMOY`.
+    pub network_fee: BTreeMap<String, Decimal>,
+    pub network_fee_usd: Decimal,
+    /// Transactions with a network fee but no USD value; `network_fee_usd` is short by them.
+    pub unpriced: u64,
+}
+
+impl FeeTotals {
+    fn add(&mut self, blockchain: &str, fee: Decimal, fee_usd: Option<Decimal>) {
+        self.transactions += 1;
+        *self.network_fee.entry(blockchain.to_string()).or_default() += fee;
+        match fee_usd {
+            Some(x) => self.network_fee_usd += x,
+            None => self.unpriced += 1,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeReport {
+    pub total: FeeTotals,
+    pub by_chain: BTreeMap<String, FeeTotals>,
+    pub by_wallet: BTreeMap<WalletId, FeeTotals>,
+    /// By UTC day of the transaction's creation.
+    pub by_day: BTreeMap<NaiveDate, FeeTotals>,
+    /// For transactions that say which fee level they used.
+    pub by_fee_level: BTreeMap<FeeLevel, FeeTotals>,
+    /// By the label [`FeeAnalyzer::label`] gave each transaction, e.g. a product line.
+    pub by_label: BTreeMap<String, FeeTotals>,
+}
+
+impl FeeReport {
+    /// Adds the fee of `transaction`, if it has one, under `label`.
+    pub fn add(&mut self, transaction: &Transaction, label: Option<&str>) {
+        let Some(fee) = transaction.network_fee else {
+            return;
+        };
+        let fee_usd = transaction.network_fee_in_usd;
+        let blockchain = text(&transaction.blockchain).unwrap_or_default();
+        let mut groups = vec![
+            &mut self.total,
+            self.by_chain.entry(blockchain.clone()).or_default(),
+            self.by_day
+                .entry(transaction.create_date.date_naive())
+                .or_default(),
+        ];
+        if let Some(wallet_id) = transaction.wallet_id {
+            groups.push(self.by_wallet.entry(wallet_id).or_default());
+        }
+        let fee_level = transaction
+            .fee_level
+            .or_else(|| transaction.estimated_fee.as_ref()?.fee_level);
+        if let Some(fee_level) = fee_level {
+            groups.push(self.by_fee_level.entry(fee_level).or_default());
+        }
+        if let Some(label) = label {
+            groups.push(self.by_label.entry(label.to_string()).or_default());
+        }
+        for group in groups {
+            group.add(&blockchain, fee, fee_usd);
+        }
+    }
+}
+
+type Label<'a> = Box<dyn Fn(&Transaction) -> Option<String> + Send + Sync + 'a>;
+
+/// Walks the transactions of a set of wallets (or of all wallets) and adds up their network
+/// fees.
+pub struct FeeAnalyzer<'a> {
+    client: &'a CircleClient,
+    wallets: Vec<WalletId>,
+    label: Option<Label<'a>>,
+}
+
+impl<'a> FeeAnalyzer<'a> {
+    pub fn new(client: &'a CircleClient) -> Self {
+        FeeAnalyzer {
+            client,
+            wallets: Vec::new(),
+            label: None,
+        }
+    }
+
+    /// Only the transactions of these wallets. All wallets by default.
+    pub fn wallets<I: IntoIterator<Item = WalletId>>(mut self, wallets: I) -> Self {
+        self.wallets.extend(wallets);
+        self
+    }
+
+    /// Labels each transaction for [`FeeReport::by_label`], e.g. by its `refId` prefix.
+    /// Transactions labelled `None` are only counted in the other breakdowns.
+    pub fn label<F>(mut self, label: F) -> Self
+    where
+        F: Fn(&Transaction) -> Option<String> + Send + Sync + 'a,
+    {
+        self.label = Some(Box::new(label));
+        self
+    }
+
+    /// The fees of every transaction created in `range`, in any state: failed transactions
+    /// that made it on chain paid gas too.
+    pub async fn analyze(&self, range: TimeRange) -> Result<FeeReport> {
+        let query = TransactionQuery::new()
+            .wallets(self.wallets.iter().copied())
+            .time_range(range);
+        self.client
+            .list_transactions_stream(query.build())
+            .try_fold(FeeReport::default(), |mut report, transaction| async move {
+                let label = self.label.as_ref().and_then(|x| x(&transaction));
+                report.add(&transaction, label.as_deref());
+                Ok(report)
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    const WALLET: Uuid = Uuid::from_u128(1);
+
+    fn transaction(blockchain: &str, day: u32, fee: Option<&str>, fee_usd: Option<&str>) -> Value {
+        json!({
+            "id": Uuid::new_v4(),
+            "blockchain": blockchain,
+            "createDate": format!("2024-03-{:02}T10:00:00Z", day),
+            "updateDate": format!("2024-03-{:02}T10:00:00Z", day),
+            "state": "COMPLETE",
+            "transactionType": "OUTBOUND",
+            "walletId": WALLET,
+            "refId": "checkout:42",
+            "networkFee": fee,
+            "networkFeeInUsd": fee_usd,
+            "estimatedFee": { "feeLevel": "MEDIUM" }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fee_report() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        transport.push_data(json!({ "transactions": [
+            transaction("MATIC-AMOY", 1, Some("0.002"), Some("0.0015")),
+            transaction("MATIC-AMOY", 1, Some("0.003"), None),
+            transaction("ETH-SEPOLIA", 2, Some("0.0001"), Some("0.35")),
+            transaction("ETH-SEPOLIA", 2, None, None),
+        ] }));
+        transport.push_data(json!({ "transactions": [] }));
+
+        let range = TimeRange::between(
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap(),
+        )
+        .unwrap();
+        let report = FeeAnalyzer::new(&client)
+            .label(|x| Some(x.ref_id.as_ref()?.split(':').next()?.to_string()))
+            .analyze(range)
+            .await
+            .unwrap();
+
+        assert_eq!(report.total.transactions, 3);
+        assert_eq!(report.total.network_fee_usd, Decimal::new(35150, 5));
+        assert_eq!(report.total.unpriced, 1);
+        assert_eq!(
+            report.by_chain["MATIC-AMOY"].network_fee["MATIC-AMOY"],
+            Decimal::new(5, 3)
+        );
+        let day = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        assert_eq!(report.by_day[&day].transactions, 1);
+        assert_eq!(report.by_fee_level[&FeeLevel::Medium], report.total);
+        assert_eq!(report.by_label["checkout"], report.total);
+        assert_eq!(report.by_wallet[&WALLET.into()], report.total);
+    }
+}
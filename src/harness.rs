@@ -0,0 +1,247 @@
+//! Repeatable integration tests against the real sandbox.
+//!
+//! A [`TestHarness`] creates a wallet set named after the test run, creates and funds wallets
+//! in it, and archives everything when the test is done. Circle can't delete wallets or wallet
+//! sets, so archiving renames them to `archived-<namespace>...` and sets the wallets' `refId`
+//! to `archived`, which keeps them out of the way of later runs.
+
+use chrono::Utc;
+use log::warn;
+use uuid::Uuid;
+
+use crate::api::{CircleClient, Environment};
+use crate::error::{CircleError, Result};
+use crate::models::blockchain::Blockchain;
+use crate::models::faucet::FaucetRequest;
+use crate::models::ids::WalletSetId;
+use crate::models::wallet_detail::WalletDetail;
+use crate::models::wallet_update::WalletUpdateRequest;
+
+const HARNESS_NAMESPACE: Uuid = Uuid::from_u128(0x2b4f_8c61_0d37_4e9a_b5c2_71f0_3a8d_e614);
+
+/// The resources of one test run. Call [`TestHarness::teardown`] at the end of the test; if
+/// the harness is dropped without it (e.g. because the test panicked), the drop archives
+/// everything instead. That needs a multi-threaded tokio runtime
+/// (`#[tokio::test(flavor = "multi_thread")]`) or no runtime at all: on a current-thread
+/// runtime the drop can't block and only logs what it left behind.
+pub struct TestHarness {
+    client: CircleClient,
+    namespace: String,
+    wallet_set_id: WalletSetId,
+    wallets: Vec<WalletDetail>,
+    torn_down: bool,
+}
+
+impl TestHarness {
+    /// Creates the wallet set `<prefix>-<timestamp>-<random>`. Refuses to run against
+    /// production.
+    pub async fn new(client: &CircleClient, prefix: &str) -> Result<Self> {
+        if *client.environment() == Environment::Production {
+            return Err(CircleError::InvalidState(
+                "the test harness doesn't run against production".to_string(),
+            ));
+        }
+        let namespace = format!(
+            "{}-{}-{}",
+            prefix,
+            Utc::now().format("%Y%m%d%H%M%S"),
+            &Uuid::new_v4().simple().to_string()[..8]
+        );
+        let key = Uuid::new_v5(&HARNESS_NAMESPACE, namespace.as_bytes());
+        let wallet_set = client
+            .create_wallet_set(key, namespace.clone())
+            .await?
+            .wallet_set;
+        Ok(TestHarness {
+            client: client.clone(),
+            namespace,
+            wallet_set_id: wallet_set.id,
+            wallets: Vec::new(),
+            torn_down: false,
+        })
+    }
+
+    /// Unique to this run, e.g. for `refId`s and other names the test creates.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn wallet_set_id(&self) -> WalletSetId {
+        self.wallet_set_id
+    }
+
+    pub fn wallets(&self) -> &[WalletDetail] {
+        &self.wallets
+    }
+
+    /// Creates `count` wallets on `blockchain` in the run's wallet set.
+    pub async fn create_wallets(
+        &mut self,
+        blockchain: Blockchain,
+        count: u32,
+    ) -> Result<Vec<WalletDetail>> {
+        let name = format!("{}:wallets:{}", self.namespace, self.wallets.len());
+        let key = Uuid::new_v5(&HARNESS_NAMESPACE, name.as_bytes());
+        let wallets = self
+            .client
+            .create_wallet(key, self.wallet_set_id, vec![blockchain], count)
+            .await?
+            .wallets;
+        self.wallets.extend(wallets.iter().cloned());
+        Ok(wallets)
+    }
+
+    /// Drips testnet tokens to `wallet` from the sandbox faucet. `tokens` picks which, e.g.
+    /// `|x| x.native().usdc()`.
+    pub async fn fund<F: FnOnce(FaucetRequest) -> FaucetRequest>(
+        &self,
+        wallet: &WalletDetail,
+        tokens: F,
+    ) -> Result<()> {
+        let request = FaucetRequest::new(wallet.address.clone(), wallet.blockchain.clone());
+        self.client.request_testnet_tokens(tokens(request)).await
+    }
+
+    /// Archives the wallets and the wallet set. Everything is attempted; the first error is
+    /// returned.
+    pub async fn teardown(mut self) -> Result<()> {
+        self.torn_down = true;
+        archive(
+            &self.client,
+            &self.namespace,
+            self.wallet_set_id,
+            &self.wallets,
+        )
+        .await
+    }
+}
+
+async fn archive(
+    client: &CircleClient,
+    namespace: &str,
+    wallet_set_id: WalletSetId,
+    wallets: &[WalletDetail],
+) -> Result<()> {
+    let mut result = Ok(());
+    for (i, wallet) in wallets.iter().enumerate() {
+        let request =
+            WalletUpdateRequest::new(format!("archived-{}-{}", namespace, i)).ref_id("archived");
+        if let Err(err) = client.update_wallet(wallet.id, request).await {
+            result = result.and(Err(err));
+        }
+    }
+    let renamed = client
+        .update_wallet_set(wallet_set_id, format!("archived-{}", namespace))
+        .await;
+    result.and(renamed.map(|_| ()))
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        if self.torn_down {
+            return;
+        }
+        let archived = match tokio::runtime::Handle::try_current() {
+            Ok(handle)
+                if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::CurrentThread =>
+            {
+                warn!(
+                    "test harness {} dropped on a current-thread runtime, wallet set {} is not archived",
+                    self.namespace, self.wallet_set_id
+                );
+                return;
+            }
+            Ok(handle) => tokio::task::block_in_place(|| {
+                handle.block_on(archive(
+                    &self.client,
+                    &self.namespace,
+                    self.wallet_set_id,
+                    &self.wallets,
+                ))
+            }),
+            Err(_) => tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(CircleError::from)
+                .and_then(|runtime| {
+                    runtime.block_on(archive(
+                        &self.client,
+                        &self.namespace,
+                        self.wallet_set_id,
+                        &self.wallets,
+                    ))
+                }),
+        };
+        if let Err(err) = archived {
+            warn!("failed to archive test harness {}: {}", self.namespace, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    fn wallet_set(id: Uuid) -> Value {
+        json!({
+            "walletSet": {
+                "id": id,
+                "custodyType": "DEVELOPER",
+                "name": "it",
+                "createDate": "2024-03-01T10:00:00Z",
+                "updateDate": "2024-03-01T10:00:00Z"
+            }
+        })
+    }
+
+    fn wallet(wallet_set_id: Uuid) -> Value {
+        json!({
+            "id": Uuid::new_v4(),
+            "address": "0x6e5eaf34c73d1cd0be4e24f923b97cf38e10d1f3",
+            "blockchain": "MATIC-AMOY",
+            "createDate": "2024-03-01T10:00:00Z",
+            "custodyType": "DEVELOPER",
+            "state": "LIVE",
+            "updateDate": "2024-03-01T10:00:00Z",
+            "walletSetId": wallet_set_id
+        })
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_harness_archives_on_drop() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let wallet_set_id = Uuid::new_v4();
+        transport.push_data(wallet_set(wallet_set_id));
+        let mut harness = TestHarness::new(&client, "it").await.unwrap();
+        assert!(harness.namespace().starts_with("it-"));
+        transport.push_data(json!({ "wallets": [wallet(wallet_set_id)] }));
+        let wallets = harness
+            .create_wallets(Blockchain::MaticAmoy, 1)
+            .await
+            .unwrap();
+        transport.push_data(json!({}));
+        harness.fund(&wallets[0], |x| x.usdc()).await.unwrap();
+
+        transport.push_data(json!({ "wallet": wallet(wallet_set_id) }));
+        transport.push_data(wallet_set(wallet_set_id));
+        let namespace = harness.namespace().to_string();
+        drop(harness);
+        let requests = transport.requests();
+        let renames = requests
+            .iter()
+            .filter(|x| x.method == reqwest::Method::PUT)
+            .map(|x| x.body.as_ref().unwrap()["name"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            renames,
+            [
+                json!(format!("archived-{}-0", namespace)),
+                json!(format!("archived-{}", namespace))
+            ]
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! Invoices on top of [`crate::checkout`]: an invoice is priced in any [`Currency`] and settled
+//! in a token, converted at the rate in effect when the customer pays. Each payment attempt
+//! gets a [`Quote`] with its own [`PaymentIntent`]; what was paid against earlier quotes counts
+//! towards the invoice, so a customer can pay in several transfers and the next quote only
+//! asks for the rest.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::checkout::{Checkout, PaymentIntent, PaymentStatus, Tolerance};
+use crate::error::{CircleError, Result};
+use crate::models::exchange_rate::CurrencyPair;
+use crate::models::ids::{TokenId, WalletId};
+use crate::models::money::Currency;
+use crate::models::notification::NotificationEvent;
+use crate::models::transaction::Transaction;
+
+/// Namespace of invoice ids, which are derived from the reference.
+const INVOICE_NAMESPACE: Uuid = Uuid::from_u128(0x5d1f0c2a_8e4b_4c1e_9a67_2f3b8d0e4a91);
+
+/// Token amounts are rounded up to this many decimals, USDC's and EURC's precision.
+const TOKEN_DECIMALS: u32 = 6;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InvoiceStatus {
+    /// Not quoted yet.
+    Draft,
+    /// Quoted, nothing received yet.
+    Open,
+    /// Part of the amount was received. The latest quote may have expired, see
+    /// [`Invoice::needs_quote`].
+    PartiallyPaid,
+    Paid,
+    /// The latest quote expired without anything received; the invoice can be quoted again.
+    Expired,
+    /// Cancelled by the merchant. Final.
+    Void,
+}
+
+/// One payment attempt: the token amount the remaining balance was converted to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    /// Token units per unit of the invoice currency.
+    pub rate: Decimal,
+    pub intent: PaymentIntent,
+}
+
+impl Quote {
+    /// What was received against this quote, in the invoice currency.
+    pub fn paid(&self) -> Decimal {
+        self.intent
+            .received()
+            .checked_div(self.rate)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Invoice {
+    /// Derived from `reference`, so the same reference always gives the same id.
+    pub id: Uuid,
+    pub reference: String,
+    pub amount: Decimal,
+    pub currency: Currency,
+    /// The wallet receiving payment. Like with [`PaymentIntent`], it must not be shared with
+    /// other open invoices.
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub token_currency: Currency,
+    /// Applied to every quote, in token units.
+    pub tolerance: Tolerance,
+    status: InvoiceStatus,
+    quotes: Vec<Quote>,
+}
+
+impl Invoice {
+    pub fn new<S: Into<String>>(
+        reference: S,
+        amount: Decimal,
+        currency: Currency,
+        wallet_id: WalletId,
+        token_id: TokenId,
+        token_currency: Currency,
+    ) -> Self {
+        let reference = reference.into();
+        Invoice {
+            id: Uuid::new_v5(&INVOICE_NAMESPACE, reference.as_bytes()),
+            reference,
+            amount,
+            currency,
+            wallet_id,
+            token_id,
+            token_currency,
+            tolerance: Tolerance::default(),
+            status: InvoiceStatus::Draft,
+            quotes: Vec::new(),
+        }
+    }
+
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn status(&self) -> InvoiceStatus {
+        self.status
+    }
+
+    pub fn quotes(&self) -> &[Quote] {
+        &self.quotes
+    }
+
+    /// The quote customers should currently pay.
+    pub fn current_quote(&self) -> Option<&Quote> {
+        self.quotes.last().filter(|x| !x.intent.is_final())
+    }
+
+    /// What was received so far, in the invoice currency.
+    pub fn paid(&self) -> Decimal {
+        self.quotes.iter().map(Quote::paid).sum()
+    }
+
+    /// What is still due, in the invoice currency.
+    pub fn remaining(&self) -> Decimal {
+        match self.status {
+            InvoiceStatus::Paid => Decimal::ZERO,
+            _ => (self.amount - self.paid()).max(Decimal::ZERO),
+        }
+    }
+
+    /// Whether the invoice can be paid but has no open quote.
+    pub fn needs_quote(&self) -> bool {
+        !matches!(self.status, InvoiceStatus::Paid | InvoiceStatus::Void)
+            && self.current_quote().is_none()
+    }
+
+    /// Quotes the remaining amount at `rate` token units per unit of the invoice currency,
+    /// valid for `ttl`.
+    pub fn quote(&mut self, rate: Decimal, ttl: chrono::Duration) -> Result<&PaymentIntent> {
+        if !self.needs_quote() {
+            return Err(CircleError::InvalidState(format!(
+                "invoice {} is {:?} and can't be quoted",
+                self.reference, self.status
+            )));
+        }
+        let amount = (self.remaining() * rate)
+            .round_dp_with_strategy(TOKEN_DECIMALS, RoundingStrategy::AwayFromZero);
+        let mut intent = PaymentIntent::new(self.wallet_id, self.token_id, amount, ttl)
+            .tolerance(self.tolerance);
+        let name = format!("quote:{}", self.quotes.len());
+        intent.id = Uuid::new_v5(&self.id, name.as_bytes());
+        self.quotes.push(Quote { rate, intent });
+        self.refresh(Utc::now());
+        Ok(&self.quotes[self.quotes.len() - 1].intent)
+    }
+
+    /// Cancels an invoice nothing was paid against yet.
+    pub fn void(&mut self) -> Result<()> {
+        if self.paid() > Decimal::ZERO || self.status == InvoiceStatus::Paid {
+            return Err(CircleError::InvalidState(format!(
+                "invoice {} was paid against and can't be voided",
+                self.reference
+            )));
+        }
+        self.status = InvoiceStatus::Void;
+        Ok(())
+    }
+
+    /// Counts `transaction` against the quote it was paid under. Returns whether it was
+    /// counted.
+    pub fn apply_transaction(&mut self, transaction: &Transaction) -> bool {
+        if self.status == InvoiceStatus::Void
+            || self
+                .quotes
+                .iter()
+                .any(|x| x.intent.transactions().contains(&transaction.id))
+        {
+            return false;
+        }
+        let applied = self
+            .quotes
+            .iter_mut()
+            .any(|x| x.intent.apply_transaction(transaction));
+        self.refresh(Utc::now());
+        applied
+    }
+
+    /// [`Invoice::apply_transaction`] for a `transactions.inbound` webhook.
+    pub fn apply_notification(&mut self, event: &NotificationEvent) -> bool {
+        match event {
+            NotificationEvent::TransactionInbound(transaction) => {
+                self.apply_transaction(transaction)
+            }
+            _ => false,
+        }
+    }
+
+    /// Re-evaluates the status at `now`, e.g. to notice an expired quote.
+    pub fn refresh(&mut self, now: DateTime<Utc>) -> InvoiceStatus {
+        if self.status == InvoiceStatus::Void {
+            return self.status;
+        }
+        let Some(latest) = self.quotes.last_mut() else {
+            return self.status;
+        };
+        let latest = latest.intent.refresh(now);
+        self.status = if latest == PaymentStatus::Paid || self.paid() >= self.amount {
+            InvoiceStatus::Paid
+        } else if self.paid() > Decimal::ZERO {
+            InvoiceStatus::PartiallyPaid
+        } else if latest == PaymentStatus::Expired {
+            InvoiceStatus::Expired
+        } else {
+            InvoiceStatus::Open
+        };
+        self.status
+    }
+}
+
+/// Quotes invoices at Circle's exchange rates and polls for their payments.
+pub struct Invoices<'a> {
+    client: &'a CircleClient,
+    checkout: Checkout<'a>,
+    quote_ttl: chrono::Duration,
+}
+
+impl<'a> Invoices<'a> {
+    pub fn new(client: &'a CircleClient) -> Self {
+        Invoices {
+            client,
+            checkout: Checkout::new(client),
+            quote_ttl: chrono::Duration::minutes(15),
+        }
+    }
+
+    /// How long a quoted rate holds. Fifteen minutes by default.
+    pub fn quote_ttl(mut self, quote_ttl: Duration) -> Self {
+        self.quote_ttl = chrono::Duration::from_std(quote_ttl).unwrap_or(self.quote_ttl);
+        self
+    }
+
+    /// Token units per unit of `currency`. A stablecoin and the currency it tracks are
+    /// treated as one to one.
+    pub async fn rate(&self, currency: Currency, token_currency: Currency) -> Result<Decimal> {
+        let pegged = matches!(
+            (currency, token_currency),
+            (Currency::Usd, Currency::Usdc) | (Currency::Eur, Currency::Eurc)
+        );
+        if currency == token_currency || pegged {
+            return Ok(Decimal::ONE);
+        }
+        let pair = CurrencyPair::new(currency, token_currency);
+        Ok(self.client.get_exchange_rates(pair).await?.rate)
+    }
+
+    /// Quotes the invoice's remaining amount at the current rate.
+    pub async fn quote<'i>(&self, invoice: &'i mut Invoice) -> Result<&'i PaymentIntent> {
+        let rate = self.rate(invoice.currency, invoice.token_currency).await?;
+        invoice.quote(rate, self.quote_ttl)
+    }
+
+    /// Looks up transfers against the current quote once and applies them.
+    pub async fn poll(&self, invoice: &mut Invoice) -> Result<InvoiceStatus> {
+        if let Some(quote) = invoice.quotes.last_mut() {
+            self.checkout.poll(&mut quote.intent).await?;
+        }
+        Ok(invoice.refresh(Utc::now()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    const WALLET_ID: &str = "ce714f5b-0d8e-4062-9454-61aa1154869b";
+    const TOKEN_ID: &str = "7adb2b7d-c9cd-5164-b2d4-b73b088274dc";
+
+    fn transfer(amount: &str) -> Transaction {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "blockchain": "MATIC-AMOY",
+            "walletId": WALLET_ID,
+            "tokenId": TOKEN_ID,
+            "amounts": [amount],
+            "state": "COMPLETE",
+            "transactionType": "INBOUND",
+            "createDate": Utc::now(),
+            "updateDate": Utc::now()
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_invoice() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let invoices = Invoices::new(&client);
+        let mut invoice = Invoice::new(
+            "INV-1001",
+            Decimal::from(100),
+            Currency::Eur,
+            WalletId::from_str(WALLET_ID).unwrap(),
+            TokenId::from_str(TOKEN_ID).unwrap(),
+            Currency::Usdc,
+        );
+        assert_eq!(
+            invoice.id,
+            Invoice::new(
+                "INV-1001",
+                Decimal::ONE,
+                Currency::Usd,
+                WalletId::default(),
+                TokenId::default(),
+                Currency::Usdc
+            )
+            .id
+        );
+        assert!(invoice.needs_quote());
+
+        transport.push_data(json!({ "pair": "EUR-USDC", "rate": "1.1" }));
+        let intent = invoices.quote(&mut invoice).await.unwrap();
+        assert_eq!(intent.amount, Decimal::from(110));
+        assert_eq!(invoice.status(), InvoiceStatus::Open);
+        assert!(invoice
+            .quote(Decimal::ONE, chrono::Duration::minutes(1))
+            .is_err());
+
+        assert!(invoice.apply_transaction(&transfer("55")));
+        assert_eq!(invoice.status(), InvoiceStatus::PartiallyPaid);
+        assert_eq!(invoice.remaining(), Decimal::from(50));
+
+        // The first quote runs out; the rest is quoted at a new rate.
+        invoice.quotes[0].intent.expires_at = Utc::now() - chrono::Duration::seconds(1);
+        invoice.refresh(Utc::now());
+        assert!(invoice.needs_quote());
+        let intent = invoice
+            .quote(Decimal::new(12, 1), chrono::Duration::minutes(15))
+            .unwrap();
+        assert_eq!(intent.amount, Decimal::from(60));
+        assert!(invoice.apply_transaction(&transfer("60")));
+        assert_eq!(invoice.status(), InvoiceStatus::Paid);
+        assert!(invoice.void().is_err());
+
+        let stored = serde_json::to_string(&invoice).unwrap();
+        assert_eq!(serde_json::from_str::<Invoice>(&stored).unwrap(), invoice);
+    }
+}
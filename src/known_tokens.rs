@@ -0,0 +1,244 @@
+//! Circle's own stablecoins, USDC and EURC, on the chains Circle wallets support.
+//!
+//! Token ids differ between Circle environments, so only contract addresses are listed here;
+//! use [`CircleClient::get_token_details`](crate::api::CircleClient::get_token_details) or a
+//! wallet balance to find the id.
+
+use std::str::FromStr;
+
+use crate::models::blockchain::Blockchain;
+use crate::models::token_amount::TokenAmountFormat;
+use crate::models::web3_address::Web3Address;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stablecoin {
+    Usdc,
+    Eurc,
+}
+
+impl Stablecoin {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Stablecoin::Usdc => "USDC",
+            Stablecoin::Eurc => "EURC",
+        }
+    }
+}
+
+/// A deployment of USDC or EURC on one chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownToken {
+    pub stablecoin: Stablecoin,
+    pub blockchain: Blockchain,
+    /// The contract address, or the mint on Solana and the account on NEAR.
+    pub address: &'static str,
+    pub decimals: u32,
+}
+
+impl KnownToken {
+    const fn new(stablecoin: Stablecoin, blockchain: Blockchain, address: &'static str) -> Self {
+        KnownToken {
+            stablecoin,
+            blockchain,
+            address,
+            decimals: 6,
+        }
+    }
+
+    pub fn amount_format(&self) -> TokenAmountFormat {
+        TokenAmountFormat::new(self.stablecoin.symbol(), self.decimals)
+    }
+
+    /// The address on EVM chains, `None` elsewhere.
+    pub fn web3_address(&self) -> Option<Web3Address> {
+        self.blockchain.evm_chain_id()?;
+        Web3Address::from_str(self.address).ok()
+    }
+}
+
+static TOKENS: &[KnownToken] = &[
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::Eth,
+        "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::EthSepolia,
+        "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::EthGoerli,
+        "0x07865c6E87B9F70255377e024ace6630C1Eaa37F",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::Avax,
+        "0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::AvaxFuji,
+        "0x5425890298aed601595a70AB815c96711a31Bc65",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::Matic,
+        "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::MaticAmoy,
+        "0x41E94Eb019C0762f9Bfcf9Fb1E58725BfB0e7582",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::MaticMumbai,
+        "0x9999f7Fea5938fD3b1E26A12c3f2fb024e194f97",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::Arb,
+        "0xaf88d065e77c8cC2239327C5EDb3A432268e5831",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::ArbSepolia,
+        "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::Base,
+        "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::BaseSepolia,
+        "0x036CbD53842c5426634e7929541eC2318f3dCF7e",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::Sol,
+        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::SolDevnet,
+        "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::Near,
+        "17208628f84f5d6ad33f0da3bbbeb27ffcb398eac501a31bd6ad2011e36133a1",
+    ),
+    KnownToken::new(
+        Stablecoin::Usdc,
+        Blockchain::NearTestnet,
+        "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af",
+    ),
+    KnownToken::new(
+        Stablecoin::Eurc,
+        Blockchain::Eth,
+        "0x1aBaEA1f7C830bD89Acc67eC4af516284b1bC33c",
+    ),
+    KnownToken::new(
+        Stablecoin::Eurc,
+        Blockchain::EthSepolia,
+        "0x08210F9170F89Ab7658F0B5E3fF39b0E03C594D4",
+    ),
+    KnownToken::new(
+        Stablecoin::Eurc,
+        Blockchain::Avax,
+        "0xC891EB4cbdEFf6e073e859e987815Ed1505c2ACD",
+    ),
+    KnownToken::new(
+        Stablecoin::Eurc,
+        Blockchain::AvaxFuji,
+        "0x5E44db7996c682E92a960b65AC713a54AD815c6B",
+    ),
+    KnownToken::new(
+        Stablecoin::Eurc,
+        Blockchain::Base,
+        "0x60a3E35Cc302bFA44Cb288Bc5a4F316Fdb1adb42",
+    ),
+    KnownToken::new(
+        Stablecoin::Eurc,
+        Blockchain::BaseSepolia,
+        "0x808456652fdb597867f38412077A9182bf77359F",
+    ),
+    KnownToken::new(
+        Stablecoin::Eurc,
+        Blockchain::Sol,
+        "HzwqbKZw8HxMN6bF2yFZNrht3c2iXXzpKcFu7uBEDKtr",
+    ),
+];
+
+/// Every known deployment.
+pub fn all() -> &'static [KnownToken] {
+    TOKENS
+}
+
+pub fn usdc_on(blockchain: &Blockchain) -> Option<&'static KnownToken> {
+    on(Stablecoin::Usdc, blockchain)
+}
+
+pub fn eurc_on(blockchain: &Blockchain) -> Option<&'static KnownToken> {
+    on(Stablecoin::Eurc, blockchain)
+}
+
+/// `stablecoin` on `blockchain`, if Circle issues it there.
+pub fn on(stablecoin: Stablecoin, blockchain: &Blockchain) -> Option<&'static KnownToken> {
+    TOKENS
+        .iter()
+        .find(|x| x.stablecoin == stablecoin && x.blockchain == *blockchain)
+}
+
+/// The known token at `address` on `blockchain`. EVM addresses match in any case.
+pub fn find(blockchain: &Blockchain, address: &str) -> Option<&'static KnownToken> {
+    TOKENS.iter().find(|x| {
+        x.blockchain == *blockchain
+            && match blockchain.evm_chain_id() {
+                Some(_) => x.address.eq_ignore_ascii_case(address),
+                None => x.address == address,
+            }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_known_tokens() {
+        assert_eq!(
+            usdc_on(&Blockchain::Base).unwrap().address,
+            "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"
+        );
+        assert!(eurc_on(&Blockchain::Arb).is_none());
+        assert!(usdc_on(&Blockchain::Unknown("APTOS".to_string())).is_none());
+
+        let usdc = find(
+            &Blockchain::EthSepolia,
+            "0x1c7d4b196cb0c7b01d743fbc6116a902379c7238",
+        )
+        .unwrap();
+        assert_eq!(usdc.stablecoin, Stablecoin::Usdc);
+        assert!(find(&Blockchain::Eth, usdc.address).is_none());
+
+        // Every EVM address parses, and every chain Circle supports has USDC.
+        for token in all() {
+            assert_eq!(
+                token.web3_address().is_some(),
+                token.blockchain.evm_chain_id().is_some()
+            );
+        }
+        assert_eq!(
+            all()
+                .iter()
+                .filter(|x| x.stablecoin == Stablecoin::Usdc)
+                .count(),
+            16
+        );
+    }
+}
@@ -1,3 +1,66 @@
+pub mod address;
+#[cfg(feature = "wallets")]
+pub mod alerts;
+#[cfg(feature = "contracts")]
+pub mod allowances;
 pub mod api;
+#[cfg(any(test, feature = "blocking"))]
+pub mod blocking;
+pub mod bulk;
+pub mod cancel;
+#[cfg(all(feature = "contracts", feature = "wallets"))]
+pub mod cctp;
+#[cfg(all(feature = "transactions", feature = "wallets", feature = "webhooks"))]
+pub mod checkout;
+pub mod config;
+#[cfg(all(feature = "transactions", feature = "wallets", feature = "webhooks"))]
+pub mod deposits;
 pub mod error;
+#[cfg(feature = "webhooks")]
+pub mod events;
+pub mod explorer;
+#[cfg(feature = "transactions")]
+pub mod export;
+#[cfg(feature = "transactions")]
+pub mod fees;
+#[cfg(feature = "it-harness")]
+pub mod harness;
+#[cfg(all(
+    feature = "payments",
+    feature = "transactions",
+    feature = "wallets",
+    feature = "webhooks"
+))]
+pub mod invoices;
+pub mod known_tokens;
 pub mod models;
+pub mod networks;
+#[cfg(feature = "wallets")]
+pub mod nft_portfolio;
+#[cfg(feature = "transactions")]
+pub mod outbox;
+#[cfg(feature = "transactions")]
+pub mod payouts;
+#[cfg(feature = "transactions")]
+pub mod policies;
+pub mod prelude;
+#[cfg(feature = "qr")]
+pub mod qr;
+#[cfg(feature = "transactions")]
+pub mod receipt;
+#[cfg(all(feature = "transactions", feature = "wallets"))]
+pub mod reconcile;
+mod runtime;
+#[cfg(feature = "transactions")]
+pub mod scheduler;
+#[cfg(feature = "sqlx")]
+pub mod sql;
+#[cfg(all(feature = "transactions", feature = "wallets"))]
+pub mod sweep;
+pub mod tags;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(all(feature = "transactions", feature = "wallets"))]
+pub mod tiering;
+#[cfg(feature = "wallets")]
+pub mod wallet_resolver;
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AccountType {
+    /// Externally owned account.
+    Eoa,
+    /// Smart contract account.
+    Sca,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
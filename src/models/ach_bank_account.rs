@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::bank_account::{
+    BankAccountStatus, BankAddress, BillingDetails, PaymentMetadata, RiskEvaluation,
+};
+use crate::models::extra::Extra;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AchBankAccountType {
+    Retail,
+    Wholesale,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AchBankAccountCreateRequest {
+    idempotency_key: Uuid,
+    plaid_processor_token: String,
+    billing_details: BillingDetails,
+    bank_account_type: Option<AchBankAccountType>,
+    metadata: PaymentMetadata,
+}
+
+pub struct AchBankAccountCreateRequestBuilder {
+    plaid_processor_token: String,
+    billing_details: BillingDetails,
+    bank_account_type: Option<AchBankAccountType>,
+    metadata: PaymentMetadata,
+}
+
+impl AchBankAccountCreateRequestBuilder {
+    pub fn new(
+        plaid_processor_token: String,
+        billing_details: BillingDetails,
+        metadata: PaymentMetadata,
+    ) -> Self {
+        AchBankAccountCreateRequestBuilder {
+            plaid_processor_token,
+            billing_details,
+            bank_account_type: None,
+            metadata,
+        }
+    }
+
+    pub fn bank_account_type(mut self, bank_account_type: AchBankAccountType) -> Self {
+        self.bank_account_type = Some(bank_account_type);
+        self
+    }
+
+    pub fn build(self, idempotency_key: Uuid) -> AchBankAccountCreateRequest {
+        AchBankAccountCreateRequest {
+            idempotency_key,
+            plaid_processor_token: self.plaid_processor_token,
+            billing_details: self.billing_details,
+            bank_account_type: self.bank_account_type,
+            metadata: self.metadata,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AchBankAccount {
+    pub id: Uuid,
+    pub status: BankAccountStatus,
+    pub account_number: Option<String>,
+    pub routing_number: Option<String>,
+    pub billing_details: Option<BillingDetails>,
+    pub bank_account_type: Option<AchBankAccountType>,
+    pub bank_address: Option<BankAddress>,
+    pub fingerprint: Option<String>,
+    pub error_code: Option<String>,
+    pub risk_evaluation: Option<RiskEvaluation>,
+    pub create_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
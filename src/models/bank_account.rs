@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingDetails {
+    pub name: String,
+    pub city: String,
+    pub country: String,
+    pub line1: String,
+    pub line2: Option<String>,
+    pub district: Option<String>,
+    pub postal_code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BankAddress {
+    pub bank_name: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub line1: Option<String>,
+    pub line2: Option<String>,
+    pub district: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentMetadata {
+    pub email: String,
+    pub phone_number: Option<String>,
+    pub session_id: String,
+    pub ip_address: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskEvaluation {
+    pub decision: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BankAccountStatus {
+    Pending,
+    Complete,
+    Failed,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+impl BankAccountStatus {
+    /// Bank accounts start out `pending` and settle into either `complete` or `failed`,
+    /// both of which are terminal.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            BankAccountStatus::Complete | BankAccountStatus::Failed
+        )
+    }
+
+    pub fn can_transition_to(&self, next: BankAccountStatus) -> bool {
+        matches!(
+            (self, next),
+            (BankAccountStatus::Pending, BankAccountStatus::Pending)
+                | (BankAccountStatus::Pending, BankAccountStatus::Complete)
+                | (BankAccountStatus::Pending, BankAccountStatus::Failed)
+        )
+    }
+}
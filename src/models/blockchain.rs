@@ -1,12 +1,96 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING-KEBAB-CASE")]
 pub enum Blockchain {
     EthGoerli,
+    EthSepolia,
     Eth,
     AvaxFuji,
     Avax,
     MaticMumbai,
+    MaticAmoy,
     Matic,
+    ArbSepolia,
+    Arb,
+    BaseSepolia,
+    Base,
+    SolDevnet,
+    Sol,
+    NearTestnet,
+    Near,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+impl Blockchain {
+    /// Whether this is a test network. Unknown chains count as testnets if their name says so.
+    pub fn is_testnet(&self) -> bool {
+        match self {
+            Blockchain::EthGoerli
+            | Blockchain::EthSepolia
+            | Blockchain::AvaxFuji
+            | Blockchain::MaticMumbai
+            | Blockchain::MaticAmoy
+            | Blockchain::ArbSepolia
+            | Blockchain::BaseSepolia
+            | Blockchain::SolDevnet
+            | Blockchain::NearTestnet => true,
+            Blockchain::Eth
+            | Blockchain::Avax
+            | Blockchain::Matic
+            | Blockchain::Arb
+            | Blockchain::Base
+            | Blockchain::Sol
+            | Blockchain::Near => false,
+            Blockchain::Unknown(name) => ["TESTNET", "SEPOLIA", "DEVNET", "FUJI", "AMOY"]
+                .iter()
+                .any(|x| name.ends_with(x)),
+        }
+    }
+
+    /// The EIP-155 chain id of EVM chains.
+    pub fn evm_chain_id(&self) -> Option<u64> {
+        match self {
+            Blockchain::Eth => Some(1),
+            Blockchain::EthGoerli => Some(5),
+            Blockchain::EthSepolia => Some(11155111),
+            Blockchain::Avax => Some(43114),
+            Blockchain::AvaxFuji => Some(43113),
+            Blockchain::Matic => Some(137),
+            Blockchain::MaticMumbai => Some(80001),
+            Blockchain::MaticAmoy => Some(80002),
+            Blockchain::Arb => Some(42161),
+            Blockchain::ArbSepolia => Some(421614),
+            Blockchain::Base => Some(8453),
+            Blockchain::BaseSepolia => Some(84532),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "strict")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unknown_blockchain() {
+        let blockchain: Blockchain = serde_json::from_str("\"APTOS-TESTNET\"").unwrap();
+        assert_eq!(blockchain, Blockchain::Unknown("APTOS-TESTNET".to_string()));
+        assert_eq!(
+            serde_json::to_string(&blockchain).unwrap(),
+            "\"APTOS-TESTNET\""
+        );
+
+        let blockchain: Blockchain = serde_json::from_str("\"MATIC-MUMBAI\"").unwrap();
+        assert_eq!(blockchain, Blockchain::MaticMumbai);
+
+        let blockchain: Blockchain = serde_json::from_str("\"ARB-SEPOLIA\"").unwrap();
+        assert_eq!(blockchain, Blockchain::ArbSepolia);
+        assert!(blockchain.is_testnet());
+        assert!(Blockchain::Unknown("APTOS-TESTNET".to_string()).is_testnet());
+        assert!(!Blockchain::Sol.is_testnet());
+    }
 }
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::{Currency, Money};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BusinessBalancesResponse {
+    pub available: Vec<Money>,
+    pub unsettled: Vec<Money>,
+}
+
+impl BusinessBalancesResponse {
+    pub fn available_in(&self, currency: Currency) -> Option<&Money> {
+        self.available.iter().find(|x| x.currency == currency)
+    }
+
+    pub fn unsettled_in(&self, currency: Currency) -> Option<&Money> {
+        self.unsettled.iter().find(|x| x.currency == currency)
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::extra::Extra;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CctpMessagesResponse {
+    pub messages: Vec<CctpMessage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CctpMessage {
+    pub attestation: String,
+    pub message: String,
+    pub event_nonce: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl CctpMessage {
+    /// Iris reports `PENDING` in place of the attestation until the burn reaches finality.
+    pub fn is_attested(&self) -> bool {
+        self.attestation.starts_with("0x") && self.message.starts_with("0x")
+    }
+}
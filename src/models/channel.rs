@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::extra::Extra;
+use crate::models::money::Currency;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelType {
+    Card,
+    Ach,
+    Wire,
+    Sepa,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelCapabilities {
+    #[serde(default)]
+    pub payments: bool,
+    #[serde(default)]
+    pub payouts: bool,
+    #[serde(default)]
+    pub refunds: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelLimit {
+    pub currency: Currency,
+    pub min_amount: Option<String>,
+    pub max_amount: Option<String>,
+    pub daily_amount: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Channel {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub channel_type: ChannelType,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub capabilities: ChannelCapabilities,
+    #[serde(default)]
+    pub limits: Vec<ChannelLimit>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Channel {
+    pub fn limit_for(&self, currency: Currency) -> Option<&ChannelLimit> {
+        self.limits.iter().find(|x| x.currency == currency)
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::extra::Extra;
+use crate::models::money::Money;
+use crate::models::pagination::{list_query_params, PageCursor, PageParams};
+use crate::models::time_range::TimeRange;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ChargebackCategory {
+    #[serde(rename = "Canceled Recurring Payment")]
+    CanceledRecurringPayment,
+    #[serde(rename = "Customer Dispute")]
+    CustomerDispute,
+    #[serde(rename = "Fraudulent")]
+    Fraudulent,
+    #[serde(rename = "General")]
+    General,
+    #[serde(rename = "Processing Error")]
+    ProcessingError,
+    #[serde(rename = "Not Defined")]
+    NotDefined,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ChargebackHistoryType {
+    #[serde(rename = "First Chargeback")]
+    FirstChargeback,
+    #[serde(rename = "Second Chargeback")]
+    SecondChargeback,
+    #[serde(rename = "Chargeback Reversal")]
+    ChargebackReversal,
+    #[serde(rename = "Representment")]
+    Representment,
+    #[serde(rename = "Chargeback Settlement")]
+    ChargebackSettlement,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChargebackHistory {
+    #[serde(rename = "type")]
+    pub history_type: ChargebackHistoryType,
+    pub amount: Money,
+    pub fee: Option<Money>,
+    pub description: Option<String>,
+    pub settlement_id: Option<Uuid>,
+    pub create_date: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Chargeback {
+    pub id: Uuid,
+    pub payment_id: Uuid,
+    pub merchant_id: Option<Uuid>,
+    pub reason_code: Option<String>,
+    pub category: Option<ChargebackCategory>,
+    pub history: Vec<ChargebackHistory>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl PageCursor for Chargeback {
+    fn cursor(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Chargeback {
+    pub fn latest_history(&self) -> Option<&ChargebackHistory> {
+        self.history.iter().max_by_key(|x| x.create_date)
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChargebackListQueryParams {
+    payment_id: Option<Uuid>,
+    #[serde(flatten)]
+    time_range: TimeRange,
+    #[serde(flatten)]
+    pagination: PageParams,
+}
+
+list_query_params!(ChargebackListQueryParams);
+
+impl ChargebackListQueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn payment_id(mut self, value: Uuid) -> Self {
+        self.payment_id = Some(value);
+        self
+    }
+
+    pub fn time_range(mut self, value: TimeRange) -> Self {
+        self.time_range = value;
+        self
+    }
+
+    pub fn pagination(mut self, value: PageParams) -> Self {
+        self.pagination = value;
+        self
+    }
+}
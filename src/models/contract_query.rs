@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::blockchain::Blockchain;
+use crate::models::extra::Extra;
+use crate::models::web3_address::Web3Address;
+
+/// A read-only call of a contract function, e.g. `balanceOf(address)`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractQueryRequest {
+    blockchain: Blockchain,
+    address: Web3Address,
+    abi_function_signature: String,
+    abi_parameters: Vec<Value>,
+}
+
+impl ContractQueryRequest {
+    pub fn new<S: Into<String>>(
+        blockchain: Blockchain,
+        address: Web3Address,
+        abi_function_signature: S,
+    ) -> Self {
+        ContractQueryRequest {
+            blockchain,
+            address,
+            abi_function_signature: abi_function_signature.into(),
+            abi_parameters: Vec::new(),
+        }
+    }
+
+    pub fn abi_parameters(mut self, abi_parameters: Vec<Value>) -> Self {
+        self.abi_parameters = abi_parameters;
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractQueryResponse {
+    /// The decoded return values.
+    pub output_values: Option<Vec<Value>>,
+    /// The ABI-encoded return data, `0x`-prefixed.
+    pub output_data: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::extra::Extra;
+use crate::models::money::{Currency, Money};
+use crate::models::pagination::{list_query_params, PageCursor, PageParams};
+use crate::models::time_range::TimeRange;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversionStatus {
+    Pending,
+    Complete,
+    Failed,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+impl ConversionStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ConversionStatus::Complete | ConversionStatus::Failed)
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionCreateRequest {
+    idempotency_key: Uuid,
+    from: Money,
+    to: ConversionTarget,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionTarget {
+    pub currency: Currency,
+}
+
+pub struct ConversionCreateRequestBuilder {
+    from: Money,
+    to: Currency,
+}
+
+impl ConversionCreateRequestBuilder {
+    pub fn new(from: Money, to: Currency) -> Self {
+        ConversionCreateRequestBuilder { from, to }
+    }
+
+    pub fn build(self, idempotency_key: Uuid) -> ConversionCreateRequest {
+        ConversionCreateRequest {
+            idempotency_key,
+            from: self.from,
+            to: ConversionTarget { currency: self.to },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversion {
+    pub id: Uuid,
+    pub from: Money,
+    pub to: Money,
+    pub rate: Option<String>,
+    pub status: ConversionStatus,
+    pub error_code: Option<String>,
+    pub create_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl PageCursor for Conversion {
+    fn cursor(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionListQueryParams {
+    status: Option<ConversionStatus>,
+    #[serde(flatten)]
+    time_range: TimeRange,
+    #[serde(flatten)]
+    pagination: PageParams,
+}
+
+list_query_params!(ConversionListQueryParams);
+
+impl ConversionListQueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(mut self, value: ConversionStatus) -> Self {
+        self.status = Some(value);
+        self
+    }
+
+    pub fn time_range(mut self, value: TimeRange) -> Self {
+        self.time_range = value;
+        self
+    }
+
+    pub fn pagination(mut self, value: PageParams) -> Self {
+        self.pagination = value;
+        self
+    }
+}
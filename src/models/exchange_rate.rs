@@ -0,0 +1,109 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::CircleError;
+use crate::models::extra::Extra;
+use crate::models::money::Currency;
+
+/// A currency pair such as `EUR-USD`, where one unit of `base` is worth `rate` units of `quote`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CurrencyPair {
+    pub base: Currency,
+    pub quote: Currency,
+}
+
+impl CurrencyPair {
+    pub fn new(base: Currency, quote: Currency) -> Self {
+        CurrencyPair { base, quote }
+    }
+
+    pub fn inverse(&self) -> Self {
+        CurrencyPair {
+            base: self.quote,
+            quote: self.base,
+        }
+    }
+}
+
+impl Display for CurrencyPair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.base, self.quote)
+    }
+}
+
+impl FromStr for CurrencyPair {
+    type Err = CircleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (base, quote) = s.split_once('-').ok_or(CircleError::ValueError)?;
+        Ok(CurrencyPair {
+            base: base.parse()?,
+            quote: quote.parse()?,
+        })
+    }
+}
+
+impl Serialize for CurrencyPair {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for CurrencyPair {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value
+            .parse()
+            .map_err(|_| serde::de::Error::custom("Invalid currency pair"))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeRate {
+    pub pair: CurrencyPair,
+    pub rate: Decimal,
+    pub update_date: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl ExchangeRate {
+    /// Converts an amount denominated in the pair's base currency into the quote currency.
+    pub fn convert(&self, amount: Decimal) -> Option<Decimal> {
+        amount.checked_mul(self.rate)
+    }
+
+    /// Converts an amount denominated in the pair's quote currency back into the base currency.
+    pub fn convert_inverse(&self, amount: Decimal) -> Option<Decimal> {
+        amount.checked_div(self.rate)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_exchange_rate() {
+        let json =
+            "{\"pair\":\"EUR-USD\",\"rate\":\"1.0854\",\"updateDate\":\"2023-11-25T14:26:38Z\"}";
+        let rate = serde_json::from_str::<ExchangeRate>(json).unwrap();
+        assert_eq!(rate.pair, CurrencyPair::new(Currency::Eur, Currency::Usd));
+        assert_eq!(
+            rate.convert(Decimal::from(100)),
+            Some(Decimal::from_str("108.54").unwrap())
+        );
+        assert_eq!(rate.pair.inverse().to_string(), "USD-EUR");
+    }
+}
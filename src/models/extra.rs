@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// Response fields Circle sent that this version of the crate doesn't model yet, keyed by
+/// their JSON name. Always empty with the `strict` feature, which rejects them instead.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Extra(HashMap<String, Value>);
+
+impl Extra {
+    pub fn into_inner(self) -> HashMap<String, Value> {
+        self.0
+    }
+}
+
+impl Deref for Extra {
+    type Target = HashMap<String, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Extra {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = HashMap::<String, Value>::deserialize(deserializer)?;
+        #[cfg(feature = "strict")]
+        if !fields.is_empty() {
+            let mut names = fields.keys().map(String::as_str).collect::<Vec<_>>();
+            names.sort_unstable();
+            return Err(serde::de::Error::custom(format!(
+                "unknown fields: {}",
+                names.join(", ")
+            )));
+        }
+        Ok(Extra(fields))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    struct Resource {
+        id: String,
+        #[serde(flatten)]
+        extra: Extra,
+    }
+
+    #[test]
+    fn test_extra_fields() {
+        let body = r#"{"id":"a","riskScore":7}"#;
+        let resource = serde_json::from_str::<Resource>(body);
+        if cfg!(feature = "strict") {
+            assert!(resource.is_err());
+        } else {
+            let resource = resource.unwrap();
+            assert_eq!(resource.id, "a");
+            assert_eq!(resource.extra.get("riskScore"), Some(&Value::from(7)));
+            assert!(!resource.extra.contains_key("id"));
+        }
+    }
+}
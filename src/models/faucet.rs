@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+use crate::models::blockchain::Blockchain;
+use crate::models::web3_address::Web3Address;
+
+/// Which testnet tokens to drip to `address`. Circle sends nothing for tokens left unset.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FaucetRequest {
+    address: Web3Address,
+    blockchain: Blockchain,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    native: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usdc: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eurc: Option<bool>,
+}
+
+impl FaucetRequest {
+    pub fn new(address: Web3Address, blockchain: Blockchain) -> Self {
+        FaucetRequest {
+            address,
+            blockchain,
+            native: None,
+            usdc: None,
+            eurc: None,
+        }
+    }
+
+    pub fn native(mut self) -> Self {
+        self.native = Some(true);
+        self
+    }
+
+    pub fn usdc(mut self) -> Self {
+        self.usdc = Some(true);
+        self
+    }
+
+    pub fn eurc(mut self) -> Self {
+        self.eurc = Some(true);
+        self
+    }
+}
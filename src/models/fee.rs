@@ -0,0 +1,106 @@
+use std::fmt::{Display, Formatter};
+
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeeLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl Display for FeeLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            FeeLevel::Low => "LOW",
+            FeeLevel::Medium => "MEDIUM",
+            FeeLevel::High => "HIGH",
+        };
+        f.write_str(text)
+    }
+}
+
+/// How the network fee of a transaction is set: a level Circle prices for you, or explicit gas
+/// settings. Prices are in gwei.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeConfig {
+    Level(FeeLevel),
+    /// Legacy pricing, for chains without EIP-1559.
+    GasPrice {
+        gas_limit: u64,
+        gas_price: f64,
+    },
+    /// EIP-1559 pricing.
+    Eip1559 {
+        gas_limit: u64,
+        max_fee: f64,
+        priority_fee: f64,
+    },
+}
+
+impl From<FeeLevel> for FeeConfig {
+    fn from(fee_level: FeeLevel) -> Self {
+        FeeConfig::Level(fee_level)
+    }
+}
+
+/// Written as the request fields Circle expects, so it is meant to be flattened into a request.
+impl Serialize for FeeConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        match self {
+            FeeConfig::Level(fee_level) => map.serialize_entry("feeLevel", fee_level)?,
+            FeeConfig::GasPrice {
+                gas_limit,
+                gas_price,
+            } => {
+                map.serialize_entry("gasLimit", gas_limit)?;
+                map.serialize_entry("gasPrice", gas_price)?;
+            }
+            FeeConfig::Eip1559 {
+                gas_limit,
+                max_fee,
+                priority_fee,
+            } => {
+                map.serialize_entry("gasLimit", gas_limit)?;
+                map.serialize_entry("maxFee", max_fee)?;
+                map.serialize_entry("priorityFee", priority_fee)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_fee_config_fields() {
+        #[derive(Serialize)]
+        struct Request {
+            #[serde(flatten)]
+            fee: Option<FeeConfig>,
+        }
+        let fields = |fee| serde_json::to_value(Request { fee }).unwrap();
+        assert_eq!(fields(None), json!({}));
+        assert_eq!(
+            fields(Some(FeeLevel::High.into())),
+            json!({ "feeLevel": "HIGH" })
+        );
+        let fee = FeeConfig::Eip1559 {
+            gas_limit: 21000,
+            max_fee: 40.0,
+            priority_fee: 2.0,
+        };
+        assert_eq!(
+            fields(Some(fee)),
+            json!({ "gasLimit": 21000, "maxFee": 40.0, "priorityFee": 2.0 })
+        );
+        assert_eq!(FeeLevel::Medium.to_string(), "MEDIUM");
+    }
+}
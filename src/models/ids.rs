@@ -0,0 +1,86 @@
+//! Distinct types for the ids of different resources, so that e.g. a wallet set id can't be
+//! passed where a wallet id is expected. Each serializes exactly like the [`Uuid`] it wraps
+//! and converts to and from one with `From`.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+macro_rules! uuid_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub Uuid);
+
+        impl $name {
+            pub fn as_uuid(&self) -> Uuid {
+                self.0
+            }
+        }
+
+        impl From<Uuid> for $name {
+            fn from(value: Uuid) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for Uuid {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<Uuid> for $name {
+            fn eq(&self, other: &Uuid) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = uuid::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Uuid::from_str(s).map($name)
+            }
+        }
+    };
+}
+
+uuid_id!(WalletId);
+uuid_id!(WalletSetId);
+uuid_id!(
+    /// A token as Circle knows it, not its contract address.
+    TokenId
+);
+uuid_id!(TransactionId);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ids() {
+        let uuid = Uuid::new_v4();
+        let wallet_id = WalletId::from(uuid);
+        assert_eq!(wallet_id, uuid);
+        assert_eq!(Uuid::from(wallet_id), uuid);
+        assert_eq!(wallet_id.to_string(), uuid.to_string());
+        assert_eq!(WalletId::from_str(&uuid.to_string()).unwrap(), wallet_id);
+        assert_eq!(
+            serde_json::to_value(wallet_id).unwrap(),
+            serde_json::to_value(uuid).unwrap()
+        );
+        assert!(WalletSetId::from_str("not a uuid").is_err());
+    }
+}
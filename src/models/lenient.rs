@@ -0,0 +1,151 @@
+//! Deserializers for amounts, which Circle sends as strings on most endpoints and as numbers on
+//! some, with `""` now and then where there's no value.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+
+struct AmountVisitor;
+
+impl<'de> Visitor<'de> for AmountVisitor {
+    type Value = Option<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an amount as a string or number")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let v = v.trim();
+        Ok((!v.is_empty()).then(|| v.to_string()))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Some(v.to_string()))
+    }
+
+    /// Rust prints floats as the shortest decimal that reads back as the same float, so `0.1`
+    /// stays `"0.1"`.
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        match v.is_finite() {
+            true => Ok(Some(v.to_string())),
+            false => Err(E::invalid_value(de::Unexpected::Float(v), &self)),
+        }
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        d.deserialize_any(self)
+    }
+}
+
+/// A string or number, as a string. `null` and `""` become `""`.
+#[cfg(feature = "payments")]
+pub(crate) fn string<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    Ok(option_string(d)?.unwrap_or_default())
+}
+
+/// A string or number, as a string. `null` and `""` become `None`.
+pub(crate) fn option_string<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    d.deserialize_any(AmountVisitor)
+}
+
+/// A string or number, as a decimal. `null` and `""` become zero.
+pub(crate) fn decimal<'de, D: Deserializer<'de>>(d: D) -> Result<Decimal, D::Error> {
+    Ok(option_decimal(d)?.unwrap_or_default())
+}
+
+/// A string or number, as a decimal. `null` and `""` become `None`.
+pub(crate) fn option_decimal<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Decimal>, D::Error> {
+    match option_string(d)? {
+        Some(x) => Decimal::from_str(&x)
+            .or_else(|_| Decimal::from_scientific(&x))
+            .map(Some)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&x), &"a decimal amount")),
+        None => Ok(None),
+    }
+}
+
+/// A list of strings or numbers, as decimals. Every item has to be an amount.
+pub(crate) fn option_decimals<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<Vec<Decimal>>, D::Error> {
+    #[derive(Deserialize)]
+    struct Amount(#[serde(deserialize_with = "decimal")] Decimal);
+
+    let amounts = Option::<Vec<Amount>>::deserialize(d)?;
+    Ok(amounts.map(|x| x.into_iter().map(|x| x.0).collect()))
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+    use serde::Serialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Amounts {
+        #[serde(default, deserialize_with = "option_decimal")]
+        fee: Option<Decimal>,
+        #[serde(default, deserialize_with = "option_decimals")]
+        amounts: Option<Vec<Decimal>>,
+    }
+
+    fn decimal() -> impl Strategy<Value = Decimal> {
+        (any::<i64>(), 0..=18u32).prop_map(|(mantissa, scale)| Decimal::new(mantissa, scale))
+    }
+
+    proptest! {
+        #[test]
+        fn test_decimal_round_trip(fee in decimal()) {
+            let amounts = Amounts { fee: Some(fee), amounts: Some(vec![fee]) };
+            let json = serde_json::to_value(&amounts).unwrap();
+            prop_assert_eq!(serde_json::from_value::<Amounts>(json).unwrap(), amounts);
+        }
+
+        #[test]
+        fn test_numbers_read_as_decimals(mantissa in any::<i64>(), fraction in 0..1000u32) {
+            let json = json!({ "fee": mantissa, "amounts": [mantissa, fraction as f64 / 8.0] });
+            let amounts = serde_json::from_value::<Amounts>(json).unwrap();
+            prop_assert_eq!(amounts.fee, Some(Decimal::from(mantissa)));
+            let parsed = amounts.amounts.unwrap();
+            prop_assert_eq!(parsed[0], Decimal::from(mantissa));
+            prop_assert_eq!(parsed[1], Decimal::from(fraction) / Decimal::from(8));
+        }
+    }
+
+    #[test]
+    fn test_empty_amounts() {
+        let amounts: Amounts =
+            serde_json::from_value(json!({ "fee": "", "amounts": null })).unwrap();
+        assert_eq!(
+            amounts,
+            Amounts {
+                fee: None,
+                amounts: None
+            }
+        );
+        let amounts: Amounts = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(amounts.fee, None);
+        let amounts: Amounts = serde_json::from_value(json!({ "fee": 1e-7 })).unwrap();
+        assert_eq!(amounts.fee, Some(Decimal::new(1, 7)));
+        assert!(serde_json::from_value::<Amounts>(json!({ "fee": "twelve" })).is_err());
+        assert!(serde_json::from_value::<Amounts>(json!({ "fee": true })).is_err());
+        assert!(serde_json::from_value::<Amounts>(json!({ "amounts": ["1", "x"] })).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::money::Money;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockBeneficiaryBank {
+    pub account_number: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MockWirePaymentRequest {
+    tracking_ref: String,
+    amount: Money,
+    beneficiary_bank: MockBeneficiaryBank,
+}
+
+impl MockWirePaymentRequest {
+    pub fn new<S: Into<String>>(
+        tracking_ref: S,
+        amount: Money,
+        beneficiary_bank: MockBeneficiaryBank,
+    ) -> Self {
+        MockWirePaymentRequest {
+            tracking_ref: tracking_ref.into(),
+            amount,
+            beneficiary_bank,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockWirePaymentResponse {
+    pub tracking_ref: String,
+    pub amount: Money,
+    pub beneficiary_bank: MockBeneficiaryBank,
+    pub status: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MockChargebackRequest {
+    payment_id: Uuid,
+}
+
+impl MockChargebackRequest {
+    pub fn new(payment_id: Uuid) -> Self {
+        MockChargebackRequest { payment_id }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockAchAccountDetails {
+    pub account_number: String,
+    pub routing_number: String,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MockAchAccountRequest {
+    account: MockAchAccountDetails,
+    balance: Money,
+}
+
+impl MockAchAccountRequest {
+    pub fn new(account: MockAchAccountDetails, balance: Money) -> Self {
+        MockAchAccountRequest { account, balance }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockAchAccountResponse {
+    pub account: MockAchAccountDetails,
+    pub balance: Money,
+    pub processor_token: String,
+}
@@ -1,30 +1,94 @@
 use uuid::Uuid;
 
+pub mod account_type;
+#[cfg(feature = "payments")]
+pub mod ach_bank_account;
 pub mod auth;
+#[cfg(feature = "payments")]
+pub mod bank_account;
 pub mod blockchain;
+#[cfg(feature = "payments")]
+pub mod business_balance;
+#[cfg(feature = "contracts")]
+pub mod cctp;
+#[cfg(feature = "payments")]
+pub mod channel;
+#[cfg(feature = "payments")]
+pub mod chargeback;
+#[cfg(feature = "contracts")]
+pub mod contract_query;
+#[cfg(feature = "payments")]
+pub mod conversion;
 pub mod custody_type;
+pub mod entity_config;
+#[cfg(feature = "payments")]
+pub mod exchange_rate;
+pub mod extra;
+#[cfg(feature = "sandbox")]
+pub mod faucet;
+pub mod fee;
+pub mod ids;
+pub(crate) mod lenient;
+#[cfg(all(feature = "sandbox", feature = "payments"))]
+pub mod mock;
+#[cfg(feature = "payments")]
+pub mod money;
+#[cfg(feature = "webhooks")]
+pub mod notification;
 mod operation;
 pub mod pagination;
+#[cfg(feature = "payments")]
+pub mod payment;
+#[cfg(feature = "payments")]
+pub mod payout;
+#[cfg(feature = "payments")]
+pub mod payout_return;
 pub mod public_key;
+#[cfg(feature = "transactions")]
+pub mod refund;
+#[cfg(feature = "payments")]
+pub mod sepa_bank_account;
+#[cfg(feature = "payments")]
+pub mod settlement;
+#[cfg(feature = "wallets")]
 pub mod signing_message;
+#[cfg(feature = "webhooks")]
+pub mod subscription;
 pub mod time_range;
+pub mod token_amount;
 pub mod token_get;
 pub mod token_info;
 pub mod transaction;
+#[cfg(feature = "transactions")]
 pub mod transaction_accelerate;
+#[cfg(feature = "transactions")]
 pub mod transaction_cancel;
+#[cfg(feature = "contracts")]
+pub mod transaction_contract_execution;
+#[cfg(feature = "transactions")]
 pub mod transaction_get;
+#[cfg(feature = "transactions")]
 pub mod transaction_list;
 pub mod transaction_state;
 pub mod transaction_transfer_create;
+pub(crate) mod validation;
+#[cfg(feature = "wallets")]
 pub mod wallet_balance;
+#[cfg(feature = "wallets")]
 pub mod wallet_create;
+#[cfg(feature = "wallets")]
 pub mod wallet_detail;
+#[cfg(feature = "wallets")]
 pub mod wallet_get;
+#[cfg(feature = "wallets")]
 pub mod wallet_list;
+#[cfg(feature = "wallets")]
 pub mod wallet_nfts;
+#[cfg(feature = "wallets")]
 mod wallet_objects;
+#[cfg(feature = "wallets")]
 pub mod wallet_set;
+#[cfg(feature = "wallets")]
 pub mod wallet_update;
 pub mod web3_address;
 pub mod web3_signature;
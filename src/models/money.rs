@@ -0,0 +1,59 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CircleError;
+use crate::models::lenient;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    Usd,
+    Eur,
+    Usdc,
+    Eurc,
+}
+
+impl Currency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Usdc => "USDC",
+            Currency::Eurc => "EURC",
+        }
+    }
+
+    pub fn is_stablecoin(&self) -> bool {
+        matches!(self, Currency::Usdc | Currency::Eurc)
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = CircleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "USD" => Ok(Currency::Usd),
+            "EUR" => Ok(Currency::Eur),
+            "USDC" => Ok(Currency::Usdc),
+            "EURC" => Ok(Currency::Eurc),
+            _ => Err(CircleError::ValueError),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Money {
+    #[serde(deserialize_with = "lenient::string")]
+    pub amount: String,
+    pub currency: Currency,
+}
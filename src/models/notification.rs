@@ -0,0 +1,119 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::Result;
+#[cfg(feature = "payments")]
+use crate::models::chargeback::Chargeback;
+#[cfg(feature = "payments")]
+use crate::models::payment::Payment;
+#[cfg(feature = "payments")]
+use crate::models::payout::Payout;
+#[cfg(feature = "payments")]
+use crate::models::payout_return::PayoutReturn;
+#[cfg(feature = "payments")]
+use crate::models::settlement::Settlement;
+use crate::models::transaction::Transaction;
+
+/// A webhook payload from either the Web3 Services or the payments product family.
+/// Payments events are only told apart with the `payments` feature, and come out as
+/// [`NotificationEvent::Unknown`] without it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationEvent {
+    TransactionInbound(Box<Transaction>),
+    TransactionOutbound(Box<Transaction>),
+    #[cfg(feature = "payments")]
+    Payment(Box<Payment>),
+    #[cfg(feature = "payments")]
+    Payout(Box<Payout>),
+    #[cfg(feature = "payments")]
+    Settlement(Box<Settlement>),
+    #[cfg(feature = "payments")]
+    Chargeback(Box<Chargeback>),
+    #[cfg(feature = "payments")]
+    Return(Box<PayoutReturn>),
+    Unknown {
+        notification_type: String,
+        payload: Value,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct NotificationEnvelope {
+    notification_type: String,
+    #[serde(flatten)]
+    rest: serde_json::Map<String, Value>,
+}
+
+impl NotificationEvent {
+    pub fn parse(body: &str) -> Result<Self> {
+        let mut envelope = serde_json::from_str::<NotificationEnvelope>(body)?;
+        let mut take = |key: &str| envelope.rest.remove(key).unwrap_or(Value::Null);
+        let event = match envelope.notification_type.as_str() {
+            "transactions.inbound" => {
+                Self::TransactionInbound(serde_json::from_value(take("notification"))?)
+            }
+            "transactions.outbound" => {
+                Self::TransactionOutbound(serde_json::from_value(take("notification"))?)
+            }
+            #[cfg(feature = "payments")]
+            "payments" => Self::Payment(serde_json::from_value(take("payment"))?),
+            #[cfg(feature = "payments")]
+            "payouts" => Self::Payout(serde_json::from_value(take("payout"))?),
+            #[cfg(feature = "payments")]
+            "settlements" => Self::Settlement(serde_json::from_value(take("settlement"))?),
+            #[cfg(feature = "payments")]
+            "chargebacks" => Self::Chargeback(serde_json::from_value(take("chargeback"))?),
+            #[cfg(feature = "payments")]
+            "returns" => Self::Return(serde_json::from_value(take("return"))?),
+            _ => Self::Unknown {
+                notification_type: envelope.notification_type,
+                payload: Value::Object(envelope.rest),
+            },
+        };
+        Ok(event)
+    }
+
+    pub fn notification_type(&self) -> &str {
+        match self {
+            Self::TransactionInbound(_) => "transactions.inbound",
+            Self::TransactionOutbound(_) => "transactions.outbound",
+            #[cfg(feature = "payments")]
+            Self::Payment(_) => "payments",
+            #[cfg(feature = "payments")]
+            Self::Payout(_) => "payouts",
+            #[cfg(feature = "payments")]
+            Self::Settlement(_) => "settlements",
+            #[cfg(feature = "payments")]
+            Self::Chargeback(_) => "chargebacks",
+            #[cfg(feature = "payments")]
+            Self::Return(_) => "returns",
+            Self::Unknown {
+                notification_type, ..
+            } => notification_type,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_payout_notification() {
+        let json = "{\"clientId\":\"c60d2d5b-203c-45bb-9f6e-93641d40a599\",\"notificationType\":\"payouts\",\"version\":1,\"payout\":{\"id\":\"b8627ae8-732b-4d25-b947-1df8f4007a29\",\"sourceWalletId\":\"1000066041\",\"destination\":{\"type\":\"wire\",\"id\":\"4b2a6f73-6f7d-4b1b-a7be-bf4d7a6bd5a2\",\"name\":\"WELLS FARGO BANK, NA ****0010\"},\"amount\":{\"amount\":\"3.14\",\"currency\":\"USD\"},\"fees\":{\"amount\":\"25.00\",\"currency\":\"USD\"},\"status\":\"complete\",\"trackingRef\":\"CIR13FB13A\",\"createDate\":\"2023-11-25T14:26:38Z\",\"updateDate\":\"2023-11-25T14:26:38Z\"}}";
+        let event = NotificationEvent::parse(json).unwrap();
+        assert_eq!(event.notification_type(), "payouts");
+        match event {
+            NotificationEvent::Payout(payout) => assert_eq!(payout.amount.amount, "3.14"),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_unknown_notification() {
+        let json = "{\"notificationType\":\"challenges.initialize\",\"notification\":{}}";
+        let event = NotificationEvent::parse(json).unwrap();
+        assert_eq!(event.notification_type(), "challenges.initialize");
+    }
+}
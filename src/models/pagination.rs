@@ -1,18 +1,45 @@
-use serde::Serialize;
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use serde::de::{IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use uuid::Uuid;
 
 use crate::error::CircleError;
 use crate::error::Result;
+use crate::models::time_range::TimeRange;
+
+/// The largest `pageSize` Circle accepts on list endpoints.
+pub const MAX_PAGE_SIZE: u8 = 50;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
 
-#[derive(Serialize, Debug, Default)]
+/// Cursor, size and ordering parameters shared by every list endpoint. Items come back newest
+/// first unless `order` says otherwise; `page_after` always continues in list order and
+/// `page_before` goes back.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-pub struct Pagination {
+pub struct PageParams {
     page_before: Option<Uuid>,
     page_after: Option<Uuid>,
     page_size: Option<u8>,
+    order: Option<SortOrder>,
 }
 
-impl Pagination {
+#[deprecated(note = "renamed to `PageParams`")]
+pub type Pagination = PageParams;
+
+impl PageParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
     pub fn page_before(mut self, value: Uuid) -> Result<Self> {
         if self.page_after.is_some() {
             Err(CircleError::ValueError)?
@@ -29,8 +56,265 @@ impl Pagination {
         Ok(self)
     }
 
-    pub fn page_size(mut self, value: u8) -> Self {
+    /// Fails unless `value` is between 1 and [`MAX_PAGE_SIZE`].
+    pub fn page_size(mut self, value: u8) -> Result<Self> {
+        if !(1..=MAX_PAGE_SIZE).contains(&value) {
+            Err(CircleError::Validation(vec![format!(
+                "pageSize must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )]))?;
+        }
         self.page_size = Some(value);
+        Ok(self)
+    }
+
+    pub fn order(mut self, value: SortOrder) -> Self {
+        self.order = Some(value);
         self
     }
+
+    pub fn size(&self) -> Option<u8> {
+        self.page_size
+    }
+
+    pub fn sort_order(&self) -> Option<SortOrder> {
+        self.order
+    }
+}
+
+/// Query parameters of a list endpoint, which the `*_stream` helpers page through.
+pub trait ListQueryParams: Clone {
+    fn page_params(&self) -> &PageParams;
+
+    fn set_page_params(&mut self, page: PageParams);
+
+    fn window(&self) -> &TimeRange;
+
+    fn set_window(&mut self, window: TimeRange);
+}
+
+#[cfg(any(feature = "wallets", feature = "transactions", feature = "payments"))]
+macro_rules! list_query_params {
+    ($ty:ty) => {
+        impl $crate::models::pagination::ListQueryParams for $ty {
+            fn page_params(&self) -> &$crate::models::pagination::PageParams {
+                &self.pagination
+            }
+
+            fn set_page_params(&mut self, page: $crate::models::pagination::PageParams) {
+                self.pagination = page;
+            }
+
+            fn window(&self) -> &$crate::models::time_range::TimeRange {
+                &self.time_range
+            }
+
+            fn set_window(&mut self, window: $crate::models::time_range::TimeRange) {
+                self.time_range = window;
+            }
+        }
+
+        impl $ty {
+            /// Shorthand for setting the page size on [`Self::pagination`].
+            pub fn page_size(mut self, value: u8) -> $crate::error::Result<Self> {
+                self.pagination = self.pagination.page_size(value)?;
+                Ok(self)
+            }
+
+            pub fn order(mut self, value: $crate::models::pagination::SortOrder) -> Self {
+                self.pagination = self.pagination.order(value);
+                self
+            }
+        }
+    };
+}
+#[cfg(any(feature = "wallets", feature = "transactions", feature = "payments"))]
+pub(crate) use list_query_params;
+
+/// Items that can be used as a page cursor.
+pub trait PageCursor {
+    fn cursor(&self) -> Uuid;
+}
+
+/// One page of a list endpoint. Derefs to the items, and deserializes from both shapes Circle
+/// uses: a bare array and an object wrapping the array under a resource name
+/// (`{"wallets": [...]}`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+}
+
+impl<T> Paginated<T> {
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T: PageCursor> Paginated<T> {
+    /// The `page_after` cursor for the page following this one, `None` if this page is empty.
+    pub fn next_cursor(&self) -> Option<Uuid> {
+        self.items.last().map(PageCursor::cursor)
+    }
+
+    /// The `page_before` cursor for the page preceding this one, `None` if this page is empty.
+    pub fn prev_cursor(&self) -> Option<Uuid> {
+        self.items.first().map(PageCursor::cursor)
+    }
+
+    /// `params` moved on to the next page, keeping its page size.
+    pub fn next_page(&self, params: &PageParams) -> Option<PageParams> {
+        let page = PageParams {
+            page_before: None,
+            page_after: self.next_cursor(),
+            page_size: params.page_size,
+            order: params.order,
+        };
+        page.page_after.map(|_| page)
+    }
+
+    /// `params` moved back to the previous page, keeping its page size.
+    pub fn prev_page(&self, params: &PageParams) -> Option<PageParams> {
+        let page = PageParams {
+            page_before: self.prev_cursor(),
+            page_after: None,
+            page_size: params.page_size,
+            order: params.order,
+        };
+        page.page_before.map(|_| page)
+    }
+}
+
+impl<T> Default for Paginated<T> {
+    fn default() -> Self {
+        Paginated { items: Vec::new() }
+    }
+}
+
+impl<T> Deref for Paginated<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<T> IntoIterator for Paginated<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Paginated<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Paginated<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct PageVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for PageVisitor<T> {
+            type Value = Paginated<T>;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                f.write_str("a list or an object wrapping a list")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                seq: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let items = Vec::deserialize(serde::de::value::SeqAccessDeserializer::new(seq))?;
+                Ok(Paginated { items })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let mut items = None;
+                while map.next_key::<String>()?.is_some() {
+                    if items.is_none() {
+                        items = Some(map.next_value()?);
+                    } else {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+                Ok(Paginated {
+                    items: items.unwrap_or_default(),
+                })
+            }
+        }
+
+        deserializer.deserialize_any(PageVisitor(PhantomData))
+    }
+}
+
+/// Serializes as the bare array, which deserializes back into the same page.
+impl<T: Serialize> Serialize for Paginated<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.items.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Item(Uuid);
+
+    impl<'de> Deserialize<'de> for Item {
+        fn deserialize<D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> std::result::Result<Self, D::Error> {
+            Uuid::deserialize(deserializer).map(Item)
+        }
+    }
+
+    impl PageCursor for Item {
+        fn cursor(&self) -> Uuid {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_paginated() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let wrapped = format!(r#"{{"wallets":["{}","{}"]}}"#, a, b);
+        let page = serde_json::from_str::<Paginated<Item>>(&wrapped).unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page.prev_cursor(), Some(a));
+        assert_eq!(page.next_cursor(), Some(b));
+
+        let params = PageParams::new()
+            .page_size(2)
+            .unwrap()
+            .order(SortOrder::Asc);
+        let next = page.next_page(&params).unwrap();
+        assert_eq!(
+            serde_json::to_value(&next).unwrap(),
+            serde_json::json!({"pageBefore": null, "pageAfter": b, "pageSize": 2, "order": "ASC"})
+        );
+        assert_eq!(
+            page.prev_page(&params).unwrap(),
+            params.clone().page_before(a).unwrap()
+        );
+
+        let bare = format!(r#"["{}"]"#, a);
+        let page = serde_json::from_str::<Paginated<Item>>(&bare).unwrap();
+        assert_eq!(page.next_cursor(), Some(a));
+
+        let empty = serde_json::from_str::<Paginated<Item>>("{}").unwrap();
+        assert!(empty.next_page(&params).is_none());
+
+        assert!(PageParams::new().page_size(0).is_err());
+        assert!(PageParams::new().page_size(MAX_PAGE_SIZE + 1).is_err());
+    }
 }
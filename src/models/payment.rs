@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::extra::Extra;
+use crate::models::money::Money;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    Pending,
+    Confirmed,
+    Paid,
+    ActionRequired,
+    Failed,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Payment {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub payment_type: String,
+    pub merchant_id: Option<Uuid>,
+    pub merchant_wallet_id: Option<String>,
+    pub amount: Money,
+    pub fees: Option<Money>,
+    pub status: PaymentStatus,
+    pub tracking_ref: Option<String>,
+    pub error_code: Option<String>,
+    pub create_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
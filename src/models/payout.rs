@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::extra::Extra;
+use crate::models::money::Money;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PayoutStatus {
+    Pending,
+    Complete,
+    Failed,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutDestination {
+    #[serde(rename = "type")]
+    pub destination_type: String,
+    pub id: Uuid,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Payout {
+    pub id: Uuid,
+    pub source_wallet_id: Option<String>,
+    pub destination: PayoutDestination,
+    pub amount: Money,
+    pub fees: Option<Money>,
+    pub status: PayoutStatus,
+    pub tracking_ref: Option<String>,
+    pub error_code: Option<String>,
+    pub create_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::extra::Extra;
+use crate::models::money::Money;
+use crate::models::pagination::{list_query_params, PageCursor, PageParams};
+use crate::models::time_range::TimeRange;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PayoutReturnStatus {
+    Pending,
+    Complete,
+    Failed,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutReturnReason {
+    AccountClosed,
+    AccountNotFound,
+    InvalidAccountNumber,
+    InvalidBankDetails,
+    BeneficiaryDeceased,
+    RefusedByBeneficiary,
+    AuthorizationRevoked,
+    PaymentStopped,
+    BankProcessingError,
+    ComplianceReturn,
+    Other,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+impl PayoutReturnReason {
+    /// Whether the payout can be resubmitted to the same destination. Reasons that point at
+    /// the destination account itself require new bank details before retrying.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, PayoutReturnReason::BankProcessingError)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutReturn {
+    pub id: Uuid,
+    pub payout_id: Uuid,
+    pub amount: Money,
+    pub fees: Option<Money>,
+    pub reason: PayoutReturnReason,
+    pub status: PayoutReturnStatus,
+    pub create_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl PageCursor for PayoutReturn {
+    fn cursor(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutReturnListQueryParams {
+    #[serde(flatten)]
+    time_range: TimeRange,
+    #[serde(flatten)]
+    pagination: PageParams,
+}
+
+list_query_params!(PayoutReturnListQueryParams);
+
+impl PayoutReturnListQueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn time_range(mut self, value: TimeRange) -> Self {
+        self.time_range = value;
+        self
+    }
+
+    pub fn pagination(mut self, value: PageParams) -> Self {
+        self.pagination = value;
+        self
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::transaction_state::TransactionState;
+use crate::models::transaction_transfer_create::FeeLevel;
+use crate::models::web3_address::Web3Address;
+
+/// How [`crate::api::CircleClient::refund_transaction`] returns funds. By default the full
+/// amount goes back to the address it came from.
+#[derive(Debug, Clone)]
+pub struct RefundOptions {
+    pub(crate) amount: Option<Decimal>,
+    pub(crate) destination: Option<Web3Address>,
+    pub(crate) fee_level: FeeLevel,
+    pub(crate) idempotency_key: Option<Uuid>,
+    pub(crate) poll_interval: Duration,
+    pub(crate) completion_timeout: Duration,
+}
+
+impl Default for RefundOptions {
+    fn default() -> Self {
+        RefundOptions {
+            amount: None,
+            destination: None,
+            fee_level: FeeLevel::Medium,
+            idempotency_key: None,
+            poll_interval: Duration::from_secs(5),
+            completion_timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+impl RefundOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refunds part of the original amount.
+    pub fn amount(mut self, amount: Decimal) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Sends the refund somewhere other than the original sender, e.g. when the payment came
+    /// from an exchange's hot wallet.
+    pub fn destination(mut self, destination: Web3Address) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee_level = fee_level;
+        self
+    }
+
+    /// By default the key is derived from the original transaction and the amount, so retrying
+    /// a refund never sends it twice. Set one explicitly to refund the same amount more than
+    /// once.
+    pub fn idempotency_key(mut self, idempotency_key: Uuid) -> Self {
+        self.idempotency_key = Some(idempotency_key);
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// How long to wait for the refund to complete. Zero returns right after submitting.
+    pub fn completion_timeout(mut self, completion_timeout: Duration) -> Self {
+        self.completion_timeout = completion_timeout;
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Refund {
+    pub original_transaction_id: TransactionId,
+    pub transaction_id: TransactionId,
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub destination: Web3Address,
+    pub amount: Decimal,
+    /// `refund:<original transaction id>`, so the refund can be traced back from Circle's side.
+    pub ref_id: String,
+    pub idempotency_key: Uuid,
+    /// The last state seen; not terminal if the completion timeout ran out first.
+    pub state: TransactionState,
+    pub tx_hash: Option<String>,
+    pub error_reason: Option<String>,
+}
+
+impl Refund {
+    pub fn is_complete(&self) -> bool {
+        self.state == TransactionState::Complete
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(
+            self.state,
+            TransactionState::Failed | TransactionState::Denied | TransactionState::Cancelled
+        )
+    }
+}
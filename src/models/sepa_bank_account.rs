@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::bank_account::{BankAccountStatus, BankAddress, BillingDetails};
+use crate::models::extra::Extra;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SepaBankAccountCreateRequest {
+    idempotency_key: Uuid,
+    iban: String,
+    billing_details: BillingDetails,
+    bank_address: BankAddress,
+}
+
+pub struct SepaBankAccountCreateRequestBuilder {
+    iban: String,
+    billing_details: BillingDetails,
+    bank_address: BankAddress,
+}
+
+impl SepaBankAccountCreateRequestBuilder {
+    pub fn new(iban: String, billing_details: BillingDetails, bank_address: BankAddress) -> Self {
+        SepaBankAccountCreateRequestBuilder {
+            iban,
+            billing_details,
+            bank_address,
+        }
+    }
+
+    pub fn build(self, idempotency_key: Uuid) -> SepaBankAccountCreateRequest {
+        SepaBankAccountCreateRequest {
+            idempotency_key,
+            iban: self.iban,
+            billing_details: self.billing_details,
+            bank_address: self.bank_address,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SepaBankAccount {
+    pub id: Uuid,
+    pub status: BankAccountStatus,
+    pub description: Option<String>,
+    pub tracking_ref: Option<String>,
+    pub fingerprint: Option<String>,
+    pub billing_details: Option<BillingDetails>,
+    pub bank_address: Option<BankAddress>,
+    pub create_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SepaInstructions {
+    pub tracking_ref: String,
+    pub beneficiary: Beneficiary,
+    pub beneficiary_bank: BeneficiaryBank,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Beneficiary {
+    pub name: String,
+    pub address1: Option<String>,
+    pub address2: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BeneficiaryBank {
+    pub name: Option<String>,
+    pub swift_code: Option<String>,
+    pub routing_number: Option<String>,
+    pub account_number: Option<String>,
+    pub currency: Option<String>,
+    pub address: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+}
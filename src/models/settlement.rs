@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::extra::Extra;
+use crate::models::money::Money;
+use crate::models::pagination::{list_query_params, PageCursor, PageParams};
+use crate::models::time_range::TimeRange;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Settlement {
+    pub id: Uuid,
+    pub merchant_wallet_id: Option<String>,
+    pub wallet_id: Option<String>,
+    pub total_debits: Option<Money>,
+    pub total_credits: Option<Money>,
+    pub payment_fees: Option<Money>,
+    pub chargeback_fees: Option<Money>,
+    pub create_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl PageCursor for Settlement {
+    fn cursor(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Settlement {
+    /// Credits minus debits and fees, or `None` if any of the rollups isn't a valid decimal.
+    pub fn net_amount(&self) -> Option<Decimal> {
+        let parse = |money: &Option<Money>| match money {
+            Some(money) => money.amount.parse::<Decimal>().ok(),
+            None => Some(Decimal::ZERO),
+        };
+        parse(&self.total_credits)?
+            .checked_sub(parse(&self.total_debits)?)?
+            .checked_sub(parse(&self.payment_fees)?)?
+            .checked_sub(parse(&self.chargeback_fees)?)
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementListQueryParams {
+    #[serde(flatten)]
+    time_range: TimeRange,
+    #[serde(flatten)]
+    pagination: PageParams,
+}
+
+list_query_params!(SettlementListQueryParams);
+
+impl SettlementListQueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn time_range(mut self, value: TimeRange) -> Self {
+        self.time_range = value;
+        self
+    }
+
+    pub fn pagination(mut self, value: PageParams) -> Self {
+        self.pagination = value;
+        self
+    }
+}
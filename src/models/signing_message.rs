@@ -1,12 +1,12 @@
 use crate::error::{CircleError, Result};
+use crate::models::ids::WalletId;
 use crate::models::web3_signature::Web3Signature;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SignMessageRequest {
-    wallet_id: Uuid,
+    wallet_id: WalletId,
     encoded_by_hex: Option<bool>,
     message: String,
     memo: Option<String>,
@@ -15,7 +15,7 @@ pub struct SignMessageRequest {
 
 #[derive(Debug, Default)]
 pub struct SignMessageRequestBuilder {
-    wallet_id: Option<Uuid>,
+    wallet_id: Option<WalletId>,
     encoded_by_hex: Option<bool>,
     message: Option<String>,
     memo: Option<String>,
@@ -23,7 +23,7 @@ pub struct SignMessageRequestBuilder {
 }
 
 impl SignMessageRequestBuilder {
-    pub fn wallet_id(mut self, wallet_id: Uuid) -> Self {
+    pub fn wallet_id(mut self, wallet_id: WalletId) -> Self {
         self.wallet_id = Some(wallet_id);
         self
     }
@@ -63,7 +63,7 @@ impl SignMessageRequestBuilder {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SignMessageResponse {
     pub signature: Web3Signature,
 }
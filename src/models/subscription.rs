@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::extra::Extra;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionCreateRequest {
+    endpoint: String,
+    notification_types: Option<Vec<String>>,
+}
+
+impl SubscriptionCreateRequest {
+    /// Subscribes `endpoint` to every notification type.
+    pub fn new<S: Into<String>>(endpoint: S) -> Self {
+        SubscriptionCreateRequest {
+            endpoint: endpoint.into(),
+            notification_types: None,
+        }
+    }
+
+    pub fn notification_types(mut self, notification_types: Vec<String>) -> Self {
+        self.notification_types = Some(notification_types);
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub endpoint: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub notification_types: Vec<String>,
+    pub create_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentsSubscriptionCreateRequest {
+    endpoint: String,
+}
+
+impl PaymentsSubscriptionCreateRequest {
+    pub fn new<S: Into<String>>(endpoint: S) -> Self {
+        PaymentsSubscriptionCreateRequest {
+            endpoint: endpoint.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentsSubscriptionDetail {
+    pub url: String,
+    pub status: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentsSubscription {
+    pub id: Uuid,
+    pub endpoint: String,
+    #[serde(default)]
+    pub subscription_details: Vec<PaymentsSubscriptionDetail>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
@@ -1,14 +1,49 @@
-use chrono::{DateTime, Utc};
-use serde::Serialize;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Serialize, Serializer};
 
-#[derive(Serialize, Debug, Default)]
+use crate::error::{CircleError, Result};
+
+/// The `from`/`to` filter of list endpoints. Both ends are inclusive and sent as RFC 3339
+/// timestamps in UTC.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeRange {
+    #[serde(serialize_with = "rfc3339")]
     from: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "rfc3339")]
     to: Option<DateTime<Utc>>,
 }
 
 impl TimeRange {
+    /// Everything created at or after `from`.
+    pub fn since(from: DateTime<Utc>) -> Self {
+        TimeRange::default().from(from)
+    }
+
+    /// Everything created at or before `to`.
+    pub fn until(to: DateTime<Utc>) -> Self {
+        TimeRange::default().to(to)
+    }
+
+    pub fn between(from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Self> {
+        if from > to {
+            Err(CircleError::ValueError)?;
+        }
+        Ok(TimeRange::default().from(from).to(to))
+    }
+
+    pub fn last(duration: Duration) -> Self {
+        Self::since(Utc::now() - duration)
+    }
+
+    pub fn last_hours(hours: i64) -> Self {
+        Self::last(Duration::hours(hours))
+    }
+
+    pub fn last_days(days: i64) -> Self {
+        Self::last(Duration::days(days))
+    }
+
     pub fn from(mut self, value: DateTime<Utc>) -> Self {
         self.from = Some(value);
         self
@@ -18,4 +53,88 @@ impl TimeRange {
         self.to = Some(value);
         self
     }
+
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        self.from
+    }
+
+    pub fn end(&self) -> Option<DateTime<Utc>> {
+        self.to
+    }
+
+    /// Cuts the range into `parts` consecutive, non-overlapping windows, oldest first. An
+    /// open end counts as now; a range without a start can't be cut and is returned whole.
+    pub fn split(&self, parts: usize) -> Vec<TimeRange> {
+        let (Some(from), true) = (self.from, parts > 1) else {
+            return vec![self.clone()];
+        };
+        let to = self.to.unwrap_or_else(Utc::now);
+        let step = (to - from).num_milliseconds() / parts as i64;
+        if step < 1 {
+            return vec![self.clone()];
+        }
+        (0..parts)
+            .map(|i| {
+                let start = from + Duration::milliseconds(step * i as i64);
+                let end = match i + 1 == parts {
+                    true => to,
+                    // Both ends are inclusive, so stop just short of the next window.
+                    false => start + Duration::milliseconds(step - 1),
+                };
+                TimeRange::default().from(start).to(end)
+            })
+            .collect()
+    }
+}
+
+fn rfc3339<S: Serializer>(
+    value: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => {
+            serializer.serialize_str(&value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        }
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_time_range() {
+        let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let to = from + Duration::milliseconds(1500);
+        let range = TimeRange::between(from, to).unwrap();
+        assert_eq!(
+            serde_qs::to_string(&range).unwrap(),
+            "from=2024-01-01T00%3A00%3A00Z&to=2024-01-01T00%3A00%3A01.500Z"
+        );
+        assert!(TimeRange::between(to, from).is_err());
+        assert_eq!(
+            serde_qs::to_string(&TimeRange::until(to)).unwrap(),
+            "to=2024-01-01T00%3A00%3A01.500Z"
+        );
+
+        let windows = TimeRange::between(from, from + Duration::seconds(3))
+            .unwrap()
+            .split(3);
+        assert_eq!(windows.len(), 3);
+        assert_eq!(
+            windows[0].end().unwrap(),
+            from + Duration::milliseconds(999)
+        );
+        assert_eq!(windows[1].start().unwrap(), from + Duration::seconds(1));
+        assert_eq!(windows[2].end().unwrap(), from + Duration::seconds(3));
+        assert_eq!(TimeRange::until(to).split(3), vec![TimeRange::until(to)]);
+
+        let range = TimeRange::last_hours(24);
+        let from = range.from.unwrap();
+        assert!(from <= Utc::now() - Duration::hours(24));
+        assert!(range.to.is_none());
+    }
 }
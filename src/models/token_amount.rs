@@ -0,0 +1,263 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::error::{CircleError, Result};
+
+/// A non-negative amount of one token. Arithmetic is checked and refuses to mix tokens, so a
+/// total of fees and amounts can't silently overflow, go negative or add up different tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenAmount {
+    token_id: Uuid,
+    amount: Decimal,
+}
+
+impl TokenAmount {
+    pub fn new(token_id: Uuid, amount: Decimal) -> Result<Self> {
+        if amount.is_sign_negative() && !amount.is_zero() {
+            return Err(CircleError::AmountOutOfRange(format!(
+                "{} is negative",
+                amount
+            )));
+        }
+        Ok(TokenAmount { token_id, amount })
+    }
+
+    pub fn zero(token_id: Uuid) -> Self {
+        TokenAmount {
+            token_id,
+            amount: Decimal::ZERO,
+        }
+    }
+
+    /// Parses an amount as Circle formats it, e.g. `"12.50"`.
+    pub fn parse(token_id: Uuid, amount: &str) -> Result<Self> {
+        let amount = Decimal::from_str(amount).map_err(|_| CircleError::ValueError)?;
+        Self::new(token_id, amount)
+    }
+
+    pub fn token_id(&self) -> Uuid {
+        self.token_id
+    }
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    pub fn checked_add(&self, other: &TokenAmount) -> Result<Self> {
+        self.same_token(other)?;
+        let amount = self.amount.checked_add(other.amount).ok_or_else(|| {
+            CircleError::AmountOutOfRange(format!("{} + {} overflows", self, other))
+        })?;
+        Self::new(self.token_id, amount)
+    }
+
+    /// Fails rather than going below zero.
+    pub fn checked_sub(&self, other: &TokenAmount) -> Result<Self> {
+        self.same_token(other)?;
+        let amount = self.amount.checked_sub(other.amount).ok_or_else(|| {
+            CircleError::AmountOutOfRange(format!("{} - {} overflows", self, other))
+        })?;
+        Self::new(self.token_id, amount)
+    }
+
+    /// Scales the amount by a unitless factor, e.g. a fee rate or a row count.
+    pub fn checked_mul(&self, factor: Decimal) -> Result<Self> {
+        let amount = self.amount.checked_mul(factor).ok_or_else(|| {
+            CircleError::AmountOutOfRange(format!("{} * {} overflows", self, factor))
+        })?;
+        Self::new(self.token_id, amount)
+    }
+
+    /// Adds up `amounts`, all of which have to be of `token_id`.
+    pub fn sum<'a, I>(token_id: Uuid, amounts: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a TokenAmount>,
+    {
+        amounts
+            .into_iter()
+            .try_fold(Self::zero(token_id), |total, x| total.checked_add(x))
+    }
+
+    fn same_token(&self, other: &TokenAmount) -> Result<()> {
+        match self.token_id == other.token_id {
+            true => Ok(()),
+            false => Err(CircleError::TokenMismatch(self.token_id, other.token_id)),
+        }
+    }
+}
+
+impl Display for TokenAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.amount)
+    }
+}
+
+/// How amounts of one token are shown to people, e.g. `1,250.00 USDC`, and read back from
+/// what they type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAmountFormat {
+    symbol: String,
+    decimals: u32,
+    min_decimals: u32,
+}
+
+impl TokenAmountFormat {
+    /// `decimals` is the token's precision. At least two of them are always shown.
+    pub fn new<S: Into<String>>(symbol: S, decimals: u32) -> Self {
+        TokenAmountFormat {
+            symbol: symbol.into(),
+            decimals,
+            min_decimals: decimals.min(2),
+        }
+    }
+
+    /// How many decimals to show even if they're zeros.
+    pub fn min_decimals(mut self, min_decimals: u32) -> Self {
+        self.min_decimals = min_decimals.min(self.decimals);
+        self
+    }
+
+    /// With thousands separators and the symbol, e.g. `1,250.00 USDC`. Decimals beyond the
+    /// token's precision are rounded off, others are all shown.
+    pub fn format(&self, amount: &TokenAmount) -> String {
+        let mut value = amount.amount().round_dp(self.decimals).normalize();
+        if value.scale() < self.min_decimals {
+            value.rescale(self.min_decimals);
+        }
+        let value = value.to_string();
+        let (int, fraction) = match value.split_once('.') {
+            Some((int, fraction)) => (int, Some(fraction)),
+            None => (value.as_str(), None),
+        };
+        let mut formatted = String::new();
+        for (i, digit) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                formatted.push(',');
+            }
+            formatted.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push('.');
+            formatted.push_str(fraction);
+        }
+        format!("{} {}", formatted, self.symbol)
+    }
+
+    /// Parses what someone typed, e.g. `1,250.5` or `1250.50 USDC`. Anything else fails: signs,
+    /// exponents, misplaced separators, other symbols, or more decimals than the token has.
+    pub fn parse(&self, token_id: Uuid, input: &str) -> Result<TokenAmount> {
+        let input = input.trim();
+        let number = input
+            .strip_suffix(self.symbol.as_str())
+            .map_or(input, str::trim_end);
+        let (int, fraction) = match number.split_once('.') {
+            Some((int, fraction)) => (int, Some(fraction)),
+            None => (number, None),
+        };
+        let digits = |x: &str| !x.is_empty() && x.bytes().all(|b| b.is_ascii_digit());
+        let groups: Vec<&str> = int.split(',').collect();
+        let valid_int = match groups.as_slice() {
+            [int] => digits(int),
+            [first, rest @ ..] => {
+                digits(first) && first.len() <= 3 && rest.iter().all(|x| x.len() == 3 && digits(x))
+            }
+            [] => false,
+        };
+        if !valid_int || !fraction.map_or(true, digits) {
+            return Err(CircleError::ValueError);
+        }
+        if fraction.map_or(0, str::len) > self.decimals as usize {
+            return Err(CircleError::AmountOutOfRange(format!(
+                "{} has more than {} decimals",
+                number, self.decimals
+            )));
+        }
+        TokenAmount::parse(token_id, &number.replace(',', ""))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let usdc = Uuid::new_v4();
+        let fee = TokenAmount::parse(usdc, "0.25").unwrap();
+        let amount = TokenAmount::parse(usdc, "12.50").unwrap();
+        assert_eq!(amount.checked_add(&fee).unwrap().to_string(), "12.75");
+        assert_eq!(amount.checked_sub(&fee).unwrap().to_string(), "12.25");
+        assert_eq!(
+            fee.checked_mul(Decimal::from(3)).unwrap().to_string(),
+            "0.75"
+        );
+        assert_eq!(
+            TokenAmount::sum(usdc, &[fee, amount, fee])
+                .unwrap()
+                .amount(),
+            Decimal::from_str("13.00").unwrap()
+        );
+
+        assert!(matches!(
+            fee.checked_sub(&amount),
+            Err(CircleError::AmountOutOfRange(_))
+        ));
+        let max = TokenAmount::new(usdc, Decimal::MAX).unwrap();
+        assert!(matches!(
+            max.checked_add(&max),
+            Err(CircleError::AmountOutOfRange(_))
+        ));
+        assert!(matches!(
+            fee.checked_mul(Decimal::NEGATIVE_ONE),
+            Err(CircleError::AmountOutOfRange(_))
+        ));
+
+        let eurc = TokenAmount::zero(Uuid::new_v4());
+        assert!(matches!(
+            amount.checked_add(&eurc),
+            Err(CircleError::TokenMismatch(..))
+        ));
+        assert!(TokenAmount::sum(usdc, &[amount, eurc]).is_err());
+    }
+
+    #[test]
+    fn test_amount_format() {
+        let usdc = Uuid::new_v4();
+        let format = TokenAmountFormat::new("USDC", 6);
+        let amount = |x: &str| TokenAmount::parse(usdc, x).unwrap();
+        assert_eq!(format.format(&amount("1250")), "1,250.00 USDC");
+        assert_eq!(format.format(&amount("1234567.5")), "1,234,567.50 USDC");
+        assert_eq!(format.format(&amount("0.000001")), "0.000001 USDC");
+        assert_eq!(format.format(&amount("999.1000000")), "999.10 USDC");
+        assert_eq!(
+            TokenAmountFormat::new("ETH", 18)
+                .min_decimals(0)
+                .format(&amount("100")),
+            "100 ETH"
+        );
+
+        for input in ["1,250.00 USDC", "1250", " 1,250 ", "1250.0USDC"] {
+            assert_eq!(format.parse(usdc, input).unwrap(), amount("1250"));
+        }
+        for input in [
+            "", "-5", "1e3", "1,25", "12,50.00", ",250", "1.", ".5", "1 250", "5 EURC", "0x10",
+        ] {
+            assert!(
+                matches!(format.parse(usdc, input), Err(CircleError::ValueError)),
+                "{:?}",
+                input
+            );
+        }
+        assert!(matches!(
+            format.parse(usdc, "0.0000001"),
+            Err(CircleError::AmountOutOfRange(_))
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::models::token_info::TokenInfo;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenGetResponse {
     pub token: TokenInfo,
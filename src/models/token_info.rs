@@ -1,19 +1,23 @@
+use crate::models::blockchain::Blockchain;
+use crate::models::extra::Extra;
+use crate::models::ids::TokenId;
 use crate::models::web3_address::Web3Address;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
-use uuid::Uuid;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenInfo {
-    pub id: Uuid,
+    pub id: TokenId,
     pub name: Option<String>,
     pub standard: Option<String>,
-    pub blockchain: String,
+    pub blockchain: Blockchain,
     pub decimals: Option<i32>,
     pub is_native: bool,
     pub symbol: Option<String>,
     pub token_address: Option<Web3Address>,
     pub create_date: DateTime<Utc>,
     pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
@@ -1,22 +1,29 @@
 use crate::models::blockchain::Blockchain;
 use crate::models::custody_type::CustodyType;
+use crate::models::extra::Extra;
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::lenient;
 use crate::models::operation::Operation;
+use crate::models::pagination::PageCursor;
 use crate::models::transaction_state::TransactionState;
 use crate::models::transaction_transfer_create::FeeLevel;
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::models::web3_address::Web3Address;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
-    pub id: Uuid,
+    pub id: TransactionId,
     pub abi_function_signature: Option<String>,
     pub abi_parameters: Option<Vec<String>>,
-    pub amounts: Option<Vec<String>>,
-    pub amount_in_usd: Option<String>,
+    #[serde(default, deserialize_with = "lenient::option_decimals")]
+    pub amounts: Option<Vec<Decimal>>,
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub amount_in_usd: Option<Decimal>,
     pub block_hash: Option<String>,
     pub block_height: Option<i64>,
     pub blockchain: Blockchain,
@@ -26,35 +33,119 @@ pub struct Transaction {
     pub destination_address: Option<Web3Address>,
     pub error_reason: Option<String>,
     pub estimated_fee: Option<EstimatedFee>,
+    pub fee_level: Option<FeeLevel>,
     pub first_confirm_date: Option<DateTime<Utc>>,
-    pub network_fee: Option<String>,
-    pub network_fee_in_usd: Option<String>,
+    /// What the transaction cost in the chain's native token, e.g. ETH.
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub network_fee: Option<Decimal>,
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub network_fee_in_usd: Option<Decimal>,
     pub nfts: Option<Vec<String>>,
     pub operation: Option<Operation>,
     pub ref_id: Option<String>,
     pub source_address: Option<Web3Address>,
     pub state: TransactionState,
-    pub token_id: Option<String>,
-    pub transaction_type: String,
+    pub token_id: Option<TokenId>,
+    pub transaction_type: TxType,
     pub tx_hash: Option<String>,
     pub update_date: DateTime<Utc>,
     pub user_id: Option<String>,
-    pub wallet_id: Option<String>,
+    pub wallet_id: Option<WalletId>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Transaction {
+    /// The fee paid in the chain's native token, or the estimate while it's still pending.
+    pub fn total_network_fee(&self) -> Option<Decimal> {
+        self.network_fee
+            .or_else(|| self.estimated_fee.as_ref()?.total_network_fee())
+    }
+}
+
+impl PageCursor for Transaction {
+    fn cursor(&self) -> Uuid {
+        self.id.into()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct EstimatedFee {
-    base_fee: Option<String>,
-    gas_limit: Option<String>,
-    gas_price: Option<String>,
-    max_fee: Option<String>,
-    priority_fee: Option<String>,
-    fee_level: Option<FeeLevel>,
+    /// In gwei, on EIP-1559 chains.
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub base_fee: Option<Decimal>,
+    /// In units of gas.
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub gas_limit: Option<Decimal>,
+    /// In gwei, on chains without EIP-1559.
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub gas_price: Option<Decimal>,
+    /// In gwei, on EIP-1559 chains.
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub max_fee: Option<Decimal>,
+    /// In gwei, on EIP-1559 chains.
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub priority_fee: Option<Decimal>,
+    /// In the chain's native token.
+    #[serde(default, deserialize_with = "lenient::option_decimal")]
+    pub network_fee: Option<Decimal>,
+    pub fee_level: Option<FeeLevel>,
+}
+
+impl EstimatedFee {
+    /// The fee in the chain's native token: `network_fee` if Circle gave one, otherwise the
+    /// most it can cost, `gas_limit` times `max_fee`, or `base_fee` plus `priority_fee`, or
+    /// `gas_price`.
+    pub fn total_network_fee(&self) -> Option<Decimal> {
+        if self.network_fee.is_some() {
+            return self.network_fee;
+        }
+        let gwei = self
+            .max_fee
+            .or_else(|| Some(self.base_fee? + self.priority_fee?))
+            .or(self.gas_price)?;
+        let fee = self.gas_limit?.checked_mul(gwei)?;
+        Some(fee / Decimal::from(1_000_000_000))
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING-KEBAB-CASE")]
 pub enum TxType {
     Inbound,
     Outbound,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_total_network_fee() {
+        let fee: EstimatedFee = serde_json::from_value(serde_json::json!({
+            "gasLimit": "21000",
+            "baseFee": "30",
+            "priorityFee": 1.5,
+            "maxFee": null
+        }))
+        .unwrap();
+        assert_eq!(fee.total_network_fee(), Some(Decimal::new(6615, 7)));
+
+        let fee = EstimatedFee {
+            max_fee: Some(Decimal::from(40)),
+            ..fee
+        };
+        assert_eq!(fee.total_network_fee(), Some(Decimal::new(84, 5)));
+
+        let fee = EstimatedFee {
+            network_fee: Some(Decimal::new(5, 4)),
+            ..fee
+        };
+        assert_eq!(fee.total_network_fee(), Some(Decimal::new(5, 4)));
+    }
 }
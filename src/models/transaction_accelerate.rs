@@ -1,14 +1,20 @@
 use crate::models::auth::Auth;
+use crate::models::ids::TransactionId;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
 #[derive(Serialize, Debug)]
 pub struct TransactionAccelerateRequest {
     #[serde(flatten)]
-    pub auth: Auth,
+    auth: Auth,
 }
 
-#[derive(Deserialize, Debug)]
+impl TransactionAccelerateRequest {
+    pub(crate) fn new(auth: Auth) -> Self {
+        TransactionAccelerateRequest { auth }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TransactionAccelerateResponse {
-    pub id: Uuid,
+    pub id: TransactionId,
 }
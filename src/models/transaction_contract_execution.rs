@@ -0,0 +1,114 @@
+use crate::models::auth::Auth;
+use crate::models::fee::{FeeConfig, FeeLevel};
+use crate::models::ids::{TransactionId, WalletId};
+use crate::models::transaction_state::TransactionState;
+use crate::models::validation::Violations;
+use crate::models::web3_address::Web3Address;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::Result;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionContractExecutionRequest {
+    #[serde(flatten)]
+    auth: Auth,
+    abi_function_signature: String,
+    abi_parameters: Vec<Value>,
+    contract_address: Web3Address,
+    amount: Option<String>,
+    #[serde(flatten)]
+    fee: Option<FeeConfig>,
+    ref_id: Option<String>,
+    wallet_id: WalletId,
+}
+
+pub struct TransactionContractExecutionRequestBuilder {
+    abi_function_signature: String,
+    abi_parameters: Vec<Value>,
+    contract_address: Web3Address,
+    amount: Option<String>,
+    fee: Option<FeeConfig>,
+    ref_id: Option<String>,
+    wallet_id: WalletId,
+}
+
+impl TransactionContractExecutionRequestBuilder {
+    pub fn new(
+        wallet_id: WalletId,
+        contract_address: Web3Address,
+        abi_function_signature: String,
+        abi_parameters: Vec<Value>,
+    ) -> Self {
+        TransactionContractExecutionRequestBuilder {
+            abi_function_signature,
+            abi_parameters,
+            contract_address,
+            amount: None,
+            fee: None,
+            ref_id: None,
+            wallet_id,
+        }
+    }
+
+    pub fn amount<S: Into<String>>(mut self, amount: S) -> Self {
+        self.amount = Some(amount.into());
+        self
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee = Some(FeeConfig::Level(fee_level));
+        self
+    }
+
+    pub fn fee(mut self, fee: FeeConfig) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    pub fn ref_id<S: Into<String>>(mut self, ref_id: S) -> Self {
+        self.ref_id = Some(ref_id.into());
+        self
+    }
+
+    /// Checks the request locally;
+    /// [`crate::api::CircleClient::create_contract_execution_transaction`] calls this before
+    /// sending.
+    pub fn validate(&self) -> Result<()> {
+        let mut violations = Violations::new();
+        let signature = &self.abi_function_signature;
+        violations.check(signature.contains('(') && signature.ends_with(')'), || {
+            format!(
+                "abiFunctionSignature {:?} should look like name(type,...)",
+                signature
+            )
+        });
+        if let Some(amount) = &self.amount {
+            violations.positive("amount", Some(amount.parse().unwrap_or(f64::NAN)));
+        }
+        violations.ref_id(self.ref_id.as_deref());
+        violations.fees(self.fee.as_ref());
+        violations.into_result()
+    }
+
+    pub fn build(self, auth: Auth) -> TransactionContractExecutionRequest {
+        TransactionContractExecutionRequest {
+            auth,
+            abi_function_signature: self.abi_function_signature,
+            abi_parameters: self.abi_parameters,
+            contract_address: self.contract_address,
+            amount: self.amount,
+            fee: self.fee,
+            ref_id: self.ref_id,
+            wallet_id: self.wallet_id,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionContractExecutionResponse {
+    pub id: TransactionId,
+    pub state: TransactionState,
+}
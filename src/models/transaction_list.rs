@@ -1,15 +1,18 @@
-use serde::{Deserialize, Serialize};
+use crate::models::ids::WalletId;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 
+use crate::error::Result;
 use crate::models::blockchain::Blockchain;
 use crate::models::custody_type::CustodyType;
 use crate::models::operation::Operation;
-use crate::models::pagination::Pagination;
+use crate::models::pagination::{list_query_params, PageParams, Paginated, SortOrder};
 use crate::models::time_range::TimeRange;
-use crate::models::transaction::Transaction;
+use crate::models::transaction::{Transaction, TxType};
 use crate::models::transaction_state::TransactionState;
 use crate::models::web3_address::Web3Address;
 
-#[derive(Serialize, Debug, Default)]
+#[derive(Serialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionListQueryParams {
     blockchain: Option<Blockchain>,
@@ -19,14 +22,16 @@ pub struct TransactionListQueryParams {
     operation: Option<Operation>,
     state: Option<TransactionState>,
     tx_hash: Option<String>,
-    tx_type: Option<String>,
+    tx_type: Option<TxType>,
     wallet_ids: Option<String>,
     #[serde(flatten)]
     time_range: TimeRange,
     #[serde(flatten)]
-    pagination: Pagination,
+    pagination: PageParams,
 }
 
+list_query_params!(TransactionListQueryParams);
+
 impl TransactionListQueryParams {
     pub fn new() -> Self {
         Self::default()
@@ -67,7 +72,7 @@ impl TransactionListQueryParams {
         self
     }
 
-    pub fn tx_type(mut self, value: String) -> Self {
+    pub fn tx_type(mut self, value: TxType) -> Self {
         self.tx_type = Some(value);
         self
     }
@@ -76,10 +81,174 @@ impl TransactionListQueryParams {
         self.wallet_ids = Some(value);
         self
     }
+
+    pub fn time_range(mut self, value: TimeRange) -> Self {
+        self.time_range = value;
+        self
+    }
+
+    pub fn pagination(mut self, value: PageParams) -> Self {
+        self.pagination = value;
+        self
+    }
 }
 
-#[derive(Deserialize, Debug, Default)]
-#[serde(rename_all = "camelCase")]
-pub struct TransactionListResponse {
-    pub transactions: Vec<Transaction>,
+/// A readable way to put together a transaction filter:
+///
+/// ```
+/// # use circle_api::models::transaction_list::TransactionQuery;
+/// # use circle_api::models::transaction_state::TransactionState;
+/// # use circle_api::models::blockchain::Blockchain;
+/// # use circle_api::models::ids::WalletId;
+/// # let (wallet_id, monday) = (WalletId::default(), chrono::Utc::now());
+/// let params = TransactionQuery::new()
+///     .wallet(wallet_id)
+///     .state(TransactionState::Confirmed)
+///     .blockchain(Blockchain::Eth)
+///     .since(monday)
+///     .build();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct TransactionQuery {
+    wallet_ids: Vec<WalletId>,
+    params: TransactionListQueryParams,
+}
+
+impl TransactionQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only transactions of this wallet. Can be called repeatedly to match any of several.
+    pub fn wallet(mut self, wallet_id: WalletId) -> Self {
+        self.wallet_ids.push(wallet_id);
+        self
+    }
+
+    pub fn wallets<I: IntoIterator<Item = WalletId>>(mut self, wallet_ids: I) -> Self {
+        self.wallet_ids.extend(wallet_ids);
+        self
+    }
+
+    pub fn state(mut self, state: TransactionState) -> Self {
+        self.params = self.params.state(state);
+        self
+    }
+
+    pub fn blockchain(mut self, blockchain: Blockchain) -> Self {
+        self.params = self.params.blockchain(blockchain);
+        self
+    }
+
+    pub fn operation(mut self, operation: Operation) -> Self {
+        self.params = self.params.operation(operation);
+        self
+    }
+
+    pub fn custody_type(mut self, custody_type: CustodyType) -> Self {
+        self.params = self.params.custody_type(custody_type);
+        self
+    }
+
+    pub fn inbound(mut self) -> Self {
+        self.params = self.params.tx_type(TxType::Inbound);
+        self
+    }
+
+    pub fn outbound(mut self) -> Self {
+        self.params = self.params.tx_type(TxType::Outbound);
+        self
+    }
+
+    pub fn destination(mut self, address: Web3Address) -> Self {
+        self.params = self.params.destination_address(address);
+        self
+    }
+
+    pub fn tx_hash<S: Into<String>>(mut self, tx_hash: S) -> Self {
+        self.params = self.params.tx_hash(tx_hash.into());
+        self
+    }
+
+    pub fn include_all(mut self) -> Self {
+        self.params = self.params.include_all(true);
+        self
+    }
+
+    pub fn since(mut self, from: DateTime<Utc>) -> Self {
+        self.params.time_range = self.params.time_range.from(from);
+        self
+    }
+
+    pub fn until(mut self, to: DateTime<Utc>) -> Self {
+        self.params.time_range = self.params.time_range.to(to);
+        self
+    }
+
+    pub fn time_range(mut self, time_range: TimeRange) -> Self {
+        self.params = self.params.time_range(time_range);
+        self
+    }
+
+    pub fn pagination(mut self, pagination: PageParams) -> Self {
+        self.params = self.params.pagination(pagination);
+        self
+    }
+
+    pub fn page_size(mut self, page_size: u8) -> Result<Self> {
+        self.params = self.params.page_size(page_size)?;
+        Ok(self)
+    }
+
+    pub fn order(mut self, order: SortOrder) -> Self {
+        self.params = self.params.order(order);
+        self
+    }
+
+    pub fn build(self) -> TransactionListQueryParams {
+        let mut params = self.params;
+        if !self.wallet_ids.is_empty() {
+            let wallet_ids = self.wallet_ids.iter().map(WalletId::to_string);
+            params.wallet_ids = Some(wallet_ids.collect::<Vec<_>>().join(","));
+        }
+        params
+    }
+}
+
+impl From<TransactionQuery> for TransactionListQueryParams {
+    fn from(query: TransactionQuery) -> Self {
+        query.build()
+    }
+}
+
+pub type TransactionListResponse = Paginated<Transaction>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transaction_query() {
+        let (a, b) = (
+            WalletId(uuid::Uuid::new_v4()),
+            WalletId(uuid::Uuid::new_v4()),
+        );
+        let params = TransactionQuery::new()
+            .wallet(a)
+            .wallet(b)
+            .state(TransactionState::Confirmed)
+            .blockchain(Blockchain::Eth)
+            .outbound()
+            .page_size(50)
+            .unwrap()
+            .order(SortOrder::Desc)
+            .build();
+        assert_eq!(
+            serde_qs::to_string(&params).unwrap(),
+            format!(
+                "blockchain=ETH&state=CONFIRMED&txType=OUTBOUND&walletIds={}%2C{}&pageSize=50&order=DESC",
+                a, b
+            )
+        );
+    }
 }
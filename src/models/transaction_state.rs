@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TransactionState {
     Initiated,
@@ -12,4 +12,22 @@ pub enum TransactionState {
     Complete,
     Failed,
     Cancelled,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+impl TransactionState {
+    /// Whether the transaction has settled, one way or the other. States added after this
+    /// version of the crate count as still pending.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TransactionState::Complete
+                | TransactionState::Failed
+                | TransactionState::Denied
+                | TransactionState::Cancelled
+        )
+    }
 }
@@ -1,8 +1,18 @@
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
 use crate::models::auth::Auth;
+use crate::models::fee::FeeConfig;
+pub use crate::models::fee::FeeLevel;
 use crate::models::transaction_state::TransactionState;
+use crate::models::validation::Violations;
 use crate::models::web3_address::Web3Address;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+
+use crate::error::{CircleError, Result};
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -11,46 +21,56 @@ pub struct TransactionTransferCreateRequest {
     auth: Auth,
     amounts: Vec<String>,
     destination_address: Web3Address,
-    fee_level: Option<FeeLevel>,
-    gas_limit: Option<u64>,
-    gas_price: Option<f64>,
-    max_fee: Option<f64>,
-    priority_fee: Option<f64>,
+    #[serde(flatten)]
+    fee: Option<FeeConfig>,
     nft_token_ids: Option<Vec<String>>,
     ref_id: Option<String>,
-    token_id: Uuid,
-    wallet_id: Uuid,
+    token_id: TokenId,
+    wallet_id: WalletId,
+}
+
+/// What an exchange or custodian may require next to a shared deposit address to credit the
+/// right account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Memo {
+    /// A free-form memo, as on Solana or Stellar.
+    Text(String),
+    /// A numeric destination tag, as on XRP.
+    Tag(u64),
+}
+
+impl Display for Memo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Memo::Text(text) => write!(f, "memo {:?}", text),
+            Memo::Tag(tag) => write!(f, "destination tag {}", tag),
+        }
+    }
 }
 
 pub struct TransactionTransferCreateRequestBuilder {
-    amounts: Vec<f64>,
+    amounts: Vec<Decimal>,
     destination_address: Web3Address,
-    fee_level: Option<FeeLevel>,
-    gas_limit: Option<u64>,
-    gas_price: Option<f64>,
-    max_fee: Option<f64>,
-    priority_fee: Option<f64>,
+    fee: Option<FeeConfig>,
+    memo: Option<Memo>,
     nft_token_ids: Option<Vec<String>>,
     ref_id: Option<String>,
-    token_id: Uuid,
-    wallet_id: Uuid,
+    token_id: TokenId,
+    wallet_id: WalletId,
 }
 
 impl TransactionTransferCreateRequestBuilder {
     pub fn new(
         destination_address: Web3Address,
-        token_id: Uuid,
-        wallet_id: Uuid,
-        amount: f64,
+        token_id: TokenId,
+        wallet_id: WalletId,
+        amount: Decimal,
     ) -> Self {
         TransactionTransferCreateRequestBuilder {
             amounts: vec![amount],
             destination_address,
-            fee_level: None,
-            gas_limit: None,
-            gas_price: None,
-            max_fee: None,
-            priority_fee: None,
+            fee: None,
+            memo: None,
             nft_token_ids: None,
             ref_id: None,
             token_id,
@@ -58,33 +78,26 @@ impl TransactionTransferCreateRequestBuilder {
         }
     }
 
-    pub fn amounts(mut self, amounts: Vec<f64>) -> Self {
+    pub fn amounts(mut self, amounts: Vec<Decimal>) -> Self {
         self.amounts = amounts;
         self
     }
 
     pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
-        self.fee_level = Some(fee_level);
+        self.fee = Some(FeeConfig::Level(fee_level));
         self
     }
 
-    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
-        self.gas_limit = Some(gas_limit);
+    pub fn fee(mut self, fee: FeeConfig) -> Self {
+        self.fee = Some(fee);
         self
     }
 
-    pub fn gas_price(mut self, gas_price: f64) -> Self {
-        self.gas_price = Some(gas_price);
-        self
-    }
-
-    pub fn max_fee(mut self, max_fee: f64) -> Self {
-        self.max_fee = Some(max_fee);
-        self
-    }
-
-    pub fn priority_fee(mut self, priority_fee: f64) -> Self {
-        self.priority_fee = Some(priority_fee);
+    /// The memo or tag the destination needs. Circle's transfer endpoint can't carry one on any
+    /// of the chains it supports, so a request with a memo fails [`Self::validate`] instead of
+    /// arriving without it, which exchanges typically can't credit.
+    pub fn memo(mut self, memo: Memo) -> Self {
+        self.memo = Some(memo);
         self
     }
 
@@ -98,16 +111,55 @@ impl TransactionTransferCreateRequestBuilder {
         self
     }
 
+    /// Checks the request locally; [`crate::api::CircleClient::create_transfer_transaction`]
+    /// calls this before sending.
+    pub fn validate(&self) -> Result<()> {
+        let mut violations = Violations::new();
+        violations.check(!self.amounts.is_empty(), || {
+            "at least one amount is required".to_string()
+        });
+        for amount in &self.amounts {
+            violations.check(*amount > Decimal::ZERO, || {
+                format!("amount must be a positive number, got {}", amount)
+            });
+        }
+        if let Some(nft_token_ids) = &self.nft_token_ids {
+            violations.check(nft_token_ids.len() == self.amounts.len(), || {
+                "nftTokenIds and amounts must have the same length".to_string()
+            });
+        }
+        if let Some(memo) = &self.memo {
+            violations.check(false, || {
+                format!("{} can't be attached: transfers carry no memo or tag", memo)
+            });
+        }
+        violations.ref_id(self.ref_id.as_deref());
+        violations.fees(self.fee.as_ref());
+        violations.into_result()
+    }
+
+    /// The token whose decimals the amounts have to be checked against, if any amount has a
+    /// fractional part. NFT transfers are counted in whole tokens and are left alone.
+    #[cfg(feature = "transactions")]
+    pub(crate) fn fractional_token_id(&self) -> Option<TokenId> {
+        let fractional = self.amounts.iter().any(|x| !x.fract().is_zero());
+        (fractional && self.nft_token_ids.is_none()).then_some(self.token_id)
+    }
+
+    pub(crate) fn validate_decimals(&self, decimals: u32) -> Result<()> {
+        let mut violations = Violations::new();
+        for amount in &self.amounts {
+            violations.decimals("amount", *amount, decimals);
+        }
+        violations.into_result()
+    }
+
     pub fn build(self, auth: Auth) -> TransactionTransferCreateRequest {
         TransactionTransferCreateRequest {
             auth,
-            amounts: self.amounts.iter().map(|x| x.to_string()).collect(),
+            amounts: self.amounts.iter().map(Decimal::to_string).collect(),
             destination_address: self.destination_address,
-            fee_level: self.fee_level,
-            gas_limit: self.gas_limit,
-            gas_price: self.gas_price,
-            max_fee: self.max_fee,
-            priority_fee: self.priority_fee,
+            fee: self.fee,
             nft_token_ids: self.nft_token_ids,
             ref_id: self.ref_id,
             token_id: self.token_id,
@@ -116,17 +168,180 @@ impl TransactionTransferCreateRequestBuilder {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum FeeLevel {
-    Low,
-    Medium,
-    High,
+/// Collects a single-amount transfer field by field. [`build`](Self::build) checks that
+/// nothing required is missing and validates the result, so what it returns can be passed
+/// straight to [`crate::api::CircleClient::create_transfer_transaction`]. Amounts are taken as
+/// decimal strings, e.g. `"12.50"`.
+#[derive(Debug, Default, Clone)]
+pub struct TransferRequestBuilder {
+    wallet_id: Option<WalletId>,
+    destination_address: Option<Web3Address>,
+    token_id: Option<TokenId>,
+    amount: Option<String>,
+    decimals: Option<u32>,
+    fee: Option<FeeConfig>,
+    memo: Option<Memo>,
+    ref_id: Option<String>,
+}
+
+impl TransferRequestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_wallet(mut self, wallet_id: WalletId) -> Self {
+        self.wallet_id = Some(wallet_id);
+        self
+    }
+
+    /// Only EVM addresses are supported for now, so transfers to Solana or NEAR destinations
+    /// can't be built here. If the address needs a memo or destination tag, pass it to
+    /// [`memo`](Self::memo).
+    pub fn to_address(mut self, destination_address: Web3Address) -> Self {
+        self.destination_address = Some(destination_address);
+        self
+    }
+
+    pub fn token(mut self, token_id: TokenId) -> Self {
+        self.token_id = Some(token_id);
+        self
+    }
+
+    /// Anything that formats as a decimal number, e.g. `"12.50"` or a [`Decimal`].
+    pub fn amount<A: ToString>(mut self, amount: A) -> Self {
+        self.amount = Some(amount.to_string());
+        self
+    }
+
+    /// The token's decimals, to check the amount against in [`build`](Self::build). Without
+    /// them the check is left to the client, which looks them up when the transfer is sent.
+    pub fn decimals(mut self, decimals: u32) -> Self {
+        self.decimals = Some(decimals);
+        self
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee = Some(FeeConfig::Level(fee_level));
+        self
+    }
+
+    pub fn fee(mut self, fee: FeeConfig) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    /// See [`TransactionTransferCreateRequestBuilder::memo`].
+    pub fn memo(mut self, memo: Memo) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    pub fn ref_id<S: Into<String>>(mut self, ref_id: S) -> Self {
+        self.ref_id = Some(ref_id.into());
+        self
+    }
+
+    pub fn build(self) -> Result<TransactionTransferCreateRequestBuilder> {
+        let amount = self.amount.ok_or(CircleError::MissingField("amount"))?;
+        let amount = Decimal::from_str(amount.trim()).map_err(|_| {
+            CircleError::Validation(vec![format!("amount {:?} is not a number", amount)])
+        })?;
+        let mut request = TransactionTransferCreateRequestBuilder::new(
+            self.destination_address
+                .ok_or(CircleError::MissingField("destination_address"))?,
+            self.token_id.ok_or(CircleError::MissingField("token_id"))?,
+            self.wallet_id
+                .ok_or(CircleError::MissingField("wallet_id"))?,
+            amount,
+        );
+        request.fee = self.fee;
+        request.memo = self.memo;
+        request.ref_id = self.ref_id;
+        request.validate()?;
+        if let Some(decimals) = self.decimals {
+            request.validate_decimals(decimals)?;
+        }
+        Ok(request)
+    }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionTransferCreateResponse {
-    pub id: Uuid,
+    pub id: TransactionId,
     pub state: TransactionState,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transfer_request_builder() {
+        let (wallet_id, token_id) = (
+            WalletId(uuid::Uuid::new_v4()),
+            TokenId(uuid::Uuid::new_v4()),
+        );
+        let destination =
+            Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let transfer = TransferRequestBuilder::new()
+            .from_wallet(wallet_id)
+            .to_address(destination.clone())
+            .token(token_id)
+            .fee_level(FeeLevel::Medium)
+            .ref_id("invoice-7");
+        assert!(matches!(
+            transfer.clone().build(),
+            Err(CircleError::MissingField("amount"))
+        ));
+        assert!(matches!(
+            transfer.clone().amount("12,50").build(),
+            Err(CircleError::Validation(_))
+        ));
+
+        assert!(matches!(
+            transfer.clone().amount("-3").build(),
+            Err(CircleError::Validation(_))
+        ));
+        assert!(matches!(
+            transfer
+                .clone()
+                .ref_id("x".repeat(1000))
+                .amount("1")
+                .build(),
+            Err(CircleError::Validation(_))
+        ));
+
+        let request = transfer
+            .clone()
+            .amount("0.000000000000000001")
+            .build()
+            .unwrap();
+        assert_eq!(request.amounts, [Decimal::new(1, 18)]);
+        assert!(matches!(
+            transfer
+                .clone()
+                .amount("0.000000000000000001")
+                .decimals(6)
+                .build(),
+            Err(CircleError::Validation(_))
+        ));
+
+        // Rather than arrive at an exchange without the memo it needs.
+        let memo = transfer.clone().amount("1").memo(Memo::Tag(12345)).build();
+        match memo {
+            Err(CircleError::Validation(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert!(errors[0].contains("destination tag 12345"));
+            }
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+
+        let request = transfer.amount("12.50").decimals(6).build().unwrap();
+        assert_eq!(request.amounts, [Decimal::new(1250, 2)]);
+        assert_eq!(request.destination_address, destination);
+        assert_eq!(request.fee, Some(FeeConfig::Level(FeeLevel::Medium)));
+        assert_eq!(request.ref_id.as_deref(), Some("invoice-7"));
+        assert_eq!((request.wallet_id, request.token_id), (wallet_id, token_id));
+    }
+}
@@ -0,0 +1,122 @@
+//! Checks run on requests before they are sent, so obviously broken input fails fast with a
+//! [`CircleError::Validation`] listing every problem instead of a round trip and a 400.
+use rust_decimal::Decimal;
+
+use crate::error::{CircleError, Result};
+use crate::models::fee::FeeConfig;
+
+pub(crate) const MAX_REF_ID_LENGTH: usize = 256;
+#[cfg(feature = "wallets")]
+pub(crate) const MAX_WALLET_COUNT: u32 = 200;
+
+#[derive(Debug, Default)]
+pub(crate) struct Violations(Vec<String>);
+
+impl Violations {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn check<F: FnOnce() -> String>(&mut self, ok: bool, message: F) {
+        if !ok {
+            self.0.push(message());
+        }
+    }
+
+    pub(crate) fn ref_id(&mut self, ref_id: Option<&str>) {
+        if let Some(ref_id) = ref_id {
+            self.check(
+                !ref_id.is_empty() && ref_id.len() <= MAX_REF_ID_LENGTH,
+                || {
+                    format!(
+                        "refId must be between 1 and {} characters",
+                        MAX_REF_ID_LENGTH
+                    )
+                },
+            );
+        }
+    }
+
+    pub(crate) fn positive(&mut self, field: &str, value: Option<f64>) {
+        if let Some(value) = value {
+            self.check(value.is_finite() && value > 0.0, || {
+                format!("{} must be a positive number, got {}", field, value)
+            });
+        }
+    }
+
+    /// Amounts with more fractional digits than the token has would be truncated or rejected
+    /// by Circle.
+    pub(crate) fn decimals(&mut self, field: &str, value: Decimal, decimals: u32) {
+        self.check(value.normalize().scale() <= decimals, || {
+            format!(
+                "{} {} has more than {} decimal places",
+                field, value, decimals
+            )
+        });
+    }
+
+    /// Circle takes either a fee level, a legacy gas price or EIP-1559 fees, never a mix.
+    pub(crate) fn fees(&mut self, fee: Option<&FeeConfig>) {
+        let gas_limit = match fee {
+            None | Some(FeeConfig::Level(_)) => return,
+            Some(FeeConfig::GasPrice {
+                gas_limit,
+                gas_price,
+            }) => {
+                self.positive("gasPrice", Some(*gas_price));
+                gas_limit
+            }
+            Some(FeeConfig::Eip1559 {
+                gas_limit,
+                max_fee,
+                priority_fee,
+            }) => {
+                self.positive("maxFee", Some(*max_fee));
+                self.positive("priorityFee", Some(*priority_fee));
+                self.check(max_fee >= priority_fee, || {
+                    "maxFee must not be lower than priorityFee".to_string()
+                });
+                gas_limit
+            }
+        };
+        self.check(*gas_limit != 0, || "gasLimit must not be 0".to_string());
+    }
+
+    pub(crate) fn into_result(self) -> Result<()> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(CircleError::Validation(self.0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_violations() {
+        let mut violations = Violations::new();
+        violations.ref_id(Some(""));
+        violations.positive("amount", Some(-1.0));
+        violations.fees(Some(&FeeConfig::Eip1559 {
+            gas_limit: 0,
+            max_fee: 1.0,
+            priority_fee: 2.0,
+        }));
+        let CircleError::Validation(messages) = violations.into_result().unwrap_err() else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(messages.len(), 4);
+
+        let mut violations = Violations::new();
+        violations.ref_id(Some("payout-1"));
+        violations.fees(Some(&FeeConfig::Eip1559 {
+            gas_limit: 21000,
+            max_fee: 2.0,
+            priority_fee: 1.0,
+        }));
+        assert!(violations.into_result().is_ok());
+    }
+}
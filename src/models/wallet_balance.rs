@@ -1,21 +1,66 @@
+use crate::models::extra::Extra;
+use crate::models::lenient;
 use crate::models::token_info::TokenInfo;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
 use crate::models::wallet_objects::WalletObjectsQueryParams;
 
 pub type WalletBalanceQueryParams = WalletObjectsQueryParams;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenBalance {
-    pub amount: String,
+    #[serde(deserialize_with = "lenient::decimal")]
+    pub amount: Decimal,
     pub token: TokenInfo,
     pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletBalanceResponse {
     pub token_balances: Vec<TokenBalance>,
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_wallet_balance_round_trip() {
+        let body = json!({ "tokenBalances": [{
+            "amount": "12.5",
+            "updateDate": "2024-03-01T10:00:00Z",
+            "token": {
+                "id": "7adb2b7d-c9cd-5164-b2d4-b73b088274dc",
+                "name": "USD Coin",
+                "standard": "ERC20",
+                "blockchain": "MATIC",
+                "decimals": 6,
+                "isNative": false,
+                "symbol": "USDC",
+                "tokenAddress": "0x2791bca1f2de4661ed88a30c99a7a9449aa84174",
+                "createDate": "2023-01-01T00:00:00Z",
+                "updateDate": "2023-01-01T00:00:00Z"
+            }
+        }] });
+        let response = serde_json::from_value::<WalletBalanceResponse>(body).unwrap();
+        assert_eq!(response.token_balances[0].amount, Decimal::new(125, 1));
+        let cached = response.clone();
+        let serialized = serde_json::to_value(&cached).unwrap();
+        assert_eq!(
+            serialized["tokenBalances"][0]["token"]["isNative"],
+            json!(false)
+        );
+        assert_eq!(
+            serde_json::from_value::<WalletBalanceResponse>(serialized).unwrap(),
+            response
+        );
+    }
+}
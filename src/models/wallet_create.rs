@@ -1,20 +1,197 @@
+use crate::models::ids::WalletSetId;
 use crate::models::wallet_detail::WalletDetail;
 
 use crate::models::blockchain::Blockchain;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::error::Result;
+use crate::models::validation::{Violations, MAX_WALLET_COUNT};
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletCreateRequest {
-    pub idempotency_key: Uuid,
-    pub entity_secret_cipher_text: String,
-    pub wallet_set_id: Uuid,
-    pub blockchains: Vec<Blockchain>,
-    pub count: u32,
+    idempotency_key: Uuid,
+    entity_secret_cipher_text: String,
+    wallet_set_id: WalletSetId,
+    blockchains: Vec<Blockchain>,
+    count: u32,
+    /// One entry per wallet, or none.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    metadata: Vec<WalletMetadata>,
+}
+
+/// The label of one wallet created by a [`WalletCreateRequest`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_id: Option<String>,
+}
+
+impl WalletMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn ref_id<S: Into<String>>(mut self, ref_id: S) -> Self {
+        self.ref_id = Some(ref_id.into());
+        self
+    }
+
+    /// `count` entries with `{index}` in the templates replaced by 0, 1, ..., e.g.
+    /// `WalletMetadata::numbered("user-{index}", None, 3)`.
+    pub fn numbered(name: &str, ref_id: Option<&str>, count: u32) -> Vec<Self> {
+        (0..count)
+            .map(|i| {
+                let index = i.to_string();
+                WalletMetadata {
+                    name: Some(name.replace("{index}", &index)),
+                    ref_id: ref_id.map(|x| x.replace("{index}", &index)),
+                }
+            })
+            .collect()
+    }
 }
 
-#[derive(Deserialize, Debug)]
+impl WalletCreateRequest {
+    pub fn validate(&self) -> Result<()> {
+        let mut violations = Violations::new();
+        violations.check((1..=MAX_WALLET_COUNT).contains(&self.count), || {
+            format!("count must be between 1 and {}", MAX_WALLET_COUNT)
+        });
+        violations.check(!self.blockchains.is_empty(), || {
+            "at least one blockchain is required".to_string()
+        });
+        if !self.metadata.is_empty() {
+            violations.check(self.metadata.len() == self.count as usize, || {
+                format!(
+                    "{} metadata entries given for {} wallets",
+                    self.metadata.len(),
+                    self.count
+                )
+            });
+        }
+        for metadata in &self.metadata {
+            violations.ref_id(metadata.ref_id.as_deref());
+        }
+        violations.into_result()
+    }
+}
+
+/// The fields of a [`WalletCreateRequest`] the caller chooses; the client adds the
+/// idempotency key and entity secret ciphertext.
+#[derive(Debug, Clone)]
+pub struct WalletCreateRequestBuilder {
+    wallet_set_id: WalletSetId,
+    blockchains: Vec<Blockchain>,
+    count: u32,
+    metadata: Vec<WalletMetadata>,
+}
+
+impl WalletCreateRequestBuilder {
+    /// One wallet on each of `blockchains`.
+    pub fn new(wallet_set_id: WalletSetId, blockchains: Vec<Blockchain>) -> Self {
+        WalletCreateRequestBuilder {
+            wallet_set_id,
+            blockchains,
+            count: 1,
+            metadata: Vec::new(),
+        }
+    }
+
+    pub fn blockchain(mut self, blockchain: Blockchain) -> Self {
+        self.blockchains.push(blockchain);
+        self
+    }
+
+    /// Wallets to create on each blockchain.
+    pub fn count(mut self, count: u32) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Labels the wallets, one entry per wallet; see [`WalletMetadata::numbered`] for
+    /// numbering them from a template.
+    pub fn metadata(mut self, metadata: Vec<WalletMetadata>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub(crate) fn build(
+        self,
+        idempotency_key: Uuid,
+        entity_secret_cipher_text: String,
+    ) -> WalletCreateRequest {
+        WalletCreateRequest {
+            idempotency_key,
+            entity_secret_cipher_text,
+            wallet_set_id: self.wallet_set_id,
+            blockchains: self.blockchains,
+            count: self.count,
+            metadata: self.metadata,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct WalletCreateResponse {
     pub wallets: Vec<WalletDetail>,
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_wallet_create_request_builder() {
+        let (wallet_set_id, idempotency_key) = (WalletSetId(Uuid::new_v4()), Uuid::new_v4());
+        let request = WalletCreateRequestBuilder::new(wallet_set_id, vec![Blockchain::Eth])
+            .blockchain(Blockchain::Matic)
+            .count(3)
+            .build(idempotency_key, "ciphertext".to_string());
+        request.validate().unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "idempotencyKey": idempotency_key,
+                "entitySecretCipherText": "ciphertext",
+                "walletSetId": wallet_set_id,
+                "blockchains": ["ETH", "MATIC"],
+                "count": 3
+            })
+        );
+        let labeled = WalletCreateRequestBuilder::new(wallet_set_id, vec![Blockchain::Eth])
+            .count(2)
+            .metadata(WalletMetadata::numbered(
+                "user-{index}",
+                Some("u{index}"),
+                2,
+            ))
+            .build(idempotency_key, "ciphertext".to_string());
+        labeled.validate().unwrap();
+        assert_eq!(
+            serde_json::to_value(&labeled).unwrap()["metadata"],
+            json!([
+                { "name": "user-0", "refId": "u0" },
+                { "name": "user-1", "refId": "u1" }
+            ])
+        );
+        let mismatched = WalletCreateRequestBuilder::new(wallet_set_id, vec![Blockchain::Eth])
+            .metadata(vec![WalletMetadata::new().name("a"), WalletMetadata::new()])
+            .build(idempotency_key, "ciphertext".to_string());
+        assert!(mismatched.validate().is_err());
+        let empty = WalletCreateRequestBuilder::new(wallet_set_id, Vec::new())
+            .build(idempotency_key, "ciphertext".to_string());
+        assert!(empty.validate().is_err());
+    }
+}
@@ -1,31 +1,77 @@
 use crate::models::blockchain::Blockchain;
+use crate::models::ids::{WalletId, WalletSetId};
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::models::account_type::AccountType;
 use crate::models::custody_type::CustodyType;
+use crate::models::extra::Extra;
+use crate::models::pagination::PageCursor;
 use crate::models::web3_address::Web3Address;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletDetail {
-    pub id: Uuid,
+    pub id: WalletId,
     pub address: Web3Address,
     pub blockchain: Blockchain,
     pub create_date: DateTime<Utc>,
     pub custody_type: CustodyType,
-    pub account_type: Option<String>,
+    pub account_type: Option<AccountType>,
     pub name: Option<String>,
     pub ref_id: Option<String>,
     pub state: WalletState,
     pub update_date: DateTime<Utc>,
     pub user_id: Option<String>,
-    pub wallet_set_id: Uuid,
+    pub wallet_set_id: WalletSetId,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
+impl PageCursor for WalletDetail {
+    fn cursor(&self) -> Uuid {
+        self.id.into()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WalletState {
     Live,
     Frozen,
+    /// A value added by Circle after this version of the crate.
+    #[serde(untagged)]
+    #[cfg_attr(feature = "strict", serde(skip_deserializing))]
+    Unknown(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wallet_detail() {
+        let json = r#"{
+            "id": "ce714f5b-0d8e-4062-9454-61aa1154869b",
+            "state": "LIVE",
+            "walletSetId": "0189bc61-7fe4-70f3-8a1b-0d14426397cb",
+            "custodyType": "DEVELOPER",
+            "address": "0xf5c83e5fede8456929d0f90e8c541dcac3d63835",
+            "blockchain": "MATIC-AMOY",
+            "accountType": "SCA",
+            "updateDate": "2023-08-03T19:33:14Z",
+            "createDate": "2023-08-03T19:33:14Z"
+        }"#;
+        let wallet = serde_json::from_str::<WalletDetail>(json).unwrap();
+        assert_eq!(wallet.custody_type, CustodyType::Developer);
+        assert_eq!(wallet.account_type, Some(AccountType::Sca));
+        assert_eq!(wallet.blockchain, Blockchain::MaticAmoy);
+
+        let json = json.replace("\"DEVELOPER\"", "\"ENDUSER\"");
+        let json = json.replace("\"SCA\"", "\"EOA\"");
+        let wallet = serde_json::from_str::<WalletDetail>(&json).unwrap();
+        assert_eq!(wallet.custody_type, CustodyType::EndUser);
+        assert_eq!(wallet.account_type, Some(AccountType::Eoa));
+    }
 }
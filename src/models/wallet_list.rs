@@ -1,36 +1,39 @@
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use crate::models::ids::WalletSetId;
+use serde::Serialize;
 
-use crate::models::pagination::Pagination;
+use crate::models::blockchain::Blockchain;
+use crate::models::pagination::{list_query_params, PageParams, Paginated};
 use crate::models::time_range::TimeRange;
 use crate::models::wallet_detail::WalletDetail;
 use crate::models::web3_address::Web3Address;
 
-#[derive(Serialize, Debug, Default)]
+#[derive(Serialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletListQueryParams {
     address: Option<Web3Address>,
-    blockchain: Option<String>,
-    wallet_set_id: Option<Uuid>,
+    blockchain: Option<Blockchain>,
+    wallet_set_id: Option<WalletSetId>,
     ref_id: Option<String>,
     #[serde(flatten)]
     time_range: TimeRange,
     #[serde(flatten)]
-    pagination: Pagination,
+    pagination: PageParams,
 }
 
+list_query_params!(WalletListQueryParams);
+
 impl WalletListQueryParams {
     pub fn address(mut self, value: Web3Address) -> Self {
         self.address = Some(value);
         self
     }
 
-    pub fn blockchain(mut self, value: String) -> Self {
+    pub fn blockchain(mut self, value: Blockchain) -> Self {
         self.blockchain = Some(value);
         self
     }
 
-    pub fn wallet_set_id(mut self, value: Uuid) -> Self {
+    pub fn wallet_set_id(mut self, value: WalletSetId) -> Self {
         self.wallet_set_id = Some(value);
         self
     }
@@ -39,10 +42,16 @@ impl WalletListQueryParams {
         self.ref_id = Some(value);
         self
     }
-}
 
-#[derive(Deserialize, Debug, Default)]
-#[serde(rename_all = "camelCase")]
-pub struct WalletListResponse {
-    pub wallets: Vec<WalletDetail>,
+    pub fn time_range(mut self, value: TimeRange) -> Self {
+        self.time_range = value;
+        self
+    }
+
+    pub fn pagination(mut self, value: PageParams) -> Self {
+        self.pagination = value;
+        self
+    }
 }
+
+pub type WalletListResponse = Paginated<WalletDetail>;
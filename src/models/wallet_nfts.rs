@@ -1,27 +1,28 @@
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::models::extra::Extra;
+use crate::models::token_info::TokenInfo;
 use crate::models::wallet_objects::WalletObjectsQueryParams;
 
 pub type WalletNftsQueryParams = WalletObjectsQueryParams;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletNftsResponse {
     pub nfts: Vec<NftBalance>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct NftBalance {
     pub amount: u64,
     pub metadata: Option<String>,
     pub nft_token_id: String,
     pub token: NftToken,
-}
-
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct NftToken {
     pub update_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
+
+pub type NftToken = TokenInfo;
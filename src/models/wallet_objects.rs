@@ -1,7 +1,6 @@
-use chrono::{DateTime, Utc};
 use serde::Serialize;
 
-use crate::models::pagination::Pagination;
+use crate::models::pagination::PageParams;
 use crate::models::time_range::TimeRange;
 use crate::models::web3_address::Web3Address;
 
@@ -12,12 +11,10 @@ pub struct WalletObjectsQueryParams {
     name: Option<String>,
     token_address: Option<Web3Address>,
     standard: Option<String>,
-    from: Option<DateTime<Utc>>,
-    to: Option<DateTime<Utc>>,
     #[serde(flatten)]
     time_range: TimeRange,
     #[serde(flatten)]
-    pagination: Pagination,
+    pagination: PageParams,
 }
 
 impl WalletObjectsQueryParams {
@@ -40,4 +37,14 @@ impl WalletObjectsQueryParams {
         self.standard = Some(value.into());
         self
     }
+
+    pub fn time_range(mut self, value: TimeRange) -> Self {
+        self.time_range = value;
+        self
+    }
+
+    pub fn pagination(mut self, value: PageParams) -> Self {
+        self.pagination = value;
+        self
+    }
 }
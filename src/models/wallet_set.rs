@@ -1,110 +1,135 @@
 use crate::models::auth::Auth;
 use crate::models::custody_type::CustodyType;
+use crate::models::extra::Extra;
+use crate::models::ids::WalletSetId;
+use crate::models::pagination::{list_query_params, PageCursor, PageParams, Paginated};
+use crate::models::time_range::TimeRange;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletSetObjectResponse {
-    pub id: Uuid,
+    pub id: WalletSetId,
     pub custody_type: CustodyType,
     pub name: String,
     pub update_date: DateTime<Utc>,
     pub create_date: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateWalletSetRequest {
     #[serde(flatten)]
-    pub auth: Auth,
-    pub name: String,
+    auth: Auth,
+    name: String,
+}
+
+/// The fields of a [`CreateWalletSetRequest`] the caller chooses; the client adds the
+/// [`Auth`].
+#[derive(Debug, Clone)]
+pub struct CreateWalletSetRequestBuilder {
+    name: String,
 }
 
-#[derive(Deserialize, Debug)]
+impl CreateWalletSetRequestBuilder {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        CreateWalletSetRequestBuilder { name: name.into() }
+    }
+
+    pub(crate) fn build(self, auth: Auth) -> CreateWalletSetRequest {
+        CreateWalletSetRequest {
+            auth,
+            name: self.name,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateWalletSetResponse {
     pub wallet_set: WalletSetObjectResponse,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateWalletSetRequest {
-    pub name: String,
+    name: String,
+}
+
+impl UpdateWalletSetRequest {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        UpdateWalletSetRequest { name: name.into() }
+    }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateWalletSetResponse {
     pub wallet_set: WalletSetObjectResponse,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct GetWalletSetResponse {
     pub wallet_set: WalletSetObjectResponse,
 }
 
-#[derive(Serialize, Debug, Default)]
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct WalletSetsQueryParams {
-    pub from: Option<DateTime<Utc>>,
-    pub to: Option<DateTime<Utc>>,
-    pub page_before: Option<String>,
-    pub page_after: Option<String>,
-    pub page_size: Option<u64>,
+    #[serde(flatten)]
+    pub time_range: TimeRange,
+    #[serde(flatten)]
+    pub pagination: PageParams,
 }
 
+list_query_params!(WalletSetsQueryParams);
+
 impl WalletSetsQueryParams {
     pub fn new() -> Self {
-        WalletSetsQueryParams {
-            from: None,
-            to: None,
-            page_before: None,
-            page_after: None,
-            page_size: None,
-        }
+        Self::default()
     }
 
     pub fn from(mut self, from: DateTime<Utc>) -> Self {
-        self.from = Some(from);
+        self.time_range = self.time_range.from(from);
         self
     }
 
     pub fn to(mut self, to: DateTime<Utc>) -> Self {
-        self.to = Some(to);
-        self
-    }
-
-    pub fn page_before<S: Into<String>>(mut self, page_before: S) -> Self {
-        self.page_before = Some(page_before.into());
+        self.time_range = self.time_range.to(to);
         self
     }
 
-    pub fn page_after<S: Into<String>>(mut self, page_after: S) -> Self {
-        self.page_after = Some(page_after.into());
+    pub fn time_range(mut self, value: TimeRange) -> Self {
+        self.time_range = value;
         self
     }
 
-    pub fn page_size(mut self, page_size: u64) -> Self {
-        self.page_size = Some(page_size);
+    pub fn pagination(mut self, value: PageParams) -> Self {
+        self.pagination = value;
         self
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletSet {
-    pub id: Uuid,
+    pub id: WalletSetId,
     pub create_date: DateTime<Utc>,
     pub custody_type: CustodyType,
     pub name: Option<String>,
     pub update_date: DateTime<Utc>,
     pub user_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct WalletSetsResponse {
-    pub wallet_sets: Vec<WalletSet>,
+impl PageCursor for WalletSet {
+    fn cursor(&self) -> Uuid {
+        self.id.into()
+    }
 }
+
+pub type WalletSetsResponse = Paginated<WalletSet>;
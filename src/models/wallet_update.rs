@@ -5,11 +5,26 @@ use crate::models::wallet_detail::WalletDetail;
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletUpdateRequest {
-    pub name: String,
-    pub ref_id: String,
+    name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    ref_id: String,
 }
 
-#[derive(Deserialize, Debug)]
+impl WalletUpdateRequest {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        WalletUpdateRequest {
+            name: name.into(),
+            ref_id: String::new(),
+        }
+    }
+
+    pub fn ref_id<S: Into<String>>(mut self, ref_id: S) -> Self {
+        self.ref_id = ref_id.into();
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletUpdateResponse {
     pub wallet: WalletDetail,
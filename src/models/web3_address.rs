@@ -1,7 +1,12 @@
+use std::fmt;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 use web3::types::Address;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+use crate::error::CircleError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct Web3Address(Address);
 
 impl From<Web3Address> for Address {
@@ -9,3 +14,29 @@ impl From<Web3Address> for Address {
         value.0
     }
 }
+
+impl From<Address> for Web3Address {
+    fn from(value: Address) -> Self {
+        Web3Address(value)
+    }
+}
+
+impl fmt::Display for Web3Address {
+    /// The full `0x`-prefixed lowercase hex address.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl FromStr for Web3Address {
+    type Err = CircleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.strip_prefix("0x").unwrap_or(s);
+        if value.len() != 40 {
+            Err(CircleError::ValueError)?
+        }
+        let address = Address::from_str(value).map_err(|_| CircleError::ValueError)?;
+        Ok(Web3Address(address))
+    }
+}
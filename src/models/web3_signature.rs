@@ -84,6 +84,7 @@ impl<'de> Deserialize<'de> for Web3Signature {
     }
 }
 
+#[cfg(test)]
 mod test {
     use super::*;
 
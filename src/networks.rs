@@ -0,0 +1,134 @@
+//! What's worth knowing about each chain Circle supports beyond its name: its native token and
+//! how long transactions take to become final there.
+
+use std::time::Duration;
+
+use crate::models::blockchain::Blockchain;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    pub blockchain: Blockchain,
+    pub name: &'static str,
+    /// The token network fees are paid in.
+    pub native_token: &'static str,
+    pub native_decimals: u32,
+    /// Roughly how many blocks Circle waits for before a transaction is `COMPLETE`.
+    pub confirmations: u32,
+    /// Roughly how long a transaction takes from being sent to being final, when the chain
+    /// isn't congested. Good for sizing timeouts, not a guarantee.
+    pub finality: Duration,
+}
+
+impl Network {
+    const fn new(
+        blockchain: Blockchain,
+        name: &'static str,
+        native_token: &'static str,
+        native_decimals: u32,
+        confirmations: u32,
+        finality_secs: u64,
+    ) -> Self {
+        Network {
+            blockchain,
+            name,
+            native_token,
+            native_decimals,
+            confirmations,
+            finality: Duration::from_secs(finality_secs),
+        }
+    }
+
+    pub fn is_testnet(&self) -> bool {
+        self.blockchain.is_testnet()
+    }
+
+    /// The EIP-155 chain id of EVM chains.
+    pub fn chain_id(&self) -> Option<u64> {
+        self.blockchain.evm_chain_id()
+    }
+}
+
+static NETWORKS: &[Network] = &[
+    Network::new(Blockchain::Eth, "Ethereum", "ETH", 18, 65, 15 * 60),
+    Network::new(
+        Blockchain::EthSepolia,
+        "Ethereum Sepolia",
+        "ETH",
+        18,
+        65,
+        15 * 60,
+    ),
+    Network::new(
+        Blockchain::EthGoerli,
+        "Ethereum Goerli",
+        "ETH",
+        18,
+        65,
+        15 * 60,
+    ),
+    Network::new(Blockchain::Avax, "Avalanche C-Chain", "AVAX", 18, 1, 5),
+    Network::new(Blockchain::AvaxFuji, "Avalanche Fuji", "AVAX", 18, 1, 5),
+    Network::new(Blockchain::Matic, "Polygon PoS", "POL", 18, 50, 2 * 60),
+    Network::new(Blockchain::MaticAmoy, "Polygon Amoy", "POL", 18, 50, 2 * 60),
+    Network::new(
+        Blockchain::MaticMumbai,
+        "Polygon Mumbai",
+        "MATIC",
+        18,
+        50,
+        2 * 60,
+    ),
+    // Rollups are final once their batch is final on Ethereum.
+    Network::new(Blockchain::Arb, "Arbitrum One", "ETH", 18, 1, 20 * 60),
+    Network::new(
+        Blockchain::ArbSepolia,
+        "Arbitrum Sepolia",
+        "ETH",
+        18,
+        1,
+        20 * 60,
+    ),
+    Network::new(Blockchain::Base, "Base", "ETH", 18, 1, 20 * 60),
+    Network::new(
+        Blockchain::BaseSepolia,
+        "Base Sepolia",
+        "ETH",
+        18,
+        1,
+        20 * 60,
+    ),
+    Network::new(Blockchain::Sol, "Solana", "SOL", 9, 32, 15),
+    Network::new(Blockchain::SolDevnet, "Solana Devnet", "SOL", 9, 32, 15),
+    Network::new(Blockchain::Near, "NEAR", "NEAR", 24, 2, 3),
+    Network::new(Blockchain::NearTestnet, "NEAR Testnet", "NEAR", 24, 2, 3),
+];
+
+/// Every chain this version of the crate knows.
+pub fn all() -> &'static [Network] {
+    NETWORKS
+}
+
+/// `None` for [`Blockchain::Unknown`].
+pub fn network(blockchain: &Blockchain) -> Option<&'static Network> {
+    NETWORKS.iter().find(|x| x.blockchain == *blockchain)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_networks() {
+        let base = network(&Blockchain::BaseSepolia).unwrap();
+        assert_eq!(base.native_token, "ETH");
+        assert_eq!(base.chain_id(), Some(84532));
+        assert!(base.is_testnet());
+        assert_eq!(network(&Blockchain::Sol).unwrap().native_decimals, 9);
+        assert!(network(&Blockchain::Unknown("APTOS".to_string())).is_none());
+
+        // Every Blockchain variant has an entry.
+        assert_eq!(all().len(), 16);
+        let testnets = all().iter().filter(|x| x.is_testnet()).count();
+        assert_eq!(testnets, 9);
+    }
+}
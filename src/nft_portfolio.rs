@@ -0,0 +1,221 @@
+//! NFT holdings across many wallets, consolidated for custody dashboards.
+
+use std::collections::BTreeMap;
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::Serialize;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::blockchain::Blockchain;
+use crate::models::ids::{TokenId, WalletId, WalletSetId};
+use crate::models::wallet_list::WalletListQueryParams;
+use crate::models::wallet_nfts::{NftBalance, WalletNftsQueryParams};
+use crate::models::web3_address::Web3Address;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NftHolding {
+    pub wallet_id: WalletId,
+    pub amount: u64,
+}
+
+/// One NFT (a contract and token id), however many wallets hold it.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioNft {
+    pub blockchain: Blockchain,
+    /// Circle's id of the collection's token.
+    pub token_id: TokenId,
+    pub contract: Option<Web3Address>,
+    /// The collection's name, or its symbol if it has none.
+    pub collection: Option<String>,
+    /// E.g. `ERC721` or `ERC1155`.
+    pub standard: Option<String>,
+    pub nft_token_id: String,
+    pub metadata: Option<String>,
+    /// Across all holdings; more than 1 only for semi-fungible standards.
+    pub amount: u64,
+    pub holdings: Vec<NftHolding>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NftPortfolio {
+    /// Sorted by collection, then token id.
+    pub nfts: Vec<PortfolioNft>,
+    /// Wallets whose NFTs couldn't be read, with the reason. They are missing from `nfts`.
+    pub failed_wallets: Vec<(WalletId, String)>,
+}
+
+impl NftPortfolio {
+    /// The NFTs of each collection, keyed by collection name (or `token_id` if it has none).
+    pub fn by_collection(&self) -> BTreeMap<String, Vec<&PortfolioNft>> {
+        let mut collections = BTreeMap::<_, Vec<_>>::new();
+        for nft in &self.nfts {
+            let name = nft
+                .collection
+                .clone()
+                .unwrap_or_else(|| nft.token_id.to_string());
+            collections.entry(name).or_default().push(nft);
+        }
+        collections
+    }
+}
+
+pub struct NftAggregator<'a> {
+    client: &'a CircleClient,
+    concurrency: usize,
+}
+
+impl<'a> NftAggregator<'a> {
+    pub fn new(client: &'a CircleClient) -> Self {
+        NftAggregator {
+            client,
+            concurrency: 4,
+        }
+    }
+
+    /// Wallets read at the same time.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// The NFTs of every wallet in `wallet_set_id`. Fails only if the wallets can't be listed.
+    pub async fn wallet_set(&self, wallet_set_id: WalletSetId) -> Result<NftPortfolio> {
+        let wallets = self
+            .client
+            .list_wallets_stream(WalletListQueryParams::default().wallet_set_id(wallet_set_id))
+            .map_ok(|x| x.id)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(self.wallets(wallets).await)
+    }
+
+    pub async fn wallets<I: IntoIterator<Item = WalletId>>(&self, wallets: I) -> NftPortfolio {
+        let results = stream::iter(wallets)
+            .map(|wallet_id| async move {
+                let nfts = self
+                    .client
+                    .get_wallet_nfts(wallet_id, WalletNftsQueryParams::default())
+                    .await;
+                (wallet_id, nfts)
+            })
+            .buffered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        let mut nfts = BTreeMap::<(Option<String>, TokenId, String), PortfolioNft>::new();
+        let mut portfolio = NftPortfolio::default();
+        for (wallet_id, result) in results {
+            match result {
+                Ok(response) => {
+                    for nft in response.nfts {
+                        add(&mut nfts, wallet_id, nft);
+                    }
+                }
+                Err(err) => portfolio.failed_wallets.push((wallet_id, err.to_string())),
+            }
+        }
+        portfolio.nfts = nfts.into_values().collect();
+        portfolio
+    }
+}
+
+fn add(
+    nfts: &mut BTreeMap<(Option<String>, TokenId, String), PortfolioNft>,
+    wallet_id: WalletId,
+    nft: NftBalance,
+) {
+    let collection = nft.token.name.clone().or(nft.token.symbol.clone());
+    let key = (collection.clone(), nft.token.id, nft.nft_token_id.clone());
+    let entry = nfts.entry(key).or_insert_with(|| PortfolioNft {
+        blockchain: nft.token.blockchain.clone(),
+        token_id: nft.token.id,
+        contract: nft.token.token_address.clone(),
+        collection,
+        standard: nft.token.standard.clone(),
+        nft_token_id: nft.nft_token_id.clone(),
+        metadata: nft.metadata.clone(),
+        amount: 0,
+        holdings: Vec::new(),
+    });
+    entry.amount += nft.amount;
+    entry.metadata = entry.metadata.take().or(nft.metadata);
+    match entry.holdings.iter_mut().find(|x| x.wallet_id == wallet_id) {
+        Some(holding) => holding.amount += nft.amount,
+        None => entry.holdings.push(NftHolding {
+            wallet_id,
+            amount: nft.amount,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    const TOKEN_ID: &str = "38f2ad29-a77b-5a44-be05-8d03923878a2";
+
+    fn nfts(ids: &[(&str, u64)]) -> Value {
+        let nfts = ids
+            .iter()
+            .map(|(id, amount)| {
+                json!({
+                    "amount": amount,
+                    "nftTokenId": id,
+                    "updateDate": "2024-03-01T10:00:00Z",
+                    "token": {
+                        "id": TOKEN_ID,
+                        "blockchain": "MATIC-AMOY",
+                        "isNative": false,
+                        "name": "Badges",
+                        "standard": "ERC1155",
+                        "tokenAddress": "0x41e94eb019c0762f9bfcf9fb1e58725bfb0e7582",
+                        "createDate": "2023-11-25T14:26:38Z",
+                        "updateDate": "2023-11-25T14:26:38Z"
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({ "nfts": nfts })
+    }
+
+    #[tokio::test]
+    async fn test_aggregate() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let [first, second, broken] = [(); 3].map(|_| WalletId(Uuid::new_v4()));
+        transport.push_data(nfts(&[("1", 1), ("7", 2)]));
+        transport.push_data(nfts(&[("7", 3)]));
+        transport.push_error(StatusCode::NOT_FOUND, 156001, "wallet not found");
+
+        let portfolio = NftAggregator::new(&client)
+            .concurrency(1)
+            .wallets([first, second, broken])
+            .await;
+        assert_eq!(portfolio.failed_wallets.len(), 1);
+        assert_eq!(portfolio.nfts.len(), 2);
+        let shared = &portfolio.nfts[1];
+        assert_eq!((shared.nft_token_id.as_str(), shared.amount), ("7", 5));
+        assert_eq!(
+            shared.holdings,
+            [
+                NftHolding {
+                    wallet_id: first,
+                    amount: 2
+                },
+                NftHolding {
+                    wallet_id: second,
+                    amount: 3
+                }
+            ]
+        );
+        assert_eq!(portfolio.by_collection()["Badges"].len(), 2);
+    }
+}
@@ -0,0 +1,367 @@
+//! The transactional outbox pattern for transfers: the application enqueues a transfer in the
+//! same database transaction as the business change that calls for it, and an
+//! [`OutboxWorker`] submits it to Circle later.
+//!
+//! Every entry's id doubles as its idempotency key, so an entry is submitted at least once and
+//! Circle deduplicates the rest, even when the worker crashes between submitting and recording
+//! the outcome.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::cancel::{run_every, CancellationToken};
+use crate::error::Result;
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::transaction_state::TransactionState;
+use crate::models::transaction_transfer_create::{FeeLevel, TransferRequestBuilder};
+use crate::models::web3_address::Web3Address;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OutboxStatus {
+    /// Not accepted by Circle yet.
+    Pending,
+    Submitted {
+        transaction_id: TransactionId,
+    },
+    Complete {
+        transaction_id: TransactionId,
+        tx_hash: Option<String>,
+    },
+    /// Rejected by Circle, or failed on chain.
+    Failed {
+        transaction_id: Option<TransactionId>,
+        reason: String,
+    },
+}
+
+impl OutboxStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OutboxStatus::Complete { .. } | OutboxStatus::Failed { .. }
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEntry {
+    /// Also the idempotency key of the transfer.
+    pub id: Uuid,
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub destination: Web3Address,
+    pub amount: Decimal,
+    pub ref_id: Option<String>,
+    pub enqueued_at: DateTime<Utc>,
+    pub status: OutboxStatus,
+    /// Submissions that failed with an error worth retrying.
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl OutboxEntry {
+    pub fn new(
+        wallet_id: WalletId,
+        token_id: TokenId,
+        destination: Web3Address,
+        amount: Decimal,
+    ) -> Self {
+        OutboxEntry {
+            id: Uuid::new_v4(),
+            wallet_id,
+            token_id,
+            destination,
+            amount,
+            ref_id: None,
+            enqueued_at: Utc::now(),
+            status: OutboxStatus::Pending,
+            attempts: 0,
+            last_error: None,
+        }
+    }
+
+    pub fn ref_id<S: Into<String>>(mut self, ref_id: S) -> Self {
+        self.ref_id = Some(ref_id.into());
+        self
+    }
+}
+
+/// Where entries are kept, usually a table in the application's database.
+pub trait OutboxStore: Send + Sync {
+    fn enqueue<'a>(&'a self, entry: &'a OutboxEntry) -> BoxFuture<'a, Result<()>>;
+
+    /// Up to `limit` entries that aren't terminal yet, oldest first.
+    fn unfinished(&self, limit: usize) -> BoxFuture<'_, Result<Vec<OutboxEntry>>>;
+
+    /// Replaces the stored entry with the same id.
+    fn update<'a>(&'a self, entry: &'a OutboxEntry) -> BoxFuture<'a, Result<()>>;
+}
+
+/// An [`OutboxStore`] that forgets everything on restart, for tests and prototypes.
+#[derive(Debug, Default)]
+pub struct InMemoryOutboxStore(Mutex<HashMap<Uuid, OutboxEntry>>);
+
+impl InMemoryOutboxStore {
+    pub fn get(&self, id: Uuid) -> Option<OutboxEntry> {
+        self.0.lock().unwrap().get(&id).cloned()
+    }
+}
+
+impl OutboxStore for InMemoryOutboxStore {
+    fn enqueue<'a>(&'a self, entry: &'a OutboxEntry) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.0
+                .lock()
+                .unwrap()
+                .entry(entry.id)
+                .or_insert(entry.clone());
+            Ok(())
+        })
+    }
+
+    fn unfinished(&self, limit: usize) -> BoxFuture<'_, Result<Vec<OutboxEntry>>> {
+        Box::pin(async move {
+            let entries = self.0.lock().unwrap();
+            let mut unfinished = entries
+                .values()
+                .filter(|x| !x.status.is_terminal())
+                .cloned()
+                .collect::<Vec<_>>();
+            unfinished.sort_by_key(|x| x.enqueued_at);
+            unfinished.truncate(limit);
+            Ok(unfinished)
+        })
+    }
+
+    fn update<'a>(&'a self, entry: &'a OutboxEntry) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.0.lock().unwrap().insert(entry.id, entry.clone());
+            Ok(())
+        })
+    }
+}
+
+/// What one [`OutboxWorker::drain_once`] did.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainReport {
+    pub submitted: usize,
+    pub completed: usize,
+    pub failed: usize,
+    /// Submissions that failed with a retryable error and stay pending.
+    pub deferred: usize,
+}
+
+pub struct OutboxWorker<'a, S> {
+    client: &'a CircleClient,
+    store: S,
+    fee_level: FeeLevel,
+    batch_size: usize,
+    interval: Duration,
+    cancellation: CancellationToken,
+}
+
+impl<'a, S: OutboxStore> OutboxWorker<'a, S> {
+    pub fn new(client: &'a CircleClient, store: S) -> Self {
+        OutboxWorker {
+            client,
+            store,
+            fee_level: FeeLevel::Medium,
+            batch_size: 100,
+            interval: Duration::from_secs(5),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee_level = fee_level;
+        self
+    }
+
+    /// Entries handled per pass.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Time between passes in [`OutboxWorker::run`].
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Stops [`OutboxWorker::run`] once `token` is cancelled, after the current pass.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Submits pending entries and checks on submitted ones, once. Only store errors are
+    /// returned.
+    pub async fn drain_once(&self) -> Result<DrainReport> {
+        let mut report = DrainReport::default();
+        for mut entry in self.store.unfinished(self.batch_size).await? {
+            match entry.status {
+                OutboxStatus::Pending => self.submit(&mut entry).await,
+                OutboxStatus::Submitted { transaction_id } => {
+                    self.check(&mut entry, transaction_id).await
+                }
+                _ => continue,
+            }
+            match entry.status {
+                OutboxStatus::Pending => report.deferred += 1,
+                OutboxStatus::Submitted { .. } => report.submitted += 1,
+                OutboxStatus::Complete { .. } => report.completed += 1,
+                OutboxStatus::Failed { .. } => report.failed += 1,
+            }
+            self.store.update(&entry).await?;
+        }
+        Ok(report)
+    }
+
+    /// Runs [`OutboxWorker::drain_once`] with [`run_every`].
+    pub async fn run<F: FnMut(Result<DrainReport>)>(&self, on_report: F) {
+        run_every(
+            self.interval,
+            &self.cancellation,
+            || self.drain_once(),
+            on_report,
+        )
+        .await
+    }
+
+    async fn submit(&self, entry: &mut OutboxEntry) {
+        let mut request = TransferRequestBuilder::new()
+            .from_wallet(entry.wallet_id)
+            .to_address(entry.destination.clone())
+            .token(entry.token_id)
+            .amount(entry.amount)
+            .fee_level(self.fee_level);
+        if let Some(ref_id) = &entry.ref_id {
+            request = request.ref_id(ref_id.clone());
+        }
+        let result = match request.build() {
+            Ok(request) => {
+                self.client
+                    .create_transfer_transaction(entry.id, request)
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(response) => {
+                entry.status = OutboxStatus::Submitted {
+                    transaction_id: response.id,
+                }
+            }
+            // Circle never accepted it, so giving up can't leave a transfer behind.
+            Err(err) if err.is_client_bug() => {
+                entry.status = OutboxStatus::Failed {
+                    transaction_id: None,
+                    reason: err.to_string(),
+                }
+            }
+            Err(err) => {
+                entry.attempts += 1;
+                entry.last_error = Some(err.to_string());
+            }
+        }
+    }
+
+    async fn check(&self, entry: &mut OutboxEntry, transaction_id: TransactionId) {
+        let transaction = match self.client.get_transaction(transaction_id, None).await {
+            Ok(response) => response.transaction,
+            Err(err) => {
+                entry.last_error = Some(err.to_string());
+                return;
+            }
+        };
+        entry.status = match transaction.state {
+            TransactionState::Complete => OutboxStatus::Complete {
+                transaction_id,
+                tx_hash: transaction.tx_hash,
+            },
+            TransactionState::Failed | TransactionState::Denied | TransactionState::Cancelled => {
+                OutboxStatus::Failed {
+                    transaction_id: Some(transaction_id),
+                    reason: transaction
+                        .error_reason
+                        .unwrap_or_else(|| format!("{:?}", transaction.state)),
+                }
+            }
+            _ => return,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_drain() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let destination =
+            Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let entry = OutboxEntry::new(
+            WalletId(Uuid::new_v4()),
+            TokenId(Uuid::new_v4()),
+            destination,
+            Decimal::from(5),
+        )
+        .ref_id("order-17");
+        let worker = OutboxWorker::new(&client, InMemoryOutboxStore::default());
+        worker.store().enqueue(&entry).await.unwrap();
+
+        // Circle is down: the entry stays pending and is retried with the same key.
+        transport.push_error(StatusCode::SERVICE_UNAVAILABLE, 9, "unavailable");
+        let report = worker.drain_once().await.unwrap();
+        assert_eq!(report.deferred, 1);
+        assert_eq!(worker.store().get(entry.id).unwrap().attempts, 1);
+
+        let transaction_id = Uuid::new_v4();
+        transport.push_data(json!({ "id": transaction_id, "state": "INITIATED" }));
+        assert_eq!(worker.drain_once().await.unwrap().submitted, 1);
+        let requests = transport.requests();
+        let keys = requests
+            .iter()
+            .map(|x| x.body.as_ref().unwrap()["idempotencyKey"].clone())
+            .collect::<Vec<_>>();
+        assert!(keys.iter().all(|x| *x == json!(entry.id)));
+
+        transport.push_data(json!({
+            "transaction": {
+                "id": transaction_id,
+                "blockchain": "MATIC-AMOY",
+                "state": "COMPLETE",
+                "txHash": "0xabc",
+                "transactionType": "OUTBOUND",
+                "createDate": "2024-03-01T11:00:00Z",
+                "updateDate": "2024-03-01T11:00:00Z"
+            }
+        }));
+        assert_eq!(worker.drain_once().await.unwrap().completed, 1);
+        assert!(worker.store().unfinished(10).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,588 @@
+//! Bulk payouts from a single developer-controlled wallet, driven by CSV files or any iterator
+//! of rows.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::cancel::CancellationToken;
+use crate::error::Result;
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::transaction::TxType;
+use crate::models::transaction_state::TransactionState;
+use crate::models::transaction_transfer_create::{
+    FeeLevel, TransactionTransferCreateRequestBuilder,
+};
+use crate::models::validation::MAX_REF_ID_LENGTH;
+use crate::models::web3_address::Web3Address;
+use crate::runtime::Instant;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutRow {
+    pub destination: String,
+    pub token_id: TokenId,
+    pub amount: String,
+    pub ref_id: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "outcome", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayoutOutcome {
+    Invalid {
+        errors: Vec<String>,
+    },
+    SubmitFailed {
+        reason: String,
+    },
+    Pending {
+        transaction_id: TransactionId,
+    },
+    Complete {
+        transaction_id: TransactionId,
+        tx_hash: Option<String>,
+    },
+    Failed {
+        transaction_id: TransactionId,
+        reason: String,
+    },
+    /// The batch was cancelled before this row was submitted.
+    Skipped,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutResult {
+    pub row: PayoutRow,
+    pub idempotency_key: Uuid,
+    pub outcome: PayoutOutcome,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchReport {
+    pub results: Vec<PayoutResult>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportLine<'a> {
+    ref_id: &'a str,
+    destination: &'a str,
+    token_id: TokenId,
+    amount: &'a str,
+    idempotency_key: Uuid,
+    outcome: &'static str,
+    transaction_id: Option<TransactionId>,
+    detail: Option<String>,
+}
+
+impl BatchReport {
+    pub fn completed(&self) -> usize {
+        self.count(|x| matches!(x, PayoutOutcome::Complete { .. }))
+    }
+
+    pub fn pending(&self) -> usize {
+        self.count(|x| matches!(x, PayoutOutcome::Pending { .. }))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|x| matches!(x, PayoutOutcome::Skipped))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.completed() - self.pending() - self.skipped()
+    }
+
+    fn count(&self, predicate: impl Fn(&PayoutOutcome) -> bool) -> usize {
+        self.results
+            .iter()
+            .filter(|x| predicate(&x.outcome))
+            .count()
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        for result in &self.results {
+            let (outcome, transaction_id, detail) = match &result.outcome {
+                PayoutOutcome::Invalid { errors } => ("INVALID", None, Some(errors.join("; "))),
+                PayoutOutcome::SubmitFailed { reason } => {
+                    ("SUBMIT_FAILED", None, Some(reason.clone()))
+                }
+                PayoutOutcome::Pending { transaction_id } => {
+                    ("PENDING", Some(*transaction_id), None)
+                }
+                PayoutOutcome::Complete {
+                    transaction_id,
+                    tx_hash,
+                } => ("COMPLETE", Some(*transaction_id), tx_hash.clone()),
+                PayoutOutcome::Failed {
+                    transaction_id,
+                    reason,
+                } => ("FAILED", Some(*transaction_id), Some(reason.clone())),
+                PayoutOutcome::Skipped => ("SKIPPED", None, None),
+            };
+            writer.serialize(ReportLine {
+                ref_id: &result.row.ref_id,
+                destination: &result.row.destination,
+                token_id: result.row.token_id,
+                amount: &result.row.amount,
+                idempotency_key: result.idempotency_key,
+                outcome,
+                transaction_id,
+                detail,
+            })?;
+        }
+        writer.flush().map_err(csv::Error::from)?;
+        Ok(())
+    }
+}
+
+/// Reads payout rows from CSV with a `destination,tokenId,amount,refId` header.
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<PayoutRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let rows = reader
+        .deserialize::<PayoutRow>()
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+type TokenDecimals = std::result::Result<Option<u32>, String>;
+
+pub struct BatchRunner<'a> {
+    client: &'a CircleClient,
+    wallet_id: WalletId,
+    fee_level: FeeLevel,
+    concurrency: usize,
+    poll_interval: Duration,
+    completion_timeout: Duration,
+    cancellation: CancellationToken,
+}
+
+impl<'a> BatchRunner<'a> {
+    pub fn new(client: &'a CircleClient, wallet_id: WalletId) -> Self {
+        BatchRunner {
+            client,
+            wallet_id,
+            fee_level: FeeLevel::Medium,
+            concurrency: 4,
+            poll_interval: Duration::from_secs(5),
+            completion_timeout: Duration::from_secs(600),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee_level = fee_level;
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn completion_timeout(mut self, completion_timeout: Duration) -> Self {
+        self.completion_timeout = completion_timeout;
+        self
+    }
+
+    /// Stops the batch once `token` is cancelled. Transfers already in flight are allowed to
+    /// finish and stay `Pending` in the report; rows that were never submitted are `Skipped`,
+    /// so re-running the same rows later picks up where the batch stopped.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// The idempotency key for a row is derived from the source wallet and the row's refId,
+    /// so re-running the same file never pays anyone twice. Rows repeating a refId within a
+    /// batch are `Invalid`.
+    pub fn idempotency_key(&self, ref_id: &str) -> Uuid {
+        Uuid::new_v5(&self.wallet_id.as_uuid(), ref_id.as_bytes())
+    }
+
+    pub async fn run<I>(&self, rows: I) -> Result<BatchReport>
+    where
+        I: IntoIterator<Item = PayoutRow>,
+    {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        let decimals = self.token_decimals(&rows).await;
+        // Rows sharing a refId share an idempotency key, so all but the first would be answered
+        // with the first row's transfer. None of them is sent, since it's unclear which is meant.
+        let mut ref_ids = HashMap::new();
+        for row in &rows {
+            *ref_ids.entry(row.ref_id.as_str()).or_insert(0) += 1;
+        }
+        let duplicates: HashSet<String> = ref_ids
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(ref_id, _)| ref_id.to_string())
+            .collect();
+
+        let mut results = stream::iter(rows)
+            .enumerate()
+            .map(|(index, row)| {
+                let (decimals, duplicates) = (&decimals, &duplicates);
+                async move { (index, self.submit(row, decimals, duplicates).await) }
+            })
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        results.sort_by_key(|(index, _)| *index);
+        let mut results = results.into_iter().map(|(_, x)| x).collect::<Vec<_>>();
+
+        self.track(&mut results).await;
+        Ok(BatchReport { results })
+    }
+
+    /// The decimals of every token in `rows`, or why they couldn't be looked up.
+    async fn token_decimals(&self, rows: &[PayoutRow]) -> HashMap<TokenId, TokenDecimals> {
+        let mut decimals = HashMap::new();
+        for row in rows {
+            if decimals.contains_key(&row.token_id) {
+                continue;
+            }
+            let token_decimals = self.client.token_decimals(row.token_id).await;
+            decimals.insert(row.token_id, token_decimals.map_err(|x| x.to_string()));
+        }
+        decimals
+    }
+
+    async fn submit(
+        &self,
+        row: PayoutRow,
+        decimals: &HashMap<TokenId, TokenDecimals>,
+        duplicates: &HashSet<String>,
+    ) -> PayoutResult {
+        let idempotency_key = self.idempotency_key(&row.ref_id);
+        let mut validated = match decimals.get(&row.token_id) {
+            Some(Err(reason)) => Err(vec![format!(
+                "could not look up token {}: {}",
+                row.token_id, reason
+            )]),
+            decimals => validate_row(&row, decimals.cloned().and_then(|x| x.ok()).flatten()),
+        };
+        if duplicates.contains(&row.ref_id) {
+            let error = format!("refId {:?} appears more than once in the batch", row.ref_id);
+            match &mut validated {
+                Err(errors) => errors.push(error),
+                Ok(_) => validated = Err(vec![error]),
+            }
+        }
+        let outcome = match validated {
+            Err(errors) => PayoutOutcome::Invalid { errors },
+            Ok(_) if self.cancellation.is_cancelled() => PayoutOutcome::Skipped,
+            Ok((destination, amount)) => {
+                let request = TransactionTransferCreateRequestBuilder::new(
+                    destination,
+                    row.token_id,
+                    self.wallet_id,
+                    amount,
+                )
+                .fee_level(self.fee_level)
+                .ref_id(row.ref_id.clone());
+                match self
+                    .client
+                    .create_transfer_transaction(idempotency_key, request)
+                    .await
+                {
+                    Ok(response) => PayoutOutcome::Pending {
+                        transaction_id: response.id,
+                    },
+                    Err(err) => PayoutOutcome::SubmitFailed {
+                        reason: err.to_string(),
+                    },
+                }
+            }
+        };
+        PayoutResult {
+            row,
+            idempotency_key,
+            outcome,
+        }
+    }
+
+    async fn track(&self, results: &mut [PayoutResult]) {
+        let deadline = Instant::now() + self.completion_timeout;
+        loop {
+            for result in results.iter_mut() {
+                let PayoutOutcome::Pending { transaction_id } = result.outcome else {
+                    continue;
+                };
+                let Ok(response) = self
+                    .client
+                    .get_transaction(transaction_id, None::<TxType>)
+                    .await
+                else {
+                    continue;
+                };
+                let transaction = response.transaction;
+                result.outcome = match transaction.state {
+                    TransactionState::Complete => PayoutOutcome::Complete {
+                        transaction_id,
+                        tx_hash: transaction.tx_hash,
+                    },
+                    TransactionState::Failed
+                    | TransactionState::Denied
+                    | TransactionState::Cancelled => PayoutOutcome::Failed {
+                        transaction_id,
+                        reason: transaction
+                            .error_reason
+                            .unwrap_or_else(|| format!("{:?}", transaction.state)),
+                    },
+                    _ => continue,
+                };
+            }
+
+            let pending = results
+                .iter()
+                .any(|x| matches!(x.outcome, PayoutOutcome::Pending { .. }));
+            if !pending || Instant::now() >= deadline || self.cancellation.is_cancelled() {
+                return;
+            }
+            if self.cancellation.sleep(self.poll_interval).await {
+                return;
+            }
+        }
+    }
+}
+
+fn validate_row(
+    row: &PayoutRow,
+    decimals: Option<u32>,
+) -> std::result::Result<(Web3Address, Decimal), Vec<String>> {
+    let mut errors = Vec::new();
+
+    let destination = Web3Address::from_str(&row.destination);
+    if destination.is_err() {
+        errors.push(format!("invalid destination address {:?}", row.destination));
+    }
+
+    if row.ref_id.is_empty() || row.ref_id.len() > MAX_REF_ID_LENGTH {
+        errors.push(format!(
+            "refId must be between 1 and {} characters",
+            MAX_REF_ID_LENGTH
+        ));
+    }
+
+    let amount = match Decimal::from_str(&row.amount) {
+        Ok(amount) if amount > Decimal::ZERO => Some(amount),
+        _ => {
+            errors.push(format!("invalid amount {:?}", row.amount));
+            None
+        }
+    };
+    if let (Some(amount), Some(decimals)) = (amount, decimals) {
+        if amount.normalize().scale() > decimals {
+            errors.push(format!(
+                "amount {} has more than {} decimal places",
+                row.amount, decimals
+            ));
+        }
+    }
+
+    match (destination, amount) {
+        (Ok(destination), Some(amount)) if errors.is_empty() => Ok((destination, amount)),
+        _ => Err(errors),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    fn row(destination: &str, amount: &str, ref_id: &str) -> PayoutRow {
+        PayoutRow {
+            destination: destination.to_string(),
+            token_id: TokenId::default(),
+            amount: amount.to_string(),
+            ref_id: ref_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_read_csv() {
+        let csv = "destination,tokenId,amount,refId\n0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48, 00000000-0000-0000-0000-000000000000 ,12.50,creator-1\n";
+        let rows = read_csv(csv.as_bytes()).unwrap();
+        assert_eq!(
+            rows,
+            vec![row(
+                "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+                "12.50",
+                "creator-1"
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_rows_dont_stop_the_batch() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let (known, deleted) = (TokenId(Uuid::new_v4()), TokenId(Uuid::new_v4()));
+        transport
+            .push_data(json!({
+                "token": {
+                    "id": known,
+                    "blockchain": "MATIC-AMOY",
+                    "decimals": 6,
+                    "isNative": false,
+                    "updateDate": "2023-11-25T14:26:38Z",
+                    "createDate": "2023-11-25T14:26:38Z"
+                }
+            }))
+            .push_error(StatusCode::NOT_FOUND, 156004, "Token not found");
+
+        let address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let mut rows = vec![row(address, "1", "dup"), row(address, "2", "dup")];
+        rows.push(PayoutRow {
+            token_id: deleted,
+            ..row(address, "3", "other")
+        });
+        rows[0].token_id = known;
+        rows[1].token_id = known;
+        let report = BatchRunner::new(&client, WalletId(Uuid::new_v4()))
+            .run(rows)
+            .await
+            .unwrap();
+
+        let errors: Vec<_> = report
+            .results
+            .iter()
+            .map(|x| match &x.outcome {
+                PayoutOutcome::Invalid { errors } => errors.join("; "),
+                outcome => panic!("unexpected outcome {:?}", outcome),
+            })
+            .collect();
+        assert!(errors[0].contains("more than once"));
+        assert!(errors[1].contains("more than once"));
+        assert!(errors[2].contains("could not look up token"));
+        // Two token lookups and no transfers.
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    fn transaction(id: TransactionId, state: &str) -> serde_json::Value {
+        json!({
+            "transaction": {
+                "id": id,
+                "blockchain": "MATIC-AMOY",
+                "state": state,
+                "transactionType": "OUTBOUND",
+                "txHash": (state == "COMPLETE").then_some("0xabc"),
+                "errorReason": (state == "FAILED").then_some("INSUFFICIENT_NATIVE_TOKEN"),
+                "createDate": "2024-03-01T10:00:00Z",
+                "updateDate": "2024-03-01T10:00:00Z"
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_batch() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let token_id = TokenId(Uuid::new_v4());
+        let (paid, bounced) = (TransactionId(Uuid::new_v4()), TransactionId(Uuid::new_v4()));
+        transport.push_data(json!({
+            "token": {
+                "id": token_id,
+                "blockchain": "MATIC-AMOY",
+                "decimals": 6,
+                "isNative": false,
+                "updateDate": "2023-11-25T14:26:38Z",
+                "createDate": "2023-11-25T14:26:38Z"
+            }
+        }));
+        transport.push_data(json!({ "id": paid, "state": "INITIATED" }));
+        transport.push_data(json!({ "id": bounced, "state": "INITIATED" }));
+        // First poll: one still on its way, one failed. Second poll: the first lands.
+        transport.push_data(transaction(paid, "SENT"));
+        transport.push_data(transaction(bounced, "FAILED"));
+        transport.push_data(transaction(paid, "COMPLETE"));
+
+        let address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let rows = [
+            row(address, "12.5", "creator-1"),
+            row(address, "3", "creator-2"),
+        ]
+        .map(|x| PayoutRow { token_id, ..x });
+        let wallet_id = WalletId(Uuid::new_v4());
+        let runner = BatchRunner::new(&client, wallet_id)
+            .concurrency(1)
+            .poll_interval(Duration::from_millis(1));
+        let report = runner.run(rows).await.unwrap();
+
+        assert_eq!(
+            report.results[0].outcome,
+            PayoutOutcome::Complete {
+                transaction_id: paid,
+                tx_hash: Some("0xabc".to_string())
+            }
+        );
+        assert_eq!(
+            report.results[1].outcome,
+            PayoutOutcome::Failed {
+                transaction_id: bounced,
+                reason: "INSUFFICIENT_NATIVE_TOKEN".to_string()
+            }
+        );
+        assert_eq!((report.completed(), report.failed()), (1, 1));
+        assert_eq!((report.pending(), report.skipped()), (0, 0));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 6);
+        let transfer = requests[1].body.as_ref().unwrap();
+        assert_eq!(transfer["amounts"], json!(["12.5"]));
+        assert_eq!(transfer["refId"], json!("creator-1"));
+        assert_eq!(
+            transfer["idempotencyKey"],
+            json!(runner.idempotency_key("creator-1"))
+        );
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.contains(&format!("COMPLETE,{},0xabc", paid)));
+        assert!(csv.contains(&format!("FAILED,{},INSUFFICIENT_NATIVE_TOKEN", bounced)));
+    }
+
+    #[test]
+    fn test_validate_row() {
+        let address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        assert_eq!(
+            validate_row(&row(address, "12.50", "a"), Some(6))
+                .unwrap()
+                .1,
+            Decimal::new(125, 1)
+        );
+        assert_eq!(
+            validate_row(&row(address, "1.0000001", "a"), Some(6))
+                .unwrap_err()
+                .len(),
+            1
+        );
+        assert_eq!(
+            validate_row(&row("0x1234", "-1", ""), Some(6))
+                .unwrap_err()
+                .len(),
+            3
+        );
+    }
+}
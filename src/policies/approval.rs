@@ -0,0 +1,448 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::{CircleError, Result};
+use crate::models::ids::{TokenId, TransactionId};
+use crate::models::transaction_transfer_create::FeeLevel;
+use crate::policies::TransferIntent;
+
+/// Transfers of more than `above` need `approvals` distinct approvals. Without a token the
+/// rule applies to every token, so only use that when all of them have similar value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRule {
+    pub token_id: Option<TokenId>,
+    pub above: Decimal,
+    pub approvals: usize,
+}
+
+impl ApprovalRule {
+    pub fn new(above: Decimal, approvals: usize) -> Self {
+        ApprovalRule {
+            token_id: None,
+            above,
+            approvals,
+        }
+    }
+
+    pub fn token(mut self, token_id: TokenId) -> Self {
+        self.token_id = Some(token_id);
+        self
+    }
+
+    fn applies(&self, intent: &TransferIntent) -> bool {
+        self.token_id.map_or(true, |x| x == intent.token_id) && intent.amount > self.above
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "decision", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Decision {
+    Approve,
+    Reject {
+        reason: String,
+    },
+    /// Not decided yet; the approver is asked again on the next [`ApprovalGate::collect`].
+    Abstain,
+}
+
+/// Someone who can approve transfers, e.g. a bridge to a chat or ticketing system. Answering
+/// [`Decision::Abstain`] until a human has decided, and being asked again later, is the usual
+/// way to wait for one.
+pub trait Approver: Send + Sync {
+    /// Approvals from approvers with the same name count once.
+    fn name(&self) -> &str;
+
+    fn review<'a>(&'a self, transfer: &'a PendingTransfer) -> BoxFuture<'a, Result<Decision>>;
+}
+
+impl<A: Approver + ?Sized> Approver for Arc<A> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn review<'a>(&'a self, transfer: &'a PendingTransfer) -> BoxFuture<'a, Result<Decision>> {
+        (**self).review(transfer)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApprovalStatus {
+    /// Waiting for approvals, or for a submission that failed with a retryable error.
+    Pending,
+    Rejected {
+        by: String,
+        reason: String,
+    },
+    Submitted {
+        transaction_id: TransactionId,
+    },
+    /// Approved, but Circle refused the transfer.
+    Failed {
+        reason: String,
+    },
+}
+
+impl ApprovalStatus {
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, ApprovalStatus::Pending)
+    }
+}
+
+/// One entry of a transfer's audit trail.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApprovalEvent {
+    Requested {
+        at: DateTime<Utc>,
+        by: String,
+        required_approvals: usize,
+    },
+    Approved {
+        at: DateTime<Utc>,
+        by: String,
+    },
+    Rejected {
+        at: DateTime<Utc>,
+        by: String,
+        reason: String,
+    },
+    Submitted {
+        at: DateTime<Utc>,
+        transaction_id: TransactionId,
+    },
+    SubmitFailed {
+        at: DateTime<Utc>,
+        reason: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransfer {
+    pub intent: TransferIntent,
+    pub required_approvals: usize,
+    /// Names of the approvers who approved, in order.
+    pub approvals: Vec<String>,
+    pub status: ApprovalStatus,
+    pub history: Vec<ApprovalEvent>,
+}
+
+/// Where transfers and their audit trails are kept, usually the application's database (see
+/// [`SqlStore`](crate::sql::SqlStore) with the `sqlx` feature). Entries are never deleted.
+pub trait ApprovalStore: Send + Sync {
+    /// Inserting a transfer whose intent id is already stored must succeed and keep the
+    /// stored one.
+    fn insert<'a>(&'a self, transfer: &'a PendingTransfer) -> BoxFuture<'a, Result<()>>;
+
+    fn get(&self, id: Uuid) -> BoxFuture<'_, Result<Option<PendingTransfer>>>;
+
+    /// Replaces the stored transfer with the same intent id.
+    fn update<'a>(&'a self, transfer: &'a PendingTransfer) -> BoxFuture<'a, Result<()>>;
+
+    /// Transfers that aren't terminal yet, oldest first.
+    fn pending(&self) -> BoxFuture<'_, Result<Vec<PendingTransfer>>>;
+}
+
+/// An [`ApprovalStore`] that forgets everything on restart, for tests and prototypes.
+#[derive(Debug, Default)]
+pub struct InMemoryApprovalStore(Mutex<HashMap<Uuid, PendingTransfer>>);
+
+impl ApprovalStore for InMemoryApprovalStore {
+    fn insert<'a>(&'a self, transfer: &'a PendingTransfer) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.0
+                .lock()
+                .unwrap()
+                .entry(transfer.intent.id)
+                .or_insert(transfer.clone());
+            Ok(())
+        })
+    }
+
+    fn get(&self, id: Uuid) -> BoxFuture<'_, Result<Option<PendingTransfer>>> {
+        Box::pin(async move { Ok(self.0.lock().unwrap().get(&id).cloned()) })
+    }
+
+    fn update<'a>(&'a self, transfer: &'a PendingTransfer) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(transfer.intent.id, transfer.clone());
+            Ok(())
+        })
+    }
+
+    fn pending(&self) -> BoxFuture<'_, Result<Vec<PendingTransfer>>> {
+        Box::pin(async move {
+            let transfers = self.0.lock().unwrap();
+            let mut pending = transfers
+                .values()
+                .filter(|x| !x.status.is_terminal())
+                .cloned()
+                .collect::<Vec<_>>();
+            pending.sort_by_key(|x| x.intent.requested_at);
+            Ok(pending)
+        })
+    }
+}
+
+/// Holds transfers above the thresholds of its [`ApprovalRule`]s until enough distinct
+/// [`Approver`]s have approved them, then submits them. Every transfer, approved or not, is
+/// kept in the store with its audit trail. The requester never counts as an approver of their
+/// own transfer.
+pub struct ApprovalGate<'a, S> {
+    client: &'a CircleClient,
+    store: S,
+    rules: Vec<ApprovalRule>,
+    approvers: Vec<Arc<dyn Approver>>,
+    fee_level: FeeLevel,
+}
+
+impl<'a, S: ApprovalStore> ApprovalGate<'a, S> {
+    pub fn new(client: &'a CircleClient, store: S) -> Self {
+        ApprovalGate {
+            client,
+            store,
+            rules: Vec::new(),
+            approvers: Vec::new(),
+            fee_level: FeeLevel::Medium,
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn rule(mut self, rule: ApprovalRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn approver<A: Approver + 'static>(mut self, approver: A) -> Self {
+        self.approvers.push(Arc::new(approver));
+        self
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee_level = fee_level;
+        self
+    }
+
+    /// The most approvals any matching rule asks for; 0 if none matches.
+    pub fn required_approvals(&self, intent: &TransferIntent) -> usize {
+        self.rules
+            .iter()
+            .filter(|x| x.applies(intent))
+            .map(|x| x.approvals)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Records `intent`, asks the approvers if it needs approval, and submits it once it has
+    /// enough. Submitting an intent that is already stored is the same as collecting it.
+    pub async fn submit(&self, intent: TransferIntent) -> Result<PendingTransfer> {
+        if self.store.get(intent.id).await?.is_some() {
+            return self.collect(intent.id).await;
+        }
+        let required_approvals = self.required_approvals(&intent);
+        let transfer = PendingTransfer {
+            history: vec![ApprovalEvent::Requested {
+                at: Utc::now(),
+                by: intent.requested_by.clone(),
+                required_approvals,
+            }],
+            intent,
+            required_approvals,
+            approvals: Vec::new(),
+            status: ApprovalStatus::Pending,
+        };
+        self.store.insert(&transfer).await?;
+        self.advance(transfer).await
+    }
+
+    /// Asks the approvers who haven't approved `id` yet again, and submits it if it has enough
+    /// approvals now.
+    pub async fn collect(&self, id: Uuid) -> Result<PendingTransfer> {
+        let transfer =
+            self.store.get(id).await?.ok_or_else(|| {
+                CircleError::InvalidState(format!("no transfer {} to approve", id))
+            })?;
+        if transfer.status.is_terminal() {
+            return Ok(transfer);
+        }
+        self.advance(transfer).await
+    }
+
+    /// [`ApprovalGate::collect`] for every pending transfer. Stops at the first error.
+    pub async fn collect_all(&self) -> Result<Vec<PendingTransfer>> {
+        let mut transfers = Vec::new();
+        for transfer in self.store.pending().await? {
+            transfers.push(self.collect(transfer.intent.id).await?);
+        }
+        Ok(transfers)
+    }
+
+    async fn advance(&self, mut transfer: PendingTransfer) -> Result<PendingTransfer> {
+        let reviewed = self.review(&mut transfer).await;
+        if reviewed.is_ok()
+            && transfer.status == ApprovalStatus::Pending
+            && transfer.approvals.len() >= transfer.required_approvals
+        {
+            self.submit_approved(&mut transfer).await;
+        }
+        self.store.update(&transfer).await?;
+        reviewed.map(|_| transfer)
+    }
+
+    async fn review(&self, transfer: &mut PendingTransfer) -> Result<()> {
+        for approver in &self.approvers {
+            if transfer.approvals.len() >= transfer.required_approvals {
+                break;
+            }
+            let name = approver.name();
+            if name == transfer.intent.requested_by || transfer.approvals.iter().any(|x| x == name)
+            {
+                continue;
+            }
+            match approver.review(transfer).await? {
+                Decision::Approve => {
+                    transfer.approvals.push(name.to_string());
+                    transfer.history.push(ApprovalEvent::Approved {
+                        at: Utc::now(),
+                        by: name.to_string(),
+                    });
+                }
+                Decision::Reject { reason } => {
+                    transfer.history.push(ApprovalEvent::Rejected {
+                        at: Utc::now(),
+                        by: name.to_string(),
+                        reason: reason.clone(),
+                    });
+                    transfer.status = ApprovalStatus::Rejected {
+                        by: name.to_string(),
+                        reason,
+                    };
+                    break;
+                }
+                Decision::Abstain => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn submit_approved(&self, transfer: &mut PendingTransfer) {
+        match transfer.intent.submit(self.client, self.fee_level).await {
+            Ok(response) => {
+                transfer.history.push(ApprovalEvent::Submitted {
+                    at: Utc::now(),
+                    transaction_id: response.id,
+                });
+                transfer.status = ApprovalStatus::Submitted {
+                    transaction_id: response.id,
+                };
+            }
+            Err(err) => {
+                transfer.history.push(ApprovalEvent::SubmitFailed {
+                    at: Utc::now(),
+                    reason: err.to_string(),
+                });
+                // Circle never accepted it, so giving up can't leave a transfer behind.
+                if err.is_client_bug() {
+                    transfer.status = ApprovalStatus::Failed {
+                        reason: err.to_string(),
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+    use crate::models::ids::WalletId;
+    use crate::models::web3_address::Web3Address;
+
+    struct Manual(&'static str, Mutex<Decision>);
+
+    impl Approver for Manual {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn review<'a>(&'a self, _: &'a PendingTransfer) -> BoxFuture<'a, Result<Decision>> {
+            let decision = self.1.lock().unwrap().clone();
+            Box::pin(async move { Ok(decision) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approval_gate() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let destination =
+            Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let intent = |amount: i64| {
+            TransferIntent::new(
+                WalletId(Uuid::new_v4()),
+                TokenId(Uuid::new_v4()),
+                destination.clone(),
+                Decimal::from(amount),
+                "carol",
+            )
+        };
+        let bob = Arc::new(Manual("bob", Mutex::new(Decision::Abstain)));
+        let gate = ApprovalGate::new(&client, InMemoryApprovalStore::default())
+            .rule(ApprovalRule::new(Decimal::from(1000), 2))
+            .approver(Manual("carol", Mutex::new(Decision::Approve)))
+            .approver(Manual("alice", Mutex::new(Decision::Approve)))
+            .approver(bob.clone());
+
+        // Small transfers go straight through.
+        let transaction_id = TransactionId(Uuid::new_v4());
+        transport.push_data(json!({ "id": transaction_id, "state": "INITIATED" }));
+        let small = gate.submit(intent(10)).await.unwrap();
+        assert_eq!(small.status, ApprovalStatus::Submitted { transaction_id });
+
+        // The requester's own approval doesn't count, and bob hasn't decided.
+        let large = gate.submit(intent(5000)).await.unwrap();
+        assert_eq!(large.status, ApprovalStatus::Pending);
+        assert_eq!(large.approvals, ["alice"]);
+        assert_eq!(transport.requests().len(), 1);
+
+        *bob.1.lock().unwrap() = Decision::Approve;
+        transport.push_data(json!({ "id": transaction_id, "state": "INITIATED" }));
+        let large = gate.collect_all().await.unwrap().remove(0);
+        assert_eq!(large.status, ApprovalStatus::Submitted { transaction_id });
+        assert_eq!(large.approvals, ["alice", "bob"]);
+        assert_eq!(large.history.len(), 4);
+        let requests = transport.requests();
+        assert_eq!(
+            requests[1].body.as_ref().unwrap()["idempotencyKey"],
+            json!(large.intent.id)
+        );
+
+        *bob.1.lock().unwrap() = Decision::Reject {
+            reason: "unknown destination".to_string(),
+        };
+        let rejected = gate.submit(intent(3000)).await.unwrap();
+        assert!(matches!(rejected.status, ApprovalStatus::Rejected { by, .. } if by == "bob"));
+        assert!(gate.store().pending().await.unwrap().is_empty());
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
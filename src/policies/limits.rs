@@ -0,0 +1,373 @@
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use futures::future::BoxFuture;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::ids::{TokenId, WalletId};
+use crate::models::transaction_transfer_create::{FeeLevel, TransactionTransferCreateResponse};
+use crate::policies::TransferIntent;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LimitWindow {
+    /// Each transfer on its own.
+    Transaction,
+    /// The last 60 minutes, rolling.
+    Hour,
+    /// The last 24 hours, rolling.
+    Day,
+}
+
+impl LimitWindow {
+    fn duration(self) -> Option<Duration> {
+        match self {
+            LimitWindow::Transaction => None,
+            LimitWindow::Hour => Some(Duration::hours(1)),
+            LimitWindow::Day => Some(Duration::days(1)),
+        }
+    }
+}
+
+/// At most `max` per `window`, for one wallet and/or token, or for everything. A limit
+/// without a token adds up amounts of different tokens, so give it one unless they all have
+/// similar value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendingLimit {
+    pub wallet_id: Option<WalletId>,
+    pub token_id: Option<TokenId>,
+    pub window: LimitWindow,
+    pub max: Decimal,
+}
+
+impl SpendingLimit {
+    pub fn per_transaction(max: Decimal) -> Self {
+        Self::new(LimitWindow::Transaction, max)
+    }
+
+    pub fn per_hour(max: Decimal) -> Self {
+        Self::new(LimitWindow::Hour, max)
+    }
+
+    pub fn per_day(max: Decimal) -> Self {
+        Self::new(LimitWindow::Day, max)
+    }
+
+    fn new(window: LimitWindow, max: Decimal) -> Self {
+        SpendingLimit {
+            wallet_id: None,
+            token_id: None,
+            window,
+            max,
+        }
+    }
+
+    pub fn wallet(mut self, wallet_id: WalletId) -> Self {
+        self.wallet_id = Some(wallet_id);
+        self
+    }
+
+    pub fn token(mut self, token_id: TokenId) -> Self {
+        self.token_id = Some(token_id);
+        self
+    }
+
+    /// Where the window of a spend at `at` starts; `None` for per-transaction limits.
+    pub fn window_start(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.window.duration().map(|x| at - x)
+    }
+
+    /// Fails if `requested` on top of `spent` in the window goes past the limit.
+    pub fn allows(
+        &self,
+        spent: Decimal,
+        requested: Decimal,
+    ) -> std::result::Result<(), LimitExceeded> {
+        match spent + requested > self.max {
+            true => Err(LimitExceeded {
+                limit: self.clone(),
+                spent,
+                requested,
+            }),
+            false => Ok(()),
+        }
+    }
+
+    fn applies(&self, intent: &TransferIntent) -> bool {
+        self.wallet_id.map_or(true, |x| x == intent.wallet_id)
+            && self.token_id.map_or(true, |x| x == intent.token_id)
+    }
+}
+
+/// A transfer that would have taken a wallet or token past one of its [`SpendingLimit`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "transfer of {requested} exceeds the {:?} limit of {} ({spent} already spent)",
+    .limit.window,
+    .limit.max
+)]
+pub struct LimitExceeded {
+    pub limit: SpendingLimit,
+    /// Spent in the limit's window before this transfer; 0 for per-transaction limits.
+    pub spent: Decimal,
+    pub requested: Decimal,
+}
+
+/// One transfer counted against the limits.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Spend {
+    /// The id of the [`TransferIntent`].
+    pub id: Uuid,
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub amount: Decimal,
+    pub at: DateTime<Utc>,
+}
+
+/// Where spends are counted, usually the application's database (see
+/// [`SqlStore`](crate::sql::SqlStore) with the `sqlx` feature). Share one store between every
+/// process that submits from the same wallets, or each of them gets the full limit.
+pub trait SpendStore: Send + Sync {
+    /// The total of the spends since `since`, of `wallet_id` and `token_id` where given.
+    fn spent(
+        &self,
+        wallet_id: Option<WalletId>,
+        token_id: Option<TokenId>,
+        since: DateTime<Utc>,
+    ) -> BoxFuture<'_, Result<Decimal>>;
+
+    /// Records `spend` unless it would go past one of `limits`, which all apply to it. Checking
+    /// and recording must be one transaction in the store, e.g. under a row lock, so concurrent
+    /// callers in any process can't both slip under a limit.
+    fn try_record<'a>(
+        &'a self,
+        spend: &'a Spend,
+        limits: &'a [SpendingLimit],
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Removes the spend with `id`, if any.
+    fn release(&self, id: Uuid) -> BoxFuture<'_, Result<()>>;
+}
+
+/// A [`SpendStore`] that forgets everything on restart, for tests and prototypes. Spends
+/// older than a day are dropped as new ones come in.
+#[derive(Debug, Default)]
+pub struct InMemorySpendStore(Mutex<Vec<Spend>>);
+
+pub(crate) fn total(
+    spends: &[Spend],
+    wallet_id: Option<WalletId>,
+    token_id: Option<TokenId>,
+    since: DateTime<Utc>,
+) -> Decimal {
+    spends
+        .iter()
+        .filter(|x| x.at >= since)
+        .filter(|x| wallet_id.map_or(true, |id| id == x.wallet_id))
+        .filter(|x| token_id.map_or(true, |id| id == x.token_id))
+        .map(|x| x.amount)
+        .sum()
+}
+
+/// Fails for the first of `limits` that `spend` would exceed on top of `spends`.
+pub(crate) fn check_limits(
+    spends: &[Spend],
+    spend: &Spend,
+    limits: &[SpendingLimit],
+) -> Result<()> {
+    for limit in limits {
+        let spent = match limit.window_start(spend.at) {
+            Some(since) => total(spends, limit.wallet_id, limit.token_id, since),
+            None => Decimal::ZERO,
+        };
+        limit.allows(spent, spend.amount)?;
+    }
+    Ok(())
+}
+
+impl SpendStore for InMemorySpendStore {
+    fn spent(
+        &self,
+        wallet_id: Option<WalletId>,
+        token_id: Option<TokenId>,
+        since: DateTime<Utc>,
+    ) -> BoxFuture<'_, Result<Decimal>> {
+        Box::pin(async move { Ok(total(&self.0.lock().unwrap(), wallet_id, token_id, since)) })
+    }
+
+    fn try_record<'a>(
+        &'a self,
+        spend: &'a Spend,
+        limits: &'a [SpendingLimit],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut spends = self.0.lock().unwrap();
+            check_limits(&spends, spend, limits)?;
+            let cutoff = spend.at - Duration::days(1);
+            spends.retain(|x| x.at >= cutoff);
+            spends.push(spend.clone());
+            Ok(())
+        })
+    }
+
+    fn release(&self, id: Uuid) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.0.lock().unwrap().retain(|x| x.id != id);
+            Ok(())
+        })
+    }
+}
+
+/// Enforces [`SpendingLimit`]s before transfers are submitted, so a compromised or buggy
+/// caller can only move so much before someone notices. Reserving a transfer is a single
+/// [`SpendStore::try_record`], so the limits hold for every process sharing a store that
+/// implements it atomically, like [`SqlStore`](crate::sql::SqlStore).
+/// [`InMemorySpendStore`] only does so within one process.
+pub struct SpendingLimits<S> {
+    store: S,
+    limits: Vec<SpendingLimit>,
+}
+
+impl<S: SpendStore> SpendingLimits<S> {
+    pub fn new(store: S) -> Self {
+        SpendingLimits {
+            store,
+            limits: Vec::new(),
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn limit(mut self, limit: SpendingLimit) -> Self {
+        self.limits.push(limit);
+        self
+    }
+
+    /// Fails with [`CircleError::LimitExceeded`](crate::error::CircleError::LimitExceeded) for
+    /// the first limit `intent` would exceed, without recording anything.
+    pub async fn check(&self, intent: &TransferIntent) -> Result<()> {
+        let now = Utc::now();
+        for limit in self.limits.iter().filter(|x| x.applies(intent)) {
+            let spent = match limit.window_start(now) {
+                Some(since) => {
+                    self.store
+                        .spent(limit.wallet_id, limit.token_id, since)
+                        .await?
+                }
+                None => Decimal::ZERO,
+            };
+            limit.allows(spent, intent.amount)?;
+        }
+        Ok(())
+    }
+
+    /// Checks `intent` and counts it against the limits if it passes, atomically as far as
+    /// the store's [`SpendStore::try_record`] is.
+    pub async fn reserve(&self, intent: &TransferIntent) -> Result<()> {
+        let limits = self
+            .limits
+            .iter()
+            .filter(|x| x.applies(intent))
+            .cloned()
+            .collect::<Vec<_>>();
+        let spend = Spend {
+            id: intent.id,
+            wallet_id: intent.wallet_id,
+            token_id: intent.token_id,
+            amount: intent.amount,
+            at: Utc::now(),
+        };
+        self.store.try_record(&spend, &limits).await
+    }
+
+    /// Stops counting a reserved transfer, e.g. because it was never submitted.
+    pub async fn release(&self, intent: &TransferIntent) -> Result<()> {
+        self.store.release(intent.id).await
+    }
+
+    /// Reserves `intent` and submits it. The reservation is released only if Circle certainly
+    /// didn't accept the transfer.
+    pub async fn submit(
+        &self,
+        client: &CircleClient,
+        intent: &TransferIntent,
+        fee_level: FeeLevel,
+    ) -> Result<TransactionTransferCreateResponse> {
+        self.reserve(intent).await?;
+        let result = intent.submit(client, fee_level).await;
+        if let Err(err) = &result {
+            if err.is_client_bug() {
+                self.release(intent).await?;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::error::CircleError;
+    use crate::models::web3_address::Web3Address;
+
+    #[tokio::test]
+    async fn test_spending_limits() {
+        let (wallet, other_wallet) = (WalletId(Uuid::new_v4()), WalletId(Uuid::new_v4()));
+        let usdc = TokenId(Uuid::new_v4());
+        let limits = SpendingLimits::new(InMemorySpendStore::default())
+            .limit(SpendingLimit::per_transaction(Decimal::from(500)).token(usdc))
+            .limit(
+                SpendingLimit::per_hour(Decimal::from(800))
+                    .wallet(wallet)
+                    .token(usdc),
+            )
+            .limit(SpendingLimit::per_day(Decimal::from(1500)).token(usdc));
+        let destination =
+            Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let intent = |wallet_id, amount: i64| {
+            TransferIntent::new(
+                wallet_id,
+                usdc,
+                destination.clone(),
+                Decimal::from(amount),
+                "payouts",
+            )
+        };
+        let exceeded = |result: Result<()>| match result {
+            Err(CircleError::LimitExceeded(err)) => (err.limit.window, err.spent),
+            other => panic!("expected a limit error, got {:?}", other),
+        };
+
+        assert_eq!(
+            exceeded(limits.reserve(&intent(wallet, 501)).await),
+            (LimitWindow::Transaction, Decimal::ZERO)
+        );
+        limits.reserve(&intent(wallet, 500)).await.unwrap();
+        let second = intent(wallet, 300);
+        limits.reserve(&second).await.unwrap();
+        assert_eq!(
+            exceeded(limits.reserve(&intent(wallet, 1)).await),
+            (LimitWindow::Hour, Decimal::from(800))
+        );
+
+        // Another wallet has its own hourly limit, but shares the daily one for the token.
+        limits.reserve(&intent(other_wallet, 500)).await.unwrap();
+        assert_eq!(
+            exceeded(limits.check(&intent(other_wallet, 300)).await),
+            (LimitWindow::Day, Decimal::from(1300))
+        );
+        limits.release(&second).await.unwrap();
+        limits.check(&intent(other_wallet, 300)).await.unwrap();
+    }
+}
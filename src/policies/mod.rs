@@ -0,0 +1,89 @@
+//! Client-side controls on outgoing transfers, enforced before anything reaches Circle.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::ids::{TokenId, WalletId};
+use crate::models::transaction_transfer_create::{
+    FeeLevel, TransactionTransferCreateResponse, TransferRequestBuilder,
+};
+use crate::models::web3_address::Web3Address;
+
+pub use approval::{
+    ApprovalEvent, ApprovalGate, ApprovalRule, ApprovalStatus, ApprovalStore, Approver, Decision,
+    InMemoryApprovalStore, PendingTransfer,
+};
+#[cfg(feature = "sqlx")]
+pub(crate) use limits::{check_limits, total};
+pub use limits::{
+    InMemorySpendStore, LimitExceeded, LimitWindow, Spend, SpendStore, SpendingLimit,
+    SpendingLimits,
+};
+
+mod approval;
+mod limits;
+
+/// A transfer someone wants to make, before it is submitted.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferIntent {
+    /// Also the idempotency key of the transfer.
+    pub id: Uuid,
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub destination: Web3Address,
+    pub amount: Decimal,
+    pub ref_id: Option<String>,
+    /// Who asked for the transfer, e.g. a user or service name.
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+}
+
+impl TransferIntent {
+    pub fn new<S: Into<String>>(
+        wallet_id: WalletId,
+        token_id: TokenId,
+        destination: Web3Address,
+        amount: Decimal,
+        requested_by: S,
+    ) -> Self {
+        TransferIntent {
+            id: Uuid::new_v4(),
+            wallet_id,
+            token_id,
+            destination,
+            amount,
+            ref_id: None,
+            requested_by: requested_by.into(),
+            requested_at: Utc::now(),
+        }
+    }
+
+    pub fn ref_id<S: Into<String>>(mut self, ref_id: S) -> Self {
+        self.ref_id = Some(ref_id.into());
+        self
+    }
+
+    pub(crate) async fn submit(
+        &self,
+        client: &CircleClient,
+        fee_level: FeeLevel,
+    ) -> Result<TransactionTransferCreateResponse> {
+        let mut request = TransferRequestBuilder::new()
+            .from_wallet(self.wallet_id)
+            .to_address(self.destination.clone())
+            .token(self.token_id)
+            .amount(self.amount)
+            .fee_level(fee_level);
+        if let Some(ref_id) = &self.ref_id {
+            request = request.ref_id(ref_id.clone());
+        }
+        client
+            .create_transfer_transaction(self.id, request.build()?)
+            .await
+    }
+}
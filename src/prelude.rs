@@ -0,0 +1,60 @@
+//! The types most integrations need, in one import:
+//!
+//! ```no_run
+//! use circle_api::prelude::*;
+//!
+//! # async fn run(wallet_id: WalletId, token_id: TokenId, to: Web3Address) -> Result<(), CircleError> {
+//! let client = CircleClient::builder()
+//!     .api_key("key")
+//!     .entity_secret("secret")
+//!     .environment(Environment::Sandbox)
+//!     .build()
+//!     .await?;
+//! let transfer = TransferRequestBuilder::new()
+//!     .from_wallet(wallet_id)
+//!     .to_address(to)
+//!     .token(token_id)
+//!     .amount("12.50")
+//!     .fee_level(FeeLevel::Medium)
+//!     .build()?;
+//! client.create_transfer_transaction(None, transfer).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`crate::error::Result`] is left out so that it doesn't shadow `std`'s `Result`.
+
+pub use crate::api::{
+    CircleClient, CircleClientBuilder, CircleErrorCode, Environment, IdempotencyPolicy,
+    RequestOptions, RetryPolicy, Timeouts,
+};
+pub use crate::config::CircleConfig;
+pub use crate::error::CircleError;
+pub use crate::models::blockchain::Blockchain;
+pub use crate::models::fee::{FeeConfig, FeeLevel};
+pub use crate::models::ids::{TokenId, TransactionId, WalletId, WalletSetId};
+pub use crate::models::pagination::{PageParams, SortOrder};
+#[cfg(feature = "wallets")]
+pub use crate::models::signing_message::SignMessageRequestBuilder;
+pub use crate::models::time_range::TimeRange;
+pub use crate::models::token_amount::{TokenAmount, TokenAmountFormat};
+pub use crate::models::transaction::{Transaction, TxType};
+#[cfg(feature = "transactions")]
+pub use crate::models::transaction_list::{TransactionListQueryParams, TransactionQuery};
+pub use crate::models::transaction_state::TransactionState;
+pub use crate::models::transaction_transfer_create::{
+    TransactionTransferCreateRequestBuilder, TransferRequestBuilder,
+};
+#[cfg(feature = "wallets")]
+pub use crate::models::wallet_balance::WalletBalanceQueryParams;
+#[cfg(feature = "wallets")]
+pub use crate::models::wallet_create::{WalletCreateRequestBuilder, WalletMetadata};
+#[cfg(feature = "wallets")]
+pub use crate::models::wallet_detail::WalletDetail;
+#[cfg(feature = "wallets")]
+pub use crate::models::wallet_list::WalletListQueryParams;
+#[cfg(feature = "wallets")]
+pub use crate::models::wallet_set::{CreateWalletSetRequestBuilder, WalletSetsQueryParams};
+#[cfg(feature = "wallets")]
+pub use crate::models::wallet_update::WalletUpdateRequest;
+pub use crate::models::web3_address::Web3Address;
@@ -0,0 +1,191 @@
+//! QR codes for deposit addresses and payment requests, for point-of-sale and invoice pages.
+//!
+//! Encode a bare address with `qr::png(&address.to_string(), 8)`, or build an EIP-681 URI
+//! with [`PaymentUri`] so wallets prefill the token and amount.
+
+use qrcode::render::svg;
+use qrcode::{Color, QrCode};
+use rust_decimal::Decimal;
+
+use crate::error::{CircleError, Result};
+use crate::models::blockchain::Blockchain;
+use crate::models::token_info::TokenInfo;
+use crate::models::web3_address::Web3Address;
+
+/// Modules of light border around the code, as the QR spec asks for.
+const QUIET_ZONE: usize = 4;
+
+/// An EIP-681 payment request: `ethereum:<to>@<chain>?value=<wei>` for the native token, or
+/// `ethereum:<token>@<chain>/transfer?address=<to>&uint256=<units>` for an ERC-20.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentUri {
+    to: Web3Address,
+    chain_id: Option<u64>,
+    amount: Option<Decimal>,
+    token: Option<Web3Address>,
+    decimals: u32,
+}
+
+impl PaymentUri {
+    pub fn new(to: Web3Address) -> Self {
+        PaymentUri {
+            to,
+            chain_id: None,
+            amount: None,
+            token: None,
+            decimals: 18,
+        }
+    }
+
+    /// Pins the request to `blockchain`. Ignored for non-EVM chains.
+    pub fn blockchain(mut self, blockchain: &Blockchain) -> Self {
+        self.chain_id = blockchain.evm_chain_id();
+        self
+    }
+
+    /// Asks for `token` on its blockchain, as returned by `CircleClient::get_token`.
+    pub fn token(mut self, token: &TokenInfo) -> Self {
+        self = self.blockchain(&token.blockchain);
+        if !token.is_native {
+            self.token = token.token_address.clone();
+        }
+        self.decimals = token.decimals.map_or(18, |x| x.max(0) as u32);
+        self
+    }
+
+    /// Asks for an ERC-20 token by contract address.
+    pub fn erc20(mut self, contract: Web3Address, decimals: u32) -> Self {
+        self.token = Some(contract);
+        self.decimals = decimals;
+        self
+    }
+
+    /// The amount to pay, in whole tokens.
+    pub fn amount(mut self, amount: Decimal) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Fails if the amount has more decimal places than the token.
+    pub fn to_uri(&self) -> Result<String> {
+        let chain = self.chain_id.map(|x| format!("@{x}")).unwrap_or_default();
+        let units = self.amount.map(|x| units(x, self.decimals)).transpose()?;
+        Ok(match (&self.token, units) {
+            (Some(token), Some(units)) => {
+                format!(
+                    "ethereum:{token}{chain}/transfer?address={}&uint256={units}",
+                    self.to
+                )
+            }
+            (Some(token), None) => {
+                format!("ethereum:{token}{chain}/transfer?address={}", self.to)
+            }
+            (None, Some(units)) => format!("ethereum:{}{chain}?value={units}", self.to),
+            (None, None) => format!("ethereum:{}{chain}", self.to),
+        })
+    }
+
+    /// The URI as a PNG, see [`png`].
+    pub fn png(&self, scale: u32) -> Result<Vec<u8>> {
+        png(&self.to_uri()?, scale)
+    }
+
+    /// The URI as an SVG document, see [`svg`].
+    pub fn svg(&self) -> Result<String> {
+        svg(&self.to_uri()?)
+    }
+}
+
+/// `amount` in the token's smallest unit.
+fn units(amount: Decimal, decimals: u32) -> Result<String> {
+    let scaled = 10i128
+        .checked_pow(decimals)
+        .and_then(|x| Decimal::try_from_i128_with_scale(x, 0).ok())
+        .and_then(|x| amount.checked_mul(x))
+        .ok_or_else(|| CircleError::AmountOutOfRange(amount.to_string()))?;
+    if amount.is_sign_negative() || !scaled.fract().is_zero() {
+        return Err(CircleError::Validation(vec![format!(
+            "amount {amount} isn't a whole number of units with {decimals} decimals"
+        )]));
+    }
+    Ok(scaled.trunc().to_string())
+}
+
+fn encode(data: &str) -> Result<QrCode> {
+    QrCode::new(data.as_bytes())
+        .map_err(|err| CircleError::Validation(vec![format!("can't encode QR code: {err}")]))
+}
+
+/// `data` as a grayscale PNG with `scale` pixels per module.
+pub fn png(data: &str, scale: u32) -> Result<Vec<u8>> {
+    let code = encode(data)?;
+    let scale = scale.max(1) as usize;
+    let modules = code.width() + 2 * QUIET_ZONE;
+    let size = modules * scale;
+    let colors = code.to_colors();
+    let mut pixels = vec![255u8; size * size];
+    for (i, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x = (i % code.width() + QUIET_ZONE) * scale;
+        let y = (i / code.width() + QUIET_ZONE) * scale;
+        for row in y..y + scale {
+            pixels[row * size + x..row * size + x + scale].fill(0);
+        }
+    }
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+    Ok(bytes)
+}
+
+/// `data` as an SVG document, scalable to any size.
+pub fn svg(data: &str) -> Result<String> {
+    Ok(encode(data)?
+        .render::<svg::Color>()
+        .quiet_zone(true)
+        .min_dimensions(200, 200)
+        .build())
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_payment_uri() {
+        let to = Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let usdc = Web3Address::from_str("0x41E94Eb019C0762f9Bfcf9Fb1E58725BfB0e7582").unwrap();
+        let uri = PaymentUri::new(to.clone())
+            .blockchain(&Blockchain::MaticAmoy)
+            .erc20(usdc.clone(), 6)
+            .amount(Decimal::new(1050, 2));
+        assert_eq!(
+            uri.to_uri().unwrap(),
+            "ethereum:0x41e94eb019c0762f9bfcf9fb1e58725bfb0e7582@80002/transfer\
+             ?address=0x6e5eaf34c73d1cd0be4e24f923b97cf38e10d1f3&uint256=10500000"
+        );
+        assert!(uri.png(4).unwrap().starts_with(b"\x89PNG"));
+        assert!(uri.svg().unwrap().contains("<svg"));
+
+        let native = PaymentUri::new(to);
+        assert_eq!(
+            native.clone().amount(Decimal::ONE).to_uri().unwrap(),
+            "ethereum:0x6e5eaf34c73d1cd0be4e24f923b97cf38e10d1f3?value=1000000000000000000"
+        );
+        assert!(native
+            .erc20(usdc, 6)
+            .amount(Decimal::new(1, 7))
+            .to_uri()
+            .is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Receipts of confirmed transactions, in a schema that stays the same as Circle's transaction
+//! model changes, for emailing customers or archiving.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CircleError, Result};
+use crate::explorer::default_explorer;
+use crate::models::blockchain::Blockchain;
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::transaction::{Transaction, TxType};
+use crate::models::transaction_state::TransactionState;
+use crate::models::web3_address::Web3Address;
+
+/// Bumped whenever a field of [`Receipt`] changes meaning or goes away.
+pub const RECEIPT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    pub version: u32,
+    pub transaction_id: TransactionId,
+    pub ref_id: Option<String>,
+    pub blockchain: Blockchain,
+    pub testnet: bool,
+    pub tx_hash: String,
+    pub explorer_url: Option<String>,
+    pub block_height: Option<i64>,
+    /// `INBOUND` or `OUTBOUND`, seen from `wallet_id`.
+    pub transaction_type: TxType,
+    pub wallet_id: Option<WalletId>,
+    pub token_id: Option<TokenId>,
+    pub amounts: Vec<Decimal>,
+    pub amount_in_usd: Option<Decimal>,
+    /// In the chain's native token.
+    pub network_fee: Option<Decimal>,
+    pub network_fee_in_usd: Option<Decimal>,
+    pub from: Option<Web3Address>,
+    pub to: Option<Web3Address>,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: DateTime<Utc>,
+}
+
+impl Receipt {
+    /// Fails with [`CircleError::InvalidState`] unless `transaction` is confirmed or complete
+    /// and has a hash.
+    pub fn from_transaction(transaction: &Transaction) -> Result<Self> {
+        let invalid = |reason: &str| {
+            CircleError::InvalidState(format!(
+                "no receipt for transaction {}: {}",
+                transaction.id, reason
+            ))
+        };
+        if !matches!(
+            transaction.state,
+            TransactionState::Confirmed | TransactionState::Complete
+        ) {
+            return Err(invalid("not confirmed"));
+        }
+        let tx_hash = transaction
+            .tx_hash
+            .clone()
+            .ok_or_else(|| invalid("no hash"))?;
+        Ok(Receipt {
+            version: RECEIPT_VERSION,
+            transaction_id: transaction.id,
+            ref_id: transaction.ref_id.clone(),
+            blockchain: transaction.blockchain.clone(),
+            testnet: transaction.blockchain.is_testnet(),
+            explorer_url: default_explorer(&transaction.blockchain)
+                .map(|x| x.transaction_url(&tx_hash)),
+            tx_hash,
+            block_height: transaction.block_height,
+            transaction_type: transaction.transaction_type.clone(),
+            wallet_id: transaction.wallet_id,
+            token_id: transaction.token_id,
+            amounts: transaction.amounts.clone().unwrap_or_default(),
+            amount_in_usd: transaction.amount_in_usd,
+            network_fee: transaction.network_fee,
+            network_fee_in_usd: transaction.network_fee_in_usd,
+            from: transaction.source_address.clone(),
+            to: transaction.destination_address.clone(),
+            created_at: transaction.create_date,
+            confirmed_at: transaction
+                .first_confirm_date
+                .unwrap_or(transaction.update_date),
+        })
+    }
+}
+
+impl TryFrom<&Transaction> for Receipt {
+    type Error = CircleError;
+
+    fn try_from(transaction: &Transaction) -> Result<Self> {
+        Self::from_transaction(transaction)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_receipt() {
+        let mut transaction: Transaction = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "blockchain": "ETH-SEPOLIA",
+            "createDate": "2024-03-01T10:00:00Z",
+            "updateDate": "2024-03-01T10:05:00Z",
+            "firstConfirmDate": "2024-03-01T10:01:00Z",
+            "state": "COMPLETE",
+            "transactionType": "OUTBOUND",
+            "amounts": ["12.50"],
+            "networkFee": "0.0001",
+            "txHash": "0xabc",
+            "sourceAddress": "0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3"
+        }))
+        .unwrap();
+
+        let receipt = Receipt::try_from(&transaction).unwrap();
+        assert_eq!(receipt.amounts, vec![Decimal::new(1250, 2)]);
+        assert_eq!(
+            receipt.explorer_url.as_deref(),
+            Some("https://sepolia.etherscan.io/tx/0xabc")
+        );
+        assert!(receipt.testnet);
+        assert_eq!(
+            receipt.confirmed_at,
+            transaction.first_confirm_date.unwrap()
+        );
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(json["version"], 1);
+        assert_eq!(json["from"], "0x6e5eaf34c73d1cd0be4e24f923b97cf38e10d1f3");
+        assert_eq!(serde_json::from_value::<Receipt>(json).unwrap(), receipt);
+
+        transaction.state = TransactionState::Sent;
+        assert!(matches!(
+            Receipt::from_transaction(&transaction),
+            Err(CircleError::InvalidState(_))
+        ));
+    }
+}
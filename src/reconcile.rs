@@ -0,0 +1,313 @@
+//! Reconciling an application's own ledger against Circle, for finance teams closing a period.
+
+use std::collections::HashMap;
+
+use crate::api::CircleClient;
+use crate::error::Result;
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::time_range::TimeRange;
+use crate::models::transaction::Transaction;
+use crate::models::transaction_list::TransactionQuery;
+use crate::models::transaction_state::TransactionState;
+use crate::models::wallet_balance::WalletBalanceQueryParams;
+use futures::future::BoxFuture;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// One movement as the application recorded it, keyed by the Circle transaction id.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub transaction_id: TransactionId,
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    /// Unsigned; direction doesn't take part in the comparison.
+    pub amount: Decimal,
+}
+
+/// The application's side of the books, usually read from its database.
+pub trait Ledger: Send + Sync {
+    /// Entries for movements created within `range`.
+    fn entries<'a>(&'a self, range: &'a TimeRange) -> BoxFuture<'a, Result<Vec<LedgerEntry>>>;
+
+    /// What the ledger thinks `wallet_id` currently holds of `token_id`. `None` skips the
+    /// balance check for that token.
+    fn balance(
+        &self,
+        _wallet_id: WalletId,
+        _token_id: TokenId,
+    ) -> BoxFuture<'_, Result<Option<Decimal>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AmountMismatch {
+    pub transaction_id: TransactionId,
+    pub ledger: Decimal,
+    pub circle: Option<Decimal>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceMismatch {
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub ledger: Decimal,
+    pub circle: Decimal,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationReport {
+    pub matched: usize,
+    /// Completed at Circle but not in the ledger.
+    pub missing_in_ledger: Vec<TransactionId>,
+    /// In the ledger but not completed at Circle within the window.
+    pub missing_at_circle: Vec<LedgerEntry>,
+    /// Transaction ids the ledger recorded more than once.
+    pub duplicated: Vec<TransactionId>,
+    pub amount_mismatches: Vec<AmountMismatch>,
+    /// Current balances, so only meaningful for a window ending now.
+    pub balance_mismatches: Vec<BalanceMismatch>,
+}
+
+impl ReconciliationReport {
+    pub fn is_clean(&self) -> bool {
+        self.missing_in_ledger.is_empty()
+            && self.missing_at_circle.is_empty()
+            && self.duplicated.is_empty()
+            && self.amount_mismatches.is_empty()
+            && self.balance_mismatches.is_empty()
+    }
+}
+
+pub struct Reconciler<'a, L> {
+    client: &'a CircleClient,
+    ledger: &'a L,
+    wallets: Vec<WalletId>,
+    balances: bool,
+}
+
+impl<'a, L: Ledger> Reconciler<'a, L> {
+    /// Reconciles the transactions of `wallets`.
+    pub fn new<I: IntoIterator<Item = WalletId>>(
+        client: &'a CircleClient,
+        ledger: &'a L,
+        wallets: I,
+    ) -> Self {
+        Reconciler {
+            client,
+            ledger,
+            wallets: wallets.into_iter().collect(),
+            balances: true,
+        }
+    }
+
+    /// Whether to compare current balances as well. On by default.
+    pub fn balances(mut self, balances: bool) -> Self {
+        self.balances = balances;
+        self
+    }
+
+    pub async fn reconcile(&self, range: TimeRange) -> Result<ReconciliationReport> {
+        let query = TransactionQuery::new()
+            .wallets(self.wallets.iter().copied())
+            .state(TransactionState::Complete)
+            .time_range(range.clone());
+        let transactions = self
+            .client
+            .list_transactions_stream(query.build())
+            .try_collect::<Vec<_>>()
+            .await?;
+        let entries = self.ledger.entries(&range).await?;
+        let mut report = compare(&entries, &transactions);
+        if self.balances {
+            report.balance_mismatches = self.compare_balances().await?;
+        }
+        Ok(report)
+    }
+
+    async fn compare_balances(&self) -> Result<Vec<BalanceMismatch>> {
+        let mut mismatches = Vec::new();
+        for wallet_id in &self.wallets {
+            let balances = self
+                .client
+                .get_wallet_balance(*wallet_id, WalletBalanceQueryParams::default())
+                .await?;
+            for balance in balances.token_balances {
+                let token_id = balance.token.id;
+                let Some(ledger) = self.ledger.balance(*wallet_id, token_id).await? else {
+                    continue;
+                };
+                if ledger != balance.amount {
+                    mismatches.push(BalanceMismatch {
+                        wallet_id: *wallet_id,
+                        token_id,
+                        ledger,
+                        circle: balance.amount,
+                    });
+                }
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+fn amount(transaction: &Transaction) -> Option<Decimal> {
+    Some(transaction.amounts.as_ref()?.iter().sum())
+}
+
+fn compare(entries: &[LedgerEntry], transactions: &[Transaction]) -> ReconciliationReport {
+    let mut report = ReconciliationReport::default();
+    let mut ledger = HashMap::<TransactionId, &LedgerEntry>::new();
+    for entry in entries {
+        if ledger.insert(entry.transaction_id, entry).is_some()
+            && !report.duplicated.contains(&entry.transaction_id)
+        {
+            report.duplicated.push(entry.transaction_id);
+        }
+    }
+    for transaction in transactions {
+        let Some(entry) = ledger.remove(&transaction.id) else {
+            report.missing_in_ledger.push(transaction.id);
+            continue;
+        };
+        let circle = amount(transaction);
+        if circle.map(|x| x.abs()) == Some(entry.amount.abs()) {
+            report.matched += 1;
+        } else {
+            report.amount_mismatches.push(AmountMismatch {
+                transaction_id: transaction.id,
+                ledger: entry.amount,
+                circle,
+            });
+        }
+    }
+    report.missing_at_circle = entries
+        .iter()
+        .filter(|x| ledger.remove(&x.transaction_id).is_some())
+        .cloned()
+        .collect();
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    struct Books(Vec<LedgerEntry>);
+
+    impl Ledger for Books {
+        fn entries<'a>(&'a self, _: &'a TimeRange) -> BoxFuture<'a, Result<Vec<LedgerEntry>>> {
+            Box::pin(async move { Ok(self.0.clone()) })
+        }
+
+        fn balance(
+            &self,
+            wallet_id: WalletId,
+            token_id: TokenId,
+        ) -> BoxFuture<'_, Result<Option<Decimal>>> {
+            let entries = self.0.iter();
+            let matching = entries.filter(|x| (x.wallet_id, x.token_id) == (wallet_id, token_id));
+            let balance = matching.map(|x| x.amount).sum();
+            Box::pin(async move { Ok(Some(balance)) })
+        }
+    }
+
+    fn transaction(id: TransactionId, amount: &str) -> Transaction {
+        serde_json::from_value(json!({
+            "id": id,
+            "blockchain": "MATIC-AMOY",
+            "amounts": [amount],
+            "state": "COMPLETE",
+            "transactionType": "INBOUND",
+            "createDate": "2024-03-01T10:00:00Z",
+            "updateDate": "2024-03-01T10:00:00Z"
+        }))
+        .unwrap()
+    }
+
+    fn entry(transaction_id: TransactionId, amount: i64) -> LedgerEntry {
+        LedgerEntry {
+            transaction_id,
+            wallet_id: WalletId::default(),
+            token_id: TokenId::default(),
+            amount: Decimal::from(amount),
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let ids = (0..5)
+            .map(|_| TransactionId(Uuid::new_v4()))
+            .collect::<Vec<_>>();
+        let transactions = [
+            transaction(ids[0], "10"),
+            transaction(ids[1], "20.5"),
+            transaction(ids[2], "30"),
+        ];
+        let entries = [
+            entry(ids[0], 10),
+            entry(ids[0], 10),
+            entry(ids[1], 20),
+            entry(ids[3], 40),
+        ];
+        let report = compare(&entries, &transactions);
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.duplicated, [ids[0]]);
+        assert_eq!(report.missing_in_ledger, [ids[2]]);
+        assert_eq!(report.missing_at_circle, [entry(ids[3], 40)]);
+        assert_eq!(
+            report.amount_mismatches,
+            [AmountMismatch {
+                transaction_id: ids[1],
+                ledger: Decimal::from(20),
+                circle: Some(Decimal::new(205, 1)),
+            }]
+        );
+        assert!(!report.is_clean());
+        assert!(compare(&entries[..1], &transactions[..1]).is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let id = TransactionId(Uuid::new_v4());
+        let books = Books(vec![entry(id, 10)]);
+        transport.push_data(json!({ "transactions": [transaction(id, "10")] }));
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(json!({ "tokenBalances": [{
+            "amount": "12",
+            "updateDate": "2024-03-01T10:00:00Z",
+            "token": {
+                "id": TokenId::default(),
+                "blockchain": "MATIC-AMOY",
+                "isNative": false,
+                "createDate": "2023-11-25T14:26:38Z",
+                "updateDate": "2023-11-25T14:26:38Z"
+            }
+        }] }));
+        let report = Reconciler::new(&client, &books, [WalletId::default()])
+            .reconcile(TimeRange::default())
+            .await
+            .unwrap();
+        assert_eq!(report.matched, 1);
+        assert_eq!(
+            report.balance_mismatches,
+            [BalanceMismatch {
+                wallet_id: WalletId::default(),
+                token_id: TokenId::default(),
+                ledger: Decimal::from(10),
+                circle: Decimal::from(12),
+            }]
+        );
+    }
+}
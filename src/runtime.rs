@@ -0,0 +1,38 @@
+//! Timer and clock primitives that work both natively (tokio) and in the browser, where
+//! `std::time::Instant` panics and there is no tokio timer.
+
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await
+}
+
+/// Runs `future` to completion, or gives up with `Err(())` after `duration`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, ()> {
+    tokio::time::timeout(duration, future).await.map_err(|_| ())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, ()> {
+    use futures::future::{select, Either};
+
+    let future = std::pin::pin!(future);
+    let timer = std::pin::pin!(sleep(duration));
+    match select(future, timer).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(()),
+    }
+}
@@ -0,0 +1,445 @@
+//! Recurring transfers, e.g. payroll or subscription disbursements.
+//!
+//! Each [`ScheduledTransfer`] remembers when it is next due, and [`Scheduler`] submits whatever
+//! is due and moves it forward in a [`ScheduleStore`], so a restarted process picks up where
+//! the last one stopped. The idempotency key of a run is derived from the schedule and the
+//! occurrence, so a run that was submitted but not yet recorded when the process died is not
+//! paid twice.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use futures::future::BoxFuture;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::cancel::{run_every, CancellationToken};
+use crate::error::{CircleError, Result};
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::transaction_transfer_create::{
+    FeeLevel, TransactionTransferCreateRequestBuilder,
+};
+use crate::models::web3_address::Web3Address;
+
+/// When a transfer recurs. Times are in UTC.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Schedule {
+    /// Every `seconds`, counted from `anchor`.
+    Interval {
+        anchor: DateTime<Utc>,
+        seconds: u64,
+    },
+    Daily {
+        at: NaiveTime,
+    },
+    Weekly {
+        weekday: Weekday,
+        at: NaiveTime,
+    },
+    /// On `day` of every month, or on the last day of months that are shorter.
+    Monthly {
+        day: u32,
+        at: NaiveTime,
+    },
+}
+
+impl Schedule {
+    /// The first occurrence strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Schedule::Interval { anchor, seconds } => {
+                let seconds = (*seconds).max(1) as i64;
+                if after < *anchor {
+                    return *anchor;
+                }
+                let elapsed = (after - *anchor).num_seconds() / seconds + 1;
+                *anchor + chrono::Duration::seconds(elapsed * seconds)
+            }
+            Schedule::Daily { at } => {
+                let mut date = after.date_naive();
+                while at_time(date, *at) <= after {
+                    date = date.succ_opt().unwrap_or(date);
+                }
+                at_time(date, *at)
+            }
+            Schedule::Weekly { weekday, at } => {
+                let mut date = after.date_naive();
+                while date.weekday() != *weekday || at_time(date, *at) <= after {
+                    date = date.succ_opt().unwrap_or(date);
+                }
+                at_time(date, *at)
+            }
+            Schedule::Monthly { day, at } => {
+                let (mut year, mut month) = (after.year(), after.month());
+                loop {
+                    let next = at_time(day_of_month(year, month, *day), *at);
+                    if next > after {
+                        return next;
+                    }
+                    (year, month) = if month == 12 {
+                        (year + 1, 1)
+                    } else {
+                        (year, month + 1)
+                    };
+                }
+            }
+        }
+    }
+}
+
+fn at_time(date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_time(time))
+}
+
+/// `day` of the month, clamped to the month's length.
+fn day_of_month(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day.clamp(1, 31))
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .unwrap_or_default()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTransfer {
+    /// Chosen by the application; part of every run's idempotency key, so it must not be
+    /// reused for a different schedule.
+    pub id: String,
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub destination: Web3Address,
+    pub amount: Decimal,
+    pub schedule: Schedule,
+    /// No runs at or after this time.
+    pub until: Option<DateTime<Utc>>,
+    /// The next occurrence to submit; `None` once the schedule has ended.
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+impl ScheduledTransfer {
+    /// Starts at the first occurrence at or after `start`.
+    pub fn new<S: Into<String>>(
+        id: S,
+        wallet_id: WalletId,
+        token_id: TokenId,
+        destination: Web3Address,
+        amount: Decimal,
+        schedule: Schedule,
+        start: DateTime<Utc>,
+    ) -> Self {
+        let next_run = schedule.next_after(start - chrono::Duration::nanoseconds(1));
+        ScheduledTransfer {
+            id: id.into(),
+            wallet_id,
+            token_id,
+            destination,
+            amount,
+            schedule,
+            until: None,
+            next_run: Some(next_run),
+        }
+    }
+
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self.next_run = self.next_run.filter(|x| *x < until);
+        self
+    }
+
+    fn advance(&mut self) {
+        let next = self.next_run.map(|x| self.schedule.next_after(x));
+        self.next_run = next.filter(|x| self.until.map_or(true, |until| *x < until));
+    }
+}
+
+/// Where schedules are kept between restarts, usually the application's database.
+pub trait ScheduleStore: Send + Sync {
+    fn list(&self) -> BoxFuture<'_, Result<Vec<ScheduledTransfer>>>;
+
+    /// Inserts or replaces the schedule with the same id.
+    fn save<'a>(&'a self, transfer: &'a ScheduledTransfer) -> BoxFuture<'a, Result<()>>;
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<()>>;
+}
+
+/// A [`ScheduleStore`] that forgets everything on restart, for tests and prototypes.
+#[derive(Debug, Default)]
+pub struct InMemoryScheduleStore(Mutex<HashMap<String, ScheduledTransfer>>);
+
+impl ScheduleStore for InMemoryScheduleStore {
+    fn list(&self) -> BoxFuture<'_, Result<Vec<ScheduledTransfer>>> {
+        Box::pin(async move {
+            let mut transfers = self.0.lock().unwrap().values().cloned().collect::<Vec<_>>();
+            transfers.sort_by(|a, b| a.id.cmp(&b.id));
+            Ok(transfers)
+        })
+    }
+
+    fn save<'a>(&'a self, transfer: &'a ScheduledTransfer) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut transfers = self.0.lock().unwrap();
+            transfers.insert(transfer.id.clone(), transfer.clone());
+            Ok(())
+        })
+    }
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.0.lock().unwrap().remove(id);
+            Ok(())
+        })
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "outcome", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RunOutcome {
+    Submitted {
+        transaction_id: TransactionId,
+    },
+    /// The occurrence stays due and is retried with the same idempotency key next tick.
+    Failed {
+        reason: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRun {
+    pub schedule_id: String,
+    pub occurrence: DateTime<Utc>,
+    pub idempotency_key: Uuid,
+    pub outcome: RunOutcome,
+}
+
+pub struct Scheduler<'a, S> {
+    client: &'a CircleClient,
+    store: S,
+    fee_level: FeeLevel,
+    catch_up: bool,
+    tick: Duration,
+    cancellation: CancellationToken,
+}
+
+impl<'a, S: ScheduleStore> Scheduler<'a, S> {
+    pub fn new(client: &'a CircleClient, store: S) -> Self {
+        Scheduler {
+            client,
+            store,
+            fee_level: FeeLevel::Medium,
+            catch_up: true,
+            tick: Duration::from_secs(60),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee_level = fee_level;
+        self
+    }
+
+    /// Whether occurrences missed while the scheduler was down are all submitted (the
+    /// default, as for payroll), or only the latest one.
+    pub fn catch_up(mut self, catch_up: bool) -> Self {
+        self.catch_up = catch_up;
+        self
+    }
+
+    /// Time between checks in [`Scheduler::run`].
+    pub fn tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// Stops [`Scheduler::run`] once `token` is cancelled, after the current tick.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    pub async fn register(&self, transfer: ScheduledTransfer) -> Result<()> {
+        if transfer.amount <= Decimal::ZERO {
+            return Err(CircleError::Validation(vec![format!(
+                "amount must be positive, got {}",
+                transfer.amount
+            )]));
+        }
+        self.store.save(&transfer).await
+    }
+
+    pub async fn unregister(&self, id: &str) -> Result<()> {
+        self.store.remove(id).await
+    }
+
+    pub fn idempotency_key(&self, transfer: &ScheduledTransfer, occurrence: DateTime<Utc>) -> Uuid {
+        let name = format!("schedule:{}:{}", transfer.id, occurrence.timestamp());
+        Uuid::new_v5(&transfer.wallet_id.as_uuid(), name.as_bytes())
+    }
+
+    /// Submits every occurrence due at `now`. Only store errors are returned; failed
+    /// submissions are reported in the runs and retried next time.
+    pub async fn run_due(&self, now: DateTime<Utc>) -> Result<Vec<ScheduledRun>> {
+        let mut runs = Vec::new();
+        for mut transfer in self.store.list().await? {
+            while let Some(occurrence) = transfer.next_run.filter(|x| *x <= now) {
+                if !self.catch_up && transfer.schedule.next_after(occurrence) <= now {
+                    transfer.advance();
+                    continue;
+                }
+                let run = self.submit(&transfer, occurrence).await;
+                let failed = matches!(run.outcome, RunOutcome::Failed { .. });
+                runs.push(run);
+                if failed {
+                    break;
+                }
+                transfer.advance();
+                self.store.save(&transfer).await?;
+            }
+            self.store.save(&transfer).await?;
+        }
+        Ok(runs)
+    }
+
+    /// Runs [`Scheduler::run_due`] with [`run_every`], once per tick.
+    pub async fn run<F: FnMut(Result<Vec<ScheduledRun>>)>(&self, on_runs: F) {
+        run_every(
+            self.tick,
+            &self.cancellation,
+            || self.run_due(Utc::now()),
+            on_runs,
+        )
+        .await
+    }
+
+    async fn submit(
+        &self,
+        transfer: &ScheduledTransfer,
+        occurrence: DateTime<Utc>,
+    ) -> ScheduledRun {
+        let idempotency_key = self.idempotency_key(transfer, occurrence);
+        let outcome = match self.submit_transfer(transfer, idempotency_key).await {
+            Ok(transaction_id) => RunOutcome::Submitted { transaction_id },
+            Err(err) => RunOutcome::Failed {
+                reason: err.to_string(),
+            },
+        };
+        ScheduledRun {
+            schedule_id: transfer.id.clone(),
+            occurrence,
+            idempotency_key,
+            outcome,
+        }
+    }
+
+    async fn submit_transfer(
+        &self,
+        transfer: &ScheduledTransfer,
+        idempotency_key: Uuid,
+    ) -> Result<TransactionId> {
+        let request = TransactionTransferCreateRequestBuilder::new(
+            transfer.destination.clone(),
+            transfer.token_id,
+            transfer.wallet_id,
+            transfer.amount,
+        )
+        .fee_level(self.fee_level);
+        let response = self
+            .client
+            .create_transfer_transaction(idempotency_key, request)
+            .await?;
+        Ok(response.id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    fn time(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().into()
+    }
+
+    #[test]
+    fn test_next_after() {
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let after = time("2024-01-31T10:00:00Z");
+        let daily = Schedule::Daily { at: nine };
+        assert_eq!(daily.next_after(after), time("2024-02-01T09:00:00Z"));
+        let weekly = Schedule::Weekly {
+            weekday: Weekday::Mon,
+            at: nine,
+        };
+        assert_eq!(weekly.next_after(after), time("2024-02-05T09:00:00Z"));
+        let monthly = Schedule::Monthly { day: 31, at: nine };
+        assert_eq!(monthly.next_after(after), time("2024-02-29T09:00:00Z"));
+        let interval = Schedule::Interval {
+            anchor: time("2024-01-01T00:00:00Z"),
+            seconds: 3600,
+        };
+        assert_eq!(interval.next_after(after), time("2024-01-31T11:00:00Z"));
+    }
+
+    #[tokio::test]
+    async fn test_run_due() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let destination =
+            Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let payroll = ScheduledTransfer::new(
+            "payroll-alice",
+            WalletId(Uuid::new_v4()),
+            TokenId(Uuid::new_v4()),
+            destination,
+            Decimal::from(1000),
+            Schedule::Monthly {
+                day: 1,
+                at: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            },
+            time("2024-01-01T00:00:00Z"),
+        );
+        let scheduler = Scheduler::new(&client, InMemoryScheduleStore::default());
+        scheduler.register(payroll.clone()).await.unwrap();
+
+        // Down through February: January's run failed, so both are due.
+        transport.push_error(StatusCode::BAD_REQUEST, 2, "Invalid entity");
+        let runs = scheduler
+            .run_due(time("2024-01-01T10:00:00Z"))
+            .await
+            .unwrap();
+        assert!(matches!(runs[0].outcome, RunOutcome::Failed { .. }));
+        let failed_key = runs[0].idempotency_key;
+
+        transport.push_data(json!({ "id": Uuid::new_v4(), "state": "INITIATED" }));
+        transport.push_data(json!({ "id": Uuid::new_v4(), "state": "INITIATED" }));
+        let runs = scheduler
+            .run_due(time("2024-02-02T00:00:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].idempotency_key, failed_key);
+        assert_eq!(runs[1].occurrence, time("2024-02-01T09:00:00Z"));
+        let stored = scheduler.store().list().await.unwrap();
+        assert_eq!(stored[0].next_run, Some(time("2024-03-01T09:00:00Z")));
+
+        assert!(scheduler
+            .run_due(time("2024-02-03T00:00:00Z"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
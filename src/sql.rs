@@ -0,0 +1,631 @@
+//! [`SqlStore`]: the crate's storage traits on Postgres or SQLite through `sqlx`, for teams that
+//! don't want to write the plumbing themselves.
+
+use chrono::{DateTime, Duration, Utc};
+use futures::future::BoxFuture;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::any::{install_default_drivers, AnyPoolOptions, AnyRow};
+use sqlx::migrate::Migrator;
+use sqlx::{AnyPool, Row};
+use uuid::Uuid;
+
+use crate::api::IdempotencyStore;
+use crate::deposits::{DepositAddress, DepositStore};
+use crate::error::{CircleError, Result};
+use crate::models::blockchain::Blockchain;
+use crate::models::ids::{TokenId, WalletId};
+use crate::outbox::{OutboxEntry, OutboxStore};
+use crate::policies::{
+    check_limits, total, ApprovalStore, PendingTransfer, Spend, SpendStore, SpendingLimit,
+};
+use crate::scheduler::{ScheduleStore, ScheduledTransfer};
+use crate::tags::{TagStore, TagTarget, Tags};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Implements [`ApprovalStore`], [`DepositStore`], [`IdempotencyStore`], [`OutboxStore`],
+/// [`ScheduleStore`], [`SpendStore`] and [`TagStore`]. Cheap to clone; clones share the connection pool.
+#[derive(Debug, Clone)]
+pub struct SqlStore {
+    pool: AnyPool,
+}
+
+impl SqlStore {
+    /// Connects to a `postgres://` or `sqlite:` URL. The driver has to be enabled with the
+    /// `postgres` or `sqlite` feature.
+    pub async fn connect(url: &str) -> Result<Self> {
+        install_default_drivers();
+        let pool = AnyPoolOptions::new().connect(url).await?;
+        Ok(SqlStore { pool })
+    }
+
+    /// Uses a pool the application already has. [`install_default_drivers`] must have been
+    /// called before it was created.
+    pub fn from_pool(pool: AnyPool) -> Self {
+        SqlStore { pool }
+    }
+
+    pub fn pool(&self) -> &AnyPool {
+        &self.pool
+    }
+
+    /// Creates or updates the crate's tables. Safe to run on every start.
+    pub async fn migrate(&self) -> Result<()> {
+        MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+}
+
+/// The JSON spelling of an enum or address without the quotes, e.g. `MATIC-AMOY`.
+fn text<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        Value::String(value) => Ok(value),
+        value => Ok(value.to_string()),
+    }
+}
+
+fn from_text<T: DeserializeOwned>(value: String) -> Result<T> {
+    Ok(serde_json::from_value(Value::String(value))?)
+}
+
+fn uuid(row: &AnyRow, column: &str) -> Result<Uuid> {
+    Ok(Uuid::parse_str(&row.try_get::<String, _>(column)?)?)
+}
+
+fn deposit_address(row: AnyRow) -> Result<DepositAddress> {
+    Ok(DepositAddress {
+        user_id: row.try_get("user_id")?,
+        blockchain: from_text(row.try_get("blockchain")?)?,
+        wallet_id: uuid(&row, "wallet_id")?.into(),
+        address: from_text(row.try_get("address")?)?,
+    })
+}
+
+impl DepositStore for SqlStore {
+    fn get<'a>(
+        &'a self,
+        user_id: &'a str,
+        blockchain: &'a Blockchain,
+    ) -> BoxFuture<'a, Result<Option<DepositAddress>>> {
+        Box::pin(async move {
+            let row = sqlx::query(
+                "SELECT user_id, blockchain, wallet_id, address FROM circle_deposit_addresses \
+                 WHERE user_id = $1 AND blockchain = $2",
+            )
+            .bind(user_id)
+            .bind(text(blockchain)?)
+            .fetch_optional(&self.pool)
+            .await?;
+            row.map(deposit_address).transpose()
+        })
+    }
+
+    fn find_by_wallet(&self, wallet_id: WalletId) -> BoxFuture<'_, Result<Option<DepositAddress>>> {
+        Box::pin(async move {
+            let row = sqlx::query(
+                "SELECT user_id, blockchain, wallet_id, address FROM circle_deposit_addresses \
+                 WHERE wallet_id = $1",
+            )
+            .bind(wallet_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+            row.map(deposit_address).transpose()
+        })
+    }
+
+    fn insert<'a>(&'a self, assignment: &'a DepositAddress) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO circle_deposit_addresses (user_id, blockchain, wallet_id, address) \
+                 VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING",
+            )
+            .bind(&assignment.user_id)
+            .bind(text(&assignment.blockchain)?)
+            .bind(assignment.wallet_id.to_string())
+            .bind(text(&assignment.address)?)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+}
+
+impl IdempotencyStore for SqlStore {
+    fn get_or_insert<'a>(&'a self, operation: &'a str, key: Uuid) -> BoxFuture<'a, Result<Uuid>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO circle_idempotency_keys (operation, idempotency_key) VALUES ($1, $2) \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(operation)
+            .bind(key.to_string())
+            .execute(&self.pool)
+            .await?;
+            let row = sqlx::query(
+                "SELECT idempotency_key FROM circle_idempotency_keys WHERE operation = $1",
+            )
+            .bind(operation)
+            .fetch_one(&self.pool)
+            .await?;
+            uuid(&row, "idempotency_key")
+        })
+    }
+
+    fn remove<'a>(&'a self, operation: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM circle_idempotency_keys WHERE operation = $1")
+                .bind(operation)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+impl SqlStore {
+    async fn save_outbox_entry(&self, entry: &OutboxEntry, replace: bool) -> Result<()> {
+        let conflict = match replace {
+            true => "DO UPDATE SET finished = excluded.finished, entry = excluded.entry",
+            false => "DO NOTHING",
+        };
+        let statement = format!(
+            "INSERT INTO circle_outbox (id, enqueued_at, finished, entry) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (id) {}",
+            conflict
+        );
+        sqlx::query(&statement)
+            .bind(entry.id.to_string())
+            .bind(entry.enqueued_at.timestamp_millis())
+            .bind(entry.status.is_terminal())
+            .bind(serde_json::to_string(entry)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+impl OutboxStore for SqlStore {
+    fn enqueue<'a>(&'a self, entry: &'a OutboxEntry) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.save_outbox_entry(entry, false))
+    }
+
+    fn unfinished(&self, limit: usize) -> BoxFuture<'_, Result<Vec<OutboxEntry>>> {
+        Box::pin(async move {
+            let rows = sqlx::query(
+                "SELECT entry FROM circle_outbox WHERE finished = $1 \
+                 ORDER BY enqueued_at LIMIT $2",
+            )
+            .bind(false)
+            .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+            .fetch_all(&self.pool)
+            .await?;
+            rows.into_iter()
+                .map(|row| Ok(serde_json::from_str(&row.try_get::<String, _>("entry")?)?))
+                .collect()
+        })
+    }
+
+    fn update<'a>(&'a self, entry: &'a OutboxEntry) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.save_outbox_entry(entry, true))
+    }
+}
+
+impl ScheduleStore for SqlStore {
+    fn list(&self) -> BoxFuture<'_, Result<Vec<ScheduledTransfer>>> {
+        Box::pin(async move {
+            let rows = sqlx::query("SELECT schedule FROM circle_schedules ORDER BY id")
+                .fetch_all(&self.pool)
+                .await?;
+            rows.into_iter()
+                .map(|row| {
+                    Ok(serde_json::from_str(
+                        &row.try_get::<String, _>("schedule")?,
+                    )?)
+                })
+                .collect()
+        })
+    }
+
+    fn save<'a>(&'a self, transfer: &'a ScheduledTransfer) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO circle_schedules (id, schedule) VALUES ($1, $2) \
+                 ON CONFLICT (id) DO UPDATE SET schedule = excluded.schedule",
+            )
+            .bind(&transfer.id)
+            .bind(serde_json::to_string(transfer)?)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM circle_schedules WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+impl SqlStore {
+    async fn save_approval(&self, transfer: &PendingTransfer, replace: bool) -> Result<()> {
+        let conflict = match replace {
+            true => "DO UPDATE SET finished = excluded.finished, transfer = excluded.transfer",
+            false => "DO NOTHING",
+        };
+        let statement = format!(
+            "INSERT INTO circle_approvals (id, requested_at, finished, transfer) \
+             VALUES ($1, $2, $3, $4) ON CONFLICT (id) {}",
+            conflict
+        );
+        sqlx::query(&statement)
+            .bind(transfer.intent.id.to_string())
+            .bind(transfer.intent.requested_at.timestamp_millis())
+            .bind(transfer.status.is_terminal())
+            .bind(serde_json::to_string(transfer)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+impl ApprovalStore for SqlStore {
+    fn insert<'a>(&'a self, transfer: &'a PendingTransfer) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.save_approval(transfer, false))
+    }
+
+    fn get(&self, id: Uuid) -> BoxFuture<'_, Result<Option<PendingTransfer>>> {
+        Box::pin(async move {
+            let row = sqlx::query("SELECT transfer FROM circle_approvals WHERE id = $1")
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await?;
+            row.map(|row| {
+                Ok(serde_json::from_str(
+                    &row.try_get::<String, _>("transfer")?,
+                )?)
+            })
+            .transpose()
+        })
+    }
+
+    fn update<'a>(&'a self, transfer: &'a PendingTransfer) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.save_approval(transfer, true))
+    }
+
+    fn pending(&self) -> BoxFuture<'_, Result<Vec<PendingTransfer>>> {
+        Box::pin(async move {
+            let rows = sqlx::query(
+                "SELECT transfer FROM circle_approvals WHERE finished = $1 ORDER BY requested_at",
+            )
+            .bind(false)
+            .fetch_all(&self.pool)
+            .await?;
+            rows.into_iter()
+                .map(|row| {
+                    Ok(serde_json::from_str(
+                        &row.try_get::<String, _>("transfer")?,
+                    )?)
+                })
+                .collect()
+        })
+    }
+}
+
+fn spends(rows: Vec<AnyRow>) -> Result<Vec<Spend>> {
+    rows.into_iter()
+        .map(|row| Ok(serde_json::from_str(&row.try_get::<String, _>("spend")?)?))
+        .collect()
+}
+
+impl SpendStore for SqlStore {
+    fn spent(
+        &self,
+        wallet_id: Option<WalletId>,
+        token_id: Option<TokenId>,
+        since: DateTime<Utc>,
+    ) -> BoxFuture<'_, Result<Decimal>> {
+        Box::pin(async move {
+            let rows = sqlx::query("SELECT spend FROM circle_spends WHERE spent_at >= $1")
+                .bind(since.timestamp_millis())
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(total(&spends(rows)?, wallet_id, token_id, since))
+        })
+    }
+
+    fn try_record<'a>(
+        &'a self,
+        spend: &'a Spend,
+        limits: &'a [SpendingLimit],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            // Taking the lock row first keeps another process from checking the same limits
+            // until this spend is committed or rolled back.
+            let mut transaction = self.pool.begin().await?;
+            sqlx::query("UPDATE circle_spend_lock SET version = version + 1 WHERE id = $1")
+                .bind(1_i64)
+                .execute(&mut *transaction)
+                .await?;
+            let cutoff = (spend.at - Duration::days(1)).timestamp_millis();
+            sqlx::query("DELETE FROM circle_spends WHERE spent_at < $1")
+                .bind(cutoff)
+                .execute(&mut *transaction)
+                .await?;
+            let rows = sqlx::query("SELECT spend FROM circle_spends WHERE spent_at >= $1")
+                .bind(cutoff)
+                .fetch_all(&mut *transaction)
+                .await?;
+            check_limits(&spends(rows)?, spend, limits)?;
+            sqlx::query("INSERT INTO circle_spends (id, spent_at, spend) VALUES ($1, $2, $3)")
+                .bind(spend.id.to_string())
+                .bind(spend.at.timestamp_millis())
+                .bind(serde_json::to_string(spend)?)
+                .execute(&mut *transaction)
+                .await?;
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+
+    fn release(&self, id: Uuid) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM circle_spends WHERE id = $1")
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+fn tag_target(row: &AnyRow) -> Result<TagTarget> {
+    let id = uuid(row, "target_id")?;
+    match row.try_get::<String, _>("kind")?.as_str() {
+        "WALLET" => Ok(TagTarget::Wallet(id)),
+        "TRANSACTION" => Ok(TagTarget::Transaction(id)),
+        _ => Err(CircleError::ValueError),
+    }
+}
+
+impl TagStore for SqlStore {
+    fn get<'a>(&'a self, target: &'a TagTarget) -> BoxFuture<'a, Result<Tags>> {
+        Box::pin(async move {
+            let rows = sqlx::query(
+                "SELECT tag_key, tag_value FROM circle_tags WHERE kind = $1 AND target_id = $2",
+            )
+            .bind(target.kind())
+            .bind(target.id().to_string())
+            .fetch_all(&self.pool)
+            .await?;
+            rows.into_iter()
+                .map(|row| Ok((row.try_get("tag_key")?, row.try_get("tag_value")?)))
+                .collect()
+        })
+    }
+
+    fn set<'a>(
+        &'a self,
+        target: &'a TagTarget,
+        key: &'a str,
+        value: &'a str,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO circle_tags (kind, target_id, tag_key, tag_value) \
+                 VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT (kind, target_id, tag_key) DO UPDATE SET tag_value = excluded.tag_value",
+            )
+            .bind(target.kind())
+            .bind(target.id().to_string())
+            .bind(key)
+            .bind(value)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn remove<'a>(&'a self, target: &'a TagTarget, key: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "DELETE FROM circle_tags WHERE kind = $1 AND target_id = $2 AND tag_key = $3",
+            )
+            .bind(target.kind())
+            .bind(target.id().to_string())
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn find<'a>(&'a self, key: &'a str, value: &'a str) -> BoxFuture<'a, Result<Vec<TagTarget>>> {
+        Box::pin(async move {
+            let rows = sqlx::query(
+                "SELECT kind, target_id FROM circle_tags WHERE tag_key = $1 AND tag_value = $2",
+            )
+            .bind(key)
+            .bind(value)
+            .fetch_all(&self.pool)
+            .await?;
+            rows.iter().map(tag_target).collect()
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use std::str::FromStr;
+
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::error::CircleError;
+    use crate::models::web3_address::Web3Address;
+    use crate::policies::{ApprovalStatus, TransferIntent};
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        install_default_drivers();
+        // Every connection to `sqlite::memory:` is a separate database.
+        let pool = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let store = SqlStore::from_pool(pool);
+        store.migrate().await.unwrap();
+        store.migrate().await.unwrap();
+
+        let address = Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let assignment = DepositAddress {
+            user_id: "alice".to_string(),
+            blockchain: Blockchain::MaticAmoy,
+            wallet_id: WalletId(Uuid::new_v4()),
+            address: address.clone(),
+        };
+        DepositStore::insert(&store, &assignment).await.unwrap();
+        DepositStore::insert(&store, &assignment).await.unwrap();
+        assert_eq!(
+            DepositStore::get(&store, "alice", &Blockchain::MaticAmoy)
+                .await
+                .unwrap(),
+            Some(assignment.clone())
+        );
+        assert_eq!(
+            store.find_by_wallet(assignment.wallet_id).await.unwrap(),
+            Some(assignment)
+        );
+
+        let mut entry = OutboxEntry::new(
+            WalletId(Uuid::new_v4()),
+            TokenId(Uuid::new_v4()),
+            address.clone(),
+            Decimal::ONE,
+        );
+        store.enqueue(&entry).await.unwrap();
+        assert_eq!(store.unfinished(10).await.unwrap(), [entry.clone()]);
+        entry.status = crate::outbox::OutboxStatus::Failed {
+            transaction_id: None,
+            reason: "rejected".to_string(),
+        };
+        OutboxStore::update(&store, &entry).await.unwrap();
+        assert!(store.unfinished(10).await.unwrap().is_empty());
+
+        let intent = TransferIntent::new(
+            WalletId(Uuid::new_v4()),
+            TokenId(Uuid::new_v4()),
+            address,
+            Decimal::from(5000),
+            "carol",
+        );
+        let mut transfer = PendingTransfer {
+            intent,
+            required_approvals: 2,
+            approvals: Vec::new(),
+            status: ApprovalStatus::Pending,
+            history: Vec::new(),
+        };
+        ApprovalStore::insert(&store, &transfer).await.unwrap();
+        ApprovalStore::insert(&store, &transfer).await.unwrap();
+        assert_eq!(
+            ApprovalStore::pending(&store).await.unwrap(),
+            [transfer.clone()]
+        );
+        transfer.status = ApprovalStatus::Rejected {
+            by: "bob".to_string(),
+            reason: "unknown destination".to_string(),
+        };
+        ApprovalStore::update(&store, &transfer).await.unwrap();
+        assert!(ApprovalStore::pending(&store).await.unwrap().is_empty());
+        assert_eq!(
+            ApprovalStore::get(&store, transfer.intent.id)
+                .await
+                .unwrap(),
+            Some(transfer)
+        );
+
+        let wallet = TagTarget::Wallet(Uuid::new_v4());
+        TagStore::set(&store, &wallet, "env", "prod").await.unwrap();
+        assert_eq!(
+            TagStore::find(&store, "env", "prod").await.unwrap(),
+            [wallet]
+        );
+
+        let key = store
+            .get_or_insert("payout:17", Uuid::new_v4())
+            .await
+            .unwrap();
+        assert_eq!(
+            store
+                .get_or_insert("payout:17", Uuid::new_v4())
+                .await
+                .unwrap(),
+            key
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_spends() {
+        install_default_drivers();
+        // A file, so that the connections share one database and really run concurrently.
+        let path = std::env::temp_dir().join(format!("circle-spends-{}.db", Uuid::new_v4()));
+        let pool = AnyPoolOptions::new()
+            .max_connections(8)
+            .connect(&format!("sqlite://{}?mode=rwc", path.display()))
+            .await
+            .unwrap();
+        let store = SqlStore::from_pool(pool);
+        store.migrate().await.unwrap();
+
+        let (wallet_id, token_id) = (WalletId(Uuid::new_v4()), TokenId(Uuid::new_v4()));
+        let limits = [SpendingLimit::per_hour(Decimal::from(100)).token(token_id)];
+        let spends = (0..20)
+            .map(|_| Spend {
+                id: Uuid::new_v4(),
+                wallet_id,
+                token_id,
+                amount: Decimal::from(10),
+                at: Utc::now(),
+            })
+            .collect::<Vec<_>>();
+        let results =
+            futures::future::join_all(spends.iter().map(|x| store.try_record(x, &limits))).await;
+        let recorded = results.iter().filter(|x| x.is_ok()).count();
+        assert_eq!(recorded, 10);
+        assert!(results
+            .iter()
+            .all(|x| matches!(x, Ok(()) | Err(CircleError::LimitExceeded(_)))));
+        let since = Utc::now() - Duration::hours(1);
+        let spent = store.spent(None, Some(token_id), since).await.unwrap();
+        assert_eq!(spent, Decimal::from(100));
+
+        // Releasing one makes room for exactly one more.
+        let recorded = spends
+            .iter()
+            .zip(&results)
+            .find(|(_, x)| x.is_ok())
+            .unwrap();
+        store.release(recorded.0.id).await.unwrap();
+        let more = |_| Spend {
+            id: Uuid::new_v4(),
+            ..spends[0].clone()
+        };
+        let (first, second) = (more(()), more(()));
+        store.try_record(&first, &limits).await.unwrap();
+        assert!(store.try_record(&second, &limits).await.is_err());
+
+        store.pool().close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! Treasury sweeps: moving whatever accumulates in deposit wallets to a master wallet.
+//!
+//! Each pass reads the balances of every deposit wallet and, for tokens with a
+//! [`SweepPolicy`], transfers everything above the policy's gas reserve once the balance
+//! crosses its threshold. A token is skipped while an outbound transfer of it from the same
+//! wallet is still pending, so funds already on their way out aren't counted, and swept,
+//! again when a new deposit changes the balance. Idempotency keys are derived from the
+//! wallet, the token and the balance snapshot, so a pass that is retried or run twice against
+//! an unchanged balance repeats the same transfer rather than sending a second one.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::cancel::{run_every, CancellationToken};
+use crate::error::Result;
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::transaction::Transaction;
+use crate::models::transaction_list::TransactionQuery;
+use crate::models::transaction_transfer_create::{FeeLevel, TransferRequestBuilder};
+use crate::models::wallet_balance::{TokenBalance, WalletBalanceQueryParams};
+use crate::models::web3_address::Web3Address;
+
+/// When and how much of one token to sweep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepPolicy {
+    pub token_id: TokenId,
+    /// Balances at or below this are left alone.
+    pub threshold: Decimal,
+    /// Left behind in the deposit wallet, e.g. to pay for gas when sweeping the native token.
+    pub reserve: Decimal,
+}
+
+impl SweepPolicy {
+    pub fn new(token_id: TokenId, threshold: Decimal) -> Self {
+        SweepPolicy {
+            token_id,
+            threshold,
+            reserve: Decimal::ZERO,
+        }
+    }
+
+    pub fn reserve(mut self, reserve: Decimal) -> Self {
+        self.reserve = reserve;
+        self
+    }
+
+    /// How much of `balance` to sweep, if anything.
+    fn amount(&self, balance: Decimal) -> Option<Decimal> {
+        let amount = balance - self.reserve;
+        (balance > self.threshold && amount > Decimal::ZERO).then_some(amount)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "outcome", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SweepOutcome {
+    /// Dry run: the transfer that would have been submitted.
+    Planned,
+    Submitted {
+        transaction_id: TransactionId,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepEntry {
+    pub wallet_id: WalletId,
+    pub token_id: TokenId,
+    pub balance: Decimal,
+    pub amount: Decimal,
+    pub idempotency_key: Uuid,
+    pub outcome: SweepOutcome,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepReport {
+    pub entries: Vec<SweepEntry>,
+    /// Wallets and tokens skipped because an earlier transfer out of them hasn't settled,
+    /// with that transfer. They are retried next pass.
+    pub in_flight: Vec<(WalletId, TokenId, TransactionId)>,
+    /// Wallets whose balances couldn't be read, with the reason. They are retried next pass.
+    pub failed_wallets: Vec<(WalletId, String)>,
+}
+
+impl SweepReport {
+    /// Per token, the total that was (or in a dry run, would have been) swept.
+    pub fn totals(&self) -> HashMap<TokenId, Decimal> {
+        let mut totals = HashMap::new();
+        for entry in &self.entries {
+            if !matches!(entry.outcome, SweepOutcome::Failed { .. }) {
+                *totals.entry(entry.token_id).or_insert(Decimal::ZERO) += entry.amount;
+            }
+        }
+        totals
+    }
+}
+
+pub struct Sweeper<'a> {
+    client: &'a CircleClient,
+    destination: Web3Address,
+    wallets: Vec<WalletId>,
+    policies: HashMap<TokenId, SweepPolicy>,
+    fee_level: FeeLevel,
+    dry_run: bool,
+    concurrency: usize,
+    interval: Duration,
+    in_flight_window: Duration,
+    cancellation: CancellationToken,
+}
+
+impl<'a> Sweeper<'a> {
+    /// Sweeps `wallets` into the master wallet at `destination`.
+    pub fn new<I: IntoIterator<Item = WalletId>>(
+        client: &'a CircleClient,
+        destination: Web3Address,
+        wallets: I,
+    ) -> Self {
+        Sweeper {
+            client,
+            destination,
+            wallets: wallets.into_iter().collect(),
+            policies: HashMap::new(),
+            fee_level: FeeLevel::Medium,
+            dry_run: false,
+            concurrency: 4,
+            interval: Duration::from_secs(300),
+            in_flight_window: Duration::from_secs(24 * 60 * 60),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Adds or replaces the policy for `policy.token_id`. Tokens without a policy are never
+    /// swept.
+    pub fn policy(mut self, policy: SweepPolicy) -> Self {
+        self.policies.insert(policy.token_id, policy);
+        self
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee_level = fee_level;
+        self
+    }
+
+    /// Reports what would be swept without submitting anything.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Time between passes in [`Sweeper::run`].
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// How far back to look for transfers that haven't settled yet. Defaults to a day.
+    pub fn in_flight_window(mut self, window: Duration) -> Self {
+        self.in_flight_window = window;
+        self
+    }
+
+    /// Stops [`Sweeper::run`] once `token` is cancelled, after the current pass.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Derived from the balance snapshot, so only a changed balance produces a new transfer.
+    pub fn idempotency_key(&self, wallet_id: WalletId, balance: &TokenBalance) -> Uuid {
+        snapshot_key("sweep", wallet_id, balance)
+    }
+
+    /// Runs a single pass over all wallets.
+    pub async fn sweep_once(&self) -> SweepReport {
+        let results = stream::iter(&self.wallets)
+            .map(|wallet_id| async move { (*wallet_id, self.sweep_wallet(*wallet_id).await) })
+            .buffered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        let mut report = SweepReport::default();
+        for (wallet_id, result) in results {
+            match result {
+                Ok((entries, in_flight)) => {
+                    report.entries.extend(entries);
+                    report.in_flight.extend(in_flight);
+                }
+                Err(err) => report.failed_wallets.push((wallet_id, err.to_string())),
+            }
+        }
+        report
+    }
+
+    /// Runs [`Sweeper::sweep_once`] with [`run_every`].
+    pub async fn run<F: FnMut(SweepReport)>(&self, on_report: F) {
+        run_every(
+            self.interval,
+            &self.cancellation,
+            || self.sweep_once(),
+            on_report,
+        )
+        .await
+    }
+
+    async fn sweep_wallet(
+        &self,
+        wallet_id: WalletId,
+    ) -> Result<(Vec<SweepEntry>, Vec<(WalletId, TokenId, TransactionId)>)> {
+        // Read before the balances: a transfer that settles in between is then already
+        // reflected in them, rather than neither pending nor deducted.
+        let pending = in_flight(self.client, [wallet_id], self.in_flight_window).await?;
+        let balances = self
+            .client
+            .get_wallet_balance(wallet_id, WalletBalanceQueryParams::default())
+            .await?;
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        for balance in balances.token_balances {
+            let Some(policy) = self.policies.get(&balance.token.id) else {
+                continue;
+            };
+            let Some(sweep) = policy.amount(balance.amount) else {
+                continue;
+            };
+            if let Some(transaction) = pending.iter().find(|x| x.token_id == Some(policy.token_id))
+            {
+                skipped.push((wallet_id, policy.token_id, transaction.id));
+                continue;
+            }
+            let idempotency_key = self.idempotency_key(wallet_id, &balance);
+            let outcome = match self.dry_run {
+                true => SweepOutcome::Planned,
+                false => {
+                    transfer(
+                        self.client,
+                        &self.destination,
+                        wallet_id,
+                        policy.token_id,
+                        sweep,
+                        idempotency_key,
+                        self.fee_level,
+                    )
+                    .await
+                }
+            };
+            entries.push(SweepEntry {
+                wallet_id,
+                token_id: policy.token_id,
+                balance: balance.amount,
+                amount: sweep,
+                idempotency_key,
+                outcome,
+            });
+        }
+        Ok((entries, skipped))
+    }
+}
+
+/// The outbound transactions of `wallets` created within `window` that haven't settled yet.
+pub(crate) async fn in_flight<I: IntoIterator<Item = WalletId>>(
+    client: &CircleClient,
+    wallets: I,
+    window: Duration,
+) -> Result<Vec<Transaction>> {
+    let wallets = wallets.into_iter().collect::<Vec<_>>();
+    if wallets.is_empty() {
+        // An empty filter would match every wallet's transactions.
+        return Ok(Vec::new());
+    }
+    let mut query = TransactionQuery::new().wallets(wallets).outbound();
+    let since = chrono::Duration::from_std(window)
+        .ok()
+        .and_then(|window| Utc::now().checked_sub_signed(window));
+    if let Some(since) = since {
+        query = query.since(since);
+    }
+    client
+        .list_transactions_stream(query.build())
+        .try_filter(|x| futures::future::ready(!x.state.is_terminal()))
+        .try_collect()
+        .await
+}
+
+/// A key that only changes with `wallet_id`'s balance of the token, so a pass that is retried
+/// against an unchanged balance repeats the same transfer.
+pub(crate) fn snapshot_key(purpose: &str, wallet_id: WalletId, balance: &TokenBalance) -> Uuid {
+    let name = format!(
+        "{}:{}:{}:{}",
+        purpose,
+        balance.token.id,
+        balance.amount,
+        balance.update_date.timestamp_millis()
+    );
+    Uuid::new_v5(&wallet_id.as_uuid(), name.as_bytes())
+}
+
+pub(crate) async fn transfer(
+    client: &CircleClient,
+    destination: &Web3Address,
+    wallet_id: WalletId,
+    token_id: TokenId,
+    amount: Decimal,
+    idempotency_key: Uuid,
+    fee_level: FeeLevel,
+) -> SweepOutcome {
+    let request = match TransferRequestBuilder::new()
+        .from_wallet(wallet_id)
+        .to_address(destination.clone())
+        .token(token_id)
+        .amount(amount)
+        .fee_level(fee_level)
+        .build()
+    {
+        Ok(request) => request,
+        Err(err) => {
+            return SweepOutcome::Failed {
+                reason: err.to_string(),
+            }
+        }
+    };
+    match client
+        .create_transfer_transaction(idempotency_key, request)
+        .await
+    {
+        Ok(response) => SweepOutcome::Submitted {
+            transaction_id: response.id,
+        },
+        Err(err) => SweepOutcome::Failed {
+            reason: err.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    const TOKEN_ID: &str = "7adb2b7d-c9cd-5164-b2d4-b73b088274dc";
+
+    fn balances(amount: &str) -> Value {
+        json!({
+            "tokenBalances": [{
+                "amount": amount,
+                "updateDate": "2024-03-01T10:00:00Z",
+                "token": {
+                    "id": TOKEN_ID,
+                    "blockchain": "MATIC-AMOY",
+                    "isNative": false,
+                    "symbol": "USDC",
+                    "createDate": "2023-11-25T14:26:38Z",
+                    "updateDate": "2023-11-25T14:26:38Z"
+                }
+            }]
+        })
+    }
+
+    fn sweep(id: TransactionId, wallet_id: WalletId, state: &str) -> Value {
+        json!({
+            "id": id,
+            "blockchain": "MATIC-AMOY",
+            "amounts": ["140"],
+            "state": state,
+            "tokenId": TOKEN_ID,
+            "walletId": wallet_id,
+            "transactionType": "OUTBOUND",
+            "createDate": "2024-03-01T10:05:00Z",
+            "updateDate": "2024-03-01T10:05:00Z"
+        })
+    }
+
+    #[test]
+    fn test_policy_amount() {
+        let policy =
+            SweepPolicy::new(TokenId::default(), Decimal::from(100)).reserve(Decimal::from(5));
+        assert_eq!(policy.amount(Decimal::from(100)), None);
+        assert_eq!(policy.amount(Decimal::from(150)), Some(Decimal::from(145)));
+        let policy = SweepPolicy::new(TokenId::default(), Decimal::ZERO).reserve(Decimal::from(5));
+        assert_eq!(policy.amount(Decimal::from(3)), None);
+    }
+
+    #[tokio::test]
+    async fn test_sweep() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let master = Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let (deposit, idle) = (WalletId(Uuid::new_v4()), WalletId(Uuid::new_v4()));
+        let token_id = TokenId::from_str(TOKEN_ID).unwrap();
+        let policy = SweepPolicy::new(token_id, Decimal::from(100)).reserve(Decimal::from(10));
+
+        // One wallet at a time, so the queued responses are consumed in order.
+        let sweeper = Sweeper::new(&client, master, [deposit, idle])
+            .policy(policy)
+            .concurrency(1);
+        let dry_run = sweeper.dry_run(true);
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("150"));
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("20"));
+        let report = dry_run.sweep_once().await;
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].outcome, SweepOutcome::Planned);
+        assert_eq!(report.totals()[&token_id], Decimal::from(140));
+        let planned_key = report.entries[0].idempotency_key;
+
+        let sweeper = dry_run.dry_run(false);
+        let transaction_id = TransactionId(Uuid::new_v4());
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("150"));
+        transport.push_data(json!({ "id": transaction_id, "state": "INITIATED" }));
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("20"));
+        let report = sweeper.sweep_once().await;
+        assert_eq!(
+            report.entries[0].outcome,
+            SweepOutcome::Submitted { transaction_id }
+        );
+        assert_eq!(report.entries[0].idempotency_key, planned_key);
+        let transfer = transport.requests().into_iter().nth(6).unwrap();
+        let body = transfer.body.unwrap();
+        assert_eq!(body["amounts"], json!(["140"]));
+        assert_eq!(body["idempotencyKey"], json!(planned_key));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_pending() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let master = Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let deposit = WalletId(Uuid::new_v4());
+        let token_id = TokenId::from_str(TOKEN_ID).unwrap();
+        let sweeper = Sweeper::new(&client, master, [deposit])
+            .policy(SweepPolicy::new(token_id, Decimal::from(100)).reserve(Decimal::from(10)));
+
+        let transaction_id = TransactionId(Uuid::new_v4());
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("150"));
+        transport.push_data(json!({ "id": transaction_id, "state": "INITIATED" }));
+        let report = sweeper.sweep_once().await;
+        assert_eq!(
+            report.entries[0].outcome,
+            SweepOutcome::Submitted { transaction_id }
+        );
+        let first_key = report.entries[0].idempotency_key;
+
+        // A deposit lands while the sweep is still pending: the new balance still counts the
+        // 140 on its way out, so nothing is swept until it settles.
+        transport.push_data(json!({ "transactions": [sweep(transaction_id, deposit, "SENT")] }));
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("200"));
+        let report = sweeper.sweep_once().await;
+        assert!(report.entries.is_empty());
+        assert_eq!(report.in_flight, [(deposit, token_id, transaction_id)]);
+        assert_eq!(transport.requests().len(), 6);
+
+        // Once it has, what arrived since is swept under a new key.
+        let second_id = TransactionId(Uuid::new_v4());
+        transport
+            .push_data(json!({ "transactions": [sweep(transaction_id, deposit, "COMPLETE")] }));
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("160"));
+        transport.push_data(json!({ "id": second_id, "state": "INITIATED" }));
+        let report = sweeper.sweep_once().await;
+        assert!(report.in_flight.is_empty());
+        assert_eq!(
+            report.entries[0].outcome,
+            SweepOutcome::Submitted {
+                transaction_id: second_id
+            }
+        );
+        assert_eq!(report.entries[0].amount, Decimal::from(150));
+        assert_ne!(report.entries[0].idempotency_key, first_key);
+
+        // A balance that doesn't parse fails the wallet rather than being skipped silently.
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("lots"));
+        let report = sweeper.sweep_once().await;
+        assert!(report.entries.is_empty());
+        assert_eq!(report.failed_wallets.len(), 1);
+        assert_eq!(report.failed_wallets[0].0, deposit);
+    }
+}
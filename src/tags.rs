@@ -0,0 +1,242 @@
+//! Local key/value tags on wallets and transactions (environment, cost center, customer tier,
+//! ...), for metadata that doesn't fit in Circle's single `refId`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::Result;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(tag = "kind", content = "id", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TagTarget {
+    Wallet(Uuid),
+    Transaction(Uuid),
+}
+
+impl TagTarget {
+    pub fn id(&self) -> Uuid {
+        match self {
+            TagTarget::Wallet(id) | TagTarget::Transaction(id) => *id,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            TagTarget::Wallet(_) => "WALLET",
+            TagTarget::Transaction(_) => "TRANSACTION",
+        }
+    }
+}
+
+pub type Tags = BTreeMap<String, String>;
+
+/// Where tags are kept, usually the application's database.
+pub trait TagStore: Send + Sync {
+    /// All tags of `target`; empty if it has none.
+    fn get<'a>(&'a self, target: &'a TagTarget) -> BoxFuture<'a, Result<Tags>>;
+
+    /// Adds the tag, replacing any value `key` had.
+    fn set<'a>(
+        &'a self,
+        target: &'a TagTarget,
+        key: &'a str,
+        value: &'a str,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn remove<'a>(&'a self, target: &'a TagTarget, key: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// Every target tagged `key` = `value`.
+    fn find<'a>(&'a self, key: &'a str, value: &'a str) -> BoxFuture<'a, Result<Vec<TagTarget>>>;
+}
+
+/// A [`TagStore`] that forgets everything on restart, for tests and prototypes.
+#[derive(Debug, Default)]
+pub struct InMemoryTagStore(Mutex<HashMap<TagTarget, Tags>>);
+
+impl TagStore for InMemoryTagStore {
+    fn get<'a>(&'a self, target: &'a TagTarget) -> BoxFuture<'a, Result<Tags>> {
+        Box::pin(async move {
+            Ok(self
+                .0
+                .lock()
+                .unwrap()
+                .get(target)
+                .cloned()
+                .unwrap_or_default())
+        })
+    }
+
+    fn set<'a>(
+        &'a self,
+        target: &'a TagTarget,
+        key: &'a str,
+        value: &'a str,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut tags = self.0.lock().unwrap();
+            let tags = tags.entry(*target).or_default();
+            tags.insert(key.to_string(), value.to_string());
+            Ok(())
+        })
+    }
+
+    fn remove<'a>(&'a self, target: &'a TagTarget, key: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if let Some(tags) = self.0.lock().unwrap().get_mut(target) {
+                tags.remove(key);
+            }
+            Ok(())
+        })
+    }
+
+    fn find<'a>(&'a self, key: &'a str, value: &'a str) -> BoxFuture<'a, Result<Vec<TagTarget>>> {
+        Box::pin(async move {
+            let tags = self.0.lock().unwrap();
+            let mut targets = tags
+                .iter()
+                .filter(|(_, tags)| tags.get(key).map(String::as_str) == Some(value))
+                .map(|(target, _)| *target)
+                .collect::<Vec<_>>();
+            targets.sort();
+            Ok(targets)
+        })
+    }
+}
+
+/// Targets carrying all of a set of tags.
+///
+/// ```
+/// # use circle_api::tags::{InMemoryTagStore, TagQuery, TagStore, TagTarget};
+/// # use uuid::Uuid;
+/// # futures::executor::block_on(async {
+/// let store = InMemoryTagStore::default();
+/// let wallet = TagTarget::Wallet(Uuid::new_v4());
+/// store.set(&wallet, "env", "prod").await.unwrap();
+/// store.set(&wallet, "tier", "gold").await.unwrap();
+/// let query = TagQuery::new().wallets().tag("env", "prod").tag("tier", "gold");
+/// assert_eq!(query.run(&store).await.unwrap(), [wallet]);
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TagQuery {
+    kind: Option<&'static str>,
+    tags: Vec<(String, String)>,
+}
+
+impl TagQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn wallets(mut self) -> Self {
+        self.kind = Some("WALLET");
+        self
+    }
+
+    pub fn transactions(mut self) -> Self {
+        self.kind = Some("TRANSACTION");
+        self
+    }
+
+    pub fn tag<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Whether `target` with `tags` matches.
+    pub fn matches(&self, target: &TagTarget, tags: &Tags) -> bool {
+        self.kind.map_or(true, |kind| kind == target.kind())
+            && self
+                .tags
+                .iter()
+                .all(|(key, value)| tags.get(key) == Some(value))
+    }
+
+    /// The matching targets in `store`. A query without tags matches nothing, since the
+    /// store can't list untagged targets.
+    pub async fn run<S: TagStore + ?Sized>(&self, store: &S) -> Result<Vec<TagTarget>> {
+        let mut matching: Option<BTreeSet<TagTarget>> = None;
+        for (key, value) in &self.tags {
+            let found = store
+                .find(key, value)
+                .await?
+                .into_iter()
+                .collect::<BTreeSet<_>>();
+            matching = Some(match matching {
+                Some(matching) => matching.intersection(&found).copied().collect(),
+                None => found,
+            });
+        }
+        Ok(matching
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|x| self.kind.map_or(true, |kind| kind == x.kind()))
+            .collect())
+    }
+
+    /// Keeps the `items` whose target (as given by `target`) matches, e.g. to narrow down a
+    /// page of wallets fetched from Circle.
+    pub async fn filter<S, T, F>(&self, store: &S, items: Vec<T>, target: F) -> Result<Vec<T>>
+    where
+        S: TagStore + ?Sized,
+        F: Fn(&T) -> TagTarget,
+    {
+        let mut kept = Vec::new();
+        for item in items {
+            let target = target(&item);
+            if self.matches(&target, &store.get(&target).await?) {
+                kept.push(item);
+            }
+        }
+        Ok(kept)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tag_query() {
+        let store = InMemoryTagStore::default();
+        let (prod, staging) = (Uuid::new_v4(), Uuid::new_v4());
+        let transaction = TagTarget::Transaction(Uuid::new_v4());
+        store
+            .set(&TagTarget::Wallet(prod), "env", "prod")
+            .await
+            .unwrap();
+        store
+            .set(&TagTarget::Wallet(prod), "costCenter", "ops")
+            .await
+            .unwrap();
+        store
+            .set(&TagTarget::Wallet(staging), "env", "staging")
+            .await
+            .unwrap();
+        store.set(&transaction, "env", "prod").await.unwrap();
+
+        let query = TagQuery::new().tag("env", "prod");
+        assert_eq!(query.run(&store).await.unwrap().len(), 2);
+        let query = query.wallets().tag("costCenter", "ops");
+        assert_eq!(query.run(&store).await.unwrap(), [TagTarget::Wallet(prod)]);
+        let wallets = query
+            .filter(&store, vec![prod, staging], |x| TagTarget::Wallet(*x))
+            .await
+            .unwrap();
+        assert_eq!(wallets, [prod]);
+
+        store
+            .remove(&TagTarget::Wallet(prod), "costCenter")
+            .await
+            .unwrap();
+        assert!(query.run(&store).await.unwrap().is_empty());
+        assert_eq!(
+            store.get(&TagTarget::Wallet(prod)).await.unwrap(),
+            Tags::from([("env".to_string(), "prod".to_string())])
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! A wiremock server preloaded with realistic Circle responses for every endpoint this crate
+//! supports, for integration tests that shouldn't depend on sandbox.
+//!
+//! ```no_run
+//! # async fn run() -> circle_api::error::Result<()> {
+//! use circle_api::testing::CircleMockServer;
+//!
+//! let server = CircleMockServer::start().await;
+//! let client = server.client().await?;
+//! let wallet = client.get_wallet(uuid::Uuid::new_v4().into()).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The fixtures are mounted with a low priority, so mocks added through
+//! [`CircleMockServer::server`] take precedence over them.
+
+use serde_json::json;
+use wiremock::matchers::{method, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::api::{CircleClient, Environment, MOCK_PUBLIC_KEY};
+use crate::error::Result;
+
+/// `X-Request-Id` returned with every fixture.
+pub const FIXTURE_REQUEST_ID: &str = "9d0a4c6e-2a3b-4f1d-8c7e-5b6a7f8e9d01";
+
+const FIXTURE_PRIORITY: u8 = 10;
+
+const ID: &str = "[0-9a-fA-F-]{36}";
+
+pub struct Fixture {
+    pub method: &'static str,
+    /// Path below the API root, with `{id}` standing for any UUID.
+    pub path: &'static str,
+    pub status: u16,
+    pub body: &'static str,
+}
+
+macro_rules! fixture {
+    ($method:literal, $path:literal, $file:literal) => {
+        Fixture {
+            method: $method,
+            path: $path,
+            status: 200,
+            body: include_str!(concat!("fixtures/", $file, ".json")),
+        }
+    };
+    ($method:literal, $path:literal, $status:literal, empty) => {
+        Fixture {
+            method: $method,
+            path: $path,
+            status: $status,
+            body: "",
+        }
+    };
+}
+
+pub const FIXTURES: &[Fixture] = &[
+    fixture!("GET", "w3s/walletSets", "wallet_sets"),
+    fixture!("POST", "w3s/developer/walletSets", "wallet_set"),
+    fixture!("PUT", "w3s/developer/walletSets/{id}", "wallet_set"),
+    fixture!("GET", "w3s/walletSets/{id}", "wallet_set"),
+    fixture!("POST", "w3s/developer/wallets", "wallets_create"),
+    fixture!("GET", "w3s/wallets", "wallets"),
+    fixture!("GET", "w3s/wallets/{id}", "wallet"),
+    fixture!("PUT", "w3s/wallets/{id}", "wallet"),
+    fixture!("GET", "w3s/wallets/{id}/balances", "wallet_balances"),
+    fixture!("GET", "w3s/wallets/{id}/nfts", "wallet_nfts"),
+    fixture!("GET", "w3s/tokens/{id}", "token"),
+    fixture!("GET", "w3s/config/entity", "entity_config"),
+    fixture!(
+        "POST",
+        "w3s/developer/transactions/transfer",
+        "transaction_create"
+    ),
+    fixture!(
+        "POST",
+        "w3s/developer/transactions/contractExecution",
+        "transaction_create"
+    ),
+    fixture!(
+        "POST",
+        "w3s/developer/transactions/{id}/accelerate",
+        "transaction_accelerate"
+    ),
+    fixture!(
+        "POST",
+        "w3s/developer/transactions/{id}/cancel",
+        "transaction_cancel"
+    ),
+    fixture!(
+        "POST",
+        "w3s/transactions/transfer/estimateFee",
+        "transfer_fee_estimate"
+    ),
+    fixture!("GET", "w3s/transactions", "transactions"),
+    fixture!("GET", "w3s/transactions/{id}", "transaction"),
+    fixture!("POST", "w3s/developer/sign/message", "sign_message"),
+    fixture!("POST", "w3s/contracts/query", "contract_query"),
+    fixture!("POST", "w3s/subscriptions", "subscription"),
+    fixture!("GET", "w3s/subscriptions", "subscriptions"),
+    fixture!("GET", "w3s/subscriptions/{id}", "subscription"),
+    fixture!("DELETE", "w3s/subscriptions/{id}", 204, empty),
+    fixture!("POST", "banks/ach", "ach_bank_account"),
+    fixture!("GET", "banks/ach/{id}", "ach_bank_account"),
+    fixture!("POST", "banks/sepa", "sepa_bank_account"),
+    fixture!("GET", "banks/sepa/{id}", "sepa_bank_account"),
+    fixture!("GET", "banks/sepa/{id}/instructions", "sepa_instructions"),
+    fixture!("GET", "businessAccount/balances", "business_balances"),
+    fixture!("GET", "exchange/rates/{pair}", "exchange_rate"),
+    fixture!("POST", "conversions", "conversion"),
+    fixture!("GET", "conversions", "conversions"),
+    fixture!("GET", "conversions/{id}", "conversion"),
+    fixture!("GET", "chargebacks", "chargebacks"),
+    fixture!("GET", "chargebacks/{id}", "chargeback"),
+    fixture!("GET", "settlements", "settlements"),
+    fixture!("GET", "settlements/{id}", "settlement"),
+    fixture!("GET", "returns", "returns"),
+    fixture!("GET", "channels", "channels"),
+    fixture!(
+        "POST",
+        "notifications/subscriptions",
+        "payments_subscription"
+    ),
+    fixture!(
+        "GET",
+        "notifications/subscriptions",
+        "payments_subscriptions"
+    ),
+    fixture!("DELETE", "notifications/subscriptions/{id}", 204, empty),
+    fixture!("POST", "faucet/drips", 204, empty),
+    fixture!("POST", "mocks/payments/wire", "mock_wire_payment"),
+    fixture!("POST", "mocks/cards/chargebacks", "chargeback"),
+    fixture!("POST", "mocks/ach/accounts", "mock_ach_account"),
+];
+
+/// Served by [`CircleMockServer::attestation_uri`] for any burn transaction.
+pub const CCTP_MESSAGES_FIXTURE: &str = include_str!("fixtures/cctp_messages.json");
+
+/// A response shaped like Circle's, with the `X-Request-Id` header the client requires. Useful
+/// for mounting overrides.
+pub fn circle_response(status: u16, body: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status)
+        .insert_header("X-Request-Id", FIXTURE_REQUEST_ID)
+        .set_body_raw(body.to_string(), "application/json")
+}
+
+pub struct CircleMockServer {
+    server: MockServer,
+}
+
+impl CircleMockServer {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let public_key = json!({ "data": { "publicKey": MOCK_PUBLIC_KEY } }).to_string();
+        Self::mount(
+            &server,
+            "GET",
+            "^/v1/w3s/config/entity/publicKey$".to_string(),
+            circle_response(200, &public_key),
+        )
+        .await;
+        for fixture in FIXTURES {
+            let path = fixture
+                .path
+                .replace("{id}", ID)
+                .replace("{pair}", "[A-Z]+-[A-Z]+");
+            Self::mount(
+                &server,
+                fixture.method,
+                format!("^/v1/{}$", path),
+                circle_response(fixture.status, fixture.body),
+            )
+            .await;
+        }
+        Self::mount(
+            &server,
+            "GET",
+            "^/v1/messages/[0-9]+/0x[0-9a-fA-F]+$".to_string(),
+            ResponseTemplate::new(200).set_body_raw(CCTP_MESSAGES_FIXTURE, "application/json"),
+        )
+        .await;
+        CircleMockServer { server }
+    }
+
+    async fn mount(server: &MockServer, verb: &str, path: String, response: ResponseTemplate) {
+        Mock::given(method(verb))
+            .and(path_regex(path))
+            .respond_with(response)
+            .with_priority(FIXTURE_PRIORITY)
+            .mount(server)
+            .await;
+    }
+
+    /// The API root to point a client at.
+    pub fn environment(&self) -> Environment {
+        Environment::custom(&format!("{}/v1/", self.server.uri()))
+            .expect("mock server URI is a valid URL")
+    }
+
+    /// The root to pass to [`CircleClient::get_cctp_messages`].
+    pub fn attestation_uri(&self) -> String {
+        format!("{}/", self.server.uri())
+    }
+
+    /// A client for this server, with a throwaway entity secret.
+    pub async fn client(&self) -> Result<CircleClient> {
+        CircleClient::builder()
+            .api_key("TEST_API_KEY:mock:mock")
+            .entity_secret(hex::encode([7u8; 32]))
+            .environment(self.environment())
+            .build()
+            .await
+    }
+
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::SimulationLog;
+    use crate::models::blockchain::Blockchain;
+    use crate::models::chargeback::ChargebackListQueryParams;
+    use crate::models::contract_query::ContractQueryRequest;
+    use crate::models::conversion::{ConversionCreateRequestBuilder, ConversionListQueryParams};
+    use crate::models::exchange_rate::CurrencyPair;
+    use crate::models::ids::{TokenId, WalletId};
+    use crate::models::money::{Currency, Money};
+    use crate::models::payout_return::PayoutReturnListQueryParams;
+    use crate::models::settlement::SettlementListQueryParams;
+    use crate::models::transaction_list::TransactionListQueryParams;
+    use crate::models::transaction_state::TransactionState;
+    use crate::models::transaction_transfer_create::TransactionTransferCreateRequestBuilder;
+    use crate::models::wallet_balance::WalletBalanceQueryParams;
+    use crate::models::wallet_list::WalletListQueryParams;
+    use crate::models::wallet_nfts::WalletNftsQueryParams;
+    use crate::models::wallet_set::WalletSetsQueryParams;
+    use crate::models::wallet_update::WalletUpdateRequest;
+
+    #[tokio::test]
+    async fn test_w3s_fixtures() {
+        let server = CircleMockServer::start().await;
+        let client = server.client().await.unwrap();
+        let id = Uuid::new_v4();
+
+        let wallet_set = client
+            .create_wallet_set(id, "treasury".to_string())
+            .await
+            .unwrap()
+            .wallet_set;
+        client
+            .list_wallet_sets(WalletSetsQueryParams::new())
+            .await
+            .unwrap();
+        client.get_wallet_set(wallet_set.id).await.unwrap();
+        client
+            .update_wallet_set(wallet_set.id, "treasury".to_string())
+            .await
+            .unwrap();
+
+        let wallets = client
+            .create_wallet(id, wallet_set.id, vec![Blockchain::MaticMumbai], 1)
+            .await
+            .unwrap()
+            .wallets;
+        let wallet = &wallets[0];
+        client
+            .list_wallets(WalletListQueryParams::default())
+            .await
+            .unwrap();
+        client.get_wallet(wallet.id).await.unwrap();
+        let update = WalletUpdateRequest::new("deposits").ref_id("customer-42");
+        client.update_wallet(wallet.id, update).await.unwrap();
+        let balances = client
+            .get_wallet_balance(wallet.id, WalletBalanceQueryParams::default())
+            .await
+            .unwrap();
+        assert_eq!(balances.token_balances[0].amount, Decimal::new(2575, 2));
+        client
+            .get_wallet_nfts(wallet.id, WalletNftsQueryParams::default())
+            .await
+            .unwrap();
+        let token = client.get_token_details(id.into()).await.unwrap().token;
+        let config = client.get_entity_config().await.unwrap();
+        assert_eq!(config.app_id, "1f5a3c52-9d4e-4b7a-8e21-6c0d3f9a7b15");
+        assert!(client.ping().await.unwrap().authenticated);
+
+        let transfer = TransactionTransferCreateRequestBuilder::new(
+            wallet.address.clone(),
+            token.id,
+            wallet.id,
+            Decimal::new(125, 1),
+        );
+        let created = client
+            .create_transfer_transaction(id, transfer)
+            .await
+            .unwrap();
+        assert_eq!(created.state, TransactionState::Initiated);
+        client.accelerate_transaction(created.id, id).await.unwrap();
+        client.cancel_transaction(created.id, None).await.unwrap();
+        client
+            .list_transactions(TransactionListQueryParams::new())
+            .await
+            .unwrap();
+        let transaction = client
+            .get_transaction(created.id, None)
+            .await
+            .unwrap()
+            .transaction;
+        assert_eq!(transaction.state, TransactionState::Complete);
+
+        client
+            .sign_ethereum_message(wallet.id, "hello".to_string())
+            .await
+            .unwrap();
+
+        let query = ContractQueryRequest::new(
+            Blockchain::MaticMumbai,
+            token.token_address.clone().unwrap(),
+            "allowance(address,address)",
+        );
+        let output = client.query_contract(query).await.unwrap();
+        assert_eq!(output.output_values, Some(vec![json!("500000000")]));
+
+        let subscription = client
+            .create_subscription("https://example.com".to_string(), None)
+            .await
+            .unwrap();
+        client.list_subscriptions().await.unwrap();
+        client.get_subscription(subscription.id).await.unwrap();
+        client.delete_subscription(subscription.id).await.unwrap();
+
+        let messages = client
+            .get_cctp_messages(&server.attestation_uri(), 0, "0xabcdef")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(messages.messages[0].is_attested());
+    }
+
+    #[cfg(feature = "sandbox")]
+    #[tokio::test]
+    async fn test_faucet_fixture() {
+        let server = CircleMockServer::start().await;
+        let client = server.client().await.unwrap();
+        let address = "0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3"
+            .parse()
+            .unwrap();
+        let drip = crate::models::faucet::FaucetRequest::new(address, Blockchain::MaticAmoy).usdc();
+        client.request_testnet_tokens(drip).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_simulated_transfer_fixtures() {
+        let server = CircleMockServer::start().await;
+        let log = SimulationLog::new();
+        let client = CircleClient::builder()
+            .api_key("TEST_API_KEY:mock:mock")
+            .entity_secret(hex::encode([7u8; 32]))
+            .environment(server.environment())
+            .simulate(log.clone())
+            .build()
+            .await
+            .unwrap();
+        let destination = "0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3"
+            .parse()
+            .unwrap();
+        let transfer = TransactionTransferCreateRequestBuilder::new(
+            destination,
+            TokenId(Uuid::new_v4()),
+            WalletId(Uuid::new_v4()),
+            Decimal::ONE,
+        );
+        client
+            .create_transfer_transaction(Uuid::new_v4(), transfer)
+            .await
+            .unwrap();
+        let estimate = log.calls()[0].fee_estimate.clone().unwrap();
+        assert_eq!(estimate["medium"]["networkFee"], "0.00405925");
+    }
+
+    #[tokio::test]
+    async fn test_payments_fixtures() {
+        let server = CircleMockServer::start().await;
+        let client = server.client().await.unwrap();
+        let id = Uuid::new_v4();
+
+        client.get_ach_bank_account(id).await.unwrap();
+        client.get_sepa_bank_account(id).await.unwrap();
+        client.get_sepa_instructions(id).await.unwrap();
+        client.get_business_balances().await.unwrap();
+        let rate = client
+            .get_exchange_rates(CurrencyPair::new(Currency::Eur, Currency::Usd))
+            .await
+            .unwrap();
+        assert_eq!(rate.rate.to_string(), "1.0862");
+
+        let from = Money {
+            amount: "100.00".to_string(),
+            currency: Currency::Eur,
+        };
+        let request = ConversionCreateRequestBuilder::new(from, Currency::Usdc);
+        let conversion = client.create_conversion(id, request).await.unwrap();
+        client.get_conversion(conversion.id).await.unwrap();
+        client
+            .list_conversions(ConversionListQueryParams::new())
+            .await
+            .unwrap();
+
+        let chargebacks = client
+            .list_chargebacks(ChargebackListQueryParams::new())
+            .await
+            .unwrap();
+        client.get_chargeback(chargebacks[0].id).await.unwrap();
+        let settlements = client
+            .list_settlements(SettlementListQueryParams::new())
+            .await
+            .unwrap();
+        client.get_settlement(settlements[0].id).await.unwrap();
+        client
+            .list_payout_returns(PayoutReturnListQueryParams::new())
+            .await
+            .unwrap();
+        client.list_channels().await.unwrap();
+
+        let subscription = client
+            .create_payments_subscription("https://example.com".to_string())
+            .await
+            .unwrap();
+        client.list_payments_subscriptions().await.unwrap();
+        client
+            .delete_payments_subscription(subscription.id)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_overrides_take_precedence() {
+        let server = CircleMockServer::start().await;
+        let client = server.client().await.unwrap();
+        let error = r#"{"code":156004,"message":"Cannot find the wallet.","errors":[]}"#;
+        Mock::given(method("GET"))
+            .and(path_regex("^/v1/w3s/wallets/[^/]+$"))
+            .respond_with(circle_response(404, error))
+            .mount(server.server())
+            .await;
+        let err = client.get_wallet(Uuid::new_v4().into()).await.unwrap_err();
+        assert!(
+            matches!(err, crate::error::CircleError::ApiError(_, ref err) if err.error_code() == crate::api::CircleErrorCode::WalletNotFound)
+        );
+    }
+}
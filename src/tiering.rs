@@ -0,0 +1,532 @@
+//! Hot/warm/cold wallet tiers: outgoing transfers only ever leave from hot wallets, which are
+//! kept at a working float by topping them up from warm wallets. Cold wallets are only
+//! recorded as such; nothing here moves funds out of them.
+//!
+//! Top-ups reuse the sweep engine's transfers. A hot wallet isn't topped up again while an
+//! earlier top-up of the same token is still pending, however its balance moved meanwhile, and
+//! pending top-ups are taken out of what the warm wallets have available. The snapshot-derived
+//! idempotency keys then only have to cover a pass that is retried against unchanged balances.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::CircleClient;
+use crate::cancel::{run_every, CancellationToken};
+use crate::error::{CircleError, Result};
+use crate::models::ids::{TokenId, TransactionId, WalletId};
+use crate::models::transaction_transfer_create::{FeeLevel, TransactionTransferCreateResponse};
+use crate::models::wallet_balance::{TokenBalance, WalletBalanceQueryParams};
+use crate::models::web3_address::Web3Address;
+use crate::policies::TransferIntent;
+use crate::sweep::{in_flight, snapshot_key, transfer, SweepOutcome};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Tier {
+    Hot,
+    Warm,
+    Cold,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TieredWallet {
+    pub wallet_id: WalletId,
+    /// Where top-ups are sent.
+    pub address: Web3Address,
+    pub tier: Tier,
+}
+
+/// Keeps hot wallets' balance of `token_id` between `minimum` and `target`: a hot wallet that
+/// drops below `minimum` is topped up to `target`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FloatTarget {
+    pub token_id: TokenId,
+    pub minimum: Decimal,
+    pub target: Decimal,
+}
+
+impl FloatTarget {
+    pub fn new(token_id: TokenId, minimum: Decimal, target: Decimal) -> Self {
+        FloatTarget {
+            token_id,
+            minimum,
+            target: target.max(minimum),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TopUpEntry {
+    /// The warm wallet the funds come from.
+    pub from: WalletId,
+    /// The hot wallet topped up.
+    pub to: WalletId,
+    pub token_id: TokenId,
+    /// The hot wallet's balance before the top-up.
+    pub balance: Decimal,
+    pub amount: Decimal,
+    pub idempotency_key: Uuid,
+    pub outcome: SweepOutcome,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TopUpReport {
+    pub entries: Vec<TopUpEntry>,
+    /// Hot wallets and tokens that are below their minimum, and by how much they stay short
+    /// of their target because the warm wallets didn't have enough.
+    pub shortfalls: Vec<(WalletId, TokenId, Decimal)>,
+    /// Hot wallets and tokens skipped because an earlier top-up hasn't settled, with that
+    /// top-up. They are retried next pass.
+    pub in_flight: Vec<(WalletId, TokenId, TransactionId)>,
+    /// Wallets whose balances couldn't be read, with the reason. They are retried next pass.
+    pub failed_wallets: Vec<(WalletId, String)>,
+}
+
+pub struct WalletTiers<'a> {
+    client: &'a CircleClient,
+    wallets: Vec<TieredWallet>,
+    floats: HashMap<TokenId, FloatTarget>,
+    fee_level: FeeLevel,
+    dry_run: bool,
+    concurrency: usize,
+    interval: Duration,
+    in_flight_window: Duration,
+    cancellation: CancellationToken,
+}
+
+impl<'a> WalletTiers<'a> {
+    pub fn new(client: &'a CircleClient) -> Self {
+        WalletTiers {
+            client,
+            wallets: Vec::new(),
+            floats: HashMap::new(),
+            fee_level: FeeLevel::Medium,
+            dry_run: false,
+            concurrency: 4,
+            interval: Duration::from_secs(300),
+            in_flight_window: Duration::from_secs(24 * 60 * 60),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Adds `wallet_id` to `tier`, or moves it there if it already has one.
+    pub fn wallet(mut self, wallet_id: WalletId, address: Web3Address, tier: Tier) -> Self {
+        self.wallets.retain(|x| x.wallet_id != wallet_id);
+        self.wallets.push(TieredWallet {
+            wallet_id,
+            address,
+            tier,
+        });
+        self
+    }
+
+    /// Adds or replaces the float target for `float.token_id`. Tokens without one are never
+    /// topped up.
+    pub fn float(mut self, float: FloatTarget) -> Self {
+        self.floats.insert(float.token_id, float);
+        self
+    }
+
+    pub fn fee_level(mut self, fee_level: FeeLevel) -> Self {
+        self.fee_level = fee_level;
+        self
+    }
+
+    /// Reports the top-ups that would be made without submitting anything.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Wallet balances read at the same time.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Time between passes in [`WalletTiers::run`].
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// How far back to look for top-ups that haven't settled yet. Defaults to a day.
+    pub fn in_flight_window(mut self, window: Duration) -> Self {
+        self.in_flight_window = window;
+        self
+    }
+
+    /// Stops [`WalletTiers::run`] once `token` is cancelled, after the current pass.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    pub fn tier(&self, wallet_id: WalletId) -> Option<Tier> {
+        self.wallets
+            .iter()
+            .find(|x| x.wallet_id == wallet_id)
+            .map(|x| x.tier)
+    }
+
+    pub fn wallets(&self, tier: Tier) -> impl Iterator<Item = &TieredWallet> {
+        self.wallets.iter().filter(move |x| x.tier == tier)
+    }
+
+    /// Points `intent` at the hot wallet with the most of its token, failing if none has
+    /// enough. The wallet the intent came with is ignored.
+    pub async fn route(&self, mut intent: TransferIntent) -> Result<TransferIntent> {
+        let hot = self.wallets(Tier::Hot).map(|x| x.wallet_id);
+        let (balances, _) = self.balances(hot).await;
+        let source = balances
+            .iter()
+            .filter_map(|(wallet_id, balances)| {
+                let balance = amount(balances, intent.token_id)?;
+                (balance >= intent.amount).then_some((balance, *wallet_id))
+            })
+            .max();
+        match source {
+            Some((_, wallet_id)) => {
+                intent.wallet_id = wallet_id;
+                Ok(intent)
+            }
+            None => Err(CircleError::InvalidState(format!(
+                "no hot wallet holds {} of token {}",
+                intent.amount, intent.token_id
+            ))),
+        }
+    }
+
+    /// Routes `intent` through a hot wallet and submits it.
+    pub async fn submit(
+        &self,
+        intent: TransferIntent,
+    ) -> Result<TransactionTransferCreateResponse> {
+        self.route(intent)
+            .await?
+            .submit(self.client, self.fee_level)
+            .await
+    }
+
+    /// Tops up every hot wallet below its minimum, once. Each top-up comes from the warm
+    /// wallet with the most of the token; if it doesn't have enough, the hot wallet gets what
+    /// it has and the rest is reported as a shortfall.
+    pub async fn top_up_once(&self) -> TopUpReport {
+        let mut report = TopUpReport::default();
+        // Read before the balances, as in the sweeper: a top-up that settles in between is then
+        // already reflected in them.
+        let warm_ids = self.wallets(Tier::Warm).map(|x| x.wallet_id);
+        let pending = match in_flight(self.client, warm_ids, self.in_flight_window).await {
+            Ok(pending) => pending,
+            Err(err) => {
+                // Without them, a top-up could be sent twice; leave it all to the next pass.
+                let wallets = self.wallets(Tier::Warm);
+                report.failed_wallets = wallets.map(|x| (x.wallet_id, err.to_string())).collect();
+                return report;
+            }
+        };
+        let ids = self
+            .wallets
+            .iter()
+            .filter(|x| x.tier != Tier::Cold)
+            .map(|x| x.wallet_id);
+        let (balances, failed) = self.balances(ids).await;
+        report.failed_wallets = failed;
+        let mut warm = self
+            .wallets(Tier::Warm)
+            .filter_map(|x| Some((x.wallet_id, balances.get(&x.wallet_id)?)))
+            .flat_map(|(wallet_id, balances)| {
+                balances
+                    .iter()
+                    .map(move |x| ((wallet_id, x.token.id), x.amount))
+            })
+            .collect::<HashMap<_, _>>();
+        for transaction in &pending {
+            let (Some(wallet_id), Some(token_id)) = (transaction.wallet_id, transaction.token_id)
+            else {
+                continue;
+            };
+            if let Some(available) = warm.get_mut(&(wallet_id, token_id)) {
+                *available -= transaction.amounts.iter().flatten().sum::<Decimal>();
+            }
+        }
+
+        for hot in self.wallets(Tier::Hot) {
+            let Some(hot_balances) = balances.get(&hot.wallet_id) else {
+                continue;
+            };
+            let mut floats = self.floats.values().collect::<Vec<_>>();
+            floats.sort_by_key(|x| x.token_id);
+            for float in floats {
+                let snapshot = hot_balances.iter().find(|x| x.token.id == float.token_id);
+                let balance = amount(hot_balances, float.token_id).unwrap_or_default();
+                if balance >= float.minimum {
+                    continue;
+                }
+                let top_up = pending.iter().find(|x| {
+                    x.token_id == Some(float.token_id)
+                        && x.destination_address.as_ref() == Some(&hot.address)
+                });
+                if let Some(top_up) = top_up {
+                    report
+                        .in_flight
+                        .push((hot.wallet_id, float.token_id, top_up.id));
+                    continue;
+                }
+                let needed = float.target - balance;
+                let source = warm
+                    .iter()
+                    .filter(|((_, token_id), available)| {
+                        *token_id == float.token_id && **available > Decimal::ZERO
+                    })
+                    .max_by_key(|(_, available)| **available)
+                    .map(|((wallet_id, _), available)| (*wallet_id, *available));
+                let Some((from, available)) = source else {
+                    report
+                        .shortfalls
+                        .push((hot.wallet_id, float.token_id, needed));
+                    continue;
+                };
+                let amount = needed.min(available);
+                if amount < needed {
+                    report
+                        .shortfalls
+                        .push((hot.wallet_id, float.token_id, needed - amount));
+                }
+                *warm.get_mut(&(from, float.token_id)).unwrap() -= amount;
+                let idempotency_key = match snapshot {
+                    Some(snapshot) => snapshot_key("top-up", hot.wallet_id, snapshot),
+                    // Nothing to derive a key from; fall back to the source's balance.
+                    None => Uuid::new_v5(
+                        &hot.wallet_id.as_uuid(),
+                        format!("top-up:{}:empty:{}:{}", float.token_id, from, available)
+                            .as_bytes(),
+                    ),
+                };
+                let outcome = match self.dry_run {
+                    true => SweepOutcome::Planned,
+                    false => {
+                        transfer(
+                            self.client,
+                            &hot.address,
+                            from,
+                            float.token_id,
+                            amount,
+                            idempotency_key,
+                            self.fee_level,
+                        )
+                        .await
+                    }
+                };
+                report.entries.push(TopUpEntry {
+                    from,
+                    to: hot.wallet_id,
+                    token_id: float.token_id,
+                    balance,
+                    amount,
+                    idempotency_key,
+                    outcome,
+                });
+            }
+        }
+        report
+    }
+
+    /// Runs [`WalletTiers::top_up_once`] with [`run_every`].
+    pub async fn run<F: FnMut(TopUpReport)>(&self, on_report: F) {
+        run_every(
+            self.interval,
+            &self.cancellation,
+            || self.top_up_once(),
+            on_report,
+        )
+        .await
+    }
+
+    async fn balances<I: IntoIterator<Item = WalletId>>(
+        &self,
+        wallets: I,
+    ) -> (
+        HashMap<WalletId, Vec<TokenBalance>>,
+        Vec<(WalletId, String)>,
+    ) {
+        let results = stream::iter(wallets)
+            .map(|wallet_id| async move {
+                let balances = self
+                    .client
+                    .get_wallet_balance(wallet_id, WalletBalanceQueryParams::default())
+                    .await;
+                (wallet_id, balances)
+            })
+            .buffered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        let mut balances = HashMap::new();
+        let mut failed = Vec::new();
+        for (wallet_id, result) in results {
+            match result {
+                Ok(response) => {
+                    balances.insert(wallet_id, response.token_balances);
+                }
+                Err(err) => failed.push((wallet_id, err.to_string())),
+            }
+        }
+        (balances, failed)
+    }
+}
+
+fn amount(balances: &[TokenBalance], token_id: TokenId) -> Option<Decimal> {
+    balances
+        .iter()
+        .find(|x| x.token.id == token_id)
+        .map(|x| x.amount)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    const TOKEN_ID: &str = "7adb2b7d-c9cd-5164-b2d4-b73b088274dc";
+
+    fn balances(amount: &str) -> Value {
+        json!({
+            "tokenBalances": [{
+                "amount": amount,
+                "updateDate": "2024-03-01T10:00:00Z",
+                "token": {
+                    "id": TOKEN_ID,
+                    "blockchain": "MATIC-AMOY",
+                    "isNative": false,
+                    "symbol": "USDC",
+                    "createDate": "2023-11-25T14:26:38Z",
+                    "updateDate": "2023-11-25T14:26:38Z"
+                }
+            }]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_tiers() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let address = Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let [hot, busy, warm, cold] = [(); 4].map(|_| WalletId(Uuid::new_v4()));
+        let token_id = TokenId::from_str(TOKEN_ID).unwrap();
+        let tiers = WalletTiers::new(&client)
+            .concurrency(1)
+            .wallet(hot, address.clone(), Tier::Hot)
+            .wallet(busy, address.clone(), Tier::Hot)
+            .wallet(warm, address.clone(), Tier::Warm)
+            .wallet(cold, address.clone(), Tier::Cold)
+            .float(FloatTarget::new(
+                token_id,
+                Decimal::from(100),
+                Decimal::from(500),
+            ));
+
+        // Both hot wallets are low; the warm wallet only covers the first fully.
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("20"));
+        transport.push_data(balances("0"));
+        transport.push_data(balances("600"));
+        transport.push_data(json!({ "id": Uuid::new_v4(), "state": "INITIATED" }));
+        transport.push_data(json!({ "id": Uuid::new_v4(), "state": "INITIATED" }));
+        let report = tiers.top_up_once().await;
+        let amounts = report
+            .entries
+            .iter()
+            .map(|x| (x.to, x.amount))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            amounts,
+            [(hot, Decimal::from(480)), (busy, Decimal::from(120))]
+        );
+        assert_eq!(report.shortfalls, [(busy, token_id, Decimal::from(380))]);
+        let requests = transport.requests();
+        assert!(requests.iter().all(|x| !x.path.contains(&cold.to_string())));
+        assert_eq!(requests[4].body.as_ref().unwrap()["walletId"], json!(warm));
+
+        // Payments leave from the hot wallet that can afford them.
+        transport.push_data(balances("30"));
+        transport.push_data(balances("450"));
+        let intent = TransferIntent::new(cold, token_id, address, Decimal::from(200), "shop");
+        assert_eq!(tiers.route(intent.clone()).await.unwrap().wallet_id, busy);
+        transport.push_data(balances("30"));
+        transport.push_data(balances("50"));
+        assert!(tiers.route(intent).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_top_up_pending() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let hot_address =
+            Web3Address::from_str("0x6E5eAf34c73D1CD0be4e24f923b97CF38e10d1f3").unwrap();
+        let new_address =
+            Web3Address::from_str("0x8f0e6a1a8b8Bd7b1B2c4E1d4eA9D2b6b1F3B8c7A").unwrap();
+        let [hot, new, warm] = [(); 3].map(|_| WalletId(Uuid::new_v4()));
+        let token_id = TokenId::from_str(TOKEN_ID).unwrap();
+        let tiers = WalletTiers::new(&client)
+            .concurrency(1)
+            .wallet(hot, hot_address.clone(), Tier::Hot)
+            .wallet(warm, new_address.clone(), Tier::Warm)
+            .float(FloatTarget::new(
+                token_id,
+                Decimal::from(100),
+                Decimal::from(500),
+            ));
+
+        let top_up_id = TransactionId(Uuid::new_v4());
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("20"));
+        transport.push_data(balances("600"));
+        transport.push_data(json!({ "id": top_up_id, "state": "INITIATED" }));
+        let report = tiers.top_up_once().await;
+        assert_eq!(report.entries[0].amount, Decimal::from(480));
+
+        // The hot wallet pays something out before the top-up lands, and a second hot wallet
+        // joins. The first isn't topped up again, and the second only gets what the warm
+        // wallet has left once the pending top-up is taken out.
+        let tiers = tiers.wallet(new, new_address, Tier::Hot);
+        let top_up = json!({
+            "id": top_up_id,
+            "blockchain": "MATIC-AMOY",
+            "amounts": ["480"],
+            "state": "QUEUED",
+            "tokenId": TOKEN_ID,
+            "walletId": warm,
+            "destinationAddress": hot_address,
+            "transactionType": "OUTBOUND",
+            "createDate": "2024-03-01T10:05:00Z",
+            "updateDate": "2024-03-01T10:05:00Z"
+        });
+        transport.push_data(json!({ "transactions": [top_up] }));
+        transport.push_data(json!({ "transactions": [] }));
+        transport.push_data(balances("5"));
+        transport.push_data(balances("600"));
+        transport.push_data(balances("0"));
+        transport.push_data(json!({ "id": Uuid::new_v4(), "state": "INITIATED" }));
+        let report = tiers.top_up_once().await;
+        assert_eq!(report.in_flight, [(hot, token_id, top_up_id)]);
+        let amounts = report
+            .entries
+            .iter()
+            .map(|x| (x.to, x.amount))
+            .collect::<Vec<_>>();
+        assert_eq!(amounts, [(new, Decimal::from(120))]);
+        assert_eq!(report.shortfalls, [(new, token_id, Decimal::from(380))]);
+    }
+}
@@ -0,0 +1,165 @@
+//! Resolving many known wallet ids to full wallet records, e.g. for reconciliation jobs that
+//! need metadata for hundreds of wallets at a time.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use futures::stream::{self, StreamExt};
+use reqwest::StatusCode;
+
+use crate::api::{CircleClient, CircleErrorCode};
+use crate::error::{CircleError, Result};
+use crate::models::ids::WalletId;
+use crate::models::wallet_detail::WalletDetail;
+
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Fetches wallets concurrently, asking for each id at most once per call and keeping up to
+/// `capacity` records cached between calls. Cached records can go stale; call
+/// [`WalletResolver::invalidate`] or [`WalletResolver::clear`] after updating a wallet.
+pub struct WalletResolver {
+    client: CircleClient,
+    concurrency: usize,
+    capacity: usize,
+    cache: Mutex<HashMap<WalletId, WalletDetail>>,
+}
+
+impl WalletResolver {
+    pub fn new(client: CircleClient) -> Self {
+        WalletResolver {
+            client,
+            concurrency: DEFAULT_CONCURRENCY,
+            capacity: DEFAULT_CAPACITY,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// How many wallets to keep between calls. `0` disables caching.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// The wallets for `wallet_ids`, keyed by id. Ids Circle doesn't know are left out of the
+    /// map; any other error fails the whole call.
+    pub async fn resolve<I>(&self, wallet_ids: I) -> Result<HashMap<WalletId, WalletDetail>>
+    where
+        I: IntoIterator<Item = WalletId>,
+    {
+        let wallet_ids = wallet_ids.into_iter().collect::<HashSet<_>>();
+        let mut wallets = HashMap::with_capacity(wallet_ids.len());
+        let mut missing = Vec::new();
+        {
+            let cache = self.cache.lock().unwrap();
+            for wallet_id in wallet_ids {
+                match cache.get(&wallet_id) {
+                    Some(wallet) => {
+                        wallets.insert(wallet_id, wallet.clone());
+                    }
+                    None => missing.push(wallet_id),
+                }
+            }
+        }
+
+        let mut fetched = stream::iter(missing)
+            .map(|wallet_id| self.fetch(wallet_id))
+            .buffer_unordered(self.concurrency);
+        while let Some(wallet) = fetched.next().await {
+            if let Some(wallet) = wallet? {
+                self.remember(&wallet);
+                wallets.insert(wallet.id, wallet);
+            }
+        }
+        Ok(wallets)
+    }
+
+    pub fn invalidate(&self, wallet_id: WalletId) {
+        self.cache.lock().unwrap().remove(&wallet_id);
+    }
+
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    async fn fetch(&self, wallet_id: WalletId) -> Result<Option<WalletDetail>> {
+        match self.client.get_wallet(wallet_id).await {
+            Ok(response) => Ok(Some(response.wallet)),
+            Err(err) if is_not_found(&err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn remember(&self, wallet: &WalletDetail) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= self.capacity && !cache.contains_key(&wallet.id) {
+            if let Some(evicted) = cache.keys().next().copied() {
+                cache.remove(&evicted);
+            }
+        }
+        cache.insert(wallet.id, wallet.clone());
+    }
+}
+
+fn is_not_found(err: &CircleError) -> bool {
+    match err {
+        CircleError::ApiError(_, err) => {
+            err.error_code() == CircleErrorCode::WalletNotFound
+                || err.status() == Some(StatusCode::NOT_FOUND)
+        }
+        _ => err.status() == Some(StatusCode::NOT_FOUND),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::MockTransport;
+
+    #[tokio::test]
+    async fn test_resolve_dedups_and_caches() {
+        let transport = MockTransport::new();
+        let client = transport.client().await;
+        let resolver = WalletResolver::new(client);
+        let wallet_id = WalletId(Uuid::new_v4());
+        transport
+            .push_data(json!({
+                "wallet": {
+                    "id": wallet_id,
+                    "state": "LIVE",
+                    "walletSetId": "0189bc61-7fe4-70f3-8a1b-0d14426397cb",
+                    "custodyType": "DEVELOPER",
+                    "address": "0xf5c83e5fede8456929d0f90e8c541dcac3d63835",
+                    "blockchain": "MATIC-AMOY",
+                    "updateDate": "2023-11-25T14:26:38Z",
+                    "createDate": "2023-11-25T14:26:38Z"
+                }
+            }))
+            .push_error(StatusCode::NOT_FOUND, 156004, "Cannot find target wallet");
+
+        let wallets = resolver.resolve([wallet_id, wallet_id]).await.unwrap();
+        assert_eq!(wallets[&wallet_id].id, wallet_id);
+        assert_eq!(transport.requests().len(), 1);
+
+        let unknown = WalletId(Uuid::new_v4());
+        let wallets = resolver.resolve([wallet_id, unknown]).await.unwrap();
+        assert_eq!(wallets.len(), 1);
+        assert_eq!(transport.requests().len(), 2);
+        assert!(transport
+            .last_request()
+            .unwrap()
+            .path
+            .ends_with(&unknown.to_string()));
+    }
+}